@@ -111,12 +111,22 @@ fn bench_swap_operations(c: &mut Criterion) {
                     .unwrap(),
                 amount_in: TokenAmount::from_human_readable("100.0", 6).unwrap(),
                 slippage_tolerance: rust_decimal::Decimal::from_str("0.005").unwrap(),
+                route: None,
+                amount_out: None,
+                sender: None,
+                override_token_state: false,
+                protocol: None,
             },
             estimated_amount_out: TokenAmount::from_human_readable("0.03", 18).unwrap(),
             price_impact: rust_decimal::Decimal::ZERO,
+            high_price_impact: false,
             gas_estimate: 200000,
             gas_cost_eth: None,
-            route: "mock".to_string(),
+            gas_cost_breakdown: None,
+            requires_approval: false,
+            route: Default::default(),
+            route_quotes: Vec::new(),
+            simulation: Default::default(),
         })
     });
     let swap_service = Arc::new(SwapService::new(Arc::new(provider), get_test_contracts()));
@@ -126,6 +136,11 @@ fn bench_swap_operations(c: &mut Criterion) {
         to_token: TokenAddress::from_hex("0x0000000000000000000000000000000000000000").unwrap(), // ETH
         amount_in: TokenAmount::from_human_readable("100.0", 6).unwrap(), // 100 USDC
         slippage_tolerance: rust_decimal::Decimal::from_str("0.005").unwrap(), // 0.5%
+        route: None,
+        amount_out: None,
+        sender: None,
+        override_token_state: false,
+        protocol: None,
     };
 
     c.bench_function("simulate_swap", |b| {
@@ -214,7 +229,17 @@ fn get_test_contracts() -> ContractAddresses {
         weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
         uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
         uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-        uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+        uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+        uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+        uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+        sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+        sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+        curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+        balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+        balancer_pool_ids: vec![
+            "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019".to_string(),
+            "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a".to_string(),
+        ],
         chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
     }
 }
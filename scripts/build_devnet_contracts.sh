@@ -0,0 +1,31 @@
+#!/bin/bash
+# Compiles the devnet fixture contracts in src/devnet/contracts with solar and writes
+# each contract's creation bytecode to src/devnet/contracts/out/<Name>.bin, which the
+# devnet embeds at build time. Re-run after editing any of the .sol sources.
+#
+# Needs solar (https://github.com/paradigmxyz/solar) 0.2 and jq on PATH, or SOLAR=/path/to/solar.
+# Optimization stays off: the fixtures are only exercised as compiled at -O none.
+
+set -euo pipefail
+
+SOLAR="${SOLAR:-solar}"
+CONTRACTS_DIR="$(cd "$(dirname "$0")/../src/devnet/contracts" && pwd)"
+OUT_DIR="$CONTRACTS_DIR/out"
+BUILD_DIR="$(mktemp -d)"
+trap 'rm -rf "$BUILD_DIR"' EXIT
+
+mkdir -p "$OUT_DIR"
+cd "$CONTRACTS_DIR"
+
+for source in *.sol; do
+    mkdir -p "$BUILD_DIR/$source"
+    "$SOLAR" -Zcodegen -O none --evm-version cancun --emit abi,bin --out-dir "$BUILD_DIR/$source" "$source"
+    jq -r --arg source "$source" '
+        .contracts | to_entries[]
+        | select((.key | startswith($source + ":")) and (.value.abi | length) > 0 and (.value.bin | length) > 4)
+        | "\(.key | split(":")[1]) \(.value.bin)"
+    ' "$BUILD_DIR/$source/combined.json" | while read -r name bin; do
+        printf '%s' "$bin" > "$OUT_DIR/$name.bin"
+        echo "  $name ($(( ${#bin} / 2 )) bytes)"
+    done
+done
@@ -26,51 +26,179 @@ sol! {
 
 /// Uniswap V3 fee tiers (in hundredths of a bip, so 3000 = 0.30%)
 pub mod fees {
+    pub const LOWEST: u32 = 100; // 0.01% for tightly pegged pairs
     pub const LOW: u32 = 500; // 0.05% for stablecoin pairs
     pub const MEDIUM: u32 = 3000; // 0.30% for most pairs
     pub const HIGH: u32 = 10000; // 1.00% for exotic pairs
+
+    /// Every tier the factory enables, cheapest first
+    pub const ALL: [u32; 4] = [LOWEST, LOW, MEDIUM, HIGH];
+
+    /// The 0.30% every Uniswap V2 and Sushiswap pair charges
+    pub const V2_PAIR: u32 = MEDIUM;
 }
 
 // ERC20 token standard interface
 sol! {
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     interface IERC20 {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+        event Approval(address indexed owner, address indexed spender, uint256 value);
+
         function totalSupply() external view returns (uint256);
         function balanceOf(address account) external view returns (uint256);
         function decimals() external view returns (uint8);
         function symbol() external view returns (string memory);
         function name() external view returns (string memory);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function transfer(address to, uint256 amount) external returns (bool);
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
     }
 }
 
-// Uniswap V3 Quoter interface for price queries
+// WETH9, which wraps ether 1:1 into an ERC20
 sol! {
     #[allow(missing_docs)]
-    #[sol(rpc)]
-    interface IUniswapV3Quoter {
-        function quoteExactInputSingle(
-            address tokenIn,
-            address tokenOut,
-            uint24 fee,
-            uint256 amountIn,
-            uint160 sqrtPriceLimitX96
-        ) external returns (uint256 amountOut);
+    #[sol(rpc, abi)]
+    interface IWETH9 {
+        event Deposit(address indexed dst, uint256 wad);
+        event Withdrawal(address indexed src, uint256 wad);
 
-        function quoteExactOutputSingle(
-            address tokenIn,
-            address tokenOut,
-            uint24 fee,
-            uint256 amountOut,
-            uint160 sqrtPriceLimitX96
-        ) external returns (uint256 amountIn);
+        function deposit() external payable;
+        function withdraw(uint256 wad) external;
+    }
+}
+
+// EIP-2612 permit extension for ERC20 tokens
+sol! {
+    #[allow(missing_docs, clippy::too_many_arguments)]
+    #[sol(rpc, abi)]
+    interface IERC20Permit {
+        function permit(
+            address owner,
+            address spender,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+        function version() external view returns (string memory);
+        function PERMIT_TYPEHASH() external view returns (bytes32);
+    }
+}
+
+// EIP-712 typed data signed for an EIP-2612 permit
+sol! {
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+}
+
+// Uniswap Permit2 signature-based allowance transfer
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc, abi)]
+    interface IPermit2 {
+        error AllowanceExpired(uint256 deadline);
+        error InsufficientAllowance(uint256 amount);
+        error InvalidNonce();
+        error SignatureExpired(uint256 signatureDeadline);
+        error InvalidSignature();
+        error InvalidSigner();
+        error InvalidSignatureLength();
+
+        #[derive(Debug)]
+        struct PermitDetails {
+            address token;
+            uint160 amount;
+            uint48 expiration;
+            uint48 nonce;
+        }
+
+        #[derive(Debug)]
+        struct PermitSingle {
+            PermitDetails details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        function allowance(address user, address token, address spender)
+            external view returns (uint160 amount, uint48 expiration, uint48 nonce);
+
+        function permit(address owner, PermitSingle memory permitSingle, bytes calldata signature)
+            external;
+    }
+}
+
+/// Uniswap Permit2 deployment (same CREATE2 address on every supported chain)
+pub const PERMIT2: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+
+// Uniswap V3 QuoterV2, which also reports the pool prices after the swap, the
+// initialized ticks it crosses and its gas cost
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc, abi)]
+    interface IUniswapV3QuoterV2 {
+        struct QuoteExactInputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint256 amountIn;
+            uint24 fee;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct QuoteExactOutputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint256 amount;
+            uint24 fee;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        function quoteExactInputSingle(QuoteExactInputSingleParams memory params)
+            external
+            returns (
+                uint256 amountOut,
+                uint160 sqrtPriceX96After,
+                uint32 initializedTicksCrossed,
+                uint256 gasEstimate
+            );
+
+        function quoteExactInput(bytes memory path, uint256 amountIn)
+            external
+            returns (
+                uint256 amountOut,
+                uint160[] memory sqrtPriceX96AfterList,
+                uint32[] memory initializedTicksCrossedList,
+                uint256 gasEstimate
+            );
+
+        function quoteExactOutputSingle(QuoteExactOutputSingleParams memory params)
+            external
+            returns (
+                uint256 amountIn,
+                uint160 sqrtPriceX96After,
+                uint32 initializedTicksCrossed,
+                uint256 gasEstimate
+            );
     }
 }
 
 // Uniswap V3 Router interface for swap simulations
 sol! {
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     interface IUniswapV3Router {
         struct ExactInputSingleParams {
             address tokenIn;
@@ -86,6 +214,17 @@ sol! {
         function exactInputSingle(ExactInputSingleParams calldata params)
             external payable returns (uint256 amountOut);
 
+        struct ExactInputParams {
+            bytes path;
+            address recipient;
+            uint256 deadline;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+        }
+
+        function exactInput(ExactInputParams calldata params)
+            external payable returns (uint256 amountOut);
+
         struct ExactOutputSingleParams {
             address tokenIn;
             address tokenOut;
@@ -99,6 +238,22 @@ sol! {
 
         function exactOutputSingle(ExactOutputSingleParams calldata params)
             external payable returns (uint256 amountIn);
+
+        function selfPermit(
+            address token,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external payable;
+
+        function multicall(bytes[] calldata data)
+            external payable returns (bytes[] memory results);
+
+        function unwrapWETH9(uint256 amountMinimum, address recipient) external payable;
+
+        function refundETH() external payable;
     }
 }
 
@@ -115,11 +270,239 @@ sol! {
     }
 }
 
+// Uniswap V3 Pool state and events
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IUniswapV3Pool {
+        function slot0() external view returns (
+            uint160 sqrtPriceX96,
+            int24 tick,
+            uint16 observationIndex,
+            uint16 observationCardinality,
+            uint16 observationCardinalityNext,
+            uint8 feeProtocol,
+            bool unlocked
+        );
+        function liquidity() external view returns (uint128);
+
+        event Swap(
+            address indexed sender,
+            address indexed recipient,
+            int256 amount0,
+            int256 amount1,
+            uint160 sqrtPriceX96,
+            uint128 liquidity,
+            int24 tick
+        );
+    }
+}
+
+// Uniswap V2 Router02, which Sushiswap's router shares
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc, abi)]
+    interface IUniswapV2Router {
+        function factory() external pure returns (address);
+
+        function getAmountsOut(uint256 amountIn, address[] calldata path)
+            external view returns (uint256[] memory amounts);
+
+        function getAmountsIn(uint256 amountOut, address[] calldata path)
+            external view returns (uint256[] memory amounts);
+
+        function swapExactTokensForTokens(
+            uint256 amountIn,
+            uint256 amountOutMin,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external returns (uint256[] memory amounts);
+
+        function swapTokensForExactTokens(
+            uint256 amountOut,
+            uint256 amountInMax,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external returns (uint256[] memory amounts);
+
+        function swapExactETHForTokens(
+            uint256 amountOutMin,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external payable returns (uint256[] memory amounts);
+
+        function swapETHForExactTokens(
+            uint256 amountOut,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external payable returns (uint256[] memory amounts);
+
+        function swapExactTokensForETH(
+            uint256 amountIn,
+            uint256 amountOutMin,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external returns (uint256[] memory amounts);
+
+        function swapTokensForExactETH(
+            uint256 amountOut,
+            uint256 amountInMax,
+            address[] calldata path,
+            address to,
+            uint256 deadline
+        ) external returns (uint256[] memory amounts);
+    }
+}
+
+// Uniswap V2 Factory interface for pair lookup
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IUniswapV2Factory {
+        function getPair(address tokenA, address tokenB) external view returns (address pair);
+    }
+}
+
+// Uniswap V2 Pair reserves and events
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IUniswapV2Pair {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function getReserves() external view returns (
+            uint112 reserve0,
+            uint112 reserve1,
+            uint32 blockTimestampLast
+        );
+
+        event Swap(
+            address indexed sender,
+            uint256 amount0In,
+            uint256 amount1In,
+            uint256 amount0Out,
+            uint256 amount1Out,
+            address indexed to
+        );
+    }
+}
+
+// Curve StableSwap pool (the int128-indexed `get_dy` of 3pool and its peers)
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface ICurvePool {
+        function coins(uint256 i) external view returns (address);
+        function get_dy(int128 i, int128 j, uint256 dx) external view returns (uint256);
+    }
+}
+
+// Balancer V2 Vault, which holds every pool's tokens and runs their swaps
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IBalancerVault {
+        struct BatchSwapStep {
+            bytes32 poolId;
+            uint256 assetInIndex;
+            uint256 assetOutIndex;
+            uint256 amount;
+            bytes userData;
+        }
+
+        struct FundManagement {
+            address sender;
+            bool fromInternalBalance;
+            address recipient;
+            bool toInternalBalance;
+        }
+
+        function getPoolTokens(bytes32 poolId) external view returns (
+            address[] memory tokens,
+            uint256[] memory balances,
+            uint256 lastChangeBlock
+        );
+
+        /// Not a view, but meant to be called with `eth_call`; `kind` 0 is GIVEN_IN
+        function queryBatchSwap(
+            uint8 kind,
+            BatchSwapStep[] memory swaps,
+            address[] memory assets,
+            FundManagement memory funds
+        ) external returns (int256[] memory assetDeltas);
+    }
+}
+
+// OP-stack GasPriceOracle predeploy for L1 data fee estimation
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IGasPriceOracle {
+        function getL1Fee(bytes memory _data) external view returns (uint256);
+    }
+}
+
+// Arbitrum NodeInterface virtual contract for L1 component estimation (eth_call only)
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface INodeInterface {
+        function gasEstimateL1Component(
+            address to,
+            bool contractCreation,
+            bytes calldata data
+        )
+            external
+            payable
+            returns (uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate);
+    }
+}
+
+/// Layer 2 networks whose transactions pay an L1 data fee on top of L2 execution
+pub mod l2 {
+    /// OP-stack GasPriceOracle predeploy (same address on every OP-stack chain)
+    pub const GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+    /// Arbitrum NodeInterface virtual contract
+    pub const NODE_INTERFACE: &str = "0x00000000000000000000000000000000000000C8";
+
+    /// Rollup family, which determines how the L1 data fee is estimated
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum L2Network {
+        OpStack,
+        Arbitrum,
+    }
+
+    impl L2Network {
+        /// Classify a chain ID; returns None for L1 and unknown chains
+        pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+            match chain_id {
+                // Optimism, Base, Zora, Mode, Fraxtal and their Sepolia testnets
+                10 | 8453 | 7777777 | 34443 | 252 | 11155420 | 84532 => Some(Self::OpStack),
+                // Arbitrum One, Arbitrum Nova, Arbitrum Sepolia
+                42161 | 42170 | 421614 => Some(Self::Arbitrum),
+                _ => None,
+            }
+        }
+
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                Self::OpStack => "op_stack",
+                Self::Arbitrum => "arbitrum",
+            }
+        }
+    }
+}
+
 /// Common utility functions for working with contracts
 pub mod utils {
-    use crate::types::{TokenAddress, WalletAddress};
+    use crate::types::{SwapProtocol, TokenAddress, WalletAddress};
     use crate::ContractAddresses;
-    use alloy::primitives::Address;
+    use alloy::primitives::{Address, Bytes};
     use anyhow::Result;
 
     /// Convert our TokenAddress type to alloy Address
@@ -155,6 +538,77 @@ pub mod utils {
         }
     }
 
+    /// Router that executes swaps on `protocol`, and that needs the input approved
+    pub fn swap_router(protocol: SwapProtocol, contracts: &ContractAddresses) -> Result<Address> {
+        parse_address(match protocol {
+            SwapProtocol::UniswapV3 => &contracts.uniswap_v3_router,
+            SwapProtocol::UniswapV2 => &contracts.uniswap_v2_router,
+            SwapProtocol::Sushiswap => &contracts.sushiswap_router,
+        })
+    }
+
+    /// The ERC20 a swap routes `token` through: WETH for native ETH, else the token itself
+    pub fn wrapped_token(token: &TokenAddress, contracts: &ContractAddresses) -> Result<Address> {
+        match token.is_native() {
+            true => parse_address(&contracts.weth),
+            false => Ok(token.address()),
+        }
+    }
+
+    /// Factory of a V2 protocol's pairs
+    pub fn pair_factory(protocol: SwapProtocol, contracts: &ContractAddresses) -> Result<Address> {
+        match protocol {
+            SwapProtocol::UniswapV3 => Err(anyhow::anyhow!("Uniswap V3 has no V2 pairs")),
+            SwapProtocol::UniswapV2 => parse_address(&contracts.uniswap_v2_factory),
+            SwapProtocol::Sushiswap => parse_address(&contracts.sushiswap_factory),
+        }
+    }
+
+    /// Intermediate tokens multi-hop routes may pass through
+    pub fn base_tokens(contracts: &ContractAddresses) -> Vec<Address> {
+        [
+            &contracts.weth,
+            &contracts.usdc,
+            &contracts.usdt,
+            &contracts.dai,
+        ]
+        .into_iter()
+        .filter_map(|addr| parse_address(addr).ok())
+        .collect()
+    }
+
+    /// Uniswap V3 path encoding: `token (20 bytes) | fee (3 bytes) | token | ...`
+    pub fn encode_path(tokens: &[Address], fees: &[u32]) -> Result<Bytes> {
+        if tokens.len() != fees.len() + 1 || fees.is_empty() {
+            return Err(anyhow::anyhow!(
+                "A path needs one more token than fees, got {} tokens and {} fees",
+                tokens.len(),
+                fees.len()
+            ));
+        }
+        let mut path = Vec::with_capacity(20 + fees.len() * 23);
+        path.extend_from_slice(tokens[0].as_slice());
+        for (fee, token) in fees.iter().zip(&tokens[1..]) {
+            path.extend_from_slice(&fee.to_be_bytes()[1..]);
+            path.extend_from_slice(token.as_slice());
+        }
+        Ok(path.into())
+    }
+
+    /// Split a Uniswap V3 path back into its tokens and fees
+    pub fn decode_path(path: &[u8]) -> Result<(Vec<Address>, Vec<u32>)> {
+        if path.len() < 43 || !(path.len() - 20).is_multiple_of(23) {
+            return Err(anyhow::anyhow!("Invalid path length {}", path.len()));
+        }
+        let mut tokens = vec![Address::from_slice(&path[..20])];
+        let mut fees = Vec::new();
+        for hop in path[20..].chunks(23) {
+            fees.push(u32::from_be_bytes([0, hop[0], hop[1], hop[2]]));
+            tokens.push(Address::from_slice(&hop[3..]));
+        }
+        Ok((tokens, fees))
+    }
+
     /// Resolve a token symbol to a known mainnet address
     pub fn resolve_token_address(symbol: &str, contracts: &ContractAddresses) -> Option<String> {
         let normalized = symbol.trim().to_ascii_uppercase();
@@ -168,9 +622,286 @@ pub mod utils {
     }
 }
 
+/// Storage locations of ERC-20 balance and allowance mappings, for `eth_call` state
+/// overrides
+pub mod storage {
+    use alloy::primitives::{keccak256, Address, B256, U256};
+
+    /// Declared slot of a mapping. Vyper hashes the slot before the key, Solidity after.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MappingSlot {
+        pub index: u64,
+        pub vyper: bool,
+    }
+
+    impl MappingSlot {
+        pub const fn solidity(index: u64) -> Self {
+            Self {
+                index,
+                vyper: false,
+            }
+        }
+
+        /// Slot of `mapping[key]`
+        pub fn key(&self, key: Address) -> B256 {
+            self.hash(key.into_word(), B256::from(U256::from(self.index)))
+        }
+
+        /// Slot of `mapping[outer][inner]`
+        pub fn nested(&self, outer: Address, inner: Address) -> B256 {
+            self.hash(inner.into_word(), self.key(outer))
+        }
+
+        fn hash(&self, key: B256, slot: B256) -> B256 {
+            if self.vyper {
+                keccak256([slot.as_slice(), key.as_slice()].concat())
+            } else {
+                keccak256([key.as_slice(), slot.as_slice()].concat())
+            }
+        }
+    }
+
+    /// Where a token keeps `balanceOf(owner)` and `allowance(owner, spender)`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TokenSlots {
+        pub balance: MappingSlot,
+        pub allowance: MappingSlot,
+    }
+}
+
+/// Decoding of calldata and logs against the ABIs above
+pub mod decode {
+    use super::{
+        IERC20Permit, IPermit2, IUniswapV2Pair, IUniswapV2Router, IUniswapV3Pool,
+        IUniswapV3QuoterV2, IUniswapV3Router, IERC20, IWETH9,
+    };
+    use crate::types::{DecodedCall, DecodedLog, RevertKind, RevertReason};
+    use alloy::dyn_abi::{DynSolValue, JsonAbiExt};
+    use alloy::json_abi::{Function, Param};
+    use alloy::primitives::{hex, Bytes, Log};
+    use alloy::sol_types::{Panic, Revert, SolError, SolEvent};
+    use serde_json::{json, Map, Value};
+    use std::sync::OnceLock;
+
+    /// Every function of the interfaces this server calls, tagged with its interface name
+    fn known_functions() -> &'static [(&'static str, Function)] {
+        static FUNCTIONS: OnceLock<Vec<(&'static str, Function)>> = OnceLock::new();
+        FUNCTIONS.get_or_init(|| {
+            [
+                ("IERC20", IERC20::abi::functions()),
+                ("IERC20Permit", IERC20Permit::abi::functions()),
+                ("IWETH9", IWETH9::abi::functions()),
+                ("IPermit2", IPermit2::abi::functions()),
+                ("IUniswapV3Router", IUniswapV3Router::abi::functions()),
+                ("IUniswapV3QuoterV2", IUniswapV3QuoterV2::abi::functions()),
+                ("IUniswapV2Router", IUniswapV2Router::abi::functions()),
+            ]
+            .into_iter()
+            .flat_map(|(interface, functions)| {
+                functions
+                    .into_values()
+                    .flatten()
+                    .map(move |function| (interface, function))
+            })
+            .collect()
+        })
+    }
+
+    /// Decode transaction calldata; None for empty calldata (plain ETH transfers)
+    /// Unknown selectors are still reported, with no signature or arguments.
+    pub fn decode_call(input: &[u8]) -> Option<DecodedCall> {
+        let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+        let unknown = DecodedCall {
+            selector: hex::encode_prefixed(selector),
+            interface: None,
+            signature: None,
+            arguments: Value::Null,
+            calls: Vec::new(),
+        };
+
+        let Some((interface, function)) = known_functions()
+            .iter()
+            .find(|(_, function)| function.selector() == selector)
+        else {
+            return Some(unknown);
+        };
+        let Ok(values) = function.abi_decode_input(&input[4..], true) else {
+            return Some(unknown);
+        };
+
+        // Router multicalls wrap the real calls as bytes[]
+        let calls = match (function.name.as_str(), values.as_slice()) {
+            ("multicall", [DynSolValue::Array(inner)]) => inner
+                .iter()
+                .filter_map(|call| match call {
+                    DynSolValue::Bytes(data) => decode_call(data),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Some(DecodedCall {
+            interface: Some(interface.to_string()),
+            signature: Some(function.signature()),
+            arguments: params_to_json(&function.inputs, &values),
+            calls,
+            ..unknown
+        })
+    }
+
+    /// Decode an ERC-20 `Transfer` or Uniswap V3 / V2 `Swap` log; None for any other log
+    pub fn decode_log(log: &Log) -> Option<DecodedLog> {
+        let topic0 = *log.topics().first()?;
+        if topic0 == IERC20::Transfer::SIGNATURE_HASH {
+            // ERC-721 shares the signature but indexes the token id, which fails validation
+            let transfer = IERC20::Transfer::decode_log_data(&log.data, true).ok()?;
+            Some(DecodedLog::Transfer {
+                token: log.address,
+                from: transfer.from,
+                to: transfer.to,
+                value: transfer.value.to_string(),
+            })
+        } else if topic0 == IUniswapV3Pool::Swap::SIGNATURE_HASH {
+            let swap = IUniswapV3Pool::Swap::decode_log_data(&log.data, true).ok()?;
+            Some(DecodedLog::Swap {
+                pool: log.address,
+                sender: swap.sender,
+                recipient: swap.recipient,
+                amount0: swap.amount0.to_string(),
+                amount1: swap.amount1.to_string(),
+                sqrt_price_x96: swap.sqrtPriceX96.to_string(),
+                liquidity: swap.liquidity.to_string(),
+                tick: swap.tick,
+            })
+        } else if topic0 == IUniswapV2Pair::Swap::SIGNATURE_HASH {
+            let swap = IUniswapV2Pair::Swap::decode_log_data(&log.data, true).ok()?;
+            Some(DecodedLog::PairSwap {
+                pair: log.address,
+                sender: swap.sender,
+                to: swap.to,
+                amount0_in: swap.amount0In.to_string(),
+                amount1_in: swap.amount1In.to_string(),
+                amount0_out: swap.amount0Out.to_string(),
+                amount1_out: swap.amount1Out.to_string(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Decode revert data as `Error(string)`, `Panic(uint256)` or a known custom error
+    pub fn decode_revert(data: &[u8]) -> RevertReason {
+        let reason = |kind, message: String| RevertReason {
+            kind,
+            message,
+            explanation: None,
+            arguments: None,
+            data: Bytes::copy_from_slice(data),
+        };
+
+        if let Ok(revert) = Revert::abi_decode(data, true) {
+            return RevertReason {
+                explanation: uniswap_explanation(&revert.reason).map(str::to_string),
+                ..reason(RevertKind::Error, revert.reason)
+            };
+        }
+        if let Ok(panic) = Panic::abi_decode(data, true) {
+            let message = match panic.kind() {
+                Some(kind) => kind.as_str().to_string(),
+                None => format!("panic code {}", panic.code),
+            };
+            return reason(RevertKind::Panic, message);
+        }
+
+        let custom = data.get(..4).and_then(|selector| {
+            IPermit2::abi::errors()
+                .into_values()
+                .flatten()
+                .find(|error| error.selector().as_slice() == selector)
+        });
+        if let Some(error) = custom {
+            if let Ok(values) = error.abi_decode_input(&data[4..], true) {
+                return RevertReason {
+                    arguments: Some(params_to_json(&error.inputs, &values)),
+                    ..reason(RevertKind::Custom, error.signature())
+                };
+            }
+        }
+
+        let message = if data.is_empty() {
+            "reverted without a reason"
+        } else {
+            "reverted with unrecognised data"
+        };
+        reason(RevertKind::Unknown, message.to_string())
+    }
+
+    /// Meaning of the terse revert strings in Uniswap V3's router and pools
+    pub fn uniswap_explanation(reason: &str) -> Option<&'static str> {
+        Some(match reason {
+            "STF" => "safeTransferFrom failed: the router could not pull the input token; check the approval and balance",
+            "ST" => "safeTransfer failed: the output token could not be sent",
+            "Too little received" => "the output fell below amountOutMinimum; the price moved beyond the slippage tolerance",
+            "Too much requested" => "the required input exceeds amountInMaximum; the price moved beyond the slippage tolerance",
+            "Transaction too old" => "the swap deadline passed before the transaction was mined",
+            "SPL" => "sqrtPriceLimitX96 is outside the pool's valid price range",
+            "AS" => "the swap amount is zero",
+            "LOK" => "the pool is locked by a reentrant call",
+            "IIA" => "the pool received less input than owed, e.g. from a fee-on-transfer token",
+            "Not WETH9" => "only WETH9 may send ETH to the router",
+            _ => return None,
+        })
+    }
+
+    /// Arguments as an object keyed by parameter name (or position, for unnamed ones)
+    fn params_to_json(params: &[Param], values: &[DynSolValue]) -> Value {
+        let fields: Map<String, Value> = params
+            .iter()
+            .zip(values)
+            .enumerate()
+            .map(|(index, (param, value))| {
+                let name = if param.name.is_empty() {
+                    index.to_string()
+                } else {
+                    param.name.clone()
+                };
+                (name, value_to_json(value, &param.components))
+            })
+            .collect();
+        Value::Object(fields)
+    }
+
+    /// Integers become decimal strings, since they routinely exceed JSON's safe range
+    fn value_to_json(value: &DynSolValue, components: &[Param]) -> Value {
+        match value {
+            DynSolValue::Bool(b) => json!(b),
+            DynSolValue::Int(i, _) => json!(i.to_string()),
+            DynSolValue::Uint(u, _) => json!(u.to_string()),
+            DynSolValue::FixedBytes(word, size) => json!(hex::encode_prefixed(&word[..*size])),
+            DynSolValue::Address(address) => json!(address.to_checksum(None)),
+            DynSolValue::Function(function) => json!(hex::encode_prefixed(function.as_slice())),
+            DynSolValue::Bytes(bytes) => json!(hex::encode_prefixed(bytes)),
+            DynSolValue::String(s) => json!(s),
+            DynSolValue::Array(items) | DynSolValue::FixedArray(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| value_to_json(item, components))
+                    .collect(),
+            ),
+            DynSolValue::Tuple(items) if components.len() == items.len() => {
+                params_to_json(components, items)
+            }
+            DynSolValue::Tuple(items) => {
+                Value::Array(items.iter().map(|item| value_to_json(item, &[])).collect())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{fees, utils};
+    use super::{fees, l2, utils};
     use crate::types::{TokenAddress, WalletAddress};
     use crate::ContractAddresses;
 
@@ -182,7 +913,17 @@ mod tests {
             weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
             uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
             uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-            uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+            uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+            uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+            uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+            sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+            sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+            curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+            balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+            balancer_pool_ids: vec![
+                "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019".to_string(),
+                "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a".to_string(),
+            ],
             chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
         }
     }
@@ -305,6 +1046,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mapping_slots_follow_compiler_layout() {
+        use super::storage::MappingSlot;
+        use alloy::primitives::{keccak256, Address, B256, U256};
+
+        let owner = Address::repeat_byte(0x11);
+        let spender = Address::repeat_byte(0x22);
+        let slot = |index: u64| B256::from(U256::from(index));
+        let hash = |a: B256, b: B256| keccak256([a.as_slice(), b.as_slice()].concat());
+
+        // Solidity: keccak256(key . slot), nested as keccak256(inner . keccak256(outer . slot))
+        let solidity = MappingSlot::solidity(3);
+        assert_eq!(solidity.key(owner), hash(owner.into_word(), slot(3)));
+        assert_eq!(
+            solidity.nested(owner, spender),
+            hash(spender.into_word(), hash(owner.into_word(), slot(3)))
+        );
+
+        // Vyper: keccak256(slot . key)
+        let vyper = MappingSlot {
+            index: 3,
+            vyper: true,
+        };
+        assert_eq!(vyper.key(owner), hash(slot(3), owner.into_word()));
+        assert_eq!(
+            vyper.nested(owner, spender),
+            hash(hash(slot(3), owner.into_word()), spender.into_word())
+        );
+    }
+
+    #[test]
+    fn test_path_round_trip() {
+        let contracts = get_test_contracts();
+        let usdc = utils::parse_address(&contracts.usdc).unwrap();
+        let weth = utils::parse_address(&contracts.weth).unwrap();
+        let dai = utils::parse_address(&contracts.dai).unwrap();
+        let path = utils::encode_path(&[usdc, weth, dai], &[fees::LOW, fees::MEDIUM]).unwrap();
+        assert_eq!(path.len(), 66);
+        assert_eq!(&path[20..23], &[0x00, 0x01, 0xf4]);
+        assert_eq!(
+            utils::decode_path(&path).unwrap(),
+            (vec![usdc, weth, dai], vec![fees::LOW, fees::MEDIUM])
+        );
+
+        assert!(utils::encode_path(&[usdc, weth], &[]).is_err());
+        assert!(utils::encode_path(&[usdc], &[fees::LOW]).is_err());
+        assert!(utils::decode_path(&path[..50]).is_err());
+    }
+
     #[test]
     fn test_token_address_conversion() {
         let contracts = get_test_contracts();
@@ -338,7 +1128,7 @@ mod tests {
             &contracts.weth,
             &contracts.uniswap_v3_factory,
             &contracts.uniswap_v3_router,
-            &contracts.uniswap_v3_quoter,
+            &contracts.uniswap_v3_quoter_v2,
             &contracts.chainlink_eth_usd_feed,
         ];
 
@@ -373,6 +1163,265 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_erc20_allowance_selectors() {
+        use super::IERC20;
+        use alloy::sol_types::SolCall;
+
+        assert_eq!(IERC20::allowanceCall::SELECTOR, [0xdd, 0x62, 0xed, 0x3e]);
+        assert_eq!(IERC20::approveCall::SELECTOR, [0x09, 0x5e, 0xa7, 0xb3]);
+    }
+
+    #[test]
+    fn test_permit_eip712_types() {
+        use super::{IPermit2, Permit};
+        use alloy::primitives::{b256, keccak256};
+        use alloy::sol_types::SolStruct;
+
+        assert_eq!(
+            keccak256(Permit::eip712_encode_type().as_bytes()),
+            b256!("6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9")
+        );
+        assert_eq!(
+            IPermit2::PermitSingle::eip712_encode_type(),
+            "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)\
+             PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)"
+        );
+        assert!(utils::parse_address(super::PERMIT2).is_ok());
+    }
+
+    #[test]
+    fn test_l2_network_classification() {
+        assert_eq!(l2::L2Network::from_chain_id(1), None);
+        assert_eq!(l2::L2Network::from_chain_id(11155111), None);
+        assert_eq!(
+            l2::L2Network::from_chain_id(10),
+            Some(l2::L2Network::OpStack)
+        );
+        assert_eq!(
+            l2::L2Network::from_chain_id(8453),
+            Some(l2::L2Network::OpStack)
+        );
+        assert_eq!(
+            l2::L2Network::from_chain_id(42161),
+            Some(l2::L2Network::Arbitrum)
+        );
+        assert_eq!(l2::L2Network::Arbitrum.as_str(), "arbitrum");
+    }
+
+    #[test]
+    fn test_l2_system_contract_addresses() {
+        assert!(utils::parse_address(l2::GAS_PRICE_ORACLE).is_ok());
+        assert!(utils::parse_address(l2::NODE_INTERFACE).is_ok());
+    }
+
+    #[test]
+    fn test_decode_call_names_arguments() {
+        use super::{decode, IERC20};
+        use alloy::primitives::{address, U256};
+        use alloy::sol_types::SolCall;
+
+        let spender = address!("E592427A0AEce92De3Edee1F18E0157C05861564");
+        let input = IERC20::approveCall {
+            spender,
+            amount: U256::from(1_000_000u64),
+        }
+        .abi_encode();
+
+        let call = decode::decode_call(&input).unwrap();
+        assert_eq!(call.selector, "0x095ea7b3");
+        assert_eq!(call.interface.as_deref(), Some("IERC20"));
+        assert_eq!(call.signature.as_deref(), Some("approve(address,uint256)"));
+        assert_eq!(
+            call.arguments,
+            serde_json::json!({
+                "spender": spender.to_checksum(None),
+                "amount": "1000000"
+            })
+        );
+        assert!(call.calls.is_empty());
+    }
+
+    #[test]
+    fn test_decode_call_unpacks_router_multicall() {
+        use super::{decode, IUniswapV3Router};
+        use alloy::primitives::{address, B256, U256};
+        use alloy::sol_types::SolCall;
+
+        let token_in = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let self_permit = IUniswapV3Router::selfPermitCall {
+            token: token_in,
+            value: U256::from(5u64),
+            deadline: U256::from(1_700_000_000u64),
+            v: 27,
+            r: B256::repeat_byte(1),
+            s: B256::repeat_byte(2),
+        }
+        .abi_encode();
+        let swap = IUniswapV3Router::exactInputSingleCall {
+            params: IUniswapV3Router::ExactInputSingleParams {
+                tokenIn: token_in,
+                tokenOut: address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+                fee: 500,
+                recipient: address!("742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7"),
+                deadline: U256::from(1_700_000_000u64),
+                amountIn: U256::from(5u64),
+                amountOutMinimum: U256::from(1u64),
+                sqrtPriceLimitX96: U256::ZERO,
+            },
+        }
+        .abi_encode();
+        let input = IUniswapV3Router::multicallCall {
+            data: vec![self_permit.into(), swap.into()],
+        }
+        .abi_encode();
+
+        let call = decode::decode_call(&input).unwrap();
+        assert_eq!(call.signature.as_deref(), Some("multicall(bytes[])"));
+        assert_eq!(call.calls.len(), 2);
+        assert_eq!(call.calls[0].arguments["v"], "27");
+        assert_eq!(
+            call.calls[1].arguments["params"]["tokenIn"],
+            token_in.to_checksum(None)
+        );
+        assert_eq!(call.calls[1].arguments["params"]["fee"], "500");
+    }
+
+    #[test]
+    fn test_decode_call_unknown_selector_and_empty_input() {
+        use super::decode;
+
+        let call = decode::decode_call(&[0xde, 0xad, 0xbe, 0xef, 0x00]).unwrap();
+        assert_eq!(call.selector, "0xdeadbeef");
+        assert!(call.signature.is_none());
+        assert!(call.arguments.is_null());
+
+        assert!(decode::decode_call(&[]).is_none());
+    }
+
+    #[test]
+    fn test_decode_transfer_and_swap_logs() {
+        use super::{decode, IUniswapV2Pair, IUniswapV3Pool, IERC20};
+        use crate::types::DecodedLog;
+        use alloy::primitives::{address, Log, I256, U256};
+        use alloy::sol_types::SolEvent;
+
+        let token = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let wallet = address!("742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7");
+        let pool = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+
+        let transfer = Log {
+            address: token,
+            data: IERC20::Transfer {
+                from: wallet,
+                to: pool,
+                value: U256::from(2_500_000u64),
+            }
+            .encode_log_data(),
+        };
+        assert_eq!(
+            decode::decode_log(&transfer),
+            Some(DecodedLog::Transfer {
+                token,
+                from: wallet,
+                to: pool,
+                value: "2500000".to_string(),
+            })
+        );
+
+        let swap = Log {
+            address: pool,
+            data: IUniswapV3Pool::Swap {
+                sender: wallet,
+                recipient: wallet,
+                amount0: I256::try_from(2_500_000i64).unwrap(),
+                amount1: I256::try_from(-1_000_000_000_000_000i64).unwrap(),
+                sqrtPriceX96: U256::from(1u64) << 96,
+                liquidity: 42,
+                tick: -200_000,
+            }
+            .encode_log_data(),
+        };
+        match decode::decode_log(&swap) {
+            Some(DecodedLog::Swap { amount1, tick, .. }) => {
+                assert_eq!(amount1, "-1000000000000000");
+                assert_eq!(tick, -200_000);
+            }
+            other => panic!("expected a Swap log, got {:?}", other),
+        }
+
+        let pair_swap = Log {
+            address: pool,
+            data: IUniswapV2Pair::Swap {
+                sender: wallet,
+                amount0In: U256::from(2_500_000u64),
+                amount1In: U256::ZERO,
+                amount0Out: U256::ZERO,
+                amount1Out: U256::from(1_000_000_000_000_000u64),
+                to: wallet,
+            }
+            .encode_log_data(),
+        };
+        match decode::decode_log(&pair_swap) {
+            Some(DecodedLog::PairSwap {
+                amount0_in,
+                amount1_out,
+                ..
+            }) => {
+                assert_eq!(amount0_in, "2500000");
+                assert_eq!(amount1_out, "1000000000000000");
+            }
+            other => panic!("expected a V2 Swap log, got {:?}", other),
+        }
+
+        // An ERC-721 Transfer shares topic0 but indexes the token id
+        let mut nft = transfer.clone();
+        let mut topics = nft.data.topics().to_vec();
+        topics.push(alloy::primitives::B256::with_last_byte(7));
+        nft.data = alloy::primitives::LogData::new_unchecked(topics, Default::default());
+        assert!(decode::decode_log(&nft).is_none());
+    }
+
+    #[test]
+    fn test_decode_revert_reasons() {
+        use super::{decode, IPermit2};
+        use crate::types::RevertKind;
+        use alloy::primitives::U256;
+        use alloy::sol_types::{Panic, PanicKind, Revert, SolError};
+
+        let stf = decode::decode_revert(
+            &Revert {
+                reason: "STF".to_string(),
+            }
+            .abi_encode(),
+        );
+        assert_eq!(stf.kind, RevertKind::Error);
+        assert_eq!(stf.message, "STF");
+        assert!(stf.explanation.unwrap().contains("approval"));
+
+        let panic = decode::decode_revert(&Panic::from(PanicKind::UnderOverflow).abi_encode());
+        assert_eq!(panic.kind, RevertKind::Panic);
+        assert_eq!(panic.message, "arithmetic underflow or overflow");
+
+        let custom = decode::decode_revert(
+            &IPermit2::InsufficientAllowance {
+                amount: U256::from(5u64),
+            }
+            .abi_encode(),
+        );
+        assert_eq!(custom.kind, RevertKind::Custom);
+        assert_eq!(custom.message, "InsufficientAllowance(uint256)");
+        assert_eq!(custom.arguments.unwrap()["amount"], "5");
+
+        let empty = decode::decode_revert(&[]);
+        assert_eq!(empty.kind, RevertKind::Unknown);
+        assert!(empty.data.is_empty());
+        assert_eq!(
+            decode::decode_revert(&[0x12, 0x34, 0x56, 0x78]).kind,
+            RevertKind::Unknown
+        );
+    }
+
     #[test]
     fn test_address_case_insensitive_parsing() {
         let contracts = get_test_contracts();
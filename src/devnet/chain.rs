@@ -0,0 +1,687 @@
+/// The simulated node's blocks, mempool and transaction execution
+use super::state::{Execution, Message, State};
+use super::BroadcastFault;
+use alloy::consensus::TxEnvelope;
+use alloy::eips::eip2718::Decodable2718;
+use alloy::primitives::{keccak256, Address, Bytes, Log, TxKind, B256, U256};
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::FeeHistory;
+use revm::primitives::{ExecutionResult, HaltReason, Output};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub(crate) const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+const TX_BASE_GAS: u64 = 21_000;
+
+/// Why a call or transaction was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChainError {
+    /// The call reverted with this data
+    Revert(Bytes),
+    /// The call needed more gas than it was given
+    OutOfGas,
+    /// The node refused the request; the message follows geth's wording
+    Rejected(String),
+}
+
+/// A call as `eth_call` and `eth_estimateGas` receive it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallRequest {
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub gas: Option<u64>,
+    /// Account and storage overrides applied for this call only
+    pub overrides: StateOverride,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MinedBlock {
+    pub number: u64,
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub timestamp: u64,
+    pub base_fee_per_gas: u128,
+    pub gas_used: u64,
+    pub transactions: Vec<B256>,
+}
+
+/// Where and how a mined transaction ended up
+#[derive(Debug, Clone)]
+pub(crate) struct Inclusion {
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub index: u64,
+    pub success: bool,
+    pub gas_used: u64,
+    pub cumulative_gas_used: u64,
+    pub effective_gas_price: u128,
+    pub logs: Vec<Log>,
+    /// The contract a successful creation deployed
+    pub contract_address: Option<Address>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TxRecord {
+    pub envelope: TxEnvelope,
+    pub from: Address,
+    pub inclusion: Option<Inclusion>,
+}
+
+/// The fields of a signed transaction the chain needs
+pub(crate) struct TxFields {
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub to: TxKind,
+    pub value: U256,
+    pub input: Bytes,
+    /// The gas price for legacy transactions
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl TxFields {
+    pub fn of(envelope: &TxEnvelope) -> Option<Self> {
+        match envelope {
+            TxEnvelope::Legacy(signed) => {
+                let tx = signed.tx();
+                Some(Self {
+                    chain_id: tx.chain_id,
+                    nonce: tx.nonce,
+                    gas_limit: tx.gas_limit as u64,
+                    to: tx.to,
+                    value: tx.value,
+                    input: tx.input.clone(),
+                    max_fee_per_gas: tx.gas_price,
+                    max_priority_fee_per_gas: tx.gas_price,
+                })
+            }
+            TxEnvelope::Eip1559(signed) => {
+                let tx = signed.tx();
+                Some(Self {
+                    chain_id: Some(tx.chain_id),
+                    nonce: tx.nonce,
+                    gas_limit: tx.gas_limit as u64,
+                    to: tx.to,
+                    value: tx.value,
+                    input: tx.input.clone(),
+                    max_fee_per_gas: tx.max_fee_per_gas,
+                    max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Price per gas paid at `base_fee`: the fee cap, or base fee plus tip if lower
+    fn effective_gas_price(&self, base_fee: u128) -> u128 {
+        self.max_fee_per_gas
+            .min(base_fee.saturating_add(self.max_priority_fee_per_gas))
+    }
+}
+
+/// Gas every transaction pays before execution: 21000 plus calldata
+fn intrinsic_gas(input: &[u8]) -> u64 {
+    TX_BASE_GAS
+        + input
+            .iter()
+            .map(|byte| if *byte == 0 { 4 } else { 16 })
+            .sum::<u64>()
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+/// Chain state behind the dev node. Blocks are produced on demand, every block has the
+/// same base fee, and only the latest state is kept: calls at older blocks see the
+/// current state.
+#[derive(Debug)]
+pub(crate) struct Chain {
+    pub chain_id: u64,
+    pub state: State,
+    pub blocks: Vec<MinedBlock>,
+    pub transactions: HashMap<B256, TxRecord>,
+    /// Unmined transactions in arrival order
+    pub pending: Vec<B256>,
+    /// Mine a block as soon as a transaction arrives
+    pub automine: bool,
+    pub base_fee_per_gas: u128,
+    /// How far behind the latest block the `finalized` tag trails
+    pub finality_depth: u64,
+    /// Fault injected into the next `eth_sendRawTransaction`
+    pub broadcast_fault: Option<BroadcastFault>,
+    /// `eth_call`s, as (target, input), that fail as if the node errored
+    pub failing_calls: Vec<(Address, Bytes)>,
+    /// How long each request takes to answer
+    pub latency: Duration,
+    /// Requests being answered, and the most there have been at once
+    pub in_flight: usize,
+    pub peak_in_flight: usize,
+}
+
+impl Chain {
+    pub fn new(chain_id: u64, base_fee_per_gas: u128) -> Self {
+        let genesis = MinedBlock {
+            number: 0,
+            hash: keccak256(chain_id.to_be_bytes()),
+            parent_hash: B256::ZERO,
+            timestamp: now(),
+            base_fee_per_gas,
+            gas_used: 0,
+            transactions: Vec::new(),
+        };
+        Self {
+            chain_id,
+            state: State::default(),
+            blocks: vec![genesis],
+            transactions: HashMap::new(),
+            pending: Vec::new(),
+            automine: true,
+            base_fee_per_gas,
+            finality_depth: 2,
+            broadcast_fault: None,
+            failing_calls: Vec::new(),
+            latency: Duration::ZERO,
+            in_flight: 0,
+            peak_in_flight: 0,
+        }
+    }
+
+    pub fn latest(&self) -> &MinedBlock {
+        self.blocks
+            .last()
+            .expect("chain always has a genesis block")
+    }
+
+    pub fn finalized(&self) -> &MinedBlock {
+        let number = self.latest().number.saturating_sub(self.finality_depth);
+        &self.blocks[number as usize]
+    }
+
+    pub fn block(&self, number: u64) -> Option<&MinedBlock> {
+        self.blocks.get(number as usize)
+    }
+
+    pub fn block_by_hash(&self, hash: &B256) -> Option<&MinedBlock> {
+        self.blocks.iter().find(|block| block.hash == *hash)
+    }
+
+    fn next_timestamp(&self) -> u64 {
+        now().max(self.latest().timestamp + 1)
+    }
+
+    /// An account's nonce, counting its contiguous pending transactions if `pending`
+    pub fn nonce(&self, address: Address, pending: bool) -> u64 {
+        let mut nonce = self.state.account(address).nonce;
+        if pending {
+            while self.pending_at(address, nonce).is_some() {
+                nonce += 1;
+            }
+        }
+        nonce
+    }
+
+    /// The pending transaction from `from` with `nonce`
+    fn pending_at(&self, from: Address, nonce: u64) -> Option<B256> {
+        self.pending.iter().copied().find(|hash| {
+            let record = &self.transactions[hash];
+            record.from == from
+                && TxFields::of(&record.envelope).is_some_and(|tx| tx.nonce == nonce)
+        })
+    }
+
+    /// The block calls run in: the next one, without a base fee, as geth runs calls
+    /// that set no gas price
+    fn call_execution(&self) -> Execution {
+        Execution {
+            chain_id: self.chain_id,
+            number: self.latest().number + 1,
+            timestamp: self.next_timestamp(),
+            base_fee_per_gas: 0,
+        }
+    }
+
+    /// The block set-up outside of any transaction runs in: the latest one, without fees
+    pub fn setup_execution(&self) -> Execution {
+        Execution {
+            chain_id: self.chain_id,
+            number: self.latest().number,
+            timestamp: self.latest().timestamp,
+            base_fee_per_gas: 0,
+        }
+    }
+
+    /// Run a call against a copy of the current state and return its output and gas
+    pub fn call(&self, request: &CallRequest) -> Result<(Bytes, u64), ChainError> {
+        let mut state = self.call_state(request)?;
+        let gas_limit = request.gas.unwrap_or(BLOCK_GAS_LIMIT);
+        Self::run_call(&mut state, &self.call_execution(), request, gas_limit)
+    }
+
+    /// The least gas a call succeeds with, found by bisection as geth does: refunds and
+    /// the 63/64 rule for nested calls let a call need more gas than it uses
+    pub fn estimate_gas(&self, request: &CallRequest) -> Result<u64, ChainError> {
+        let mut state = self.call_state(request)?;
+        let exec = self.call_execution();
+        let cap = request.gas.unwrap_or(BLOCK_GAS_LIMIT);
+        let (_, used) = Self::run_call(&mut state, &exec, request, cap)?;
+        let (mut low, mut high) = (used - 1, cap);
+        while low + 1 < high {
+            let middle = low + (high - low) / 2;
+            match Self::run_call(&mut state, &exec, request, middle) {
+                Ok(_) => high = middle,
+                Err(_) => low = middle,
+            }
+        }
+        Ok(high)
+    }
+
+    /// A copy of the current state with the call's overrides applied
+    fn call_state(&self, request: &CallRequest) -> Result<State, ChainError> {
+        let mut state = self.state.clone();
+        state.apply_overrides(&request.overrides);
+        if state.account(request.from.unwrap_or_default()).balance < request.value {
+            return Err(ChainError::Rejected(
+                "insufficient funds for gas * price + value".to_string(),
+            ));
+        }
+        Ok(state)
+    }
+
+    fn run_call(
+        state: &mut State,
+        exec: &Execution,
+        request: &CallRequest,
+        gas_limit: u64,
+    ) -> Result<(Bytes, u64), ChainError> {
+        let message = Message {
+            from: request.from.unwrap_or_default(),
+            to: request.to.map_or(TxKind::Create, TxKind::Call),
+            value: request.value,
+            input: request.input.clone(),
+            gas_limit,
+            ..Default::default()
+        };
+        match state
+            .transact(exec, message, false)
+            .map_err(ChainError::Rejected)?
+        {
+            ExecutionResult::Success {
+                output, gas_used, ..
+            } => Ok((output.into_data(), gas_used)),
+            ExecutionResult::Revert { output, .. } => Err(ChainError::Revert(output)),
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(_),
+                ..
+            } => Err(ChainError::OutOfGas),
+            ExecutionResult::Halt { .. } => Err(ChainError::Revert(Bytes::new())),
+        }
+    }
+
+    /// Validate a signed transaction and add it to the mempool, mining it right away
+    /// when automine is on
+    pub fn send_raw_transaction(&mut self, raw: &[u8]) -> Result<B256, ChainError> {
+        let reject = |message: &str| ChainError::Rejected(message.to_string());
+        let envelope = TxEnvelope::decode_2718(&mut &raw[..])
+            .map_err(|e| ChainError::Rejected(format!("invalid transaction: {}", e)))?;
+        let tx = TxFields::of(&envelope).ok_or_else(|| reject("transaction type not supported"))?;
+        let from = envelope
+            .recover_signer()
+            .map_err(|_| reject("invalid sender"))?;
+        let hash = keccak256(raw);
+
+        if self.transactions.contains_key(&hash) {
+            return Err(reject("already known"));
+        }
+        if tx.chain_id.is_some_and(|id| id != self.chain_id) {
+            return Err(reject("invalid chain id for signer"));
+        }
+        let account = self.state.account(from);
+        if tx.nonce < account.nonce {
+            return Err(ChainError::Rejected(format!(
+                "nonce too low: next nonce {}, tx nonce {}",
+                account.nonce, tx.nonce
+            )));
+        }
+        if tx.gas_limit < intrinsic_gas(&tx.input) {
+            return Err(reject("intrinsic gas too low"));
+        }
+        if tx.gas_limit > BLOCK_GAS_LIMIT {
+            return Err(reject("exceeds block gas limit"));
+        }
+        if tx.max_fee_per_gas < self.base_fee_per_gas {
+            return Err(reject("max fee per gas less than block base fee"));
+        }
+        let cost = U256::from(tx.gas_limit) * U256::from(tx.max_fee_per_gas) + tx.value;
+        if account.balance < cost {
+            return Err(reject("insufficient funds for gas * price + value"));
+        }
+
+        // Same sender and nonce replaces the pending transaction if both fees rise 10%
+        if let Some(existing) = self.pending_at(from, tx.nonce) {
+            let old = TxFields::of(&self.transactions[&existing].envelope)
+                .expect("pending transactions have supported types");
+            let bumped = |fee: u128| fee.saturating_add(fee.div_ceil(10));
+            if tx.max_fee_per_gas < bumped(old.max_fee_per_gas)
+                || tx.max_priority_fee_per_gas < bumped(old.max_priority_fee_per_gas)
+            {
+                return Err(reject("replacement transaction underpriced"));
+            }
+            self.forget(&existing);
+        }
+
+        self.transactions.insert(
+            hash,
+            TxRecord {
+                envelope,
+                from,
+                inclusion: None,
+            },
+        );
+        self.pending.push(hash);
+        if self.automine {
+            self.mine();
+        }
+        Ok(hash)
+    }
+
+    /// Remove a pending transaction as if the mempool evicted it
+    pub fn forget(&mut self, hash: &B256) -> bool {
+        let Some(position) = self.pending.iter().position(|pending| pending == hash) else {
+            return false;
+        };
+        self.pending.remove(position);
+        self.transactions.remove(hash);
+        true
+    }
+
+    /// Mine one block with every pending transaction that can run, in nonce order per
+    /// sender; returns its number
+    pub fn mine(&mut self) -> u64 {
+        let parent = self.latest().clone();
+        let number = parent.number + 1;
+        let timestamp = self.next_timestamp();
+        let hash = keccak256(
+            [
+                parent.hash.as_slice(),
+                &number.to_be_bytes(),
+                &timestamp.to_be_bytes(),
+            ]
+            .concat(),
+        );
+
+        // Transactions whose nonce was used by another one can never be mined
+        let stale: Vec<B256> = self
+            .pending
+            .iter()
+            .copied()
+            .filter(|pending| {
+                let record = &self.transactions[pending];
+                TxFields::of(&record.envelope)
+                    .is_some_and(|tx| tx.nonce < self.state.account(record.from).nonce)
+            })
+            .collect();
+        for pending in &stale {
+            self.forget(pending);
+        }
+
+        let exec = Execution {
+            chain_id: self.chain_id,
+            number,
+            timestamp,
+            base_fee_per_gas: self.base_fee_per_gas,
+        };
+        let mut included = Vec::new();
+        let mut gas_used = 0;
+        while let Some(next) = self.pending.iter().copied().find(|pending| {
+            let record = &self.transactions[pending];
+            TxFields::of(&record.envelope)
+                .is_some_and(|tx| tx.nonce == self.state.account(record.from).nonce)
+        }) {
+            self.pending.retain(|pending| *pending != next);
+            let Some(inclusion) = self.execute(&next, &exec, hash, included.len(), gas_used) else {
+                // It can no longer pay for its gas, so no block will take it
+                self.transactions.remove(&next);
+                continue;
+            };
+            gas_used = inclusion.cumulative_gas_used;
+            if let Some(record) = self.transactions.get_mut(&next) {
+                record.inclusion = Some(inclusion);
+            }
+            included.push(next);
+        }
+
+        self.blocks.push(MinedBlock {
+            number,
+            hash,
+            parent_hash: parent.hash,
+            timestamp,
+            base_fee_per_gas: self.base_fee_per_gas,
+            gas_used,
+            transactions: included,
+        });
+        number
+    }
+
+    /// Run a mined transaction in the EVM, which bumps the nonce and charges gas whether
+    /// or not it reverts; `None` if it can't be run at all
+    fn execute(
+        &mut self,
+        hash: &B256,
+        exec: &Execution,
+        block_hash: B256,
+        index: usize,
+        cumulative_gas_before: u64,
+    ) -> Option<Inclusion> {
+        let record = &self.transactions[hash];
+        let tx = TxFields::of(&record.envelope).expect("pending transactions have supported types");
+        let message = Message {
+            from: record.from,
+            to: tx.to,
+            value: tx.value,
+            input: tx.input.clone(),
+            gas_limit: tx.gas_limit,
+            gas_price: tx.max_fee_per_gas,
+            priority_fee: match record.envelope {
+                TxEnvelope::Legacy(_) => None,
+                _ => Some(tx.max_priority_fee_per_gas),
+            },
+            chain_id: tx.chain_id,
+            nonce: Some(tx.nonce),
+        };
+        let (success, gas_used, logs, contract_address) =
+            match self.state.transact(exec, message, true).ok()? {
+                ExecutionResult::Success {
+                    gas_used,
+                    logs,
+                    output,
+                    ..
+                } => match output {
+                    Output::Create(_, address) => (true, gas_used, logs, address),
+                    Output::Call(_) => (true, gas_used, logs, None),
+                },
+                ExecutionResult::Revert { gas_used, .. }
+                | ExecutionResult::Halt { gas_used, .. } => (false, gas_used, Vec::new(), None),
+            };
+
+        Some(Inclusion {
+            block_number: exec.number,
+            block_hash,
+            index: index as u64,
+            success,
+            gas_used,
+            cumulative_gas_used: cumulative_gas_before + gas_used,
+            effective_gas_price: tx.effective_gas_price(exec.base_fee_per_gas),
+            logs,
+            contract_address,
+        })
+    }
+
+    /// `eth_feeHistory` over `block_count` blocks ending at `newest`
+    pub fn fee_history(&self, block_count: u64, newest: u64, percentiles: &[f64]) -> FeeHistory {
+        let newest = newest.min(self.latest().number);
+        let oldest = (newest + 1).saturating_sub(block_count.max(1));
+        let blocks = &self.blocks[oldest as usize..=newest as usize];
+
+        let mut base_fee_per_gas: Vec<u128> =
+            blocks.iter().map(|block| block.base_fee_per_gas).collect();
+        base_fee_per_gas.push(self.base_fee_per_gas);
+        let reward = blocks
+            .iter()
+            .map(|block| {
+                let mut tips: Vec<u128> = block
+                    .transactions
+                    .iter()
+                    .filter_map(|hash| {
+                        let tx = TxFields::of(&self.transactions.get(hash)?.envelope)?;
+                        Some(
+                            tx.effective_gas_price(block.base_fee_per_gas)
+                                .saturating_sub(block.base_fee_per_gas),
+                        )
+                    })
+                    .collect();
+                tips.sort_unstable();
+                percentiles
+                    .iter()
+                    .map(|percentile| {
+                        // Empty blocks report zero, as geth does
+                        let index = ((percentile / 100.0) * tips.len() as f64) as usize;
+                        tips.get(index.min(tips.len().saturating_sub(1)))
+                            .copied()
+                            .unwrap_or(0)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        FeeHistory {
+            base_fee_per_gas,
+            gas_used_ratio: blocks
+                .iter()
+                .map(|block| block.gas_used as f64 / BLOCK_GAS_LIMIT as f64)
+                .collect(),
+            oldest_block: oldest,
+            reward: Some(reward),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::eips::eip2718::Encodable2718;
+    use alloy::network::{EthereumWallet, TransactionBuilder};
+    use alloy::rpc::types::TransactionRequest;
+    use alloy::signers::local::PrivateKeySigner;
+
+    const BASE_FEE: u128 = 1_000_000_000;
+
+    fn funded_chain(signer: &PrivateKeySigner) -> Chain {
+        let mut chain = Chain::new(1337, BASE_FEE);
+        chain.state.account_mut(signer.address()).balance = U256::from(10u128.pow(20));
+        chain
+    }
+
+    async fn transfer(signer: &PrivateKeySigner, nonce: u64, max_fee: u128, tip: u128) -> Vec<u8> {
+        TransactionRequest::default()
+            .with_to(Address::repeat_byte(0x11))
+            .with_value(U256::from(1))
+            .with_nonce(nonce)
+            .with_chain_id(1337)
+            .with_gas_limit(21_000)
+            .with_max_fee_per_gas(max_fee)
+            .with_max_priority_fee_per_gas(tip)
+            .build(&EthereumWallet::from(signer.clone()))
+            .await
+            .unwrap()
+            .encoded_2718()
+    }
+
+    #[tokio::test]
+    async fn test_automine_includes_and_bumps_nonce() {
+        let signer = PrivateKeySigner::random();
+        let mut chain = funded_chain(&signer);
+        let hash = chain
+            .send_raw_transaction(&transfer(&signer, 0, BASE_FEE * 2, 1).await)
+            .unwrap();
+
+        assert_eq!(chain.latest().number, 1);
+        assert_eq!(chain.nonce(signer.address(), false), 1);
+        let inclusion = chain.transactions[&hash].inclusion.as_ref().unwrap();
+        assert!(inclusion.success);
+        assert_eq!(inclusion.gas_used, 21_000);
+        assert_eq!(
+            chain.state.account(Address::repeat_byte(0x11)).balance,
+            U256::from(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_nonce_too_low() {
+        let signer = PrivateKeySigner::random();
+        let mut chain = funded_chain(&signer);
+        chain
+            .send_raw_transaction(&transfer(&signer, 0, BASE_FEE * 2, 1).await)
+            .unwrap();
+
+        let error = chain
+            .send_raw_transaction(&transfer(&signer, 0, BASE_FEE * 3, 2).await)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ChainError::Rejected("nonce too low: next nonce 1, tx nonce 0".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replacement_needs_ten_percent_bump() {
+        let signer = PrivateKeySigner::random();
+        let mut chain = funded_chain(&signer);
+        chain.automine = false;
+        let original = chain
+            .send_raw_transaction(&transfer(&signer, 0, 2_000_000_000, 100).await)
+            .unwrap();
+
+        let underpriced = chain
+            .send_raw_transaction(&transfer(&signer, 0, 2_100_000_000, 110).await)
+            .unwrap_err();
+        assert_eq!(
+            underpriced,
+            ChainError::Rejected("replacement transaction underpriced".to_string())
+        );
+
+        let replacement = chain
+            .send_raw_transaction(&transfer(&signer, 0, 2_200_000_000, 110).await)
+            .unwrap();
+        assert_eq!(chain.pending, vec![replacement]);
+        assert!(!chain.transactions.contains_key(&original));
+
+        chain.mine();
+        assert!(chain.pending.is_empty());
+        assert_eq!(
+            chain.transactions[&replacement]
+                .inclusion
+                .as_ref()
+                .unwrap()
+                .block_number,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_fee_below_base_fee() {
+        let signer = PrivateKeySigner::random();
+        let mut chain = funded_chain(&signer);
+        let error = chain
+            .send_raw_transaction(&transfer(&signer, 0, BASE_FEE - 1, 0).await)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ChainError::Rejected("max fee per gas less than block base fee".to_string())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {PoolMath} from "Shared.sol";
+
+/// Balancer V2's Vault with the calls the server quotes through. It holds every pool's
+/// tokens and tracks each pool's balances, as the real Vault does; its pools are
+/// two-token 50/50 weighted pools, which price like constant-product pools. Errors use
+/// Balancer's `BAL#` codes.
+contract BalancerVault {
+    struct BatchSwapStep {
+        bytes32 poolId;
+        uint256 assetInIndex;
+        uint256 assetOutIndex;
+        uint256 amount;
+        bytes userData;
+    }
+
+    struct FundManagement {
+        address sender;
+        bool fromInternalBalance;
+        address recipient;
+        bool toInternalBalance;
+    }
+
+    address public immutable owner;
+    mapping(bytes32 => mapping(uint256 => address)) private poolTokens;
+    mapping(bytes32 => mapping(uint256 => uint256)) private poolBalances;
+    /// In hundredths of a bip, like Uniswap V3 fees
+    mapping(bytes32 => uint256) private poolFees;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    /// Register a pool over tokens the Vault already holds
+    function registerPool(
+        bytes32 poolId,
+        address tokenA,
+        address tokenB,
+        uint256 balanceA,
+        uint256 balanceB,
+        uint256 fee
+    ) external {
+        require(msg.sender == owner, "BalancerVault: caller is not the owner");
+        poolTokens[poolId][0] = tokenA;
+        poolTokens[poolId][1] = tokenB;
+        poolBalances[poolId][0] = balanceA;
+        poolBalances[poolId][1] = balanceB;
+        poolFees[poolId] = fee;
+    }
+
+    function getPoolTokens(bytes32 poolId)
+        external
+        view
+        returns (address[] memory tokens, uint256[] memory balances, uint256 lastChangeBlock)
+    {
+        _ensurePool(poolId);
+        tokens = new address[](2);
+        balances = new uint256[](2);
+        tokens[0] = poolTokens[poolId][0];
+        tokens[1] = poolTokens[poolId][1];
+        balances[0] = poolBalances[poolId][0];
+        balances[1] = poolBalances[poolId][1];
+        lastChangeBlock = 0;
+    }
+
+    /// GIVEN_IN (`kind` 0) only. Each step prices against the pool's balances before the
+    /// batch, and a zero amount takes the previous step's output. The steps are read
+    /// from calldata by offset, since solar doesn't decode structs with a dynamic member.
+    function queryBatchSwap(
+        uint8 kind,
+        BatchSwapStep[] calldata,
+        address[] calldata assets,
+        FundManagement calldata
+    ) external returns (int256[] memory assetDeltas) {
+        require(kind == 0);
+        uint256 swaps;
+        assembly {
+            swaps := add(4, calldataload(36))
+        }
+        assetDeltas = new int256[](assets.length);
+        uint256 previousOut = 0;
+        for (uint256 k = 0; k < _word(swaps, 0); k++) {
+            uint256 step = swaps + 32 + _word(swaps, k + 1);
+            bytes32 poolId = bytes32(_word(step, 0));
+            _ensurePool(poolId);
+            uint256 assetIn = _word(step, 1);
+            uint256 assetOut = _word(step, 2);
+            require(assetIn < assets.length && assetOut < assets.length, "BAL#100");
+            uint256 amount = _word(step, 3);
+            if (amount == 0) {
+                amount = previousOut;
+            }
+            uint256 indexIn = _tokenIndex(poolId, assets[assetIn]);
+            uint256 indexOut = _tokenIndex(poolId, assets[assetOut]);
+            previousOut = PoolMath.amountOut(
+                poolFees[poolId], poolBalances[poolId][indexIn], poolBalances[poolId][indexOut], amount
+            );
+            require(amount < 2 ** 255 && previousOut < 2 ** 255);
+            assetDeltas[assetIn] += int256(amount);
+            assetDeltas[assetOut] -= int256(previousOut);
+        }
+    }
+
+    function _ensurePool(bytes32 poolId) private view {
+        require(poolTokens[poolId][0] != address(0), "BAL#500");
+    }
+
+    /// Where `token` sits in the pool
+    function _tokenIndex(bytes32 poolId, address token) private view returns (uint256) {
+        require(token == poolTokens[poolId][0] || token == poolTokens[poolId][1], "BAL#521");
+        return token == poolTokens[poolId][0] ? 0 : 1;
+    }
+
+    /// Word `index` of the calldata at `offset`
+    function _word(uint256 offset, uint256 index) private pure returns (uint256 word) {
+        assembly {
+            word := calldataload(add(offset, mul(index, 0x20)))
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {TokenCalls} from "Shared.sol";
+
+/// Curve's 3pool, quoting only. Near balance StableSwap trades almost one for one, so
+/// this prices as constant-sum over the coins' 18-decimal amounts, less the fee. Its
+/// reserves are its token balances, and like the Vyper pool it reverts without data on a
+/// bad coin index or a quote the pool can't pay.
+contract CurvePool is TokenCalls {
+    uint256 private constant N_COINS = 3;
+    uint256 private constant FEE_DENOMINATOR = 10 ** 10;
+
+    address private coin0;
+    address private coin1;
+    address private coin2;
+    /// Over `FEE_DENOMINATOR`, as Curve counts it
+    uint256 public fee;
+
+    constructor(address coin0_, address coin1_, address coin2_, uint256 fee_) {
+        coin0 = coin0_;
+        coin1 = coin1_;
+        coin2 = coin2_;
+        fee = fee_;
+    }
+
+    function coins(uint256 i) public view returns (address coin) {
+        require(i < N_COINS);
+        coin = coin0;
+        if (i == 1) {
+            coin = coin1;
+        } else if (i == 2) {
+            coin = coin2;
+        }
+    }
+
+    function get_dy(int128 i, int128 j, uint256 dx) external view returns (uint256 dy) {
+        require(i != j && i >= 0 && j >= 0);
+        address from = coins(uint256(int256(i)));
+        address to = coins(uint256(int256(j)));
+        dy = dx * _precision(from) * (FEE_DENOMINATOR - fee) / FEE_DENOMINATOR / _precision(to);
+        require(dy < _balanceOf(to, address(this)));
+    }
+
+    /// What scales one of `token`'s units to 18 decimals
+    function _precision(address token) private view returns (uint256) {
+        return 10 ** (18 - _decimals(token));
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Signatures, TokenCalls} from "Shared.sol";
+
+/// Uniswap's Permit2, with the allowance transfers the server signs for: `permit` for a
+/// `PermitSingle`, `allowance` and `transferFrom`. Each allowance packs its amount,
+/// expiration and nonce into one word as Permit2 does. `permit` reads its calldata by
+/// offset, since solar doesn't decode a struct followed by dynamic bytes.
+contract Permit2 is TokenCalls {
+    error AllowanceExpired(uint256 deadline);
+    error InsufficientAllowance(uint256 amount);
+    error InvalidNonce();
+    error SignatureExpired(uint256 signatureDeadline);
+    error InvalidSignature();
+    error InvalidSigner();
+    error InvalidSignatureLength();
+
+    struct PermitDetails {
+        address token;
+        uint160 amount;
+        uint48 expiration;
+        uint48 nonce;
+    }
+
+    struct PermitSingle {
+        PermitDetails details;
+        address spender;
+        uint256 sigDeadline;
+    }
+
+    bytes32 public constant _PERMIT_DETAILS_TYPEHASH =
+        keccak256("PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)");
+    bytes32 public constant _PERMIT_SINGLE_TYPEHASH = keccak256(
+        "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)"
+    );
+    bytes32 private constant _TYPE_HASH = keccak256("EIP712Domain(string name,uint256 chainId,address verifyingContract)");
+    bytes32 private constant _HASHED_NAME = keccak256("Permit2");
+
+    mapping(address => mapping(address => mapping(address => uint256))) private packedAllowances;
+
+    function DOMAIN_SEPARATOR() public view returns (bytes32) {
+        uint256 chainId;
+        assembly {
+            chainId := chainid()
+        }
+        return keccak256(abi.encode(_TYPE_HASH, _HASHED_NAME, chainId, address(this)));
+    }
+
+    function allowance(address user, address token, address spender)
+        external
+        view
+        returns (uint160 amount, uint48 expiration, uint48 nonce)
+    {
+        uint256 packed = packedAllowances[user][token][spender];
+        amount = uint160(packed);
+        expiration = uint48(packed >> 160);
+        nonce = uint48(packed >> 208);
+    }
+
+    /// Calldata words: the owner, the details' token, amount, expiration and nonce, the
+    /// spender, the signature deadline and the signature's offset
+    function permit(address, PermitSingle calldata, bytes calldata) external {
+        uint256 sigDeadline = _word(6);
+        if (block.timestamp > sigDeadline) {
+            revert SignatureExpired(sigDeadline);
+        }
+        address owner = address(uint160(_word(0)));
+        address token = address(uint160(_word(1)));
+        address spender = address(uint160(_word(5)));
+        bytes32 details = keccak256(abi.encode(_PERMIT_DETAILS_TYPEHASH, token, _word(2), _word(3), _word(4)));
+        bytes32 structHash = keccak256(abi.encode(_PERMIT_SINGLE_TYPEHASH, details, spender, sigDeadline));
+        address signer = _signer(keccak256(abi.encodePacked("\x19\x01", DOMAIN_SEPARATOR(), structHash)));
+        if (signer == address(0)) {
+            revert InvalidSignature();
+        }
+        if (signer != owner) {
+            revert InvalidSigner();
+        }
+
+        uint256 nonce = packedAllowances[owner][token][spender] >> 208;
+        if (nonce != _word(4)) {
+            revert InvalidNonce();
+        }
+        // An expiration of zero lasts until the end of this block
+        uint256 expiration = _word(3);
+        if (expiration == 0) {
+            expiration = block.timestamp;
+        }
+        packedAllowances[owner][token][spender] = uint160(_word(2)) | (expiration << 160) | ((nonce + 1) << 208);
+    }
+
+    function transferFrom(address from, address to, uint160 amount, address token) external {
+        uint256 packed = packedAllowances[from][token][msg.sender];
+        uint256 expiration = uint48(packed >> 160);
+        if (block.timestamp > expiration) {
+            revert AllowanceExpired(expiration);
+        }
+        uint256 maxAmount = uint160(packed);
+        if (maxAmount != type(uint160).max) {
+            if (amount > maxAmount) {
+                revert InsufficientAllowance(maxAmount);
+            }
+            packedAllowances[from][token][msg.sender] = packed - amount;
+        }
+        require(_transferFrom(token, from, to, amount), "TRANSFER_FROM_FAILED");
+    }
+
+    /// Recover the signer of `digest` from `permit`'s 65-byte or EIP-2098 64-byte signature
+    function _signer(bytes32 digest) private view returns (address) {
+        uint256 signature = 4 + _word(7);
+        uint256 length = _calldataWord(signature);
+        bytes32 r = bytes32(_calldataWord(signature + 32));
+        uint256 s = _calldataWord(signature + 64);
+        uint256 v;
+        if (length == 65) {
+            v = _calldataWord(signature + 96) >> 248;
+        } else if (length == 64) {
+            v = (s >> 255) + 27;
+            s = s & (2 ** 255 - 1);
+        } else {
+            revert InvalidSignatureLength();
+        }
+        return Signatures.recover(digest, v, r, bytes32(s));
+    }
+
+    /// Argument word `index` of the call
+    function _word(uint256 index) private pure returns (uint256) {
+        return _calldataWord(4 + index * 32);
+    }
+
+    function _calldataWord(uint256 offset) private pure returns (uint256 word) {
+        assembly {
+            word := calldataload(offset)
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// A Chainlink aggregator whose answer the deployer sets; every update starts a new
+/// round, answered at the block it lands in
+contract FixturePriceFeed {
+    address public immutable owner;
+    uint8 public immutable decimals;
+    string public description;
+    int256 public latestAnswer;
+    uint80 public latestRound;
+    uint256 public latestTimestamp;
+
+    constructor(string memory description_, uint8 decimals_, int256 answer) {
+        owner = msg.sender;
+        description = description_;
+        decimals = decimals_;
+        latestAnswer = answer;
+        latestRound = 1;
+        latestTimestamp = block.timestamp;
+    }
+
+    function latestRoundData()
+        external
+        view
+        returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound)
+    {
+        roundId = latestRound;
+        answer = latestAnswer;
+        startedAt = latestTimestamp;
+        updatedAt = latestTimestamp;
+        answeredInRound = latestRound;
+    }
+
+    function updateAnswer(int256 answer) external {
+        require(msg.sender == owner, "FixturePriceFeed: caller is not the owner");
+        latestAnswer = answer;
+        latestRound += 1;
+        latestTimestamp = block.timestamp;
+    }
+}
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Pricing shared by the pool fixtures. Every pool prices like a constant-product pool
+/// over its reserves; for a Uniswap V3 pool that is a single full-range position.
+/// Branches and loops are written in assembly, which solar compiles more reliably than
+/// the equivalent Solidity.
+library PoolMath {
+    /// Fees are in hundredths of a bip, as Uniswap V3 counts them
+    uint256 internal constant FEE_DENOMINATOR = 1_000_000;
+
+    /// Output for an exact input, after the fee
+    function amountOut(uint256 fee, uint256 reserveIn, uint256 reserveOut, uint256 amountIn)
+        internal
+        pure
+        returns (uint256 out)
+    {
+        uint256 afterFee = amountIn * (FEE_DENOMINATOR - fee) / FEE_DENOMINATOR;
+        uint256 denominator = reserveIn + afterFee;
+        assembly {
+            if denominator {
+                out := div(mul(reserveOut, afterFee), denominator)
+            }
+        }
+    }
+
+    /// Input needed for an exact output, including the fee. Callers check that the
+    /// pool holds more than `out` and has some of the input token.
+    function amountIn(uint256 fee, uint256 reserveIn, uint256 reserveOut, uint256 out)
+        internal
+        pure
+        returns (uint256 amount)
+    {
+        uint256 numerator = reserveIn * out;
+        uint256 remaining = reserveOut - out;
+        uint256 beforeFee = numerator / remaining;
+        assembly {
+            if mod(numerator, remaining) {
+                beforeFee := add(beforeFee, 1)
+            }
+        }
+        uint256 scaled = beforeFee * FEE_DENOMINATOR;
+        uint256 divisor = FEE_DENOMINATOR - fee;
+        amount = scaled / divisor;
+        assembly {
+            if mod(scaled, divisor) {
+                amount := add(amount, 1)
+            }
+        }
+    }
+
+    /// Integer square root, rounded down
+    function sqrt(uint256 x) internal pure returns (uint256 z) {
+        assembly {
+            z := x
+            let y := shr(1, add(x, 1))
+            if iszero(x) {
+                y := 0
+            }
+            for {} lt(y, z) {} {
+                z := y
+                y := shr(1, add(div(x, y), y))
+            }
+        }
+    }
+
+    /// Liquidity of a full-range position holding both reserves
+    function liquidity(uint256 reserve0, uint256 reserve1) internal pure returns (uint256) {
+        return sqrt(reserve0 * reserve1);
+    }
+
+    /// `sqrt(reserve1 / reserve0)` as Q64.96, which is `liquidity / reserve0` for a
+    /// full-range position; zero for an empty pool
+    function sqrtPriceX96(uint256 reserve0, uint256 reserve1) internal pure returns (uint256 price) {
+        uint256 shifted = liquidity(reserve0, reserve1) << 96;
+        assembly {
+            if reserve0 {
+                price := div(shifted, reserve0)
+            }
+        }
+    }
+
+    /// Uniswap's `TickMath.getTickAtSqrtRatio`: the greatest tick whose price is at or
+    /// below `price`. Empty pools, whose price is zero, sit at tick 0.
+    function tick(uint256 price) internal pure returns (int24 result) {
+        assembly {
+            function sqrtRatioAtTick(t) -> sqrtRatio {
+                let absTick := t
+                if slt(t, 0) {
+                    absTick := sub(0, t)
+                }
+                let ratio := 0x100000000000000000000000000000000
+                if and(absTick, 0x1) {
+                    ratio := 0xfffcb933bd6fad37aa2d162d1a594001
+                }
+                if and(absTick, 0x2) {
+                    ratio := shr(128, mul(ratio, 0xfff97272373d413259a46990580e213a))
+                }
+                if and(absTick, 0x4) {
+                    ratio := shr(128, mul(ratio, 0xfff2e50f5f656932ef12357cf3c7fdcc))
+                }
+                if and(absTick, 0x8) {
+                    ratio := shr(128, mul(ratio, 0xffe5caca7e10e4e61c3624eaa0941cd0))
+                }
+                if and(absTick, 0x10) {
+                    ratio := shr(128, mul(ratio, 0xffcb9843d60f6159c9db58835c926644))
+                }
+                if and(absTick, 0x20) {
+                    ratio := shr(128, mul(ratio, 0xff973b41fa98c081472e6896dfb254c0))
+                }
+                if and(absTick, 0x40) {
+                    ratio := shr(128, mul(ratio, 0xff2ea16466c96a3843ec78b326b52861))
+                }
+                if and(absTick, 0x80) {
+                    ratio := shr(128, mul(ratio, 0xfe5dee046a99a2a811c461f1969c3053))
+                }
+                if and(absTick, 0x100) {
+                    ratio := shr(128, mul(ratio, 0xfcbe86c7900a88aedcffc83b479aa3a4))
+                }
+                if and(absTick, 0x200) {
+                    ratio := shr(128, mul(ratio, 0xf987a7253ac413176f2b074cf7815e54))
+                }
+                if and(absTick, 0x400) {
+                    ratio := shr(128, mul(ratio, 0xf3392b0822b70005940c7a398e4b70f3))
+                }
+                if and(absTick, 0x800) {
+                    ratio := shr(128, mul(ratio, 0xe7159475a2c29b7443b29c7fa6e889d9))
+                }
+                if and(absTick, 0x1000) {
+                    ratio := shr(128, mul(ratio, 0xd097f3bdfd2022b8845ad8f792aa5825))
+                }
+                if and(absTick, 0x2000) {
+                    ratio := shr(128, mul(ratio, 0xa9f746462d870fdf8a65dc1f90e061e5))
+                }
+                if and(absTick, 0x4000) {
+                    ratio := shr(128, mul(ratio, 0x70d869a156d2a1b890bb3df62baf32f7))
+                }
+                if and(absTick, 0x8000) {
+                    ratio := shr(128, mul(ratio, 0x31be135f97d08fd981231505542fcfa6))
+                }
+                if and(absTick, 0x10000) {
+                    ratio := shr(128, mul(ratio, 0x9aa508b5b7a84e1c677de54f3e99bc9))
+                }
+                if and(absTick, 0x20000) {
+                    ratio := shr(128, mul(ratio, 0x5d6af8dedb81196699c329225ee604))
+                }
+                if and(absTick, 0x40000) {
+                    ratio := shr(128, mul(ratio, 0x2216e584f5fa1ea926041bedfe98))
+                }
+                if and(absTick, 0x80000) {
+                    ratio := shr(128, mul(ratio, 0x48a170391f7dc42444e8fa2))
+                }
+                if sgt(t, 0) {
+                    ratio := div(not(0), ratio)
+                }
+                // Round up, so the tick of the result is `t`
+                sqrtRatio := add(shr(32, ratio), iszero(iszero(and(ratio, 0xffffffff))))
+            }
+
+            // Prices outside the tick range clamp to its ends
+            let p := price
+            if lt(p, 4295128739) {
+                p := 4295128739
+            }
+            if iszero(lt(p, 1461446703485210103287273052203988822378723970342)) {
+                p := 1461446703485210103287273052203988822378723970341
+            }
+
+            let ratio := shl(32, p)
+            let r := ratio
+            let msb := 0
+            let f := shl(7, gt(r, 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(6, gt(r, 0xFFFFFFFFFFFFFFFF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(5, gt(r, 0xFFFFFFFF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(4, gt(r, 0xFFFF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(3, gt(r, 0xFF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(2, gt(r, 0xF))
+            msb := or(msb, f)
+            r := shr(f, r)
+            f := shl(1, gt(r, 0x3))
+            msb := or(msb, f)
+            r := shr(f, r)
+            msb := or(msb, gt(r, 0x1))
+
+            switch lt(msb, 128)
+            case 0 {
+                r := shr(sub(msb, 127), ratio)
+            }
+            default {
+                r := shl(sub(127, msb), ratio)
+            }
+
+            let logTwo := shl(64, sub(msb, 128))
+            for { let bit := 63 } gt(bit, 49) { bit := sub(bit, 1) } {
+                r := shr(127, mul(r, r))
+                f := shr(128, r)
+                logTwo := or(logTwo, shl(bit, f))
+                r := shr(f, r)
+            }
+
+            let logSqrt10001 := mul(logTwo, 255738958999603826347141)
+            let tickLow := sar(128, sub(logSqrt10001, 3402992956809132418596140100660247210))
+            let tickHigh := sar(128, add(logSqrt10001, 291339464771989622907027621153398088495))
+            result := tickLow
+            if iszero(eq(tickLow, tickHigh)) {
+                if iszero(gt(sqrtRatioAtTick(tickHigh), p)) {
+                    result := tickHigh
+                }
+            }
+            if iszero(price) {
+                result := 0
+            }
+        }
+    }
+}
+
+/// ERC-20 calls made in assembly with explicit success checks. Solar 0.2 neither checks
+/// the success of high-level external calls nor decodes all of their return data, so
+/// the fixtures never make one. The transfer helpers settle `ok` with a short-circuit so
+/// solar never inlines them into a caller whose token address is another call's result.
+abstract contract TokenCalls {
+    /// `token.balanceOf(account)`, bubbling up a revert
+    function _balanceOf(address token, address account) internal view returns (uint256 amount) {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0x70a08231))
+            mstore(add(ptr, 4), account)
+            if iszero(staticcall(gas(), token, ptr, 36, 0, 0x20)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            if lt(returndatasize(), 0x20) {
+                revert(0, 0)
+            }
+            amount := mload(0)
+        }
+    }
+
+    /// `token.decimals()`
+    function _decimals(address token) internal view returns (uint256 decimals) {
+        assembly {
+            mstore(0, shl(224, 0x313ce567))
+            if iszero(staticcall(gas(), token, 0, 4, 0, 0x20)) {
+                returndatacopy(0, 0, returndatasize())
+                revert(0, returndatasize())
+            }
+            if lt(returndatasize(), 0x20) {
+                revert(0, 0)
+            }
+            decimals := mload(0)
+        }
+    }
+
+    /// `token.transfer(to, amount)`; false if it reverts or returns false
+    function _transfer(address token, address to, uint256 amount) internal returns (bool ok) {
+        bool success;
+        uint256 size;
+        uint256 word;
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0xa9059cbb))
+            mstore(add(ptr, 4), to)
+            mstore(add(ptr, 36), amount)
+            mstore(0, 1)
+            success := call(gas(), token, 0, ptr, 68, 0, 0x20)
+            size := returndatasize()
+            word := mload(0)
+        }
+        ok = success && (size == 0 || word == 1);
+    }
+
+    /// `token.transferFrom(from, to, amount)`; false if it reverts or returns false
+    function _transferFrom(address token, address from, address to, uint256 amount) internal returns (bool ok) {
+        bool success;
+        uint256 size;
+        uint256 word;
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0x23b872dd))
+            mstore(add(ptr, 4), from)
+            mstore(add(ptr, 36), to)
+            mstore(add(ptr, 68), amount)
+            mstore(0, 1)
+            success := call(gas(), token, 0, ptr, 100, 0, 0x20)
+            size := returndatasize()
+            word := mload(0)
+        }
+        ok = success && (size == 0 || word == 1);
+    }
+
+    /// Send `amount` wei to `to`; false if the call fails
+    function _sendEther(address to, uint256 amount) internal returns (bool ok) {
+        assembly {
+            ok := call(gas(), to, amount, 0, 0, 0, 0)
+        }
+    }
+
+    /// `weth.deposit{value: amount}()`, bubbling up a revert
+    function _deposit(address weth, uint256 amount) internal {
+        assembly {
+            mstore(0, shl(224, 0xd0e30db0))
+            if iszero(call(gas(), weth, amount, 0, 4, 0, 0)) {
+                returndatacopy(0, 0, returndatasize())
+                revert(0, returndatasize())
+            }
+        }
+    }
+
+    /// `weth.withdraw(amount)`, bubbling up a revert
+    function _withdraw(address weth, uint256 amount) internal {
+        assembly {
+            mstore(0, shl(224, 0x2e1a7d4d))
+            mstore(4, amount)
+            if iszero(call(gas(), weth, 0, 0, 36, 0, 0)) {
+                returndatacopy(0, 0, returndatasize())
+                revert(0, returndatasize())
+            }
+        }
+    }
+}
+
+/// Signature recovery through the ecrecover precompile, which solar's `ecrecover`
+/// builtin doesn't reach
+library Signatures {
+    /// The signer of `digest`, or address zero for an invalid signature
+    function recover(bytes32 digest, uint256 v, bytes32 r, bytes32 s) internal view returns (address signer) {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, digest)
+            mstore(add(ptr, 0x20), v)
+            mstore(add(ptr, 0x40), r)
+            mstore(add(ptr, 0x60), s)
+            mstore(0, 0)
+            if iszero(staticcall(gas(), 1, ptr, 0x80, 0, 0x20)) {
+                revert(0, 0)
+            }
+            signer := mload(0)
+        }
+    }
+}
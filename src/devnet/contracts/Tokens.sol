@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Signatures} from "Shared.sol";
+
+/// ERC-20 whose balance and allowance mappings sit at the storage slots given to the
+/// constructor, so one contract stands in for tokens with different layouts. Only the
+/// deployer can mint.
+contract FixtureToken {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    string public name;
+    string public symbol;
+    uint256 public totalSupply;
+    uint8 public immutable decimals;
+    address public immutable minter;
+    uint256 private immutable balanceSlot;
+    uint256 private immutable allowanceSlot;
+
+    constructor(
+        string memory name_,
+        string memory symbol_,
+        uint8 decimals_,
+        uint256 balanceSlot_,
+        uint256 allowanceSlot_
+    ) {
+        name = name_;
+        symbol = symbol_;
+        decimals = decimals_;
+        minter = msg.sender;
+        balanceSlot = balanceSlot_;
+        allowanceSlot = allowanceSlot_;
+    }
+
+    function balanceOf(address account) public view returns (uint256 amount) {
+        bytes32 slot = keccak256(abi.encode(account, balanceSlot));
+        assembly {
+            amount := sload(slot)
+        }
+    }
+
+    function allowance(address owner, address spender) public view returns (uint256 amount) {
+        bytes32 slot = _allowanceLocation(owner, spender);
+        assembly {
+            amount := sload(slot)
+        }
+    }
+
+    function approve(address spender, uint256 amount) external returns (bool) {
+        _approve(msg.sender, spender, amount);
+        return true;
+    }
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        _transfer(msg.sender, to, amount);
+        return true;
+    }
+
+    function transferFrom(address from, address to, uint256 amount) external returns (bool) {
+        uint256 allowed = allowance(from, msg.sender);
+        if (allowed != type(uint256).max) {
+            require(allowed >= amount, "ERC20: insufficient allowance");
+            _approve(from, msg.sender, allowed - amount);
+        }
+        _transfer(from, to, amount);
+        return true;
+    }
+
+    function mint(address to, uint256 amount) external {
+        require(msg.sender == minter, "FixtureToken: caller is not the minter");
+        totalSupply += amount;
+        _setBalance(to, balanceOf(to) + amount);
+        emit Transfer(address(0), to, amount);
+    }
+
+    function _transfer(address from, address to, uint256 amount) internal {
+        uint256 balance = balanceOf(from);
+        require(balance >= amount, "ERC20: transfer amount exceeds balance");
+        _setBalance(from, balance - amount);
+        _setBalance(to, balanceOf(to) + amount);
+        emit Transfer(from, to, amount);
+    }
+
+    function _approve(address owner, address spender, uint256 amount) internal {
+        bytes32 slot = _allowanceLocation(owner, spender);
+        assembly {
+            sstore(slot, amount)
+        }
+        emit Approval(owner, spender, amount);
+    }
+
+    function _setBalance(address account, uint256 amount) private {
+        bytes32 slot = keccak256(abi.encode(account, balanceSlot));
+        assembly {
+            sstore(slot, amount)
+        }
+    }
+
+    /// Where `allowance[owner][spender]` lives, as Solidity lays out nested mappings
+    function _allowanceLocation(address owner, address spender) private view returns (bytes32) {
+        return keccak256(abi.encode(spender, keccak256(abi.encode(owner, allowanceSlot))));
+    }
+}
+
+/// A [`FixtureToken`] with EIP-2612 permits, signed over the domain `version` names
+contract FixturePermitToken is FixtureToken {
+    bytes32 public constant PERMIT_TYPEHASH =
+        keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)");
+    bytes32 private constant DOMAIN_TYPEHASH =
+        keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+
+    string public version;
+    mapping(address => uint256) public nonces;
+
+    constructor(
+        string memory name_,
+        string memory symbol_,
+        uint8 decimals_,
+        uint256 balanceSlot_,
+        uint256 allowanceSlot_,
+        string memory version_
+    ) FixtureToken(name_, symbol_, decimals_, balanceSlot_, allowanceSlot_) {
+        version = version_;
+    }
+
+    function DOMAIN_SEPARATOR() public view returns (bytes32) {
+        uint256 chainId;
+        assembly {
+            chainId := chainid()
+        }
+        return keccak256(
+            abi.encode(
+                DOMAIN_TYPEHASH,
+                keccak256(bytes(name)),
+                keccak256(bytes(version)),
+                chainId,
+                address(this)
+            )
+        );
+    }
+
+    function permit(
+        address owner,
+        address spender,
+        uint256 value,
+        uint256 deadline,
+        uint8 v,
+        bytes32 r,
+        bytes32 s
+    ) external {
+        require(block.timestamp <= deadline, "ERC20Permit: expired deadline");
+        bytes32 structHash =
+            keccak256(abi.encode(PERMIT_TYPEHASH, owner, spender, value, nonces[owner], deadline));
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", DOMAIN_SEPARATOR(), structHash));
+        address signer = Signatures.recover(digest, v, r, s);
+        require(signer != address(0) && signer == owner, "ERC20Permit: invalid signature");
+        nonces[owner] += 1;
+        _approve(owner, spender, value);
+    }
+}
+
+/// Wrapped Ether as deployed on mainnet (WETH9), with its storage layout: `balanceOf`
+/// at slot 3 and `allowance` at slot 4
+contract WETH9 {
+    event Approval(address indexed src, address indexed guy, uint256 wad);
+    event Transfer(address indexed src, address indexed dst, uint256 wad);
+    event Deposit(address indexed dst, uint256 wad);
+    event Withdrawal(address indexed src, uint256 wad);
+
+    string public name;
+    string public symbol;
+    uint8 public decimals;
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    constructor() {
+        string memory name_ = "Wrapped Ether";
+        string memory symbol_ = "WETH";
+        name = name_;
+        symbol = symbol_;
+        decimals = 18;
+    }
+
+    receive() external payable {
+        deposit();
+    }
+
+    function deposit() public payable {
+        balanceOf[msg.sender] += msg.value;
+        emit Deposit(msg.sender, msg.value);
+    }
+
+    function withdraw(uint256 wad) public {
+        require(balanceOf[msg.sender] >= wad);
+        balanceOf[msg.sender] -= wad;
+        payable(msg.sender).transfer(wad);
+        emit Withdrawal(msg.sender, wad);
+    }
+
+    function totalSupply() public view returns (uint256) {
+        return address(this).balance;
+    }
+
+    function approve(address guy, uint256 wad) public returns (bool) {
+        allowance[msg.sender][guy] = wad;
+        emit Approval(msg.sender, guy, wad);
+        return true;
+    }
+
+    function transfer(address dst, uint256 wad) public returns (bool) {
+        return transferFrom(msg.sender, dst, wad);
+    }
+
+    function transferFrom(address src, address dst, uint256 wad) public returns (bool) {
+        require(balanceOf[src] >= wad);
+
+        if (src != msg.sender && allowance[src][msg.sender] != type(uint256).max) {
+            require(allowance[src][msg.sender] >= wad);
+            allowance[src][msg.sender] -= wad;
+        }
+
+        balanceOf[src] -= wad;
+        balanceOf[dst] += wad;
+
+        emit Transfer(src, dst, wad);
+
+        return true;
+    }
+}
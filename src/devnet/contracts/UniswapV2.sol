@@ -0,0 +1,428 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {TokenCalls} from "Shared.sol";
+
+/// Looks up the pairs the devnet registers; pairs are deployed at the addresses the
+/// real factory would create them at, so nothing is created here
+contract UniswapV2Factory {
+    address public immutable owner;
+    mapping(address => mapping(address => address)) public getPair;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    function registerPair(address tokenA, address tokenB, address pair) external {
+        require(msg.sender == owner, "UniswapV2Factory: caller is not the owner");
+        getPair[tokenA][tokenB] = pair;
+        getPair[tokenB][tokenA] = pair;
+    }
+}
+
+/// A Uniswap V2 pair without its liquidity token: reserves are whatever it held at the
+/// last `swap` or `sync`, and swaps must keep the 0.3% fee's constant product. Flash
+/// swaps are not supported, so `swap`'s data is ignored.
+contract UniswapV2Pair is TokenCalls {
+    address public immutable token0;
+    address public immutable token1;
+    uint256 private reserve0;
+    uint256 private reserve1;
+    uint256 private blockTimestampLast;
+
+    constructor(address token0_, address token1_) {
+        token0 = token0_;
+        token1 = token1_;
+    }
+
+    function getReserves() external view returns (uint112 reserve0_, uint112 reserve1_, uint32 blockTimestampLast_) {
+        reserve0_ = uint112(reserve0);
+        reserve1_ = uint112(reserve1);
+        blockTimestampLast_ = uint32(blockTimestampLast);
+    }
+
+    function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes calldata data) external {
+        require(amount0Out > 0 || amount1Out > 0, "UniswapV2: INSUFFICIENT_OUTPUT_AMOUNT");
+        uint256 oldReserve0 = reserve0;
+        uint256 oldReserve1 = reserve1;
+        require(amount0Out < oldReserve0 && amount1Out < oldReserve1, "UniswapV2: INSUFFICIENT_LIQUIDITY");
+        require(to != token0 && to != token1, "UniswapV2: INVALID_TO");
+        if (amount0Out > 0) {
+            require(_transfer(token0, to, amount0Out), "UniswapV2: TRANSFER_FAILED");
+        }
+        if (amount1Out > 0) {
+            require(_transfer(token1, to, amount1Out), "UniswapV2: TRANSFER_FAILED");
+        }
+        uint256 balance0 = _balanceOf(token0, address(this));
+        uint256 balance1 = _balanceOf(token1, address(this));
+        uint256 amount0In = _received(balance0, oldReserve0, amount0Out);
+        uint256 amount1In = _received(balance1, oldReserve1, amount1Out);
+        require(amount0In > 0 || amount1In > 0, "UniswapV2: INSUFFICIENT_INPUT_AMOUNT");
+        uint256 balance0Adjusted = balance0 * 1000 - amount0In * 3;
+        uint256 balance1Adjusted = balance1 * 1000 - amount1In * 3;
+        require(balance0Adjusted * balance1Adjusted >= oldReserve0 * oldReserve1 * 1000 ** 2, "UniswapV2: K");
+
+        _update(balance0, balance1);
+        _emitSwap(amount0In, amount1In, amount0Out, amount1Out, to);
+        data;
+    }
+
+    /// Match the reserves to the balances, after tokens arrive outside a swap
+    function sync() external {
+        _update(_balanceOf(token0, address(this)), _balanceOf(token1, address(this)));
+    }
+
+    function _update(uint256 balance0, uint256 balance1) private {
+        require(balance0 <= type(uint112).max && balance1 <= type(uint112).max, "UniswapV2: OVERFLOW");
+        reserve0 = balance0;
+        reserve1 = balance1;
+        blockTimestampLast = block.timestamp % 2 ** 32;
+        assembly {
+            mstore(0, balance0)
+            mstore(0x20, balance1)
+            // Sync(uint112,uint112)
+            log1(0, 0x40, 0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1)
+        }
+    }
+
+    /// What arrived on top of the reserve left after paying `amountOut`
+    function _received(uint256 balanceNow, uint256 reserve, uint256 amountOut) private pure returns (uint256 amount) {
+        uint256 left = reserve - amountOut;
+        assembly {
+            if gt(balanceNow, left) {
+                amount := sub(balanceNow, left)
+            }
+        }
+    }
+
+    /// Logged in assembly, since solar encodes event data over the free memory pointer
+    /// once it runs past two words
+    function _emitSwap(uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address to)
+        private
+    {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, amount0In)
+            mstore(add(ptr, 0x20), amount1In)
+            mstore(add(ptr, 0x40), amount0Out)
+            mstore(add(ptr, 0x60), amount1Out)
+            // Swap(address,uint256,uint256,uint256,uint256,address)
+            log3(ptr, 0x80, 0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822, caller(), to)
+        }
+    }
+}
+
+/// Uniswap's Router02 over the pairs of one factory, with the swaps the server makes.
+/// Paths come straight from calldata: each helper takes the offset of a path's first
+/// token and its length. Helpers that reassign a local return a value, since solar
+/// inlines void functions without reserving frame space for their locals.
+contract UniswapV2Router02 is TokenCalls {
+    // Addresses that calls go to live in storage, since solar can reload a storage slot
+    // as a call operand but not an immutable
+    address public factory;
+    address public WETH;
+
+    constructor(address factory_, address weth) {
+        factory = factory_;
+        WETH = weth;
+    }
+
+    receive() external payable {
+        require(msg.sender == WETH);
+    }
+
+    function getAmountsOut(uint256 amountIn, address[] calldata path)
+        external
+        view
+        returns (uint256[] memory amounts)
+    {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        amounts = new uint256[](_pathLength(path.length));
+        _amountsOut(amounts, amountIn, start);
+    }
+
+    function getAmountsIn(uint256 amountOut, address[] calldata path)
+        external
+        view
+        returns (uint256[] memory amounts)
+    {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        amounts = new uint256[](_pathLength(path.length));
+        _amountsIn(amounts, amountOut, start);
+    }
+
+    function swapExactTokensForTokens(
+        uint256 amountIn,
+        uint256 amountOutMin,
+        address[] calldata path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] memory amounts) {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        amounts = new uint256[](_pathLength(path.length));
+        require(_amountsOut(amounts, amountIn, start) >= amountOutMin, "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+        _payFirstPair(amounts[0], start, msg.sender);
+        _swap(amounts, start, to);
+    }
+
+    function swapTokensForExactTokens(
+        uint256 amountOut,
+        uint256 amountInMax,
+        address[] calldata path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] memory amounts) {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        amounts = new uint256[](_pathLength(path.length));
+        require(_amountsIn(amounts, amountOut, start) <= amountInMax, "UniswapV2Router: EXCESSIVE_INPUT_AMOUNT");
+        _payFirstPair(amounts[0], start, msg.sender);
+        _swap(amounts, start, to);
+    }
+
+    function swapExactETHForTokens(uint256 amountOutMin, address[] calldata path, address to, uint256 deadline)
+        external
+        payable
+        returns (uint256[] memory amounts)
+    {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        require(_token(start, 0) == WETH, "UniswapV2Router: INVALID_PATH");
+        amounts = new uint256[](_pathLength(path.length));
+        require(_amountsOut(amounts, msg.value, start) >= amountOutMin, "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+        _deposit(WETH, amounts[0]);
+        _payFirstPair(amounts[0], start, address(this));
+        _swap(amounts, start, to);
+    }
+
+    function swapETHForExactTokens(uint256 amountOut, address[] calldata path, address to, uint256 deadline)
+        external
+        payable
+        returns (uint256[] memory amounts)
+    {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        require(_token(start, 0) == WETH, "UniswapV2Router: INVALID_PATH");
+        amounts = new uint256[](_pathLength(path.length));
+        require(_amountsIn(amounts, amountOut, start) <= msg.value, "UniswapV2Router: EXCESSIVE_INPUT_AMOUNT");
+        _deposit(WETH, amounts[0]);
+        _payFirstPair(amounts[0], start, address(this));
+        _swap(amounts, start, to);
+        // Ether sent beyond the input is refunded
+        if (msg.value > amounts[0]) {
+            require(_sendEther(msg.sender, msg.value - amounts[0]), "TransferHelper: ETH_TRANSFER_FAILED");
+        }
+    }
+
+    function swapExactTokensForETH(
+        uint256 amountIn,
+        uint256 amountOutMin,
+        address[] calldata path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] memory amounts) {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        require(_token(start, path.length - 1) == WETH, "UniswapV2Router: INVALID_PATH");
+        amounts = new uint256[](_pathLength(path.length));
+        uint256 out = _amountsOut(amounts, amountIn, start);
+        require(out >= amountOutMin, "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+        _payFirstPair(amounts[0], start, msg.sender);
+        _swap(amounts, start, address(this));
+        _withdraw(WETH, out);
+        require(_sendEther(to, out), "TransferHelper: ETH_TRANSFER_FAILED");
+    }
+
+    function swapTokensForExactETH(
+        uint256 amountOut,
+        uint256 amountInMax,
+        address[] calldata path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] memory amounts) {
+        uint256 start;
+        assembly {
+            start := path.offset
+        }
+        _ensure(deadline);
+        require(_token(start, path.length - 1) == WETH, "UniswapV2Router: INVALID_PATH");
+        amounts = new uint256[](_pathLength(path.length));
+        require(_amountsIn(amounts, amountOut, start) <= amountInMax, "UniswapV2Router: EXCESSIVE_INPUT_AMOUNT");
+        _payFirstPair(amounts[0], start, msg.sender);
+        _swap(amounts, start, address(this));
+        _withdraw(WETH, amountOut);
+        require(_sendEther(to, amountOut), "TransferHelper: ETH_TRANSFER_FAILED");
+    }
+
+    function _ensure(uint256 deadline) private view {
+        require(deadline >= block.timestamp, "UniswapV2Router: EXPIRED");
+    }
+
+    function _pathLength(uint256 length) private pure returns (uint256) {
+        require(length >= 2, "UniswapV2Library: INVALID_PATH");
+        return length;
+    }
+
+    /// Token `index` of the path starting at calldata offset `path`
+    function _token(uint256 path, uint256 index) private pure returns (address token) {
+        assembly {
+            token := calldataload(add(path, mul(index, 0x20)))
+        }
+    }
+
+    /// `UniswapV2Library.getAmountsOut`, into `amounts`; returns the last of them
+    function _amountsOut(uint256[] memory amounts, uint256 amountIn, uint256 path)
+        private
+        view
+        returns (uint256 amountOut)
+    {
+        amounts[0] = amountIn;
+        for (uint256 i = 0; i + 1 < amounts.length; i++) {
+            address pair = _pairFor(_token(path, i), _token(path, i + 1));
+            uint256 reserveIn = _reserveOf(pair, _token(path, i), _token(path, i + 1));
+            uint256 reserveOut = _reserveOf(pair, _token(path, i + 1), _token(path, i));
+            amounts[i + 1] = _amountOut(amounts[i], reserveIn, reserveOut);
+        }
+        amountOut = amounts[amounts.length - 1];
+    }
+
+    /// `UniswapV2Library.getAmountsIn`, into `amounts`; returns the first of them
+    function _amountsIn(uint256[] memory amounts, uint256 amountOut, uint256 path)
+        private
+        view
+        returns (uint256 amountIn)
+    {
+        uint256 last = amounts.length - 1;
+        amounts[last] = amountOut;
+        for (uint256 i = last; i > 0; i--) {
+            address pair = _pairFor(_token(path, i - 1), _token(path, i));
+            uint256 reserveIn = _reserveOf(pair, _token(path, i - 1), _token(path, i));
+            uint256 reserveOut = _reserveOf(pair, _token(path, i), _token(path, i - 1));
+            amounts[i - 1] = _amountIn(amounts[i], reserveIn, reserveOut);
+        }
+        amountIn = amounts[0];
+    }
+
+    /// `UniswapV2Library.getAmountOut`
+    function _amountOut(uint256 amountIn, uint256 reserveIn, uint256 reserveOut) private pure returns (uint256) {
+        require(amountIn > 0, "UniswapV2Library: INSUFFICIENT_INPUT_AMOUNT");
+        require(reserveIn > 0 && reserveOut > 0, "UniswapV2Library: INSUFFICIENT_LIQUIDITY");
+        uint256 amountInWithFee = amountIn * 997;
+        return amountInWithFee * reserveOut / (reserveIn * 1000 + amountInWithFee);
+    }
+
+    /// `UniswapV2Library.getAmountIn`
+    function _amountIn(uint256 amountOut, uint256 reserveIn, uint256 reserveOut) private pure returns (uint256) {
+        require(amountOut > 0, "UniswapV2Library: INSUFFICIENT_OUTPUT_AMOUNT");
+        require(reserveIn > 0 && reserveOut > 0, "UniswapV2Library: INSUFFICIENT_LIQUIDITY");
+        return reserveIn * amountOut * 1000 / ((reserveOut - amountOut) * 997) + 1;
+    }
+
+    /// Move the input into the first pair: pulled from `payer`, or from the router's own
+    /// balance when it wrapped the input from ether
+    function _payFirstPair(uint256 amount, uint256 path, address payer) private {
+        address token = _token(path, 0);
+        address pair = _pairFor(token, _token(path, 1));
+        if (payer == address(this)) {
+            require(_transfer(token, pair, amount), "TransferHelper: TRANSFER_FAILED");
+        } else {
+            require(_transferFrom(token, payer, pair, amount), "TransferHelper: TRANSFER_FROM_FAILED");
+        }
+    }
+
+    /// Swap through each pair, which pays its output straight into the next pair; returns
+    /// what the last pair paid `to`
+    function _swap(uint256[] memory amounts, uint256 path, address to) private returns (uint256 amountOut) {
+        for (uint256 i = 0; i + 1 < amounts.length; i++) {
+            address input = _token(path, i);
+            address output = _token(path, i + 1);
+            uint256 amount0Out = amounts[i + 1];
+            uint256 amount1Out = 0;
+            if (input < output) {
+                amount0Out = 0;
+                amount1Out = amounts[i + 1];
+            }
+            address pair = _pairFor(input, output);
+            if (i + 2 < amounts.length) {
+                _pairSwap(pair, amount0Out, amount1Out, _pairFor(output, _token(path, i + 2)));
+            } else {
+                _pairSwap(pair, amount0Out, amount1Out, to);
+            }
+        }
+        amountOut = amounts[amounts.length - 1];
+    }
+
+    /// The factory's pair for two tokens; a missing pair is address zero, whose empty
+    /// reserves then revert without data
+    function _pairFor(address tokenA, address tokenB) private view returns (address pair) {
+        address lookup = factory;
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0xe6a43905))
+            mstore(add(ptr, 4), tokenA)
+            mstore(add(ptr, 36), tokenB)
+            if iszero(staticcall(gas(), lookup, ptr, 68, 0, 0x20)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            pair := mload(0)
+        }
+    }
+
+    /// The reserve of `token` in the pair of `token` and `other`
+    function _reserveOf(address pair, address token, address other) private view returns (uint256 reserve) {
+        assembly {
+            mstore(0, shl(224, 0x0902f1ac))
+            if iszero(staticcall(gas(), pair, 0, 4, 0, 0x40)) {
+                returndatacopy(0, 0, returndatasize())
+                revert(0, returndatasize())
+            }
+            if lt(returndatasize(), 0x40) {
+                revert(0, 0)
+            }
+            reserve := mload(0x20)
+            if lt(token, other) {
+                reserve := mload(0)
+            }
+        }
+    }
+
+    /// `pair.swap(amount0Out, amount1Out, to, "")`, bubbling up a revert
+    function _pairSwap(address pair, uint256 amount0Out, uint256 amount1Out, address to) private {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0x022c0d9f))
+            mstore(add(ptr, 4), amount0Out)
+            mstore(add(ptr, 36), amount1Out)
+            mstore(add(ptr, 68), to)
+            mstore(add(ptr, 100), 0x80)
+            mstore(add(ptr, 132), 0)
+            if iszero(call(gas(), pair, 0, ptr, 164, 0, 0)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+        }
+    }
+}
@@ -0,0 +1,639 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {PoolMath, TokenCalls} from "Shared.sol";
+
+/// Looks up the pools the devnet registers; pools are deployed at the addresses
+/// Uniswap's factory would create them at, so nothing is created here
+contract UniswapV3Factory {
+    address public immutable owner;
+    mapping(address => mapping(address => mapping(uint24 => address))) public getPool;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    function registerPool(address tokenA, address tokenB, uint24 fee, address pool) external {
+        require(msg.sender == owner, "UniswapV3Factory: caller is not the owner");
+        getPool[tokenA][tokenB][fee] = pool;
+        getPool[tokenB][tokenA][fee] = pool;
+    }
+}
+
+/// A Uniswap V3 pool holding a single full-range position, which prices like a
+/// constant-product pool over its token balances and never crosses a tick. Swaps follow
+/// the real pool's flow: the output goes out first, then the swap callback pays the input.
+contract UniswapV3Pool is TokenCalls {
+    event Swap(
+        address indexed sender,
+        address indexed recipient,
+        int256 amount0,
+        int256 amount1,
+        uint160 sqrtPriceX96,
+        uint128 liquidity,
+        int24 tick
+    );
+
+    address public immutable token0;
+    address public immutable token1;
+    uint24 public immutable fee;
+
+    constructor(address token0_, address token1_, uint24 fee_) {
+        token0 = token0_;
+        token1 = token1_;
+        fee = fee_;
+    }
+
+    function slot0()
+        external
+        view
+        returns (
+            uint160 sqrtPriceX96,
+            int24 tick,
+            uint16 observationIndex,
+            uint16 observationCardinality,
+            uint16 observationCardinalityNext,
+            uint8 feeProtocol,
+            bool unlocked
+        )
+    {
+        uint256 reserve0 = _balanceOf(token0, address(this));
+        uint256 reserve1 = _balanceOf(token1, address(this));
+        uint256 price = PoolMath.sqrtPriceX96(reserve0, reserve1);
+        sqrtPriceX96 = uint160(price);
+        tick = PoolMath.tick(price);
+        observationIndex = 0;
+        observationCardinality = 1;
+        observationCardinalityNext = 1;
+        feeProtocol = 0;
+        unlocked = true;
+    }
+
+    function liquidity() external view returns (uint128) {
+        uint256 reserve0 = _balanceOf(token0, address(this));
+        uint256 reserve1 = _balanceOf(token1, address(this));
+        return uint128(PoolMath.liquidity(reserve0, reserve1));
+    }
+
+    /// Swap an exact input (`amountSpecified > 0`) or for an exact output (`< 0`). The
+    /// price limit is ignored, since a full-range position never runs out of ticks.
+    function swap(
+        address recipient,
+        bool zeroForOne,
+        int256 amountSpecified,
+        uint160 sqrtPriceLimitX96,
+        bytes calldata data
+    ) external returns (int256 amount0, int256 amount1) {
+        require(amountSpecified != 0, "AS");
+        address tokenIn = token1;
+        address tokenOut = token0;
+        if (zeroForOne) {
+            tokenIn = token0;
+            tokenOut = token1;
+        }
+        uint256 reserveIn = _balanceOf(tokenIn, address(this));
+        uint256 reserveOut = _balanceOf(tokenOut, address(this));
+
+        uint256 amountIn;
+        uint256 amountOut;
+        if (amountSpecified > 0) {
+            amountIn = uint256(amountSpecified);
+            amountOut = PoolMath.amountOut(fee, reserveIn, reserveOut, amountIn);
+            require(amountOut > 0, "AS");
+        } else {
+            amountOut = uint256(-amountSpecified);
+            require(amountOut < reserveOut && reserveIn > 0, "SPL");
+            amountIn = PoolMath.amountIn(fee, reserveIn, reserveOut, amountOut);
+        }
+
+        amount0 = -int256(amountOut);
+        amount1 = int256(amountIn);
+        if (zeroForOne) {
+            amount0 = int256(amountIn);
+            amount1 = -int256(amountOut);
+        }
+
+        require(_transfer(tokenOut, recipient, amountOut), "TF");
+        _swapCallback(amount0, amount1, data);
+        require(_balanceOf(tokenIn, address(this)) >= reserveIn + amountIn, "IIA");
+
+        _emitSwap(recipient, amount0, amount1);
+        sqrtPriceLimitX96;
+    }
+
+    /// `msg.sender.uniswapV3SwapCallback(amount0, amount1, data)`, bubbling up a revert.
+    /// The call size goes through scratch memory, since solar can't hand a value read
+    /// from calldata straight to a call.
+    function _swapCallback(int256 amount0, int256 amount1, bytes calldata data) private {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0xfa461e33))
+            mstore(add(ptr, 4), amount0)
+            mstore(add(ptr, 36), amount1)
+            mstore(add(ptr, 68), 0x60)
+            mstore(add(ptr, 100), data.length)
+            calldatacopy(add(ptr, 132), data.offset, data.length)
+            mstore(0, add(132, and(add(data.length, 31), not(31))))
+            if iszero(call(gas(), caller(), 0, ptr, mload(0), 0, 0)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+        }
+    }
+
+    /// The swap's event, priced at the reserves after it. It is logged in assembly:
+    /// solar encodes event data from memory offset 0, over the free memory pointer and
+    /// the function's locals once it runs past two words.
+    function _emitSwap(address recipient, int256 amount0, int256 amount1) private {
+        uint256 reserve0 = _balanceOf(token0, address(this));
+        uint256 reserve1 = _balanceOf(token1, address(this));
+        uint256 price = PoolMath.sqrtPriceX96(reserve0, reserve1);
+        uint256 position = PoolMath.liquidity(reserve0, reserve1);
+        int24 currentTick = PoolMath.tick(price);
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, amount0)
+            mstore(add(ptr, 32), amount1)
+            mstore(add(ptr, 64), price)
+            mstore(add(ptr, 96), position)
+            mstore(add(ptr, 128), currentTick)
+            // Swap(address,address,int256,int256,uint160,uint128,int24)
+            log3(ptr, 160, 0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67, caller(), recipient)
+        }
+    }
+}
+
+/// Calls shared by the quoter and the router
+abstract contract PoolCalls is TokenCalls {
+    // Addresses that calls go to live in storage, since solar can reload a storage slot
+    // as a call operand but not an immutable
+    address public factory;
+
+    constructor(address factory_) {
+        factory = factory_;
+    }
+
+    /// The registered pool; calling a pool that doesn't exist reverts without data
+    function _getPool(address tokenA, address tokenB, uint24 fee) internal view returns (address pool) {
+        address lookup = factory;
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0x1698ee82))
+            mstore(add(ptr, 4), tokenA)
+            mstore(add(ptr, 36), tokenB)
+            mstore(add(ptr, 68), fee)
+            if iszero(staticcall(gas(), lookup, ptr, 100, 0, 0x20)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            pool := mload(0)
+            if iszero(pool) {
+                revert(0, 0)
+            }
+        }
+    }
+
+    /// Start `pool.swap` with the callback data `(tokenIn, tokenOut, fee, payer)`; a
+    /// revert is left for the caller to read from the return data
+    function _startSwap(
+        address pool,
+        address recipient,
+        address tokenIn,
+        address tokenOut,
+        uint24 fee,
+        int256 amountSpecified,
+        address payer
+    ) internal returns (bool ok) {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0x128acb08))
+            mstore(add(ptr, 4), recipient)
+            mstore(add(ptr, 36), lt(tokenIn, tokenOut))
+            mstore(add(ptr, 68), amountSpecified)
+            mstore(add(ptr, 100), 0)
+            mstore(add(ptr, 132), 0xa0)
+            mstore(add(ptr, 164), 0x80)
+            mstore(add(ptr, 196), tokenIn)
+            mstore(add(ptr, 228), tokenOut)
+            mstore(add(ptr, 260), fee)
+            mstore(add(ptr, 292), payer)
+            ok := call(gas(), pool, 0, ptr, 324, 0, 0)
+        }
+    }
+
+    /// Read `(tokenIn, tokenOut)` back from swap callback data. Solar hands back at most
+    /// two values from an internal function intact, so the rest comes from `_callbackPayer`.
+    function _callbackTokens(bytes calldata data) internal pure returns (address tokenIn, address tokenOut) {
+        require(data.length == 128);
+        assembly {
+            tokenIn := calldataload(data.offset)
+            tokenOut := calldataload(add(data.offset, 32))
+        }
+    }
+
+    /// Read `(fee, payer)` back from swap callback data
+    function _callbackPayer(bytes calldata data) internal pure returns (uint24 fee, address payer) {
+        require(data.length == 128);
+        assembly {
+            fee := calldataload(add(data.offset, 64))
+            payer := calldataload(add(data.offset, 96))
+        }
+    }
+}
+
+/// Uniswap's QuoterV2 over the fixture pools: each quote runs the swap and reverts
+/// out of the callback with its result. Liquidity spans the full tick range, so no
+/// initialized tick is ever crossed.
+contract QuoterV2 is PoolCalls {
+    struct QuoteExactInputSingleParams {
+        address tokenIn;
+        address tokenOut;
+        uint256 amountIn;
+        uint24 fee;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    struct QuoteExactOutputSingleParams {
+        address tokenIn;
+        address tokenOut;
+        uint256 amount;
+        uint24 fee;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    constructor(address factory_) PoolCalls(factory_) {}
+
+    /// Reverts with `(amount, sqrtPriceX96After, gas)`: the output for an exact input
+    /// or the input for an exact output, and the pool's price once the input is paid
+    function uniswapV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes calldata data) external view {
+        (address tokenIn, address tokenOut) = _callbackTokens(data);
+        uint256 amountToPay = uint256(amount1Delta);
+        uint256 amountReceived = uint256(-amount0Delta);
+        if (amount0Delta > 0) {
+            amountToPay = uint256(amount0Delta);
+            amountReceived = uint256(-amount1Delta);
+        }
+        uint256 reserveIn = _balanceOf(tokenIn, msg.sender) + amountToPay;
+        uint256 reserveOut = _balanceOf(tokenOut, msg.sender);
+        uint256 price = PoolMath.sqrtPriceX96(reserveOut, reserveIn);
+        if (tokenIn < tokenOut) {
+            price = PoolMath.sqrtPriceX96(reserveIn, reserveOut);
+        }
+        assembly {
+            // An exact output quote is marked by a zero payer
+            let amount := amountReceived
+            if iszero(calldataload(add(data.offset, 96))) {
+                amount := amountToPay
+            }
+            mstore(0, amount)
+            mstore(0x20, price)
+            revert(0, 0x40)
+        }
+    }
+
+    function quoteExactInputSingle(QuoteExactInputSingleParams calldata params)
+        external
+        returns (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+    {
+        address pool = _getPool(params.tokenIn, params.tokenOut, params.fee);
+        uint256 gasBefore = _gasLeft();
+        (uint256 amount, uint160 price) = _quote(pool, params.tokenIn, params.tokenOut, params.fee, int256(params.amountIn));
+        amountOut = amount;
+        sqrtPriceX96After = price;
+        initializedTicksCrossed = 0;
+        gasEstimate = gasBefore - _gasLeft();
+    }
+
+    function quoteExactOutputSingle(QuoteExactOutputSingleParams calldata params)
+        external
+        returns (uint256 amountIn, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+    {
+        address pool = _getPool(params.tokenIn, params.tokenOut, params.fee);
+        uint256 gasBefore = _gasLeft();
+        (uint256 amount, uint160 price) = _quote(pool, params.tokenIn, params.tokenOut, params.fee, -int256(params.amount));
+        amountIn = amount;
+        sqrtPriceX96After = price;
+        initializedTicksCrossed = 0;
+        gasEstimate = gasBefore - _gasLeft();
+    }
+
+    /// Quote each pool of an encoded path in turn: a token, then a 3-byte fee and the
+    /// next token per pool. Malformed paths revert without data.
+    function quoteExactInput(bytes calldata path, uint256 amountIn)
+        external
+        returns (
+            uint256 amountOut,
+            uint160[] memory sqrtPriceX96AfterList,
+            uint32[] memory initializedTicksCrossedList,
+            uint256 gasEstimate
+        )
+    {
+        uint256 hops = _hops(path.length);
+        sqrtPriceX96AfterList = new uint160[](hops);
+        initializedTicksCrossedList = new uint32[](hops);
+        amountOut = amountIn;
+        for (uint256 i = 0; i < hops; i++) {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            assembly {
+                let start := add(path.offset, mul(i, 23))
+                tokenIn := shr(96, calldataload(start))
+                fee := shr(232, calldataload(add(start, 20)))
+                tokenOut := shr(96, calldataload(add(start, 23)))
+            }
+            address pool = _getPool(tokenIn, tokenOut, fee);
+            uint256 gasBefore = _gasLeft();
+            (uint256 out, uint160 price) = _quote(pool, tokenIn, tokenOut, fee, int256(amountOut));
+            gasEstimate += gasBefore - _gasLeft();
+            amountOut = out;
+            sqrtPriceX96AfterList[i] = price;
+        }
+    }
+
+    /// Pools along a path of `length` bytes
+    function _hops(uint256 length) internal pure returns (uint256 hops) {
+        assembly {
+            if or(lt(length, 43), mod(sub(length, 20), 23)) {
+                revert(0, 0)
+            }
+            hops := div(sub(length, 20), 23)
+        }
+    }
+
+    /// Run one swap up to its callback and read the result it reverts with; any other
+    /// revert is passed on
+    function _quote(address pool, address tokenIn, address tokenOut, uint24 fee, int256 amountSpecified)
+        internal
+        returns (uint256 amount, uint160 sqrtPriceX96After)
+    {
+        // A zero payer tells the callback the quote is for an exact output
+        address payer = address(this);
+        if (amountSpecified < 0) {
+            payer = address(0);
+        }
+        bool ok = _startSwap(pool, address(this), tokenIn, tokenOut, fee, amountSpecified, payer);
+        assembly {
+            if or(ok, iszero(eq(returndatasize(), 0x40))) {
+                let ptr := mload(0x40)
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            returndatacopy(0, 0, 0x40)
+            amount := mload(0)
+            sqrtPriceX96After := mload(0x20)
+        }
+    }
+
+    function _gasLeft() internal view returns (uint256 left) {
+        assembly {
+            left := gas()
+        }
+    }
+}
+
+/// Uniswap's SwapRouter over the fixture pools, with the PeripheryPayments and
+/// SelfPermit calls the server makes
+contract SwapRouter is PoolCalls {
+    struct ExactInputSingleParams {
+        address tokenIn;
+        address tokenOut;
+        uint24 fee;
+        address recipient;
+        uint256 deadline;
+        uint256 amountIn;
+        uint256 amountOutMinimum;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    struct ExactInputParams {
+        bytes path;
+        address recipient;
+        uint256 deadline;
+        uint256 amountIn;
+        uint256 amountOutMinimum;
+    }
+
+    struct ExactOutputSingleParams {
+        address tokenIn;
+        address tokenOut;
+        uint24 fee;
+        address recipient;
+        uint256 deadline;
+        uint256 amountOut;
+        uint256 amountInMaximum;
+        uint160 sqrtPriceLimitX96;
+    }
+
+    address public WETH9;
+
+    constructor(address factory_, address weth) PoolCalls(factory_) {
+        WETH9 = weth;
+    }
+
+    receive() external payable {
+        require(msg.sender == WETH9, "Not WETH9");
+    }
+
+    /// Pay the pool what the swap owes it, from the payer in the callback data
+    function uniswapV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes calldata data) external {
+        (address tokenIn, address tokenOut) = _callbackTokens(data);
+        (uint24 fee, address payer) = _callbackPayer(data);
+        require(msg.sender == _getPool(tokenIn, tokenOut, fee));
+        uint256 amountToPay = uint256(amount1Delta);
+        if (amount0Delta > 0) {
+            amountToPay = uint256(amount0Delta);
+        }
+        _pay(tokenIn, payer, msg.sender, amountToPay);
+    }
+
+    function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256 amountOut) {
+        require(block.timestamp <= params.deadline, "Transaction too old");
+        amountOut = _exactInput(params.tokenIn, params.tokenOut, params.fee, params.amountIn, params.recipient, msg.sender);
+        require(amountOut >= params.amountOutMinimum, "Too little received");
+    }
+
+    /// Swap along an encoded path; later pools are paid from what the router received
+    /// from the one before. Solar decodes a calldata struct with a dynamic member as if
+    /// it were static, so the parameters are read straight from calldata.
+    function exactInput(ExactInputParams calldata) external payable returns (uint256 amountOut) {
+        uint256 params;
+        uint256 path;
+        uint256 hops;
+        assembly {
+            params := add(4, calldataload(4))
+            path := add(params, calldataload(params))
+            let length := calldataload(path)
+            if or(lt(length, 43), mod(sub(length, 20), 23)) {
+                revert(0, 0)
+            }
+            hops := div(sub(length, 20), 23)
+        }
+        require(block.timestamp <= _word(params, 2), "Transaction too old");
+        amountOut = _word(params, 3);
+        for (uint256 i = 0; i < hops; i++) {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            assembly {
+                let start := add(add(path, 32), mul(i, 23))
+                tokenIn := shr(96, calldataload(start))
+                fee := shr(232, calldataload(add(start, 20)))
+                tokenOut := shr(96, calldataload(add(start, 23)))
+            }
+            address payer = address(this);
+            if (i == 0) {
+                payer = msg.sender;
+            }
+            address recipient = address(this);
+            if (i + 1 == hops) {
+                recipient = address(uint160(_word(params, 1)));
+            }
+            amountOut = _exactInput(tokenIn, tokenOut, fee, amountOut, recipient, payer);
+        }
+        require(amountOut >= _word(params, 4), "Too little received");
+    }
+
+    function exactOutputSingle(ExactOutputSingleParams calldata params) external payable returns (uint256 amountIn) {
+        require(block.timestamp <= params.deadline, "Transaction too old");
+        address pool = _getPool(params.tokenIn, params.tokenOut, params.fee);
+        bool ok = _startSwap(
+            pool, params.recipient, params.tokenIn, params.tokenOut, params.fee, -int256(params.amountOut), msg.sender
+        );
+        amountIn = _swapResult(ok, params.tokenIn < params.tokenOut, true);
+        require(amountIn <= params.amountInMaximum, "Too much requested");
+    }
+
+    function selfPermit(address token, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s)
+        external
+        payable
+    {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, shl(224, 0xd505accf))
+            mstore(add(ptr, 4), caller())
+            mstore(add(ptr, 36), address())
+            mstore(add(ptr, 68), value)
+            mstore(add(ptr, 100), deadline)
+            mstore(add(ptr, 132), v)
+            mstore(add(ptr, 164), r)
+            mstore(add(ptr, 196), s)
+            if iszero(call(gas(), token, 0, ptr, 228, 0, 0)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+        }
+    }
+
+    function unwrapWETH9(uint256 amountMinimum, address recipient) external payable {
+        uint256 balance = _balanceOf(WETH9, address(this));
+        require(balance >= amountMinimum, "Insufficient WETH9");
+        if (balance > 0) {
+            _withdraw(WETH9, balance);
+            require(_sendEther(recipient, balance), "STE");
+        }
+    }
+
+    function refundETH() external payable {
+        if (address(this).balance > 0) {
+            require(_sendEther(msg.sender, address(this).balance), "STE");
+        }
+    }
+
+    /// Delegatecall each call in turn, so every one keeps the original sender and value
+    function multicall(bytes[] calldata data) external payable returns (bytes[] memory results) {
+        results = new bytes[](data.length);
+        for (uint256 i = 0; i < data.length; i++) {
+            uint256 item;
+            assembly {
+                item := add(data.offset, calldataload(add(data.offset, mul(i, 0x20))))
+            }
+            uint256 length = _word(item, 0);
+            bytes memory input = new bytes(length);
+            uint256 ptr;
+            assembly {
+                ptr := add(input, 0x20)
+                calldatacopy(ptr, add(item, 0x20), length)
+            }
+            uint256 size = _delegate(ptr, length);
+            bytes memory result = new bytes(size);
+            assembly {
+                returndatacopy(add(result, 0x20), 0, size)
+            }
+            results[i] = result;
+        }
+    }
+
+    /// Delegatecall this contract with the `length` bytes of calldata at `ptr`, bubbling
+    /// up a revert; returns the size of the return data
+    function _delegate(uint256 ptr, uint256 length) private returns (uint256 size) {
+        assembly {
+            // solar can't pass `address()` to a call directly, so it goes through memory
+            mstore(0, address())
+            if iszero(delegatecall(gas(), mload(0), ptr, length, 0, 0)) {
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            size := returndatasize()
+        }
+    }
+
+    /// Word `index` of the calldata at `offset`
+    function _word(uint256 offset, uint256 index) internal pure returns (uint256 word) {
+        assembly {
+            word := calldataload(add(offset, mul(index, 32)))
+        }
+    }
+
+    /// Swap an exact input through one pool and return the output
+    function _exactInput(
+        address tokenIn,
+        address tokenOut,
+        uint24 fee,
+        uint256 amountIn,
+        address recipient,
+        address payer
+    ) internal returns (uint256 amountOut) {
+        address pool = _getPool(tokenIn, tokenOut, fee);
+        bool ok = _startSwap(pool, recipient, tokenIn, tokenOut, fee, int256(amountIn), payer);
+        amountOut = _swapResult(ok, tokenIn < tokenOut, false);
+    }
+
+    /// The input (`paid`) or output of a finished `swap`, from its `(amount0, amount1)`
+    /// return data; a failed swap's revert is bubbled up
+    function _swapResult(bool ok, bool zeroForOne, bool paid) internal pure returns (uint256 amount) {
+        assembly {
+            if iszero(ok) {
+                let ptr := mload(0x40)
+                returndatacopy(ptr, 0, returndatasize())
+                revert(ptr, returndatasize())
+            }
+            returndatacopy(0, 0, 0x40)
+            let amount0 := mload(0)
+            let amount1 := mload(0x20)
+            // The pool's side of each token: positive in, negative out
+            let delta := amount1
+            if eq(zeroForOne, paid) {
+                delta := amount0
+            }
+            amount := delta
+            if slt(delta, 0) {
+                amount := sub(0, delta)
+            }
+        }
+    }
+
+    /// Pay as PeripheryPayments does: with ether the router holds when paying WETH, from
+    /// the router's own balance when it is the payer, and otherwise by pulling from
+    /// `payer`
+    function _pay(address token, address payer, address recipient, uint256 value) internal {
+        if (token == WETH9 && address(this).balance >= value) {
+            _deposit(WETH9, value);
+            require(_transfer(WETH9, recipient, value), "STF");
+        } else if (payer == address(this)) {
+            require(_transfer(token, recipient, value), "STF");
+        } else {
+            require(_transferFrom(token, payer, recipient, value), "STF");
+        }
+    }
+}
@@ -0,0 +1,257 @@
+/// The contracts the simulated node is seeded with, compiled from the Solidity in
+/// `contracts/` by `scripts/build_devnet_contracts.sh`
+/// Each one is deployed by [`OWNER`] and then moved to its mainnet address, and set-up
+/// such as minting runs through the EVM like any other call
+use super::state::{Execution, Message, State};
+use alloy::primitives::{address, hex, Address, Bytes, TxKind, U256};
+use alloy::sol;
+use alloy::sol_types::{Revert, SolCall, SolError};
+use revm::primitives::{ExecutionResult, Output};
+
+/// Deploys the fixtures, which makes it the only account that can mint their tokens,
+/// register their pools and update their price feed
+pub(crate) const OWNER: Address = address!("dEdEdEDedEdEdedEDEdeDEdEDEDEDEdEDEdedede");
+
+/// Creation code of each fixture contract, hex-encoded
+pub(crate) mod code {
+    pub const WETH9: &str = include_str!("contracts/out/WETH9.bin");
+    pub const FIXTURE_TOKEN: &str = include_str!("contracts/out/FixtureToken.bin");
+    pub const FIXTURE_PERMIT_TOKEN: &str = include_str!("contracts/out/FixturePermitToken.bin");
+    pub const FIXTURE_PRICE_FEED: &str = include_str!("contracts/out/FixturePriceFeed.bin");
+    pub const UNISWAP_V3_FACTORY: &str = include_str!("contracts/out/UniswapV3Factory.bin");
+    pub const UNISWAP_V3_POOL: &str = include_str!("contracts/out/UniswapV3Pool.bin");
+    pub const QUOTER_V2: &str = include_str!("contracts/out/QuoterV2.bin");
+    pub const SWAP_ROUTER: &str = include_str!("contracts/out/SwapRouter.bin");
+    pub const UNISWAP_V2_FACTORY: &str = include_str!("contracts/out/UniswapV2Factory.bin");
+    pub const UNISWAP_V2_PAIR: &str = include_str!("contracts/out/UniswapV2Pair.bin");
+    pub const UNISWAP_V2_ROUTER: &str = include_str!("contracts/out/UniswapV2Router02.bin");
+    pub const CURVE_POOL: &str = include_str!("contracts/out/CurvePool.bin");
+    pub const BALANCER_VAULT: &str = include_str!("contracts/out/BalancerVault.bin");
+    pub const PERMIT2: &str = include_str!("contracts/out/Permit2.bin");
+}
+
+sol! {
+    /// Owner-only calls of the fixtures, which the server never makes
+    interface IFixtureToken {
+        function mint(address to, uint256 amount) external;
+    }
+
+    interface IFixturePriceFeed {
+        function updateAnswer(int256 answer) external;
+    }
+
+    interface IFixtureV3Factory {
+        function registerPool(address tokenA, address tokenB, uint24 fee, address pool) external;
+    }
+
+    interface IFixtureV2Factory {
+        function registerPair(address tokenA, address tokenB, address pair) external;
+    }
+
+    interface IFixtureV2Pair {
+        function sync() external;
+    }
+}
+
+// Apart from the V3 factory, whose `registerPool` one `sol!` would take for an overload
+sol! {
+    interface IFixtureBalancerVault {
+        function registerPool(
+            bytes32 poolId,
+            address tokenA,
+            address tokenB,
+            uint256 balanceA,
+            uint256 balanceB,
+            uint256 fee
+        ) external;
+    }
+}
+
+/// Run a set-up message from [`OWNER`], keeping its changes if `commit`;
+/// returns its output and the address of the contract it created
+fn run(
+    state: &mut State,
+    exec: &Execution,
+    to: TxKind,
+    value: U256,
+    input: Bytes,
+    commit: bool,
+) -> anyhow::Result<(Bytes, Option<Address>)> {
+    let message = Message {
+        from: OWNER,
+        to,
+        value,
+        input,
+        gas_limit: super::chain::BLOCK_GAS_LIMIT,
+        ..Default::default()
+    };
+    match state
+        .transact(exec, message, commit)
+        .map_err(|e| anyhow::anyhow!("Fixture call to {:?} failed: {}", to, e))?
+    {
+        ExecutionResult::Success { output, .. } => Ok(match output {
+            Output::Call(output) => (output, None),
+            Output::Create(output, created) => (output, created),
+        }),
+        ExecutionResult::Revert { output, .. } => {
+            let reason = Revert::abi_decode(&output, true)
+                .map(|revert| revert.reason)
+                .unwrap_or_else(|_| output.to_string());
+            Err(anyhow::anyhow!(
+                "Fixture call to {:?} reverted: {}",
+                to,
+                reason
+            ))
+        }
+        ExecutionResult::Halt { reason, .. } => Err(anyhow::anyhow!(
+            "Fixture call to {:?} halted: {:?}",
+            to,
+            reason
+        )),
+    }
+}
+
+/// Deploy `code` with ABI-encoded constructor `args` and move it to `at`
+pub(crate) fn deploy(
+    state: &mut State,
+    exec: &Execution,
+    code: &str,
+    args: &[u8],
+    at: Address,
+) -> anyhow::Result<()> {
+    let mut input = hex::decode(code.trim())?;
+    input.extend_from_slice(args);
+    let (_, created) = run(state, exec, TxKind::Create, U256::ZERO, input.into(), true)?;
+    let created = created.ok_or_else(|| anyhow::anyhow!("Deploying {} created nothing", at))?;
+    state.relocate(created, at);
+    Ok(())
+}
+
+/// Make `call` to `to` from [`OWNER`], which is handed the `value` wei it sends
+pub(crate) fn transact(
+    state: &mut State,
+    exec: &Execution,
+    to: Address,
+    value: U256,
+    call: impl SolCall,
+) -> anyhow::Result<()> {
+    state.account_mut(OWNER).balance += value;
+    run(
+        state,
+        exec,
+        TxKind::Call(to),
+        value,
+        call.abi_encode().into(),
+        true,
+    )?;
+    Ok(())
+}
+
+/// Answer `call` to `to` without changing any state
+pub(crate) fn view<C: SolCall>(
+    state: &mut State,
+    exec: &Execution,
+    to: Address,
+    call: C,
+) -> anyhow::Result<C::Return> {
+    let (output, _) = run(
+        state,
+        exec,
+        TxKind::Call(to),
+        U256::ZERO,
+        call.abi_encode().into(),
+        false,
+    )?;
+    Ok(C::abi_decode_returns(&output, true)?)
+}
+
+/// Order a pair as Uniswap does, lower address first
+pub(crate) fn sort_tokens(token_a: Address, token_b: Address) -> (Address, Address) {
+    if token_a < token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{DevNode, UNISWAP_V3_QUOTER_V2, USDC, WETH};
+    use super::*;
+    use crate::contracts::{fees, IUniswapV3Pool, IUniswapV3QuoterV2};
+    use alloy::primitives::Uint;
+
+    fn ether(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10u64).pow(U256::from(18))
+    }
+
+    #[test]
+    fn test_pool_price_matches_reserves() {
+        let mut chain = DevNode::seeded_chain().unwrap();
+        let exec = chain.setup_execution();
+        let pool = DevNode::pool_address(WETH, USDC, fees::MEDIUM);
+        let slot0 = view(&mut chain.state, &exec, pool, IUniswapV3Pool::slot0Call {}).unwrap();
+        let liquidity = view(
+            &mut chain.state,
+            &exec,
+            pool,
+            IUniswapV3Pool::liquidityCall {},
+        )
+        .unwrap()
+        ._0;
+
+        // 3,000,000 USDC (6 decimals) as token0 against 1000 WETH
+        let (usdc, weth) = (U256::from(3_000_000_000_000u64), ether(1_000));
+        assert!(U256::from(liquidity).pow(U256::from(2)) <= usdc * weth);
+        // price = (sqrt_price / 2^96)^2 = weth / usdc = 1e18 / 3e9
+        let price = slot0.sqrtPriceX96.to_string().parse::<f64>().unwrap() / 2f64.powi(96);
+        assert!((price * price - 1e18 / 3e9).abs() / (1e18 / 3e9) < 1e-9);
+        assert_eq!(slot0.tick, 196_256);
+    }
+
+    #[test]
+    fn test_exact_output_quote_inverts_exact_input() {
+        let mut chain = DevNode::seeded_chain().unwrap();
+        let exec = chain.setup_execution();
+        let quote_in = |state: &mut State, amount_in: U256| {
+            let call = IUniswapV3QuoterV2::quoteExactInputSingleCall {
+                params: IUniswapV3QuoterV2::QuoteExactInputSingleParams {
+                    tokenIn: WETH,
+                    tokenOut: USDC,
+                    amountIn: amount_in,
+                    fee: fees::MEDIUM,
+                    sqrtPriceLimitX96: Uint::ZERO,
+                },
+            };
+            view(state, &exec, UNISWAP_V3_QUOTER_V2, call)
+                .unwrap()
+                .amountOut
+        };
+        let amount_out = quote_in(&mut chain.state, ether(1));
+        // Fee and price impact keep the output below spot
+        assert!(amount_out < U256::from(3_000_000_000u64));
+        assert!(amount_out > U256::from(2_980_000_000u64));
+
+        let quote_out = IUniswapV3QuoterV2::quoteExactOutputSingleCall {
+            params: IUniswapV3QuoterV2::QuoteExactOutputSingleParams {
+                tokenIn: WETH,
+                tokenOut: USDC,
+                amount: amount_out,
+                fee: fees::MEDIUM,
+                sqrtPriceLimitX96: Uint::ZERO,
+            },
+        };
+        let amount_in = view(&mut chain.state, &exec, UNISWAP_V3_QUOTER_V2, quote_out)
+            .unwrap()
+            .amountIn;
+        assert!(amount_in <= ether(1));
+        assert!(quote_in(&mut chain.state, amount_in) >= amount_out);
+    }
+
+    #[test]
+    fn test_sort_tokens() {
+        let (low, high) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        assert_eq!(sort_tokens(high, low), (low, high));
+        assert_eq!(sort_tokens(low, high), (low, high));
+    }
+}
@@ -0,0 +1,563 @@
+/// Simulated node for offline end-to-end tests
+/// Serves the Ethereum JSON-RPC methods the server uses from an in-memory chain that runs
+/// calls and transactions in revm. It is seeded at the mainnet addresses with contracts
+/// compiled from `contracts/`: WETH9, USDC, USDT and DAI, the Chainlink ETH/USD feed,
+/// Uniswap V3 (factory, quoter, router and pools), the Uniswap V2 and Sushiswap factories
+/// and routers, Curve's 3pool, a Balancer V2 WETH/USDC pool and Permit2.
+///
+/// The fixtures answer the real contracts' ABIs with their storage layouts, so
+/// `AlloyEthereumProvider` and everything above it run unchanged with no network, and
+/// gas, logs and revert data come from executed bytecode. Their pricing is simplified:
+/// V3 pools are single full-range positions that never cross a tick, Curve prices as
+/// constant-sum and Balancer as constant-product. Tests against this node check how the
+/// server encodes calls, reads results and tracks transactions, not how the real
+/// contracts price; that needs a mainnet fork (see `test_execute_swap_on_dev_chain`).
+mod chain;
+mod fixtures;
+mod rpc;
+mod state;
+
+use crate::contracts::{fees, IERC20, IWETH9, PERMIT2};
+use crate::types::SwapProtocol;
+use crate::ContractAddresses;
+use alloy::primitives::{address, b256, keccak256, Address, Bytes, B256, I256, U256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::SolValue;
+use chain::Chain;
+use fixtures::{
+    code, IFixtureBalancerVault, IFixturePriceFeed, IFixtureToken, IFixtureV2Factory,
+    IFixtureV2Pair, IFixtureV3Factory,
+};
+use rpc::SharedChain;
+use state::{Execution, State};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// Chain ID of the simulated node (anvil's and hardhat's default)
+pub const DEV_CHAIN_ID: u64 = 31337;
+/// First anvil / hardhat development account, funded with 10,000 ETH at genesis
+pub const DEV_PRIVATE_KEY: &str =
+    "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+/// Base fee of every simulated block: 1 gwei
+pub const DEV_BASE_FEE_PER_GAS: u128 = 1_000_000_000;
+
+const WETH: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+const USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+const USDT: Address = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+const DAI: Address = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+const ETH_USD_FEED: Address = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+const UNISWAP_V3_FACTORY: Address = address!("1F98431c8aD98523631AE4a59f267346ea31F984");
+const UNISWAP_V3_ROUTER: Address = address!("E592427A0AEce92De3Edee1F18E0157C05861564");
+const UNISWAP_V3_QUOTER_V2: Address = address!("61fFE014bA17989E743c5F6cB21bF9697530B21e");
+/// Hash of the V3 pool creation code, for deriving pool addresses
+const POOL_INIT_CODE_HASH: B256 =
+    b256!("e34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54");
+const UNISWAP_V2_FACTORY: Address = address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f");
+const UNISWAP_V2_ROUTER: Address = address!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D");
+const SUSHISWAP_FACTORY: Address = address!("C0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac");
+const SUSHISWAP_ROUTER: Address = address!("d9e1cE17f2641f24aE83637ab66a2cca9C378B9F");
+/// Hashes of the Uniswap V2 and Sushiswap pair creation code, for deriving pair addresses
+const UNISWAP_V2_PAIR_INIT_CODE_HASH: B256 =
+    b256!("96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f");
+const SUSHISWAP_PAIR_INIT_CODE_HASH: B256 =
+    b256!("e18a34eb0e04b04f7a0ac29a6e80748dca96319b42c520bfa4543b4b0e0b2c8b");
+const CURVE_3POOL: Address = address!("bEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7");
+const BALANCER_VAULT: Address = address!("BA12222222228d8Ba445958a75a0704d566BF2C8");
+/// Balancer's B-50USDC-50WETH pool
+const BALANCER_WETH_USDC_POOL_ID: B256 =
+    b256!("96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019");
+
+/// ETH/USD price of the seeded feed and pools
+const ETH_USD: u64 = 3_000;
+/// The 3pool's 0.01% fee, over Curve's denominator of 1e10
+const CURVE_FEE: u64 = 1_000_000;
+
+/// A failure injected into the next `eth_sendRawTransaction`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastFault {
+    /// Refuse the transaction with a JSON-RPC error carrying this message
+    Reject(String),
+    /// Accept the transaction but answer with a 502, as a proxy that timed out would
+    LoseResponse,
+}
+
+/// A running simulated node. The JSON-RPC server stops when this is dropped.
+pub struct DevNode {
+    chain: SharedChain,
+    url: String,
+    server: JoinHandle<()>,
+}
+
+impl DevNode {
+    /// Seed the default fixtures and serve them on an ephemeral localhost port
+    pub async fn spawn() -> anyhow::Result<Self> {
+        let chain = Arc::new(Mutex::new(Self::seeded_chain()?));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let router = axum::Router::new()
+            .route("/", axum::routing::post(rpc::handle_rpc))
+            .with_state(chain.clone());
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::error!("Simulated node RPC server stopped: {}", e);
+            }
+        });
+        info!(url = %url, "Simulated node listening");
+        Ok(Self { chain, url, server })
+    }
+
+    fn seeded_chain() -> anyhow::Result<Chain> {
+        let mut chain = Chain::new(DEV_CHAIN_ID, DEV_BASE_FEE_PER_GAS);
+        let exec = chain.setup_execution();
+        let state = &mut chain.state;
+        let ether = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+
+        let dev_account = PrivateKeySigner::from_str(DEV_PRIVATE_KEY.trim_start_matches("0x"))?;
+        state.account_mut(dev_account.address()).balance = ether(10_000);
+
+        // Mainnet storage layouts, so state overrides need the same slot discovery
+        fixtures::deploy(state, &exec, code::WETH9, &[], WETH)?;
+        let tokens = [
+            (USDC, "USD Coin", "USDC", 6u64, 9u64, 10u64, Some("2")),
+            (USDT, "Tether USD", "USDT", 6, 2, 5, None),
+            (DAI, "Dai Stablecoin", "DAI", 18, 2, 3, None),
+        ];
+        for (address, name, symbol, decimals, balance, allowance, permit_version) in tokens {
+            let (name, symbol) = (name.to_string(), symbol.to_string());
+            let (decimals, balance, allowance) = (
+                U256::from(decimals),
+                U256::from(balance),
+                U256::from(allowance),
+            );
+            let (code, args) = match permit_version {
+                Some(version) => (
+                    code::FIXTURE_PERMIT_TOKEN,
+                    (
+                        name,
+                        symbol,
+                        decimals,
+                        balance,
+                        allowance,
+                        version.to_string(),
+                    )
+                        .abi_encode_params(),
+                ),
+                None => (
+                    code::FIXTURE_TOKEN,
+                    (name, symbol, decimals, balance, allowance).abi_encode_params(),
+                ),
+            };
+            fixtures::deploy(state, &exec, code, &args, address)?;
+        }
+
+        let answer = I256::try_from(ETH_USD * 100_000_000)?;
+        let args = ("ETH / USD".to_string(), U256::from(8), answer).abi_encode_params();
+        fixtures::deploy(state, &exec, code::FIXTURE_PRICE_FEED, &args, ETH_USD_FEED)?;
+        let factory = UNISWAP_V3_FACTORY.abi_encode();
+        fixtures::deploy(
+            state,
+            &exec,
+            code::UNISWAP_V3_FACTORY,
+            &[],
+            UNISWAP_V3_FACTORY,
+        )?;
+        fixtures::deploy(
+            state,
+            &exec,
+            code::QUOTER_V2,
+            &factory,
+            UNISWAP_V3_QUOTER_V2,
+        )?;
+        let args = (UNISWAP_V3_FACTORY, WETH).abi_encode_params();
+        fixtures::deploy(state, &exec, code::SWAP_ROUTER, &args, UNISWAP_V3_ROUTER)?;
+        // V2 factories and routers start without pairs; tests add them with `add_v2_pair`
+        for (factory, router) in [
+            (UNISWAP_V2_FACTORY, UNISWAP_V2_ROUTER),
+            (SUSHISWAP_FACTORY, SUSHISWAP_ROUTER),
+        ] {
+            fixtures::deploy(state, &exec, code::UNISWAP_V2_FACTORY, &[], factory)?;
+            let args = (factory, WETH).abi_encode_params();
+            fixtures::deploy(state, &exec, code::UNISWAP_V2_ROUTER, &args, router)?;
+        }
+        fixtures::deploy(
+            state,
+            &exec,
+            code::PERMIT2,
+            &[],
+            Address::from_str(PERMIT2)?,
+        )?;
+
+        let usd = |amount: u64, decimals: u32| U256::from(amount) * U256::from(10u64.pow(decimals));
+        let weth_reserve = 1_000;
+        let pools = [
+            (
+                WETH,
+                USDC,
+                fees::MEDIUM,
+                ether(weth_reserve),
+                usd(weth_reserve * ETH_USD, 6),
+            ),
+            (
+                WETH,
+                USDC,
+                fees::LOW,
+                ether(weth_reserve),
+                usd(weth_reserve * ETH_USD, 6),
+            ),
+            (
+                WETH,
+                USDT,
+                fees::MEDIUM,
+                ether(weth_reserve),
+                usd(weth_reserve * ETH_USD, 6),
+            ),
+            (
+                WETH,
+                DAI,
+                fees::MEDIUM,
+                ether(weth_reserve),
+                ether(weth_reserve * ETH_USD),
+            ),
+            // Deployed but never funded, like many long-tail tiers on mainnet
+            (WETH, USDC, fees::LOWEST, U256::ZERO, U256::ZERO),
+            (USDC, USDT, fees::LOW, usd(5_000_000, 6), usd(5_000_000, 6)),
+            (USDC, DAI, fees::LOW, usd(5_000_000, 6), ether(5_000_000)),
+            (USDT, DAI, fees::LOW, usd(5_000_000, 6), ether(5_000_000)),
+        ];
+        for (token_a, token_b, fee, amount_a, amount_b) in pools {
+            Self::insert_pool(&mut chain, token_a, token_b, fee, amount_a, amount_b)?;
+        }
+
+        // Curve's 3pool, charging 0.01%, and a Balancer WETH/USDC pool charging 0.3%
+        let state = &mut chain.state;
+        let three_pool = [
+            (DAI, ether(5_000_000)),
+            (USDC, usd(5_000_000, 6)),
+            (USDT, usd(5_000_000, 6)),
+        ];
+        let args = (DAI, USDC, USDT, U256::from(CURVE_FEE)).abi_encode_params();
+        fixtures::deploy(state, &exec, code::CURVE_POOL, &args, CURVE_3POOL)?;
+        for (token, amount) in three_pool {
+            Self::mint_into(state, &exec, token, CURVE_3POOL, amount)?;
+        }
+        let (weth_balance, usdc_balance) = (ether(500), usd(500 * ETH_USD, 6));
+        fixtures::deploy(state, &exec, code::BALANCER_VAULT, &[], BALANCER_VAULT)?;
+        Self::mint_into(state, &exec, WETH, BALANCER_VAULT, weth_balance)?;
+        Self::mint_into(state, &exec, USDC, BALANCER_VAULT, usdc_balance)?;
+        let register = IFixtureBalancerVault::registerPoolCall {
+            poolId: BALANCER_WETH_USDC_POOL_ID,
+            tokenA: WETH,
+            tokenB: USDC,
+            balanceA: weth_balance,
+            balanceB: usdc_balance,
+            fee: U256::from(fees::MEDIUM),
+        };
+        fixtures::transact(state, &exec, BALANCER_VAULT, U256::ZERO, register)?;
+        Ok(chain)
+    }
+
+    fn insert_pool(
+        chain: &mut Chain,
+        token_a: Address,
+        token_b: Address,
+        fee: u32,
+        amount_a: U256,
+        amount_b: U256,
+    ) -> anyhow::Result<Address> {
+        let exec = chain.setup_execution();
+        let state = &mut chain.state;
+        let pool = Self::pool_address(token_a, token_b, fee);
+        let (token0, token1) = fixtures::sort_tokens(token_a, token_b);
+        let args = (token0, token1, U256::from(fee)).abi_encode_params();
+        fixtures::deploy(state, &exec, code::UNISWAP_V3_POOL, &args, pool)?;
+        let register = IFixtureV3Factory::registerPoolCall {
+            tokenA: token_a,
+            tokenB: token_b,
+            fee,
+            pool,
+        };
+        fixtures::transact(state, &exec, UNISWAP_V3_FACTORY, U256::ZERO, register)?;
+        for (token, amount) in [(token_a, amount_a), (token_b, amount_b)] {
+            Self::mint_into(state, &exec, token, pool, amount)?;
+        }
+        Ok(pool)
+    }
+
+    /// Mint through the token fixture, or for WETH wrap fresh ether and send it on
+    fn mint_into(
+        state: &mut State,
+        exec: &Execution,
+        token: Address,
+        to: Address,
+        amount: U256,
+    ) -> anyhow::Result<()> {
+        if state.code(token).is_empty() {
+            return Err(anyhow::anyhow!("No token fixture at {}", token));
+        }
+        if token == WETH {
+            fixtures::transact(state, exec, WETH, amount, IWETH9::depositCall {})?;
+            let transfer = IERC20::transferCall { to, amount };
+            fixtures::transact(state, exec, WETH, U256::ZERO, transfer)
+        } else {
+            let mint = IFixtureToken::mintCall { to, amount };
+            fixtures::transact(state, exec, token, U256::ZERO, mint)
+        }
+    }
+
+    /// Address Uniswap V3's factory deploys the pool for a pair and fee tier to
+    pub fn pool_address(token_a: Address, token_b: Address, fee: u32) -> Address {
+        let (token0, token1) = fixtures::sort_tokens(token_a, token_b);
+        let mut key = [0u8; 96];
+        key[12..32].copy_from_slice(token0.as_slice());
+        key[44..64].copy_from_slice(token1.as_slice());
+        key[64..].copy_from_slice(&U256::from(fee).to_be_bytes::<32>());
+        UNISWAP_V3_FACTORY.create2(keccak256(key), POOL_INIT_CODE_HASH)
+    }
+
+    /// Address a V2 factory deploys the pair for two tokens to
+    pub fn pair_address(protocol: SwapProtocol, token_a: Address, token_b: Address) -> Address {
+        let (token0, token1) = fixtures::sort_tokens(token_a, token_b);
+        let salt = keccak256([token0.as_slice(), token1.as_slice()].concat());
+        match protocol {
+            SwapProtocol::Sushiswap => {
+                SUSHISWAP_FACTORY.create2(salt, SUSHISWAP_PAIR_INIT_CODE_HASH)
+            }
+            _ => UNISWAP_V2_FACTORY.create2(salt, UNISWAP_V2_PAIR_INIT_CODE_HASH),
+        }
+    }
+
+    fn chain(&self) -> MutexGuard<'_, Chain> {
+        self.chain.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// JSON-RPC endpoint URL
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Addresses of the seeded contracts, lowercase like `TokenAddress::to_hex`
+    pub fn contracts(&self) -> ContractAddresses {
+        let hex = |address: Address| format!("{:#x}", address);
+        ContractAddresses {
+            usdc: hex(USDC),
+            usdt: hex(USDT),
+            dai: hex(DAI),
+            weth: hex(WETH),
+            uniswap_v3_factory: hex(UNISWAP_V3_FACTORY),
+            uniswap_v3_router: hex(UNISWAP_V3_ROUTER),
+            uniswap_v3_quoter_v2: hex(UNISWAP_V3_QUOTER_V2),
+            uniswap_v2_factory: hex(UNISWAP_V2_FACTORY),
+            uniswap_v2_router: hex(UNISWAP_V2_ROUTER),
+            sushiswap_factory: hex(SUSHISWAP_FACTORY),
+            sushiswap_router: hex(SUSHISWAP_ROUTER),
+            curve_pools: vec![hex(CURVE_3POOL)],
+            balancer_vault: hex(BALANCER_VAULT),
+            balancer_pool_ids: vec![BALANCER_WETH_USDC_POOL_ID.to_string()],
+            chainlink_eth_usd_feed: hex(ETH_USD_FEED),
+        }
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.chain().latest().number
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        self.chain().state.account(address).balance
+    }
+
+    pub fn set_balance(&self, address: Address, wei: U256) {
+        self.chain().state.account_mut(address).balance = wei;
+    }
+
+    /// A token fixture's balance for `owner`
+    pub fn token_balance(&self, token: Address, owner: Address) -> anyhow::Result<U256> {
+        let mut chain = self.chain();
+        let exec = chain.setup_execution();
+        if chain.state.code(token).is_empty() {
+            return Err(anyhow::anyhow!("No token fixture at {}", token));
+        }
+        let call = IERC20::balanceOfCall { account: owner };
+        Ok(fixtures::view(&mut chain.state, &exec, token, call)?._0)
+    }
+
+    /// Mint `amount` of a token fixture to `to` outside of any transaction
+    pub fn mint(&self, token: Address, to: Address, amount: U256) -> anyhow::Result<()> {
+        let mut chain = self.chain();
+        let exec = chain.setup_execution();
+        Self::mint_into(&mut chain.state, &exec, token, to, amount)
+    }
+
+    /// Set the ETH/USD feed's answer (8 decimals), starting a new round at the latest
+    /// block
+    pub fn set_eth_usd_answer(&self, answer: I256) {
+        let mut chain = self.chain();
+        let exec = chain.setup_execution();
+        let update = IFixturePriceFeed::updateAnswerCall { answer };
+        fixtures::transact(&mut chain.state, &exec, ETH_USD_FEED, U256::ZERO, update)
+            .expect("the owner can update the feed");
+    }
+
+    /// Deploy an ERC-20 token fixture at `address`, with Solidity's layout for a token
+    /// whose first state variables are `balanceOf` and `allowance`
+    pub fn add_token(&self, address: Address, name: &str, symbol: &str, decimals: u8) {
+        let mut chain = self.chain();
+        let exec = chain.setup_execution();
+        let args = (
+            name.to_string(),
+            symbol.to_string(),
+            U256::from(decimals),
+            U256::ZERO,
+            U256::from(1),
+        )
+            .abi_encode_params();
+        fixtures::deploy(&mut chain.state, &exec, code::FIXTURE_TOKEN, &args, address)
+            .expect("the token fixture deploys");
+    }
+
+    /// Deploy a pool for a pair of token fixtures at its factory address, minting its
+    /// initial reserves
+    pub fn add_pool(
+        &self,
+        token_a: Address,
+        token_b: Address,
+        fee: u32,
+        amount_a: U256,
+        amount_b: U256,
+    ) -> anyhow::Result<Address> {
+        Self::insert_pool(&mut self.chain(), token_a, token_b, fee, amount_a, amount_b)
+    }
+
+    /// Deploy a Uniswap V2 or Sushiswap pair for two token fixtures at its factory
+    /// address, minting its initial reserves
+    pub fn add_v2_pair(
+        &self,
+        protocol: SwapProtocol,
+        token_a: Address,
+        token_b: Address,
+        amount_a: U256,
+        amount_b: U256,
+    ) -> anyhow::Result<Address> {
+        let factory = match protocol {
+            SwapProtocol::UniswapV2 => UNISWAP_V2_FACTORY,
+            SwapProtocol::Sushiswap => SUSHISWAP_FACTORY,
+            SwapProtocol::UniswapV3 => {
+                return Err(anyhow::anyhow!("Uniswap V3 has pools, not V2 pairs"))
+            }
+        };
+        let mut chain = self.chain();
+        let exec = chain.setup_execution();
+        let state = &mut chain.state;
+        let pair = Self::pair_address(protocol, token_a, token_b);
+        let args = fixtures::sort_tokens(token_a, token_b).abi_encode_params();
+        fixtures::deploy(state, &exec, code::UNISWAP_V2_PAIR, &args, pair)?;
+        let register = IFixtureV2Factory::registerPairCall {
+            tokenA: token_a,
+            tokenB: token_b,
+            pair,
+        };
+        fixtures::transact(state, &exec, factory, U256::ZERO, register)?;
+        for (token, amount) in [(token_a, amount_a), (token_b, amount_b)] {
+            Self::mint_into(state, &exec, token, pair, amount)?;
+        }
+        // Reserves are what the pair held at its last sync
+        fixtures::transact(state, &exec, pair, U256::ZERO, IFixtureV2Pair::syncCall {})?;
+        Ok(pair)
+    }
+    /// Mine every transaction as it arrives (the default), or leave them pending until
+    /// [`Self::mine`]
+    pub fn set_automine(&self, enabled: bool) {
+        self.chain().automine = enabled;
+    }
+
+    /// Mine one block with the pending transactions; returns its number
+    pub fn mine(&self) -> u64 {
+        self.chain().mine()
+    }
+
+    /// Mine `count` blocks; returns the last one's number
+    pub fn mine_blocks(&self, count: u64) -> u64 {
+        let mut chain = self.chain();
+        for _ in 0..count {
+            chain.mine();
+        }
+        chain.latest().number
+    }
+
+    /// Evict a pending transaction as a node's mempool would; false if it isn't pending
+    pub fn drop_transaction(&self, tx_hash: &B256) -> bool {
+        self.chain().forget(tx_hash)
+    }
+
+    /// Make the next `eth_sendRawTransaction` fail with `fault`
+    pub fn fail_next_broadcast(&self, fault: BroadcastFault) {
+        self.chain().broadcast_fault = Some(fault);
+    }
+
+    /// Make every `eth_call` of `input` on `to` fail with a node error rather than a revert
+    pub fn fail_calls(&self, to: Address, input: Bytes) {
+        self.chain().failing_calls.push((to, input));
+    }
+
+    /// Delay every response by `latency`, as a remote node would
+    pub fn set_latency(&self, latency: Duration) {
+        self.chain().latency = latency;
+    }
+
+    /// The most JSON-RPC requests that have been in flight at once
+    pub fn peak_concurrent_requests(&self) -> usize {
+        self.chain().peak_in_flight
+    }
+
+    /// Transactions waiting to be mined
+    pub fn pending_transactions(&self) -> Vec<B256> {
+        self.chain().pending.clone()
+    }
+}
+
+impl Drop for DevNode {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_address_matches_mainnet() {
+        // The USDC/WETH 0.05% and 0.3% pools
+        assert_eq!(
+            DevNode::pool_address(WETH, USDC, fees::LOW),
+            address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")
+        );
+        assert_eq!(
+            DevNode::pool_address(USDC, WETH, fees::MEDIUM),
+            address!("8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spawn_seeds_fixtures() {
+        let node = DevNode::spawn().await.unwrap();
+        let dev_account = PrivateKeySigner::from_str(DEV_PRIVATE_KEY.trim_start_matches("0x"))
+            .unwrap()
+            .address();
+        assert_eq!(node.block_number(), 0);
+        assert_eq!(
+            node.balance(dev_account),
+            U256::from(10_000u64) * U256::from(10u64).pow(U256::from(18))
+        );
+        let pool = DevNode::pool_address(WETH, USDC, fees::MEDIUM);
+        assert_eq!(
+            node.token_balance(USDC, pool).unwrap(),
+            U256::from(3_000_000_000_000u64)
+        );
+        assert!(node.token_balance(Address::ZERO, pool).is_err());
+        assert!(node.url().starts_with("http://127.0.0.1:"));
+    }
+}
@@ -0,0 +1,358 @@
+/// JSON-RPC front end of the simulated node
+/// Answers the `eth_` methods the server uses with the same shapes geth returns, so the
+/// production provider and alloy's deserializers run unchanged against it
+use super::chain::{CallRequest, Chain, ChainError, Inclusion, MinedBlock, TxFields, TxRecord};
+use super::BroadcastFault;
+use alloy::consensus::{Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom, TxEnvelope};
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{Address, Bloom, Bytes, TxKind, B256, B64, U256, U64};
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::{
+    Block, BlockTransactions, Header, Log, Parity, Signature, Transaction, TransactionReceipt,
+    TransactionRequest,
+};
+use alloy::sol_types::{Revert, SolError};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+pub(crate) type SharedChain = Arc<Mutex<Chain>>;
+
+/// Handle a JSON-RPC request or batch
+pub(crate) async fn handle_rpc(
+    State(chain): State<SharedChain>,
+    Json(body): Json<Value>,
+) -> Response {
+    let latency = {
+        let mut chain = chain.lock().unwrap_or_else(|e| e.into_inner());
+        chain.in_flight += 1;
+        chain.peak_in_flight = chain.peak_in_flight.max(chain.in_flight);
+        chain.latency
+    };
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+    let responses = match &body {
+        Value::Array(requests) => requests
+            .iter()
+            .map(|request| respond(&chain, request))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        request => respond(&chain, request),
+    };
+    chain.lock().unwrap_or_else(|e| e.into_inner()).in_flight -= 1;
+    match responses {
+        Some(responses) => Json(responses).into_response(),
+        // What a client sees when a proxy gives up on the node mid-request
+        None => StatusCode::BAD_GATEWAY.into_response(),
+    }
+}
+
+/// The response to one request; `None` if it is lost after the node acted on it
+fn respond(chain: &SharedChain, request: &Value) -> Option<Value> {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or(json!([]));
+    let mut chain = chain.lock().unwrap_or_else(|e| e.into_inner());
+    let fault = match method {
+        "eth_sendRawTransaction" => chain.broadcast_fault.take(),
+        _ => None,
+    };
+    let outcome = match &fault {
+        Some(BroadcastFault::Reject(message)) => Err(rpc_error(-32000, message.clone())),
+        _ => dispatch(&mut chain, method, &params),
+    };
+    if matches!(fault, Some(BroadcastFault::LoseResponse)) {
+        return None;
+    }
+    Some(match outcome {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+    })
+}
+
+fn rpc_error(code: i64, message: impl Into<String>) -> Value {
+    json!({"code": code, "message": message.into()})
+}
+
+/// Errors as geth reports them: reverts use code 3 with the revert data attached
+fn chain_error(error: ChainError) -> Value {
+    match error {
+        ChainError::Revert(data) => {
+            let message = match Revert::abi_decode(&data, true) {
+                Ok(revert) => format!("execution reverted: {}", revert.reason),
+                Err(_) => "execution reverted".to_string(),
+            };
+            json!({"code": 3, "message": message, "data": data})
+        }
+        ChainError::OutOfGas => rpc_error(-32000, "out of gas"),
+        ChainError::Rejected(message) => rpc_error(-32000, message),
+    }
+}
+
+/// Positional parameter `index`; a missing one reads as `null`
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, Value> {
+    serde_json::from_value(params.get(index).cloned().unwrap_or(Value::Null))
+        .map_err(|e| rpc_error(-32602, format!("invalid argument {}: {}", index, e)))
+}
+
+fn quantity(value: u64) -> Value {
+    json!(format!("{:#x}", value))
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<Value, Value> {
+    serde_json::to_value(value).map_err(|e| rpc_error(-32603, e.to_string()))
+}
+
+fn dispatch(chain: &mut Chain, method: &str, params: &Value) -> Result<Value, Value> {
+    match method {
+        "web3_clientVersion" => Ok(json!("ethereum-mcp-server-devnet")),
+        "net_version" => Ok(json!(chain.chain_id.to_string())),
+        "eth_chainId" => Ok(quantity(chain.chain_id)),
+        "eth_blockNumber" => Ok(quantity(chain.latest().number)),
+        "eth_gasPrice" => to_json(U256::from(chain.base_fee_per_gas)),
+        "eth_getBalance" => {
+            let address = param(params, 0)?;
+            to_json(chain.state.account(address).balance)
+        }
+        "eth_getTransactionCount" => {
+            let address = param(params, 0)?;
+            let tag: Option<BlockNumberOrTag> = param(params, 1)?;
+            let pending = matches!(tag, Some(BlockNumberOrTag::Pending));
+            Ok(quantity(chain.nonce(address, pending)))
+        }
+        "eth_getCode" => {
+            let address: Address = param(params, 0)?;
+            to_json(chain.state.code(address))
+        }
+        "eth_call" => {
+            let request = call_request(param(params, 0)?, param(params, 2)?);
+            let failing = chain
+                .failing_calls
+                .iter()
+                .any(|(to, input)| request.to == Some(*to) && request.input == *input);
+            if failing {
+                return Err(rpc_error(-32603, "Injected call failure"));
+            }
+            let (output, _) = chain.call(&request).map_err(chain_error)?;
+            to_json(output)
+        }
+        "eth_estimateGas" => {
+            let request = call_request(param(params, 0)?, param(params, 2)?);
+            let gas = chain.estimate_gas(&request).map_err(chain_error)?;
+            Ok(quantity(gas))
+        }
+        "eth_sendRawTransaction" => {
+            let raw: Bytes = param(params, 0)?;
+            let hash = chain.send_raw_transaction(&raw).map_err(chain_error)?;
+            to_json(hash)
+        }
+        "eth_feeHistory" => {
+            let block_count: U64 = param(params, 0)?;
+            let newest: BlockNumberOrTag = param(params, 1)?;
+            let percentiles: Vec<f64> = param(params, 2)?;
+            let newest = resolve_block(chain, newest)
+                .map(|block| block.number)
+                .unwrap_or(chain.latest().number);
+            to_json(chain.fee_history(block_count.to::<u64>(), newest, &percentiles))
+        }
+        "eth_getBlockByNumber" => {
+            let tag: BlockNumberOrTag = param(params, 0)?;
+            let full: bool = param(params, 1)?;
+            match resolve_block(chain, tag) {
+                Some(block) => to_json(rpc_block(chain, block, full)),
+                None => Ok(Value::Null),
+            }
+        }
+        "eth_getBlockByHash" => {
+            let hash: B256 = param(params, 0)?;
+            let full: bool = param(params, 1)?;
+            match chain.block_by_hash(&hash) {
+                Some(block) => to_json(rpc_block(chain, block, full)),
+                None => Ok(Value::Null),
+            }
+        }
+        "eth_getTransactionByHash" => {
+            let hash: B256 = param(params, 0)?;
+            match chain.transactions.get(&hash) {
+                Some(record) => to_json(rpc_transaction(&hash, record)),
+                None => Ok(Value::Null),
+            }
+        }
+        "eth_getTransactionReceipt" => {
+            let hash: B256 = param(params, 0)?;
+            match chain.transactions.get(&hash) {
+                Some(record) => match &record.inclusion {
+                    Some(inclusion) => to_json(rpc_receipt(&hash, record, inclusion)),
+                    None => Ok(Value::Null),
+                },
+                None => Ok(Value::Null),
+            }
+        }
+        _ => Err(rpc_error(
+            -32601,
+            format!("the method {} does not exist/is not available", method),
+        )),
+    }
+}
+
+fn call_request(request: TransactionRequest, overrides: Option<StateOverride>) -> CallRequest {
+    CallRequest {
+        from: request.from,
+        to: match request.to {
+            Some(TxKind::Call(to)) => Some(to),
+            _ => None,
+        },
+        value: request.value.unwrap_or_default(),
+        input: request.input.input().cloned().unwrap_or_default(),
+        gas: request.gas.map(|gas| gas as u64),
+        overrides: overrides.unwrap_or_default(),
+    }
+}
+
+fn resolve_block(chain: &Chain, tag: BlockNumberOrTag) -> Option<&MinedBlock> {
+    match tag {
+        BlockNumberOrTag::Latest | BlockNumberOrTag::Pending => Some(chain.latest()),
+        BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized => Some(chain.finalized()),
+        BlockNumberOrTag::Earliest => chain.block(0),
+        BlockNumberOrTag::Number(number) => chain.block(number),
+    }
+}
+
+fn signature(envelope: &TxEnvelope) -> Option<Signature> {
+    let signature = match envelope {
+        TxEnvelope::Legacy(signed) => signed.signature(),
+        TxEnvelope::Eip1559(signed) => signed.signature(),
+        _ => return None,
+    };
+    Some(Signature {
+        r: signature.r(),
+        s: signature.s(),
+        v: U256::from(signature.v().to_u64()),
+        y_parity: Some(Parity(signature.v().y_parity())),
+    })
+}
+
+fn rpc_transaction(hash: &B256, record: &TxRecord) -> Transaction {
+    let tx = TxFields::of(&record.envelope).expect("stored transactions have supported types");
+    let inclusion = record.inclusion.as_ref();
+    let is_legacy = matches!(record.envelope, TxEnvelope::Legacy(_));
+    Transaction {
+        hash: *hash,
+        nonce: tx.nonce,
+        block_hash: inclusion.map(|i| i.block_hash),
+        block_number: inclusion.map(|i| i.block_number),
+        transaction_index: inclusion.map(|i| i.index),
+        from: record.from,
+        to: match tx.to {
+            TxKind::Call(to) => Some(to),
+            TxKind::Create => None,
+        },
+        value: tx.value,
+        // Mined EIP-1559 transactions report the price they paid
+        gas_price: match inclusion {
+            Some(inclusion) => Some(inclusion.effective_gas_price),
+            None if is_legacy => Some(tx.max_fee_per_gas),
+            None => None,
+        },
+        gas: tx.gas_limit as u128,
+        max_fee_per_gas: (!is_legacy).then_some(tx.max_fee_per_gas),
+        max_priority_fee_per_gas: (!is_legacy).then_some(tx.max_priority_fee_per_gas),
+        input: tx.input,
+        signature: signature(&record.envelope),
+        chain_id: tx.chain_id,
+        access_list: (!is_legacy).then(Default::default),
+        transaction_type: Some(if is_legacy { 0 } else { 2 }),
+        ..Default::default()
+    }
+}
+
+fn rpc_receipt(hash: &B256, record: &TxRecord, inclusion: &Inclusion) -> TransactionReceipt {
+    let tx = TxFields::of(&record.envelope).expect("stored transactions have supported types");
+    let mut logs_bloom = Bloom::ZERO;
+    let logs = inclusion
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(index, log)| {
+            logs_bloom.accrue_log(log);
+            Log {
+                inner: log.clone(),
+                block_hash: Some(inclusion.block_hash),
+                block_number: Some(inclusion.block_number),
+                block_timestamp: None,
+                transaction_hash: Some(*hash),
+                transaction_index: Some(inclusion.index),
+                log_index: Some(index as u64),
+                removed: false,
+            }
+        })
+        .collect();
+    let receipt = ReceiptWithBloom {
+        receipt: Receipt {
+            status: Eip658Value::Eip658(inclusion.success),
+            cumulative_gas_used: inclusion.cumulative_gas_used as u128,
+            logs,
+        },
+        logs_bloom,
+    };
+    TransactionReceipt {
+        inner: match record.envelope {
+            TxEnvelope::Legacy(_) => ReceiptEnvelope::Legacy(receipt),
+            _ => ReceiptEnvelope::Eip1559(receipt),
+        },
+        transaction_hash: *hash,
+        transaction_index: Some(inclusion.index),
+        block_hash: Some(inclusion.block_hash),
+        block_number: Some(inclusion.block_number),
+        gas_used: inclusion.gas_used as u128,
+        effective_gas_price: inclusion.effective_gas_price,
+        blob_gas_used: None,
+        blob_gas_price: None,
+        from: record.from,
+        to: match tx.to {
+            TxKind::Call(to) => Some(to),
+            TxKind::Create => None,
+        },
+        contract_address: inclusion.contract_address,
+        state_root: None,
+    }
+}
+
+fn rpc_block(chain: &Chain, block: &MinedBlock, full: bool) -> Block {
+    let transactions = if full {
+        BlockTransactions::Full(
+            block
+                .transactions
+                .iter()
+                .filter_map(|hash| Some(rpc_transaction(hash, chain.transactions.get(hash)?)))
+                .collect(),
+        )
+    } else {
+        BlockTransactions::Hashes(block.transactions.clone())
+    };
+    Block {
+        header: Header {
+            hash: Some(block.hash),
+            parent_hash: block.parent_hash,
+            number: Some(block.number),
+            gas_limit: super::chain::BLOCK_GAS_LIMIT as u128,
+            gas_used: block.gas_used as u128,
+            timestamp: block.timestamp,
+            base_fee_per_gas: Some(block.base_fee_per_gas),
+            total_difficulty: Some(U256::ZERO),
+            mix_hash: Some(B256::ZERO),
+            nonce: Some(B64::ZERO),
+            ..Default::default()
+        },
+        transactions,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,171 @@
+/// World state of the simulated node: an in-memory EVM database that calls and
+/// transactions run against with revm
+use super::chain::BLOCK_GAS_LIMIT;
+use alloy::primitives::{Address, Bytes, TxKind, U256};
+use alloy::rpc::types::state::StateOverride;
+use revm::db::{AccountState, CacheDB, EmptyDB};
+use revm::primitives::{AccountInfo, Bytecode, ExecutionResult, SpecId};
+use revm::{DatabaseRef, Evm};
+
+/// The block a call or transaction runs in
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Execution {
+    pub chain_id: u64,
+    pub number: u64,
+    pub timestamp: u64,
+    pub base_fee_per_gas: u128,
+}
+
+/// One call or transaction as the EVM runs it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Message {
+    pub from: Address,
+    pub to: TxKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub gas_limit: u64,
+    /// The gas price for legacy transactions and calls, the fee cap for EIP-1559 ones
+    pub gas_price: u128,
+    /// Set for EIP-1559 transactions only
+    pub priority_fee: Option<u128>,
+    pub chain_id: Option<u64>,
+    /// Checked and bumped when set; calls leave it unset
+    pub nonce: Option<u64>,
+}
+
+/// Accounts, code and storage. Callers run calls against a copy and transactions
+/// against the original.
+#[derive(Clone, Debug)]
+pub(crate) struct State {
+    db: CacheDB<EmptyDB>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            db: CacheDB::new(EmptyDB::default()),
+        }
+    }
+}
+
+impl State {
+    pub fn account(&self, address: Address) -> AccountInfo {
+        self.db
+            .basic_ref(address)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    pub fn account_mut(&mut self, address: Address) -> &mut AccountInfo {
+        let account = self.db.accounts.entry(address).or_default();
+        // A lookup of an empty address caches it as missing, which hides its balance
+        if matches!(account.account_state, AccountState::NotExisting) {
+            account.account_state = AccountState::None;
+        }
+        &mut account.info
+    }
+
+    /// Deployed code at `address`; empty for plain accounts
+    pub fn code(&self, address: Address) -> Bytes {
+        let info = self.account(address);
+        match info.code {
+            Some(code) => code.original_bytes(),
+            None => self
+                .db
+                .code_by_hash_ref(info.code_hash)
+                .map(|code| code.original_bytes())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn set_code(&mut self, address: Address, code: Bytes) {
+        let mut info = self.account(address);
+        let code = Bytecode::new_raw(code);
+        info.code_hash = code.hash_slow();
+        info.code = Some(code);
+        self.db.insert_account_info(address, info);
+    }
+
+    /// Move an account's code and storage to another address, for contracts that must
+    /// sit at an address no deployment would give them
+    pub fn relocate(&mut self, from: Address, to: Address) {
+        if let Some(account) = self.db.accounts.remove(&from) {
+            self.db.accounts.insert(to, account);
+        }
+    }
+
+    /// Apply `eth_call` state overrides: `state` replaces an account's storage and
+    /// `stateDiff` patches it
+    pub fn apply_overrides(&mut self, overrides: &StateOverride) {
+        for (address, account) in overrides {
+            if let Some(balance) = account.balance {
+                self.account_mut(*address).balance = balance;
+            }
+            if let Some(nonce) = account.nonce {
+                self.account_mut(*address).nonce = nonce.to::<u64>();
+            }
+            if let Some(code) = &account.code {
+                self.set_code(*address, code.clone());
+            }
+            if let Some(state) = &account.state {
+                let storage = state
+                    .iter()
+                    .map(|(slot, value)| {
+                        (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0))
+                    })
+                    .collect();
+                let _ = self.db.replace_account_storage(*address, storage);
+            }
+            for (slot, value) in account.state_diff.iter().flatten() {
+                let _ = self.db.insert_account_storage(
+                    *address,
+                    U256::from_be_bytes(slot.0),
+                    U256::from_be_bytes(value.0),
+                );
+            }
+        }
+    }
+
+    /// Run `message` in the block `exec` describes, keeping its state changes if
+    /// `commit`. Fails with the node's reason when the message can't be run at all.
+    pub fn transact(
+        &mut self,
+        exec: &Execution,
+        message: Message,
+        commit: bool,
+    ) -> Result<ExecutionResult, String> {
+        let mut evm = Evm::builder()
+            .with_db(&mut self.db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_cfg_env(|cfg| {
+                cfg.chain_id = exec.chain_id;
+                // The fixture routers are larger than mainnet's contract size limit
+                cfg.limit_contract_code_size = Some(usize::MAX);
+            })
+            .modify_block_env(|block| {
+                block.number = U256::from(exec.number);
+                block.timestamp = U256::from(exec.timestamp);
+                block.basefee = U256::from(exec.base_fee_per_gas);
+                block.gas_limit = U256::from(BLOCK_GAS_LIMIT);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = message.from;
+                tx.transact_to = message.to;
+                tx.value = message.value;
+                tx.data = message.input;
+                tx.gas_limit = message.gas_limit;
+                tx.gas_price = U256::from(message.gas_price);
+                tx.gas_priority_fee = message.priority_fee.map(U256::from);
+                tx.chain_id = message.chain_id;
+                tx.nonce = message.nonce;
+            })
+            .build();
+        let result = if commit {
+            evm.transact_commit()
+        } else {
+            evm.transact().map(|outcome| outcome.result)
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
@@ -1,8 +1,12 @@
 /// Ethereum MCP Server Library
 /// Clean public API following SOLID principles
+use rust_decimal::Decimal;
+use services::swap::{DEFAULT_MAX_PRICE_IMPACT, DEFAULT_PRICE_IMPACT_WARNING};
 use std::fmt;
 
 pub mod contracts;
+#[cfg(any(test, feature = "devnet"))]
+pub mod devnet;
 pub mod providers;
 pub mod server;
 pub mod services;
@@ -23,7 +27,18 @@ pub struct ContractAddresses {
     pub weth: String,
     pub uniswap_v3_factory: String,
     pub uniswap_v3_router: String,
-    pub uniswap_v3_quoter: String,
+    /// QuoterV2, used for every Uniswap V3 quote since it also reports gas and ticks crossed
+    pub uniswap_v3_quoter_v2: String,
+    pub uniswap_v2_factory: String,
+    pub uniswap_v2_router: String,
+    /// Sushiswap's Uniswap V2 fork
+    pub sushiswap_factory: String,
+    pub sushiswap_router: String,
+    /// Curve StableSwap pools quoted by `compare_quotes`
+    pub curve_pools: Vec<String>,
+    pub balancer_vault: String,
+    /// Balancer V2 pool IDs quoted by `compare_quotes`
+    pub balancer_pool_ids: Vec<String>,
     pub chainlink_eth_usd_feed: String,
 }
 
@@ -36,7 +51,17 @@ impl Default for ContractAddresses {
             weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
             uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
             uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-            uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+            uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+            uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+            uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+            sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+            sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+            curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+            balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+            balancer_pool_ids: vec![
+                "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019".to_string(),
+                "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a".to_string(),
+            ],
             chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
         }
     }
@@ -58,7 +83,19 @@ pub struct Config {
     pub rate_limit_burst: u32,
     pub cors_allow_origins: String,
     // Trading limits
+    /// Maximum USD value of a single swap
     pub max_swap_amount: u64,
+    pub swap_quote_ttl_seconds: u64,
+    /// Price impact percent above which swap quotes are flagged
+    pub price_impact_warning_percent: Decimal,
+    /// Price impact percent above which swap quotes are refused
+    pub max_price_impact_percent: Decimal,
+    /// Longest a single `wait_for_transaction` call may block
+    pub wait_for_transaction_max_seconds: u64,
+    /// Journal of signed transactions; `None` keeps nonce tracking in memory only
+    pub tx_store_path: Option<String>,
+    /// Fixture file capturing every RPC request and response, for replay in tests
+    pub rpc_record_path: Option<String>,
     // Network configuration
     pub ethereum_request_timeout_seconds: u64,
     pub ethereum_max_concurrent_requests: usize,
@@ -75,6 +112,8 @@ impl fmt::Debug for Config {
             .field("server_port", &self.server_port)
             .field("log_level", &self.log_level)
             .field("wallet_private_key", &"[REDACTED]")
+            .field("tx_store_path", &self.tx_store_path)
+            .field("rpc_record_path", &self.rpc_record_path)
             .field("contracts", &self.contracts)
             .finish()
     }
@@ -101,7 +140,13 @@ impl Config {
             rate_limit_rps: 2,
             rate_limit_burst: 10,
             cors_allow_origins: "*".to_string(),
-            max_swap_amount: 1_000_000_000, // 1B tokens default
+            max_swap_amount: 1_000_000_000, // $1B default
+            swap_quote_ttl_seconds: 60,
+            price_impact_warning_percent: DEFAULT_PRICE_IMPACT_WARNING,
+            max_price_impact_percent: DEFAULT_MAX_PRICE_IMPACT,
+            wait_for_transaction_max_seconds: 120,
+            tx_store_path: None,
+            rpc_record_path: None,
             ethereum_request_timeout_seconds: 30,
             ethereum_max_concurrent_requests: 10,
             contracts: ContractAddresses {
@@ -111,7 +156,19 @@ impl Config {
                 weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
                 uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
                 uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-                uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+                uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+                uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+                uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+                sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+                sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+                curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+                balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+                balancer_pool_ids: vec![
+                    "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019"
+                        .to_string(),
+                    "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a"
+                        .to_string(),
+                ],
                 chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
             },
         }
@@ -179,6 +236,36 @@ impl Config {
             .and_then(|v| v.parse::<u64>().ok())
             .unwrap_or(1_000_000_000);
 
+        let swap_quote_ttl_seconds = std::env::var("SWAP_QUOTE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        let price_impact_warning_percent = std::env::var("PRICE_IMPACT_WARNING_PERCENT")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(DEFAULT_PRICE_IMPACT_WARNING);
+
+        let max_price_impact_percent = std::env::var("MAX_PRICE_IMPACT_PERCENT")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(DEFAULT_MAX_PRICE_IMPACT);
+
+        let wait_for_transaction_max_seconds = std::env::var("WAIT_FOR_TRANSACTION_MAX_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(120);
+
+        let tx_store_path = std::env::var("TX_STORE_PATH")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let rpc_record_path = std::env::var("RPC_RECORD_PATH")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
         let ethereum_request_timeout_seconds = std::env::var("ETHEREUM_REQUEST_TIMEOUT_SECONDS")
             .ok()
             .and_then(|v| v.parse::<u64>().ok())
@@ -189,6 +276,15 @@ impl Config {
             .and_then(|v| v.parse::<usize>().ok())
             .unwrap_or(10);
 
+        // Comma-separated lists; set to an empty string to disable
+        let csv_env = |name: &str| {
+            std::env::var(name).ok().map(|list| {
+                list.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+            })
+        };
         let contracts = ContractAddresses {
             usdc: std::env::var("USDC_ADDRESS")
                 .unwrap_or_else(|_| "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string()),
@@ -202,8 +298,28 @@ impl Config {
                 .unwrap_or_else(|_| "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string()),
             uniswap_v3_router: std::env::var("UNISWAP_V3_ROUTER")
                 .unwrap_or_else(|_| "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string()),
-            uniswap_v3_quoter: std::env::var("UNISWAP_V3_QUOTER")
-                .unwrap_or_else(|_| "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string()),
+            uniswap_v3_quoter_v2: std::env::var("UNISWAP_V3_QUOTER_V2")
+                .unwrap_or_else(|_| "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string()),
+            uniswap_v2_factory: std::env::var("UNISWAP_V2_FACTORY")
+                .unwrap_or_else(|_| "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string()),
+            uniswap_v2_router: std::env::var("UNISWAP_V2_ROUTER")
+                .unwrap_or_else(|_| "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string()),
+            sushiswap_factory: std::env::var("SUSHISWAP_FACTORY")
+                .unwrap_or_else(|_| "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string()),
+            sushiswap_router: std::env::var("SUSHISWAP_ROUTER")
+                .unwrap_or_else(|_| "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string()),
+            curve_pools: csv_env("CURVE_POOLS")
+                .unwrap_or_else(|| vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()]),
+            balancer_vault: std::env::var("BALANCER_VAULT")
+                .unwrap_or_else(|_| "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string()),
+            balancer_pool_ids: csv_env("BALANCER_POOL_IDS").unwrap_or_else(|| {
+                vec![
+                    "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019"
+                        .to_string(),
+                    "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a"
+                        .to_string(),
+                ]
+            }),
             chainlink_eth_usd_feed: std::env::var("CHAINLINK_ETH_USD_FEED")
                 .unwrap_or_else(|_| "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string()),
         };
@@ -221,6 +337,12 @@ impl Config {
             rate_limit_burst,
             cors_allow_origins,
             max_swap_amount,
+            swap_quote_ttl_seconds,
+            price_impact_warning_percent,
+            max_price_impact_percent,
+            wait_for_transaction_max_seconds,
+            tx_store_path,
+            rpc_record_path,
             ethereum_request_timeout_seconds,
             ethereum_max_concurrent_requests,
             contracts,
@@ -302,6 +424,25 @@ impl Config {
         if self.max_swap_amount == 0 {
             return Err(anyhow::anyhow!("MAX_SWAP_AMOUNT must be greater than 0"));
         }
+        if self.swap_quote_ttl_seconds == 0 || self.swap_quote_ttl_seconds > 600 {
+            return Err(anyhow::anyhow!(
+                "SWAP_QUOTE_TTL_SECONDS must be between 1 and 600"
+            ));
+        }
+        if self.price_impact_warning_percent <= Decimal::ZERO
+            || self.price_impact_warning_percent > self.max_price_impact_percent
+            || self.max_price_impact_percent > Decimal::ONE_HUNDRED
+        {
+            return Err(anyhow::anyhow!(
+                "PRICE_IMPACT_WARNING_PERCENT must be positive and at most MAX_PRICE_IMPACT_PERCENT, which must be at most 100"
+            ));
+        }
+        if self.wait_for_transaction_max_seconds == 0 || self.wait_for_transaction_max_seconds > 900
+        {
+            return Err(anyhow::anyhow!(
+                "WAIT_FOR_TRANSACTION_MAX_SECONDS must be between 1 and 900"
+            ));
+        }
 
         // Network configuration validation
         if self.ethereum_request_timeout_seconds == 0 || self.ethereum_request_timeout_seconds > 300
@@ -553,6 +694,69 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_config_validation_swap_quote_ttl() {
+        let mut config = Config::new(
+            "https://mainnet.infura.io/v3/test".to_string(),
+            "127.0.0.1".to_string(),
+            3000,
+            "info".to_string(),
+            "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string(),
+        );
+        assert_eq!(config.swap_quote_ttl_seconds, 60);
+
+        config.swap_quote_ttl_seconds = 0;
+        assert!(config.validate().is_err());
+
+        config.swap_quote_ttl_seconds = 601;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_price_impact_limits() {
+        let mut config = Config::new(
+            "https://mainnet.infura.io/v3/test".to_string(),
+            "127.0.0.1".to_string(),
+            3000,
+            "info".to_string(),
+            "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string(),
+        );
+        assert_eq!(config.price_impact_warning_percent, Decimal::from(3));
+        assert_eq!(config.max_price_impact_percent, Decimal::from(15));
+
+        config.price_impact_warning_percent = Decimal::ZERO;
+        assert!(config.validate().is_err());
+
+        config.price_impact_warning_percent = Decimal::from(20);
+        assert!(config.validate().is_err());
+
+        config.price_impact_warning_percent = Decimal::from(5);
+        config.max_price_impact_percent = Decimal::from(101);
+        assert!(config.validate().is_err());
+
+        config.max_price_impact_percent = Decimal::from(100);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_validation_wait_for_transaction_max() {
+        let mut config = Config::new(
+            "https://mainnet.infura.io/v3/test".to_string(),
+            "127.0.0.1".to_string(),
+            3000,
+            "info".to_string(),
+            "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string(),
+        );
+        assert_eq!(config.wait_for_transaction_max_seconds, 120);
+        assert!(config.validate().is_ok());
+
+        config.wait_for_transaction_max_seconds = 0;
+        assert!(config.validate().is_err());
+
+        config.wait_for_transaction_max_seconds = 901;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_config_validation_private_key_formats() {
         // Test valid format with 0x prefix
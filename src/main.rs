@@ -1,13 +1,17 @@
 /// Main application entry point
 /// Proper dependency injection and graceful shutdown
 use ethereum_mcp_server::{
-    providers::ProviderFactory,
+    providers::{ProviderFactory, RpcRecorder, TransactionStore},
     server::http::{AppState, HttpServer},
-    services::{BalanceService, PriceService, SwapService, TransactionStatusService},
+    services::{
+        AllowanceService, BalanceService, GasService, PriceService, SwapService,
+        TransactionStatusService,
+    },
     Config,
 };
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::Duration;
+use tracing::{error, info, warn};
 
 /// Initialize logging subsystem
 pub fn initialize_logging() {
@@ -34,12 +38,32 @@ pub async fn load_config() -> anyhow::Result<Config> {
 
 /// Initialize Ethereum provider and services
 pub async fn initialize_services(config: &Config) -> anyhow::Result<AppState> {
+    // Persist signed transactions so a restart doesn't lose track of in-flight ones
+    let tx_store = match &config.tx_store_path {
+        Some(path) => Some(Arc::new(TransactionStore::open(path).await?)),
+        None => {
+            warn!("TX_STORE_PATH not set - pending transactions will not survive a restart");
+            None
+        }
+    };
+
+    // Capture RPC traffic for replay in regression tests
+    let rpc_recorder = match &config.rpc_record_path {
+        Some(path) => {
+            warn!(path = %path, "RPC_RECORD_PATH set - recording every RPC request and response");
+            Some(Arc::new(RpcRecorder::create(path).await?))
+        }
+        None => None,
+    };
+
     // Initialize Ethereum provider (strategic interface for testing)
     let ethereum_provider = ProviderFactory::create_ethereum_provider_with_failover(
         config.ethereum_rpc_urls.clone(),
         config.wallet_private_key().to_string(),
         config.ethereum_max_concurrent_requests,
         config.ethereum_request_timeout_seconds,
+        tx_store,
+        rpc_recorder,
     )
     .await?;
     info!("Ethereum provider initialized");
@@ -50,12 +74,24 @@ pub async fn initialize_services(config: &Config) -> anyhow::Result<AppState> {
         ethereum_provider.clone(),
         config.contracts.clone(),
     ));
-    let swap_service = Arc::new(SwapService::new(
+    let swap_service = Arc::new(
+        SwapService::new(ethereum_provider.clone(), config.contracts.clone())
+            .with_quote_ttl(Duration::from_secs(config.swap_quote_ttl_seconds))
+            .with_price_impact_limits(
+                config.price_impact_warning_percent,
+                config.max_price_impact_percent,
+            )
+            .with_max_swap_value(config.max_swap_amount.into()),
+    );
+    let transaction_status_service = Arc::new(
+        TransactionStatusService::new(ethereum_provider.clone())
+            .with_max_wait(Duration::from_secs(config.wait_for_transaction_max_seconds)),
+    );
+    let gas_service = Arc::new(GasService::new(ethereum_provider.clone()));
+    let allowance_service = Arc::new(AllowanceService::new(
         ethereum_provider.clone(),
         config.contracts.clone(),
     ));
-    let transaction_status_service =
-        Arc::new(TransactionStatusService::new(ethereum_provider.clone()));
 
     info!("Services initialized");
 
@@ -65,7 +101,8 @@ pub async fn initialize_services(config: &Config) -> anyhow::Result<AppState> {
         price_service,
         swap_service,
         transaction_status_service,
-        config.max_swap_amount,
+        gas_service,
+        allowance_service,
     ))
 }
 
@@ -0,0 +1,596 @@
+/// Fault-injecting provider decorator for resilience testing
+/// Wraps any `EthereumProvider` and, per method, adds latency and makes a seeded share of
+/// calls fail with RPC errors, hang until the caller times out, or come back malformed.
+/// Block numbers and confirmations can be reported behind the chain, like a lagging node.
+use super::EthereumProvider;
+use crate::types::{
+    AllowanceInfo, BalanceInfo, GasFeeEstimate, PermitSignature, QuoteComparison, ReplacementKind,
+    ReplacementResult, SwapParams, SwapResult, TokenAddress, TokenAmount, TokenPrice,
+    TransactionDetail, TransactionStatusInfo, WalletAddress,
+};
+use crate::ContractAddresses;
+use alloy::primitives::{Address, B256, U256};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::debug;
+
+/// Faults injected into calls of one provider method
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultConfig {
+    /// Added to every call before anything else happens
+    pub latency: Duration,
+    /// Share of calls (0.0-1.0) failing with an RPC error
+    pub error_rate: f64,
+    /// Share of calls that hang for the hang duration and then fail
+    pub timeout_rate: f64,
+    /// Share of calls failing as an undecodable node response
+    pub malformed_rate: f64,
+    /// How many blocks behind the chain block numbers and confirmations are reported
+    pub stale_blocks: u64,
+}
+
+impl FaultConfig {
+    /// Every call fails with an RPC error
+    pub fn failing() -> Self {
+        Self {
+            error_rate: 1.0,
+            ..Default::default()
+        }
+    }
+}
+
+/// A fault that was injected into a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    Error,
+    Timeout,
+    Malformed,
+}
+
+/// Provider decorator injecting configurable faults into `inner`
+pub struct ChaosProvider {
+    inner: Arc<dyn EthereumProvider>,
+    default_faults: RwLock<FaultConfig>,
+    method_faults: RwLock<HashMap<String, FaultConfig>>,
+    hang_duration: Duration,
+    /// splitmix64 state, so a seed reproduces the same fault sequence
+    rng: Mutex<u64>,
+    calls: AtomicUsize,
+    injected: Mutex<HashMap<Fault, usize>>,
+}
+
+impl ChaosProvider {
+    /// Wrap `inner` with no faults configured
+    pub fn new(inner: Arc<dyn EthereumProvider>) -> Self {
+        Self {
+            inner,
+            default_faults: RwLock::new(FaultConfig::default()),
+            method_faults: RwLock::new(HashMap::new()),
+            hang_duration: Duration::from_secs(60),
+            rng: Mutex::new(0x5eed),
+            calls: AtomicUsize::new(0),
+            injected: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seed the fault sequence
+    pub fn with_seed(self, seed: u64) -> Self {
+        *self.rng.lock().unwrap_or_else(|e| e.into_inner()) = seed;
+        self
+    }
+
+    /// Faults for every method without its own configuration
+    pub fn with_faults(self, faults: FaultConfig) -> Self {
+        self.set_faults(faults);
+        self
+    }
+
+    /// Faults for one method, by its `EthereumProvider` name (e.g. `"get_block_number"`)
+    pub fn with_method_faults(self, method: &str, faults: FaultConfig) -> Self {
+        self.set_method_faults(method, faults);
+        self
+    }
+
+    /// How long a timed-out call hangs before failing (default 60s)
+    pub fn with_hang_duration(mut self, hang_duration: Duration) -> Self {
+        self.hang_duration = hang_duration;
+        self
+    }
+
+    /// Change the default faults while in use, e.g. to let an outage recover
+    pub fn set_faults(&self, faults: FaultConfig) {
+        *self
+            .default_faults
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = faults;
+    }
+
+    /// Change one method's faults while in use
+    pub fn set_method_faults(&self, method: &str, faults: FaultConfig) {
+        self.method_faults
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(method.to_string(), faults);
+    }
+
+    /// Calls received, including ones that failed
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// How many times `fault` was injected
+    pub fn injected(&self, fault: Fault) -> usize {
+        self.injected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&fault)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn faults_for(&self, method: &str) -> FaultConfig {
+        self.method_faults
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(method)
+            .cloned()
+            .unwrap_or_else(|| {
+                self.default_faults
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone()
+            })
+    }
+
+    /// Uniform sample in [0, 1)
+    fn sample(&self) -> f64 {
+        let mut state = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn record(&self, fault: Fault) {
+        *self
+            .injected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(fault)
+            .or_default() += 1;
+    }
+
+    /// Apply `method`'s faults ahead of forwarding the call; returns its stale block lag
+    async fn inject(&self, method: &str) -> anyhow::Result<u64> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let faults = self.faults_for(method);
+        if !faults.latency.is_zero() {
+            tokio::time::sleep(faults.latency).await;
+        }
+
+        // One draw per call, so the rates partition it
+        let draw = self.sample();
+        let fault = if draw < faults.error_rate {
+            Some(Fault::Error)
+        } else if draw < faults.error_rate + faults.timeout_rate {
+            Some(Fault::Timeout)
+        } else if draw < faults.error_rate + faults.timeout_rate + faults.malformed_rate {
+            Some(Fault::Malformed)
+        } else {
+            None
+        };
+        let Some(fault) = fault else {
+            return Ok(faults.stale_blocks);
+        };
+        self.record(fault);
+        debug!(method, ?fault, "Injecting fault");
+        match fault {
+            Fault::Error => Err(anyhow::anyhow!(
+                "{} failed: server returned an error response: error code -32603: injected fault",
+                method
+            )),
+            Fault::Timeout => {
+                tokio::time::sleep(self.hang_duration).await;
+                Err(anyhow::anyhow!("{} failed: request timed out", method))
+            }
+            Fault::Malformed => Err(anyhow::anyhow!(
+                "{} failed: deserialization error: expected value at line 1 column 1",
+                method
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl EthereumProvider for ChaosProvider {
+    async fn get_eth_balance(&self, wallet: &WalletAddress) -> anyhow::Result<BalanceInfo> {
+        self.inject("get_eth_balance").await?;
+        self.inner.get_eth_balance(wallet).await
+    }
+
+    async fn get_erc20_balance(
+        &self,
+        wallet: &WalletAddress,
+        token: &TokenAddress,
+    ) -> anyhow::Result<BalanceInfo> {
+        self.inject("get_erc20_balance").await?;
+        self.inner.get_erc20_balance(wallet, token).await
+    }
+
+    async fn get_allowance(
+        &self,
+        owner: &WalletAddress,
+        token: &TokenAddress,
+        spender: &Address,
+    ) -> anyhow::Result<AllowanceInfo> {
+        self.inject("get_allowance").await?;
+        self.inner.get_allowance(owner, token, spender).await
+    }
+
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+    ) -> anyhow::Result<B256> {
+        self.inject("approve_token").await?;
+        self.inner.approve_token(token, spender, amount).await
+    }
+
+    async fn wrap_eth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        self.inject("wrap_eth").await?;
+        self.inner.wrap_eth(weth, amount).await
+    }
+
+    async fn unwrap_weth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        self.inject("unwrap_weth").await?;
+        self.inner.unwrap_weth(weth, amount).await
+    }
+
+    async fn get_token_decimals(&self, token: &TokenAddress) -> anyhow::Result<u8> {
+        self.inject("get_token_decimals").await?;
+        self.inner.get_token_decimals(token).await
+    }
+
+    async fn get_token_symbol(&self, token: &TokenAddress) -> anyhow::Result<String> {
+        self.inject("get_token_symbol").await?;
+        self.inner.get_token_symbol(token).await
+    }
+
+    async fn get_token_price(
+        &self,
+        token: &TokenAddress,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<TokenPrice> {
+        self.inject("get_token_price").await?;
+        self.inner.get_token_price(token, contracts).await
+    }
+
+    async fn simulate_swap(
+        &self,
+        params: &SwapParams,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<SwapResult> {
+        self.inject("simulate_swap").await?;
+        self.inner.simulate_swap(params, contracts).await
+    }
+
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount_in: &TokenAmount,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<QuoteComparison> {
+        self.inject("compare_quotes").await?;
+        self.inner
+            .compare_quotes(from_token, to_token, amount_in, contracts)
+            .await
+    }
+
+    async fn execute_swap<'a>(
+        &self,
+        params: &SwapParams,
+        amount_out_minimum: &TokenAmount,
+        permit: Option<&'a PermitSignature>,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<B256> {
+        self.inject("execute_swap").await?;
+        self.inner
+            .execute_swap(params, amount_out_minimum, permit, contracts)
+            .await
+    }
+
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        value: U256,
+        deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        self.inject("sign_permit").await?;
+        self.inner
+            .sign_permit(token, spender, value, deadline)
+            .await
+    }
+
+    async fn sign_permit2(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+        expiration: u64,
+        sig_deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        self.inject("sign_permit2").await?;
+        self.inner
+            .sign_permit2(token, spender, amount, expiration, sig_deadline)
+            .await
+    }
+
+    async fn get_gas_price(&self) -> anyhow::Result<U256> {
+        self.inject("get_gas_price").await?;
+        self.inner.get_gas_price().await
+    }
+
+    async fn get_block_number(&self) -> anyhow::Result<u64> {
+        let stale_blocks = self.inject("get_block_number").await?;
+        Ok(self
+            .inner
+            .get_block_number()
+            .await?
+            .saturating_sub(stale_blocks))
+    }
+
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate> {
+        self.inject("get_gas_fees").await?;
+        self.inner.get_gas_fees().await
+    }
+
+    async fn get_transaction_status(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<TransactionStatusInfo> {
+        let stale_blocks = self.inject("get_transaction_status").await?;
+        let mut status = self.inner.get_transaction_status(tx_hash).await?;
+        status.confirmations = status.confirmations.saturating_sub(stale_blocks);
+        Ok(status)
+    }
+
+    async fn get_transaction_detail(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<Option<TransactionDetail>> {
+        self.inject("get_transaction_detail").await?;
+        self.inner.get_transaction_detail(tx_hash).await
+    }
+
+    async fn replace_transaction(
+        &self,
+        tx_hash: &B256,
+        kind: ReplacementKind,
+    ) -> anyhow::Result<ReplacementResult> {
+        self.inject("replace_transaction").await?;
+        self.inner.replace_transaction(tx_hash, kind).await
+    }
+
+    async fn health_check(&self) -> anyhow::Result<()> {
+        self.inject("health_check").await?;
+        self.inner.health_check().await
+    }
+
+    fn wallet_address(&self) -> WalletAddress {
+        self.inner.wallet_address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{
+        CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState,
+        MockEthereumProvider,
+    };
+    use crate::types::TransactionStatus;
+    use std::time::Instant;
+
+    fn block_source(block: u64) -> Arc<dyn EthereumProvider> {
+        let mut mock = MockEthereumProvider::new();
+        mock.expect_get_block_number().returning(move || Ok(block));
+        mock.expect_get_gas_price()
+            .returning(|| Ok(U256::from(1_000_000_000u64)));
+        Arc::new(mock)
+    }
+
+    async fn error_pattern(seed: u64) -> Vec<bool> {
+        let chaos = ChaosProvider::new(block_source(100))
+            .with_seed(seed)
+            .with_faults(FaultConfig {
+                error_rate: 0.3,
+                ..Default::default()
+            });
+        let mut pattern = Vec::new();
+        for _ in 0..1000 {
+            pattern.push(chaos.get_gas_price().await.is_err());
+        }
+        pattern
+    }
+
+    #[tokio::test]
+    async fn test_no_faults_passes_through() {
+        let chaos = ChaosProvider::new(block_source(100));
+        assert_eq!(chaos.get_block_number().await.unwrap(), 100);
+        assert_eq!(chaos.calls(), 1);
+        assert_eq!(chaos.injected(Fault::Error), 0);
+    }
+
+    #[tokio::test]
+    async fn test_error_rate_is_seeded() {
+        let pattern = error_pattern(7).await;
+        let failures = pattern.iter().filter(|failed| **failed).count();
+        assert!((250..350).contains(&failures), "{} failures", failures);
+        assert_eq!(pattern, error_pattern(7).await);
+        assert_ne!(pattern, error_pattern(8).await);
+    }
+
+    #[tokio::test]
+    async fn test_method_faults_override_default() {
+        let chaos = ChaosProvider::new(block_source(100))
+            .with_faults(FaultConfig::failing())
+            .with_method_faults("get_block_number", FaultConfig::default());
+        assert!(chaos.get_block_number().await.is_ok());
+        let error = chaos.get_gas_price().await.unwrap_err();
+        assert!(error.to_string().contains("get_gas_price failed"));
+        assert_eq!(chaos.injected(Fault::Error), 1);
+
+        chaos.set_faults(FaultConfig::default());
+        assert!(chaos.get_gas_price().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_responses_fail_to_decode() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig {
+            malformed_rate: 1.0,
+            ..Default::default()
+        });
+        let error = chaos.get_block_number().await.unwrap_err();
+        assert!(error.to_string().contains("deserialization error"));
+        assert_eq!(chaos.injected(Fault::Malformed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_hang_until_the_caller_gives_up() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig {
+            timeout_rate: 1.0,
+            ..Default::default()
+        });
+        let result =
+            tokio::time::timeout(Duration::from_millis(50), chaos.get_block_number()).await;
+        assert!(result.is_err());
+        assert_eq!(chaos.injected(Fault::Timeout), 1);
+
+        let chaos = chaos.with_hang_duration(Duration::from_millis(10));
+        let error = chaos.get_block_number().await.unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_latency_delays_every_call() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig {
+            latency: Duration::from_millis(30),
+            ..Default::default()
+        });
+        let started = Instant::now();
+        chaos.get_block_number().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn test_stale_blocks_lag_block_number_and_confirmations() {
+        let mut mock = MockEthereumProvider::new();
+        mock.expect_get_block_number().returning(|| Ok(100));
+        mock.expect_get_transaction_status().returning(|hash| {
+            Ok(TransactionStatusInfo {
+                transaction_hash: format!("{:#x}", hash),
+                status: TransactionStatus::Confirmed,
+                confirmations: 3,
+                block_number: Some(98),
+                finalized: false,
+                replaced_by: None,
+                detail: None,
+                revert_reason: None,
+            })
+        });
+        let chaos = ChaosProvider::new(Arc::new(mock)).with_faults(FaultConfig {
+            stale_blocks: 5,
+            ..Default::default()
+        });
+
+        assert_eq!(chaos.get_block_number().await.unwrap(), 95);
+        let status = chaos.get_transaction_status(&B256::ZERO).await.unwrap();
+        assert_eq!(status.confirmations, 0);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_and_fails_fast_under_errors() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig::failing());
+        let breaker = CircuitBreaker::with_config(CircuitBreakerConfig {
+            failure_threshold: 3,
+            timeout_duration: Duration::from_secs(60),
+            success_threshold: 1,
+        });
+
+        for _ in 0..3 {
+            let result = breaker.call(|| chaos.get_block_number()).await;
+            assert!(matches!(
+                result,
+                Err(CircuitBreakerError::OperationFailed(_))
+            ));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Healed or not, an open breaker no longer reaches the provider
+        chaos.set_faults(FaultConfig::default());
+        let result = breaker.call(|| chaos.get_block_number()).await;
+        assert!(matches!(result, Err(CircuitBreakerError::CircuitOpen)));
+        assert_eq!(chaos.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_counts_timeouts_as_failures() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig {
+            timeout_rate: 1.0,
+            ..Default::default()
+        });
+        let breaker = CircuitBreaker::with_config(CircuitBreakerConfig {
+            failure_threshold: 2,
+            timeout_duration: Duration::from_secs(60),
+            success_threshold: 1,
+        });
+        let with_deadline = || async {
+            tokio::time::timeout(Duration::from_millis(20), chaos.get_block_number())
+                .await
+                .map_err(|_| anyhow::anyhow!("deadline exceeded"))?
+        };
+
+        for _ in 0..2 {
+            assert!(breaker.call(with_deadline).await.is_err());
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_half_open_recovery() {
+        let chaos = ChaosProvider::new(block_source(100)).with_faults(FaultConfig::failing());
+        let breaker = CircuitBreaker::with_config(CircuitBreakerConfig {
+            failure_threshold: 2,
+            timeout_duration: Duration::from_millis(100),
+            success_threshold: 2,
+        });
+        for _ in 0..2 {
+            let _ = breaker.call(|| chaos.get_block_number()).await;
+        }
+        assert!(matches!(
+            breaker.call(|| chaos.get_block_number()).await,
+            Err(CircuitBreakerError::CircuitOpen)
+        ));
+
+        // Still failing when the timeout lapses: the half-open probe reopens it
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(breaker.call(|| chaos.get_block_number()).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Recovered: probes succeed and close it after `success_threshold` of them
+        chaos.set_faults(FaultConfig::default());
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(breaker.call(|| chaos.get_block_number()).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.call(|| chaos.get_block_number()).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
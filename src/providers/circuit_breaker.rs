@@ -136,12 +136,9 @@ impl CircuitBreaker {
     fn on_failure(&self) {
         let failure_count = self.failure_count.fetch_add(1, Ordering::Relaxed) + 1;
 
-        // Record timestamp of failure
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        self.last_failure_time.store(now, Ordering::Relaxed);
+        // Record timestamp of failure, in milliseconds so sub-second timeouts hold
+        self.last_failure_time
+            .store(Self::now_millis(), Ordering::Relaxed);
 
         let current_state = {
             let state = self.state.read().unwrap();
@@ -207,16 +204,19 @@ impl CircuitBreaker {
 
         if current_state == CircuitState::Open {
             let last_failure = self.last_failure_time.load(Ordering::Relaxed);
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-
-            if now - last_failure >= self.config.timeout_duration.as_secs() {
+            let elapsed = Self::now_millis().saturating_sub(last_failure);
+            if u128::from(elapsed) >= self.config.timeout_duration.as_millis() {
                 self.half_open_circuit();
             }
         }
     }
+
+    fn now_millis() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
 }
 
 /// Circuit breaker error types
@@ -0,0 +1,228 @@
+/// Quote adapters for DEXes besides Uniswap
+/// Each venue sits behind `DexQuoter`, so `compare_quotes` can put their outputs side by
+/// side with Uniswap's without knowing how each one prices a swap.
+use crate::contracts::{IBalancerVault, ICurvePool};
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::providers::RootProvider;
+use alloy::transports::Transport;
+use async_trait::async_trait;
+use tracing::debug;
+
+/// Typical gas of a whole Curve StableSwap `exchange` transaction
+pub const CURVE_SWAP_GAS: u64 = 150_000;
+
+/// Typical gas of a whole Balancer V2 single-pool swap transaction
+pub const BALANCER_SWAP_GAS: u64 = 160_000;
+
+/// Curve pools hold at most eight coins
+const MAX_CURVE_COINS: u64 = 8;
+
+/// Balancer's `SwapKind.GIVEN_IN`
+const GIVEN_IN: u8 = 0;
+
+/// A venue's best output for an exact input, in raw units
+#[derive(Debug, Clone, PartialEq)]
+pub struct DexQuote {
+    pub pools: Vec<Address>,
+    pub amount_out: U256,
+    /// Gas of the whole swap transaction
+    pub gas_estimate: u64,
+}
+
+/// Quotes exact-input swaps on one venue
+#[async_trait]
+pub trait DexQuoter: Send + Sync {
+    /// Name the venue's quotes are reported under
+    fn venue(&self) -> &'static str;
+
+    /// Best output for selling `amount_in` of `from` for `to`; None if the venue has no
+    /// pool holding both tokens
+    async fn quote(
+        &self,
+        from: Address,
+        to: Address,
+        amount_in: U256,
+    ) -> anyhow::Result<Option<DexQuote>>;
+}
+
+/// Curve StableSwap pools, quoted with `get_dy`
+pub struct CurveQuoter<'a, T> {
+    provider: &'a RootProvider<T>,
+    pools: Vec<Address>,
+}
+
+impl<'a, T> CurveQuoter<'a, T> {
+    pub fn new(provider: &'a RootProvider<T>, pools: Vec<Address>) -> Self {
+        Self { provider, pools }
+    }
+}
+
+impl<T: Transport + Clone> CurveQuoter<'_, T> {
+    /// The pool's coins, read with `coins(i)` until it reverts past the last one
+    async fn coins(&self, pool: Address) -> Vec<Address> {
+        let curve_pool = ICurvePool::new(pool, self.provider);
+        let mut coins = Vec::new();
+        for i in 0..MAX_CURVE_COINS {
+            match curve_pool.coins(U256::from(i)).call().await {
+                Ok(coin) => coins.push(coin._0),
+                Err(_) => break,
+            }
+        }
+        coins
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Clone> DexQuoter for CurveQuoter<'_, T> {
+    fn venue(&self) -> &'static str {
+        "curve"
+    }
+
+    async fn quote(
+        &self,
+        from: Address,
+        to: Address,
+        amount_in: U256,
+    ) -> anyhow::Result<Option<DexQuote>> {
+        let mut best: Option<DexQuote> = None;
+        let mut failure = None;
+        for &pool in &self.pools {
+            let coins = self.coins(pool).await;
+            let index = |token: Address| coins.iter().position(|coin| *coin == token);
+            let (Some(i), Some(j)) = (index(from), index(to)) else {
+                continue;
+            };
+            match ICurvePool::new(pool, self.provider)
+                .get_dy(i as i128, j as i128, amount_in)
+                .call()
+                .await
+            {
+                Ok(dy) if best.as_ref().is_none_or(|b| dy._0 > b.amount_out) => {
+                    best = Some(DexQuote {
+                        pools: vec![pool],
+                        amount_out: dy._0,
+                        gas_estimate: CURVE_SWAP_GAS,
+                    })
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    debug!(%pool, "Curve get_dy failed: {}", e);
+                    failure = Some(e);
+                }
+            }
+        }
+        match (best, failure) {
+            (None, Some(e)) => Err(anyhow::anyhow!("Curve quote reverted: {}", e)),
+            (best, _) => Ok(best),
+        }
+    }
+}
+
+/// Balancer V2 pools, quoted with the Vault's `queryBatchSwap`
+pub struct BalancerQuoter<'a, T> {
+    provider: &'a RootProvider<T>,
+    vault: Address,
+    pool_ids: Vec<B256>,
+}
+
+impl<'a, T> BalancerQuoter<'a, T> {
+    pub fn new(provider: &'a RootProvider<T>, vault: Address, pool_ids: Vec<B256>) -> Self {
+        Self {
+            provider,
+            vault,
+            pool_ids,
+        }
+    }
+
+    /// A pool ID starts with the pool's address
+    pub fn pool_address(pool_id: &B256) -> Address {
+        Address::from_slice(&pool_id[..20])
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Clone> DexQuoter for BalancerQuoter<'_, T> {
+    fn venue(&self) -> &'static str {
+        "balancer"
+    }
+
+    async fn quote(
+        &self,
+        from: Address,
+        to: Address,
+        amount_in: U256,
+    ) -> anyhow::Result<Option<DexQuote>> {
+        let vault = IBalancerVault::new(self.vault, self.provider);
+        let mut best: Option<DexQuote> = None;
+        let mut failure = None;
+        for pool_id in &self.pool_ids {
+            let tokens = match vault.getPoolTokens(*pool_id).call().await {
+                Ok(pool) => pool.tokens,
+                Err(e) => {
+                    debug!(%pool_id, "Balancer getPoolTokens failed: {}", e);
+                    continue;
+                }
+            };
+            if !tokens.contains(&from) || !tokens.contains(&to) {
+                continue;
+            }
+            let swap = IBalancerVault::BatchSwapStep {
+                poolId: *pool_id,
+                assetInIndex: U256::ZERO,
+                assetOutIndex: U256::from(1),
+                amount: amount_in,
+                userData: Bytes::new(),
+            };
+            let funds = IBalancerVault::FundManagement {
+                sender: Address::ZERO,
+                fromInternalBalance: false,
+                recipient: Address::ZERO,
+                toInternalBalance: false,
+            };
+            match vault
+                .queryBatchSwap(GIVEN_IN, vec![swap], vec![from, to], funds)
+                .call()
+                .await
+            {
+                // The Vault pays out negative deltas
+                Ok(query) => {
+                    let Some(delta) = query.assetDeltas.get(1).filter(|d| d.is_negative()) else {
+                        continue;
+                    };
+                    let amount_out = delta.unsigned_abs();
+                    if best.as_ref().is_none_or(|b| amount_out > b.amount_out) {
+                        best = Some(DexQuote {
+                            pools: vec![Self::pool_address(pool_id)],
+                            amount_out,
+                            gas_estimate: BALANCER_SWAP_GAS,
+                        });
+                    }
+                }
+                Err(e) => {
+                    debug!(%pool_id, "Balancer queryBatchSwap failed: {}", e);
+                    failure = Some(e);
+                }
+            }
+        }
+        match (best, failure) {
+            (None, Some(e)) => Err(anyhow::anyhow!("Balancer quote reverted: {}", e)),
+            (best, _) => Ok(best),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, b256};
+    use alloy::transports::http::{Client, Http};
+
+    #[test]
+    fn test_balancer_pool_address_from_id() {
+        let pool_id = b256!("96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019");
+        assert_eq!(
+            BalancerQuoter::<Http<Client>>::pool_address(&pool_id),
+            address!("96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8")
+        );
+    }
+}
@@ -1,22 +1,60 @@
 /// Alloy-based Ethereum provider implementation
 /// Production implementation with proper error handling and resource management
 use super::EthereumProvider;
-use crate::contracts::{utils, IChainlinkAggregator, IUniswapV3Quoter, IUniswapV3Router, IERC20};
-use crate::providers::{CircuitBreaker, CircuitBreakerError};
+use crate::contracts::decode;
+use crate::contracts::l2::{self, L2Network};
+use crate::contracts::storage::{MappingSlot, TokenSlots};
+use crate::contracts::{
+    fees, utils, IChainlinkAggregator, IERC20Permit, IGasPriceOracle, INodeInterface, IPermit2,
+    IUniswapV2Factory, IUniswapV2Pair, IUniswapV2Router, IUniswapV3Factory, IUniswapV3Pool,
+    IUniswapV3QuoterV2, IUniswapV3Router, Permit, IERC20, IWETH9, PERMIT2,
+};
+use crate::providers::{
+    BalancerQuoter, CircuitBreaker, CircuitBreakerError, CurveQuoter, DexQuote, DexQuoter,
+    StoredTransaction, StoredTxStatus, TransactionStore,
+};
 use crate::types::*;
 use crate::ContractAddresses;
-use alloy::primitives::{Uint, B256, I256, U256};
+use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+use alloy::eips::eip2718::{Decodable2718, Encodable2718};
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy::network::{EthereumWallet, TransactionBuilder};
+use alloy::primitives::{keccak256, Address, Bytes, Signature, TxKind, Uint, B256, I256, U256};
 use alloy::providers::{Provider, ProviderBuilder, RootProvider};
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::types::state::{AccountOverride, StateOverride};
+use alloy::rpc::types::{FeeHistory, TransactionRequest};
 use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use alloy::sol_types::{Eip712Domain, Revert, SolCall, SolError, SolStruct};
 use alloy::transports::http::{Client, Http};
+use alloy::transports::{Transport, TransportError};
 use async_trait::async_trait;
 use chrono::Utc;
+use futures::future::join_all;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::Semaphore;
-use tracing::{info, instrument, warn};
+use tokio::sync::{OnceCell, Semaphore};
+use tracing::{debug, info, instrument, warn};
+
+/// Number of recent blocks sampled by `eth_feeHistory`
+const FEE_HISTORY_BLOCKS: u64 = 20;
+/// Reward percentiles sampled for the slow / normal / fast priority fee tiers
+const FEE_HISTORY_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+/// Base cost of any transaction, which QuoterV2's gas estimate leaves out
+const TRANSACTION_BASE_GAS: u64 = 21_000;
+/// Token transfers into and out of each pool, which QuoterV2 skips by reverting in
+/// the swap callback
+const HOP_TRANSFER_GAS: u64 = 60_000;
+/// A V2 pair's `swap`, excluding the token transfers; stands in for QuoterV2's gas
+/// estimate on V2 routes
+const V2_PAIR_SWAP_GAS: u64 = 45_000;
+/// Storage slots probed for a token's balance and allowance mappings; mainnet tokens
+/// declare them well within this
+const MAX_PROBED_STORAGE_SLOT: u64 = 20;
 
 /// Alloy-based Ethereum provider with connection pooling and rate limiting
 pub struct AlloyEthereumProvider<T> {
@@ -24,11 +62,45 @@ pub struct AlloyEthereumProvider<T> {
     wallet_address: WalletAddress,
     request_semaphore: Arc<Semaphore>,
     circuit_breaker: CircuitBreaker,
-    _nonce_manager: Arc<super::NonceManager>,
+    nonce_manager: Arc<super::NonceManager>,
+    signer: PrivateKeySigner,
+    wallet: EthereumWallet,
+    chain_id: OnceCell<u64>,
+    tx_store: Option<Arc<TransactionStore>>,
+    /// Balance and allowance storage slots found per token, for state overrides
+    token_slots: Mutex<HashMap<Address, TokenSlots>>,
+}
+
+/// A quoted route, from QuoterV2 on V3 and the router's `getAmountsOut` /
+/// `getAmountsIn` on V2. `amount` is the output of an exact-input quote and the input
+/// of an exact-output one.
+#[derive(Debug, Clone)]
+struct PathQuote {
+    amount: U256,
+    sqrt_prices_x96_after: Vec<U256>,
+    initialized_ticks_crossed: u32,
+    gas_estimate: u64,
+}
+
+/// The parts of a sent transaction that a speed-up keeps
+struct ReplaceableTx {
+    to: TxKind,
+    value: U256,
+    input: Bytes,
+    gas_limit: u128,
+    max_fee_per_gas: u128,
+    max_priority_fee_per_gas: u128,
+}
+
+/// A Uniswap protocol as a [`DexQuoter`]: the best-quoting of its candidate routes
+struct UniswapQuoter<'a, T> {
+    provider: &'a AlloyEthereumProvider<T>,
+    protocol: SwapProtocol,
+    contracts: &'a ContractAddresses,
 }
 
 // Shared utility functions
-impl AlloyEthereumProvider<Http<Client>> {
+impl<T> AlloyEthereumProvider<T> {
     pub fn u256_to_decimal(value: U256) -> anyhow::Result<Decimal> {
         Decimal::from_str(&value.to_string())
             .map_err(|e| anyhow::anyhow!("Failed to convert U256 to Decimal: {}", e))
@@ -55,6 +127,578 @@ impl AlloyEthereumProvider<Http<Client>> {
             .map_err(|e| anyhow::anyhow!("Invalid wallet private key: {}", e))
     }
 
+    /// Predict the next block's base fee from the EIP-1559 update rule
+    /// (elasticity multiplier 2, base fee max change denominator 8)
+    pub fn predict_next_base_fee(base_fee: u128, gas_used_ratio: f64) -> u128 {
+        let ratio = if gas_used_ratio.is_finite() {
+            gas_used_ratio.clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        let change = base_fee as f64 * (ratio - 0.5) / 0.5 / 8.0;
+        if change > 0.0 {
+            base_fee.saturating_add((change as u128).max(1))
+        } else {
+            base_fee.saturating_sub((-change) as u128)
+        }
+    }
+
+    /// Build slow / normal / fast fee suggestions from an `eth_feeHistory` response
+    pub fn fee_estimate_from_history(history: &FeeHistory) -> anyhow::Result<GasFeeEstimate> {
+        let block_count = history.gas_used_ratio.len();
+        if history.base_fee_per_gas.is_empty() || block_count == 0 {
+            return Err(anyhow::anyhow!(
+                "Fee history returned no base fees - chain may not support EIP-1559"
+            ));
+        }
+
+        // base_fee_per_gas has one entry per block plus the node's value for the next block
+        let base_fee_per_gas =
+            history.base_fee_per_gas[block_count.min(history.base_fee_per_gas.len()) - 1];
+        let next_base_fee_per_gas = match history.base_fee_per_gas.get(block_count) {
+            Some(next) => *next,
+            None => Self::predict_next_base_fee(
+                base_fee_per_gas,
+                history.gas_used_ratio[block_count - 1],
+            ),
+        };
+
+        let rewards = history.reward.clone().unwrap_or_default();
+        let mut tiers = [0u128; 3];
+        for (tier, fee) in tiers.iter_mut().enumerate() {
+            // Empty blocks report zero rewards, so they are left out of the sample
+            let mut samples: Vec<u128> = rewards
+                .iter()
+                .filter_map(|block| block.get(tier).copied())
+                .filter(|reward| *reward > 0)
+                .collect();
+            samples.sort_unstable();
+            *fee = samples.get(samples.len() / 2).copied().unwrap_or(0);
+        }
+        // Keep tiers ordered even when the sampled percentiles are noisy
+        tiers[1] = tiers[1].max(tiers[0]);
+        tiers[2] = tiers[2].max(tiers[1]);
+
+        let suggestion = |priority: u128| FeeSuggestion {
+            max_priority_fee_per_gas: priority,
+            max_fee_per_gas: next_base_fee_per_gas
+                .saturating_mul(2)
+                .saturating_add(priority),
+        };
+
+        Ok(GasFeeEstimate {
+            base_fee_per_gas,
+            next_base_fee_per_gas,
+            slow: suggestion(tiers[0]),
+            normal: suggestion(tiers[1]),
+            fast: suggestion(tiers[2]),
+            latest_block: history.oldest_block + block_count as u64 - 1,
+        })
+    }
+
+    /// RLP-encode an unsigned EIP-1559 transaction, the payload OP-stack `getL1Fee` expects
+    pub fn unsigned_eip1559_payload(
+        chain_id: u64,
+        to: Address,
+        input: Bytes,
+        gas_limit: u64,
+        fees: &FeeSuggestion,
+    ) -> Bytes {
+        let tx = TxEip1559 {
+            chain_id,
+            gas_limit: gas_limit as u128,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            to: TxKind::Call(to),
+            input,
+            ..Default::default()
+        };
+        tx.encoded_for_signing().into()
+    }
+
+    /// Reconstruct a token's EIP-2612 domain by trying candidate versions until one
+    /// reproduces the on-chain `DOMAIN_SEPARATOR`
+    pub fn eip2612_domain(
+        name: &str,
+        versions: &[String],
+        chain_id: u64,
+        token: Address,
+        domain_separator: B256,
+    ) -> Option<Eip712Domain> {
+        versions.iter().find_map(|version| {
+            let domain = Eip712Domain::new(
+                Some(name.to_string().into()),
+                Some(version.clone().into()),
+                Some(U256::from(chain_id)),
+                Some(token),
+                None,
+            );
+            (domain.separator() == domain_separator).then_some(domain)
+        })
+    }
+
+    /// Permit2's EIP-712 domain (name only, no version)
+    pub fn permit2_domain(chain_id: u64, permit2: Address) -> Eip712Domain {
+        Eip712Domain::new(
+            Some("Permit2".into()),
+            None,
+            Some(U256::from(chain_id)),
+            Some(permit2),
+            None,
+        )
+    }
+
+    /// Package an ECDSA signature in the forms permit consumers expect
+    pub fn permit_signature_parts(signature: &Signature) -> (Bytes, u8, B256, B256) {
+        let bytes = signature.as_bytes();
+        (
+            Bytes::copy_from_slice(&bytes),
+            bytes[64],
+            B256::from(signature.r()),
+            B256::from(signature.s()),
+        )
+    }
+
+    /// Convert a wei amount to ETH
+    pub fn wei_to_eth(wei: u128) -> Decimal {
+        Decimal::from(wei) / Decimal::from(10_u64.pow(18))
+    }
+
+    /// Status of a transaction with no receipt, given its nonce and the sender's mined
+    /// transaction count
+    pub fn unmined_status(
+        nonce: u64,
+        sender_mined_count: u64,
+        known_to_node: bool,
+        replaced_by_us: bool,
+    ) -> TransactionStatus {
+        if replaced_by_us || sender_mined_count > nonce {
+            TransactionStatus::Replaced
+        } else if known_to_node {
+            TransactionStatus::Pending
+        } else {
+            TransactionStatus::Dropped
+        }
+    }
+
+    /// Revert reason carried by an RPC error, from its `data` field or from a
+    /// geth-style "execution reverted: <reason>" message
+    pub fn revert_reason(error: &TransportError) -> Option<RevertReason> {
+        let payload = error.as_error_resp()?;
+        if let Some(Ok(data)) = payload.try_data_as::<Bytes>() {
+            return Some(decode::decode_revert(&data));
+        }
+        let reason = payload
+            .message
+            .strip_prefix("execution reverted")?
+            .trim_start_matches(':')
+            .trim();
+        if reason.is_empty() {
+            return Some(decode::decode_revert(&[]));
+        }
+        let data = Revert {
+            reason: reason.to_string(),
+        }
+        .abi_encode();
+        Some(decode::decode_revert(&data))
+    }
+
+    /// Wrap an RPC error, keeping its decoded revert reason for the caller
+    pub fn revert_error(context: &str, error: TransportError) -> anyhow::Error {
+        match Self::revert_reason(&error) {
+            Some(reason) => RevertError {
+                context: context.to_string(),
+                reason,
+            }
+            .into(),
+            None => anyhow::anyhow!("{}: {}", context, error),
+        }
+    }
+
+    /// [`Self::revert_error`] for contract binding calls
+    fn contract_revert_error(context: &str, error: alloy::contract::Error) -> anyhow::Error {
+        match error {
+            alloy::contract::Error::TransportError(e) => Self::revert_error(context, e),
+            e => anyhow::anyhow!("{}: {}", context, e),
+        }
+    }
+
+    /// Swap output less its gas cost, priced in the output token at `output_per_eth`
+    /// and rounded down to the token's smallest unit; never below zero
+    pub fn net_amount_out(
+        amount_out: &TokenAmount,
+        gas_cost_eth: Decimal,
+        output_per_eth: Decimal,
+    ) -> TokenAmount {
+        let net = (amount_out.raw - gas_cost_eth * output_per_eth)
+            .round_dp_with_strategy(
+                amount_out.decimals as u32,
+                rust_decimal::RoundingStrategy::ToZero,
+            )
+            .max(Decimal::ZERO);
+        TokenAmount::new(net, amount_out.decimals)
+    }
+
+    /// Swap input plus its gas cost, priced in the input token at `input_per_eth` and
+    /// rounded up to the token's smallest unit
+    pub fn net_amount_in(
+        amount_in: &TokenAmount,
+        gas_cost_eth: Decimal,
+        input_per_eth: Decimal,
+    ) -> TokenAmount {
+        let net = (amount_in.raw + gas_cost_eth * input_per_eth).round_dp_with_strategy(
+            amount_in.decimals as u32,
+            rust_decimal::RoundingStrategy::AwayFromZero,
+        );
+        TokenAmount::new(net, amount_in.decimals)
+    }
+
+    /// ETH cost of `gas_estimate` units at `gas_price` wei
+    pub fn gas_cost_eth(gas_estimate: u64, gas_price: U256) -> Decimal {
+        let gas_price_dec = Self::u256_to_decimal(gas_price).unwrap_or_default();
+        (Decimal::from(gas_estimate) * gas_price_dec) / Decimal::from(10_u64.pow(18))
+    }
+
+    /// Order venue quotes best first, by net-of-gas output where every quote has one
+    /// and by gross output otherwise
+    pub fn rank_venue_quotes(quotes: &mut [VenueQuote]) {
+        let all_net = quotes.iter().all(|q| q.net_amount_out.is_some());
+        let value = |q: &VenueQuote| match (&q.net_amount_out, all_net) {
+            (Some(net), true) => net.raw,
+            _ => q.amount_out.raw,
+        };
+        quotes.sort_by_key(|q| std::cmp::Reverse(value(q)));
+    }
+
+    /// Order route quotes best first: least input, then most output, each net of gas
+    /// where every quote has that figure and gross otherwise. Exact-input quotes share
+    /// their input and exact-output quotes their output, so each kind is decided by the
+    /// key that varies.
+    pub fn rank_route_quotes(quotes: &mut [RouteQuote]) {
+        let all_net_in = quotes.iter().all(|q| q.net_amount_in.is_some());
+        let all_net_out = quotes.iter().all(|q| q.net_amount_out.is_some());
+        let cost = |q: &RouteQuote| match (&q.net_amount_in, all_net_in) {
+            (Some(net), true) => net.raw,
+            _ => q.amount_in.raw,
+        };
+        let value = |q: &RouteQuote| match (&q.net_amount_out, all_net_out) {
+            (Some(net), true) => net.raw,
+            _ => q.amount_out.raw,
+        };
+        quotes.sort_by(|a, b| cost(a).cmp(&cost(b)).then_with(|| value(b).cmp(&value(a))));
+    }
+
+    /// Raw output per raw input at a pool's current price less its fee, from the
+    /// pool's `sqrtPriceX96` (the square root of token1 per token0, as Q64.96)
+    pub fn hop_mid_rate(hop: &RouteHop, sqrt_price_x96: U256) -> f64 {
+        let sqrt_price = sqrt_price_x96
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or(f64::MAX)
+            / 2f64.powi(96);
+        let price = sqrt_price * sqrt_price;
+        let rate = if hop.token_in.address() < hop.token_out.address() {
+            price
+        } else {
+            1.0 / price
+        };
+        rate * (1.0 - hop.fee_tier as f64 / 1_000_000.0)
+    }
+
+    /// Raw output per raw input at a V2 pair's reserves less its fee; reserves are in
+    /// token0, token1 order
+    pub fn v2_hop_mid_rate(hop: &RouteHop, reserve0: U256, reserve1: U256) -> f64 {
+        let to_f64 = |value: U256| value.to_string().parse::<f64>().unwrap_or(f64::MAX);
+        let (reserve_in, reserve_out) = if hop.token_in.address() < hop.token_out.address() {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        };
+        if reserve_in.is_zero() {
+            return 0.0;
+        }
+        to_f64(reserve_out) / to_f64(reserve_in) * (1.0 - hop.fee_tier as f64 / 1_000_000.0)
+    }
+
+    /// Percent by which a swap's rate falls short of the route's `mid_rate`, to four
+    /// decimal places. Fees are part of the mid rate, so this is the price movement
+    /// caused by the swap's size alone.
+    pub fn price_impact(mid_rate: f64, amount_in: U256, amount_out: U256) -> Decimal {
+        let to_f64 = |value: U256| value.to_string().parse::<f64>().unwrap_or(f64::MAX);
+        if !mid_rate.is_finite() || mid_rate <= 0.0 || amount_in.is_zero() {
+            return Decimal::ZERO;
+        }
+        let execution_rate = to_f64(amount_out) / to_f64(amount_in);
+        let impact = ((1.0 - execution_rate / mid_rate) * 100.0).clamp(0.0, 100.0);
+        Decimal::from_f64_retain(impact)
+            .unwrap_or_default()
+            .round_dp(4)
+    }
+
+    /// Gas for a swap transaction along `route` when the router call can't be
+    /// estimated (e.g. before approval), from QuoterV2's estimate for its pool swaps
+    pub fn quoted_route_gas(route: &SwapRoute, quoter_gas_estimate: u64) -> u64 {
+        quoter_gas_estimate + TRANSACTION_BASE_GAS + HOP_TRANSFER_GAS * route.hops.len() as u64
+    }
+
+    /// SwapRouter calldata for a quoted swap (`params.route` set): exact-input swaps
+    /// must receive at least `amount_out_minimum`, exact-output swaps may spend at most
+    /// `params.maximum_amount_in()`
+    pub fn swap_calldata(
+        params: &SwapParams,
+        recipient: Address,
+        deadline: U256,
+        amount_out_minimum: U256,
+    ) -> anyhow::Result<Bytes> {
+        let route = params
+            .route
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Swap has no route"))?;
+        if route.protocol.is_v2() {
+            return Self::v2_swap_calldata(params, route, recipient, deadline, amount_out_minimum);
+        }
+        match &params.amount_out {
+            None => Self::exact_input_calldata(
+                route,
+                recipient,
+                deadline,
+                Self::decimal_to_u256(params.amount_in.to_raw_units()?)?,
+                amount_out_minimum,
+            ),
+            Some(amount_out) => Self::exact_output_calldata(
+                route,
+                recipient,
+                deadline,
+                amount_out.to_u256()?,
+                params.maximum_amount_in().to_u256()?,
+            ),
+        }
+    }
+
+    /// Router calls for a quoted swap. On V3, native ETH output is swapped to the router
+    /// as WETH and unwrapped to `recipient`, and ether left over from an exact-output
+    /// swap is refunded; V2 routers have dedicated ETH functions instead.
+    pub fn swap_calls(
+        params: &SwapParams,
+        router: Address,
+        recipient: Address,
+        deadline: U256,
+        amount_out_minimum: U256,
+    ) -> anyhow::Result<Vec<Bytes>> {
+        let v3 = params
+            .route
+            .as_ref()
+            .is_some_and(|route| !route.protocol.is_v2());
+        let eth_out = v3 && params.to_token.is_native();
+        let swap_recipient = if eth_out { router } else { recipient };
+        let mut calls = vec![Self::swap_calldata(
+            params,
+            swap_recipient,
+            deadline,
+            amount_out_minimum,
+        )?];
+        if eth_out {
+            calls.push(
+                IUniswapV3Router::unwrapWETH9Call {
+                    amountMinimum: amount_out_minimum,
+                    recipient,
+                }
+                .abi_encode()
+                .into(),
+            );
+        }
+        if v3 && params.from_token.is_native() && params.amount_out.is_some() {
+            calls.push(IUniswapV3Router::refundETHCall {}.abi_encode().into());
+        }
+        Ok(calls)
+    }
+
+    /// Router transaction running `calls`, in one `multicall` when there are several.
+    /// Native ETH input is sent as the transaction's value, which the router wraps.
+    pub fn swap_transaction(
+        params: &SwapParams,
+        router: Address,
+        mut calls: Vec<Bytes>,
+    ) -> anyhow::Result<TransactionRequest> {
+        let input = match calls.len() {
+            0 => return Err(anyhow::anyhow!("Swap has no router calls")),
+            1 => calls.remove(0),
+            _ => IUniswapV3Router::multicallCall { data: calls }
+                .abi_encode()
+                .into(),
+        };
+        let value = match params.from_token.is_native() {
+            true => params.maximum_amount_in().to_u256()?,
+            false => U256::ZERO,
+        };
+        Ok(TransactionRequest::default()
+            .with_to(router)
+            .with_input(input)
+            .with_value(value))
+    }
+
+    /// Router02 calldata along the route's token path: `swapExactTokensForTokens` for
+    /// exact-input swaps and `swapTokensForExactTokens` for exact-output ones, or their
+    /// `ETH` variants when either side is native ETH
+    pub fn v2_swap_calldata(
+        params: &SwapParams,
+        route: &SwapRoute,
+        recipient: Address,
+        deadline: U256,
+        amount_out_minimum: U256,
+    ) -> anyhow::Result<Bytes> {
+        if route.hops.is_empty() {
+            return Err(anyhow::anyhow!("Swap route has no pools"));
+        }
+        let path = route.tokens();
+        let eth_in = params.from_token.is_native();
+        let eth_out = params.to_token.is_native();
+        Ok(match &params.amount_out {
+            None => {
+                let amount_in = Self::decimal_to_u256(params.amount_in.to_raw_units()?)?;
+                match (eth_in, eth_out) {
+                    (true, _) => IUniswapV2Router::swapExactETHForTokensCall {
+                        amountOutMin: amount_out_minimum,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                    (false, true) => IUniswapV2Router::swapExactTokensForETHCall {
+                        amountIn: amount_in,
+                        amountOutMin: amount_out_minimum,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                    (false, false) => IUniswapV2Router::swapExactTokensForTokensCall {
+                        amountIn: amount_in,
+                        amountOutMin: amount_out_minimum,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                }
+            }
+            Some(amount_out) => {
+                let amount_out = amount_out.to_u256()?;
+                let amount_in_max = params.maximum_amount_in().to_u256()?;
+                match (eth_in, eth_out) {
+                    (true, _) => IUniswapV2Router::swapETHForExactTokensCall {
+                        amountOut: amount_out,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                    (false, true) => IUniswapV2Router::swapTokensForExactETHCall {
+                        amountOut: amount_out,
+                        amountInMax: amount_in_max,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                    (false, false) => IUniswapV2Router::swapTokensForExactTokensCall {
+                        amountOut: amount_out,
+                        amountInMax: amount_in_max,
+                        path,
+                        to: recipient,
+                        deadline,
+                    }
+                    .abi_encode(),
+                }
+            }
+        }
+        .into())
+    }
+
+    /// SwapRouter `exactOutputSingle` calldata; exact-output swaps use a single pool
+    pub fn exact_output_calldata(
+        route: &SwapRoute,
+        recipient: Address,
+        deadline: U256,
+        amount_out: U256,
+        amount_in_maximum: U256,
+    ) -> anyhow::Result<Bytes> {
+        match route.hops.as_slice() {
+            [hop] => Ok(IUniswapV3Router::exactOutputSingleCall {
+                params: IUniswapV3Router::ExactOutputSingleParams {
+                    tokenIn: hop.token_in.address(),
+                    tokenOut: hop.token_out.address(),
+                    fee: hop.fee_tier,
+                    recipient,
+                    deadline,
+                    amountOut: amount_out,
+                    amountInMaximum: amount_in_maximum,
+                    sqrtPriceLimitX96: U256::ZERO,
+                },
+            }
+            .abi_encode()
+            .into()),
+            hops => Err(anyhow::anyhow!(
+                "Exact-output swaps need a single-pool route, got {} pools",
+                hops.len()
+            )),
+        }
+    }
+
+    /// SwapRouter calldata for an exact-input swap along `route`: `exactInputSingle`
+    /// for one pool, `exactInput` with an encoded path otherwise
+    pub fn exact_input_calldata(
+        route: &SwapRoute,
+        recipient: Address,
+        deadline: U256,
+        amount_in: U256,
+        amount_out_minimum: U256,
+    ) -> anyhow::Result<Bytes> {
+        match route.hops.as_slice() {
+            [] => Err(anyhow::anyhow!("Swap route has no pools")),
+            [hop] => Ok(IUniswapV3Router::exactInputSingleCall {
+                params: IUniswapV3Router::ExactInputSingleParams {
+                    tokenIn: hop.token_in.address(),
+                    tokenOut: hop.token_out.address(),
+                    fee: hop.fee_tier,
+                    recipient,
+                    deadline,
+                    amountIn: amount_in,
+                    amountOutMinimum: amount_out_minimum,
+                    sqrtPriceLimitX96: U256::ZERO,
+                },
+            }
+            .abi_encode()
+            .into()),
+            _ => Ok(IUniswapV3Router::exactInputCall {
+                params: IUniswapV3Router::ExactInputParams {
+                    path: utils::encode_path(&route.tokens(), &route.fee_tiers())?,
+                    recipient,
+                    deadline,
+                    amountIn: amount_in,
+                    amountOutMinimum: amount_out_minimum,
+                },
+            }
+            .abi_encode()
+            .into()),
+        }
+    }
+
+    /// Fees for a replacement transaction: at least 10% above the original's fee cap
+    /// and tip (the mempool replacement rule) and no lower than the current `target`
+    pub fn replacement_fees(
+        original_max_fee: u128,
+        original_priority_fee: u128,
+        target: &FeeSuggestion,
+    ) -> (u128, u128) {
+        let bump = |fee: u128| fee.saturating_add(fee.div_ceil(10));
+        let priority_fee = bump(original_priority_fee).max(target.max_priority_fee_per_gas);
+        let max_fee = bump(original_max_fee)
+            .max(target.max_fee_per_gas)
+            .max(priority_fee);
+        (max_fee, priority_fee)
+    }
+
     async fn acquire_permit(&self) -> anyhow::Result<tokio::sync::SemaphorePermit<'_>> {
         tokio::time::timeout(Duration::from_secs(10), self.request_semaphore.acquire())
             .await
@@ -62,14 +706,22 @@ impl AlloyEthereumProvider<Http<Client>> {
             .map_err(|e| anyhow::anyhow!("Failed to acquire request permit: {}", e))
     }
 
-    async fn retry_with_backoff<F, Fut, T>(
+    /// Await a single request under its own permit. Quoting fans out across pools,
+    /// base tokens and protocols, so it takes a permit per request rather than one for
+    /// the whole operation; the node never sees more than `max_concurrent_requests`.
+    async fn permitted<F: std::future::IntoFuture>(&self, request: F) -> anyhow::Result<F::Output> {
+        let _permit = self.acquire_permit().await?;
+        Ok(request.await)
+    }
+
+    async fn retry_with_backoff<F, Fut, R>(
         operation: F,
         max_retries: u32,
         operation_name: &str,
-    ) -> anyhow::Result<T>
+    ) -> anyhow::Result<R>
     where
         F: Fn() -> Fut,
-        Fut: std::future::Future<Output = anyhow::Result<T>>,
+        Fut: std::future::Future<Output = anyhow::Result<R>>,
     {
         let mut attempts = 0;
         loop {
@@ -99,10 +751,10 @@ impl AlloyEthereumProvider<Http<Client>> {
         }
     }
 
-    async fn execute_with_circuit<F, Fut, T>(&self, operation: F, name: &str) -> anyhow::Result<T>
+    async fn execute_with_circuit<F, Fut, R>(&self, operation: F, name: &str) -> anyhow::Result<R>
     where
         F: FnOnce() -> Fut,
-        Fut: std::future::Future<Output = anyhow::Result<T>>,
+        Fut: std::future::Future<Output = anyhow::Result<R>>,
     {
         self.circuit_breaker
             .call(operation)
@@ -111,43 +763,1070 @@ impl AlloyEthereumProvider<Http<Client>> {
                 CircuitBreakerError::CircuitOpen => {
                     anyhow::anyhow!("Circuit breaker open for operation {}", name)
                 }
-                CircuitBreakerError::OperationFailed(e) => e,
-            })
+                CircuitBreakerError::OperationFailed(e) => e,
+            })
+    }
+}
+
+impl AlloyEthereumProvider<Http<Client>> {
+    /// Create new Ethereum provider with rate limiting and timeouts
+    #[instrument(skip(rpc_url, wallet_private_key))]
+    pub async fn new(
+        rpc_url: String,
+        wallet_private_key: String,
+        max_concurrent_requests: usize,
+        request_timeout_seconds: u64,
+    ) -> anyhow::Result<Self> {
+        let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+        Self::with_provider(
+            provider,
+            wallet_private_key,
+            max_concurrent_requests,
+            request_timeout_seconds,
+        )
+        .await
+    }
+}
+
+impl<T: Transport + Clone> AlloyEthereumProvider<T> {
+    /// Create a provider over any JSON-RPC transport, e.g. a recording or replaying one
+    pub async fn with_transport(
+        transport: T,
+        wallet_private_key: String,
+        max_concurrent_requests: usize,
+        request_timeout_seconds: u64,
+    ) -> anyhow::Result<Self> {
+        let provider = RootProvider::new(RpcClient::new(transport, false));
+        Self::with_provider(
+            provider,
+            wallet_private_key,
+            max_concurrent_requests,
+            request_timeout_seconds,
+        )
+        .await
+    }
+
+    async fn with_provider(
+        provider: RootProvider<T>,
+        wallet_private_key: String,
+        max_concurrent_requests: usize,
+        request_timeout_seconds: u64,
+    ) -> anyhow::Result<Self> {
+        let signer = Self::parse_private_key(&wallet_private_key)?;
+        let wallet_address = WalletAddress::new(signer.address());
+        info!("Wallet loaded successfully (address redacted for security)");
+
+        let nonce_manager = Arc::new(super::NonceManager::new());
+        let instance = Self {
+            provider,
+            wallet_address,
+            request_semaphore: Arc::new(Semaphore::new(max_concurrent_requests)),
+            circuit_breaker: CircuitBreaker::new(),
+            nonce_manager,
+            wallet: EthereumWallet::from(signer.clone()),
+            signer,
+            chain_id: OnceCell::new(),
+            tx_store: None,
+            token_slots: Mutex::new(HashMap::new()),
+        };
+
+        tokio::time::timeout(
+            Duration::from_secs(request_timeout_seconds),
+            instance.health_check(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Provider health check timed out"))??;
+
+        info!("Ethereum provider initialized successfully");
+        Ok(instance)
+    }
+
+    /// Journal signed transactions to `store`, first reconciling the transactions it
+    /// still lists as pending from a previous run against the chain
+    pub async fn with_transaction_store(
+        mut self,
+        store: Arc<TransactionStore>,
+    ) -> anyhow::Result<Self> {
+        self.tx_store = Some(store.clone());
+        self.reconcile_transactions(&store).await?;
+        Ok(self)
+    }
+
+    /// Settle stored pending transactions: mark mined or replaced ones, rebroadcast any
+    /// the node has forgotten, and hand the rest to the nonce manager for tracking
+    async fn reconcile_transactions(&self, store: &TransactionStore) -> anyhow::Result<()> {
+        let stored = store.pending(&self.wallet_address).await;
+        if stored.is_empty() {
+            return Ok(());
+        }
+        let mined_count = self.transaction_count(false).await?;
+
+        let mut still_pending = Vec::new();
+        for tx in stored {
+            let status = if tx.nonce < mined_count {
+                // The nonce is used; either this transaction or a replacement was mined
+                match self.provider.get_transaction_receipt(tx.tx_hash).await? {
+                    Some(_) => StoredTxStatus::Mined,
+                    None => StoredTxStatus::Replaced,
+                }
+            } else if self
+                .provider
+                .get_transaction_by_hash(tx.tx_hash)
+                .await?
+                .is_some()
+            {
+                StoredTxStatus::Pending
+            } else {
+                match self
+                    .provider
+                    .send_raw_transaction(&tx.raw_transaction)
+                    .await
+                {
+                    Ok(_) => {
+                        info!(tx_hash = %tx.tx_hash, nonce = tx.nonce, "Rebroadcast stored transaction");
+                        StoredTxStatus::Pending
+                    }
+                    Err(e) if super::NonceManager::is_nonce_too_low(&e.to_string()) => {
+                        StoredTxStatus::Replaced
+                    }
+                    Err(e) => {
+                        warn!(tx_hash = %tx.tx_hash, nonce = tx.nonce, "Could not rebroadcast stored transaction: {}", e);
+                        StoredTxStatus::Dropped
+                    }
+                }
+            };
+
+            if status == StoredTxStatus::Pending {
+                still_pending.push(tx);
+            } else {
+                info!(tx_hash = %tx.tx_hash, nonce = tx.nonce, ?status, "Stored transaction settled");
+                store.update_status(&tx.tx_hash, status).await?;
+            }
+        }
+
+        // Rebroadcasts are included in the pending count, so sync only after them
+        let pending_count = self.transaction_count(true).await?;
+        self.nonce_manager
+            .initialize_nonce(&self.wallet_address, pending_count)
+            .await;
+        for tx in &still_pending {
+            self.nonce_manager
+                .record_transaction(&self.wallet_address, tx.nonce, tx.tx_hash)
+                .await;
+        }
+        info!(
+            pending = still_pending.len(),
+            "Reconciled stored transactions with chain"
+        );
+        Ok(())
+    }
+
+    /// Chain ID of the connected network, fetched once and cached
+    async fn chain_id(&self) -> anyhow::Result<u64> {
+        self.chain_id
+            .get_or_try_init(|| async {
+                self.provider
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to fetch chain ID: {}", e))
+            })
+            .await
+            .copied()
+    }
+
+    /// Sign a contract call with the configured wallet and broadcast it as an EIP-1559
+    /// transaction priced at the "normal" fee tier
+    async fn sign_and_send(&self, tx: TransactionRequest, operation: &str) -> anyhow::Result<B256> {
+        let fees = self.get_gas_fees().await?;
+        let chain_id = self.chain_id().await?;
+        let from = self.wallet_address.address();
+        let tx = tx.with_from(from);
+
+        let _permit = self.acquire_permit().await?;
+        // Estimating from the real wallet surfaces reverts (allowance, balance) before signing
+        let gas_estimate = self.provider.estimate_gas(&tx).await.map_err(|e| {
+            Self::revert_error(&format!("{} transaction would revert", operation), e)
+        })?;
+
+        let tx = tx
+            .with_chain_id(chain_id)
+            .with_gas_limit(gas_estimate.saturating_mul(12) / 10)
+            .with_max_fee_per_gas(fees.normal.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.normal.max_priority_fee_per_gas);
+
+        // One resync-and-retry if another client used the wallet's next nonce
+        let mut resynced = false;
+        loop {
+            let nonce = self
+                .nonce_manager
+                .get_next_nonce(&self.wallet_address, || self.transaction_count(true))
+                .await?;
+
+            match self
+                .broadcast_with_nonce(tx.clone(), nonce, operation)
+                .await
+            {
+                Ok(tx_hash) => {
+                    self.nonce_manager
+                        .record_transaction(&self.wallet_address, nonce, tx_hash)
+                        .await;
+                    info!(nonce, tx_hash = %tx_hash, "{} transaction broadcast", operation);
+                    return Ok(tx_hash);
+                }
+                Err(e) if !resynced && super::NonceManager::is_nonce_too_low(&e.to_string()) => {
+                    warn!(
+                        nonce,
+                        "Nonce {} already used, resynchronizing with chain", nonce
+                    );
+                    let (pending_count, mined_count) = tokio::try_join!(
+                        self.transaction_count(true),
+                        self.transaction_count(false)
+                    )?;
+                    self.nonce_manager
+                        .resync_nonce(&self.wallet_address, pending_count)
+                        .await;
+                    self.nonce_manager
+                        .prune_confirmed(&self.wallet_address, mined_count)
+                        .await;
+                    resynced = true;
+                }
+                Err(e) => {
+                    match e.downcast_ref::<UnconfirmedBroadcast>() {
+                        // The node may hold the transaction, so its nonce stays taken
+                        // until the chain says otherwise (a resync after "nonce too low")
+                        Some(unconfirmed) => {
+                            warn!(nonce, tx_hash = %unconfirmed.tx_hash, "{} broadcast unconfirmed, keeping its nonce", operation);
+                            self.nonce_manager
+                                .record_transaction(
+                                    &self.wallet_address,
+                                    nonce,
+                                    unconfirmed.tx_hash,
+                                )
+                                .await;
+                        }
+                        // Never sent, or refused by the node: the nonce is free again
+                        None => {
+                            self.nonce_manager
+                                .release_nonce(&self.wallet_address, nonce)
+                                .await
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Sign `tx` with `nonce` and broadcast it
+    async fn broadcast_with_nonce(
+        &self,
+        tx: TransactionRequest,
+        nonce: u64,
+        operation: &str,
+    ) -> anyhow::Result<B256> {
+        let envelope = tx
+            .with_nonce(nonce)
+            .build(&self.wallet)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to sign {} transaction: {}", operation, e))?;
+        let raw_transaction = Bytes::from(envelope.encoded_2718());
+        let tx_hash = keccak256(&raw_transaction);
+
+        // Journal before sending so a crash mid-broadcast can't lose track of the transaction
+        if let Some(store) = &self.tx_store {
+            store
+                .record(StoredTransaction {
+                    wallet: self.wallet_address.clone(),
+                    nonce,
+                    tx_hash,
+                    raw_transaction: raw_transaction.clone(),
+                    operation: operation.to_string(),
+                    status: StoredTxStatus::Pending,
+                    updated_at: Utc::now(),
+                })
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Not broadcasting {} transaction: {}", operation, e)
+                })?;
+        }
+
+        // Broadcasting is not idempotent, so no retry_with_backoff here
+        let result = self
+            .execute_with_circuit(
+                || async {
+                    match self.provider.send_raw_transaction(&raw_transaction).await {
+                        Ok(pending) => Ok(*pending.tx_hash()),
+                        Err(e) if Self::never_broadcast(&e) => Err(e.into()),
+                        Err(e) => Err(UnconfirmedBroadcast {
+                            tx_hash,
+                            reason: e.to_string(),
+                        }
+                        .into()),
+                    }
+                },
+                operation,
+            )
+            .await;
+
+        // Only a transaction that certainly never reached the node is dropped; an
+        // unconfirmed one stays pending for reconciliation to settle
+        let unsent = result
+            .as_ref()
+            .is_err_and(|e| e.downcast_ref::<UnconfirmedBroadcast>().is_none());
+        if let (true, Some(store)) = (unsent, &self.tx_store) {
+            if let Err(e) = store.update_status(&tx_hash, StoredTxStatus::Dropped).await {
+                warn!(tx_hash = %tx_hash, "Failed to mark unsent transaction as dropped: {}", e);
+            }
+        }
+        result
+    }
+
+    /// Whether a failed `eth_sendRawTransaction` certainly left the transaction off the
+    /// node: the node answered with an error, or the request was never sent. Timeouts,
+    /// dropped connections and unreadable responses may follow an accepted transaction.
+    fn never_broadcast(error: &TransportError) -> bool {
+        use alloy::transports::RpcError;
+        matches!(
+            error,
+            RpcError::ErrorResp(_)
+                | RpcError::SerError(_)
+                | RpcError::UnsupportedFeature(_)
+                | RpcError::LocalUsageError(_)
+        )
+    }
+
+    /// Parameters of a sent transaction, from the node or, if it has forgotten it,
+    /// from the signed copy in the transaction journal
+    async fn replaceable_transaction(&self, tx_hash: &B256) -> anyhow::Result<ReplaceableTx> {
+        if let Some(tx) = self.provider.get_transaction_by_hash(*tx_hash).await? {
+            let legacy_price = tx.gas_price.unwrap_or_default();
+            return Ok(ReplaceableTx {
+                to: tx.to.map_or(TxKind::Create, TxKind::Call),
+                value: tx.value,
+                input: tx.input,
+                gas_limit: tx.gas,
+                max_fee_per_gas: tx.max_fee_per_gas.unwrap_or(legacy_price),
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas.unwrap_or(legacy_price),
+            });
+        }
+
+        let stored = match &self.tx_store {
+            Some(store) => store.get(tx_hash).await,
+            None => None,
+        }
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Transaction {} is unknown to the node and not in the transaction store",
+                tx_hash
+            )
+        })?;
+        match TxEnvelope::decode_2718(&mut stored.raw_transaction.as_ref())? {
+            TxEnvelope::Eip1559(signed) => {
+                let tx = signed.tx();
+                Ok(ReplaceableTx {
+                    to: tx.to,
+                    value: tx.value,
+                    input: tx.input.clone(),
+                    gas_limit: tx.gas_limit,
+                    max_fee_per_gas: tx.max_fee_per_gas,
+                    max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                })
+            }
+            _ => Err(anyhow::anyhow!(
+                "Stored transaction {} is not an EIP-1559 transaction",
+                tx_hash
+            )),
+        }
+    }
+
+    /// The configured wallet's transaction count, including mempool transactions if `pending`
+    async fn transaction_count(&self, pending: bool) -> anyhow::Result<u64> {
+        self.transaction_count_of(self.wallet_address.address(), pending)
+            .await
+    }
+
+    /// An account's transaction count, including mempool transactions if `pending`
+    async fn transaction_count_of(&self, address: Address, pending: bool) -> anyhow::Result<u64> {
+        let request = self.provider.get_transaction_count(address);
+        let request = if pending {
+            request.pending()
+        } else {
+            request.latest()
+        };
+        request
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch transaction count: {}", e))
+    }
+
+    /// Number of the latest finalized block, if the node supports the `finalized` tag
+    async fn finalized_block_number(&self) -> Option<u64> {
+        match self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Finalized, false)
+            .await
+        {
+            Ok(block) => block.and_then(|b| b.header.number),
+            Err(e) => {
+                debug!("Finalized block unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Recover why a mined transaction failed by replaying it with `eth_call` on top of
+    /// its parent block. Best effort: transactions that depended on earlier ones in the
+    /// same block may not revert in the replay.
+    async fn replay_revert_reason(
+        &self,
+        tx_hash: &B256,
+        block_number: Option<u64>,
+        gas_used: u128,
+    ) -> Option<RevertReason> {
+        let tx = match self.provider.get_transaction_by_hash(*tx_hash).await {
+            Ok(tx) => tx?,
+            Err(e) => {
+                debug!("Could not fetch failed transaction for replay: {}", e);
+                return None;
+            }
+        };
+        let request = TransactionRequest::default()
+            .with_from(tx.from)
+            .with_kind(tx.to.map_or(TxKind::Create, TxKind::Call))
+            .with_value(tx.value)
+            .with_input(tx.input.clone())
+            .with_gas_limit(tx.gas);
+
+        let replayed = match block_number.and_then(|block| block.checked_sub(1)) {
+            Some(parent) => match self
+                .provider
+                .call(&request)
+                .block(BlockId::number(parent))
+                .await
+            {
+                Ok(_) => None,
+                Err(e) => Self::revert_reason(&e),
+            },
+            None => None,
+        };
+
+        match replayed {
+            Some(reason) if reason.kind != RevertKind::Unknown => Some(reason),
+            // Running out of gas leaves no revert data, but uses the whole limit
+            _ if gas_used >= tx.gas => Some(RevertReason {
+                kind: RevertKind::OutOfGas,
+                message: format!("ran out of gas (limit {})", tx.gas),
+                explanation: None,
+                arguments: None,
+                data: Bytes::new(),
+            }),
+            other => other,
+        }
+    }
+
+    /// Split the gas cost of a transaction on an L2 into L2 execution and L1 data fees
+    async fn l2_gas_cost_breakdown(
+        &self,
+        network: L2Network,
+        to: Address,
+        input: Bytes,
+        gas_estimate: u64,
+        fees: &GasFeeEstimate,
+    ) -> anyhow::Result<GasCostBreakdown> {
+        let gas_price = fees.normal_gas_price();
+        let (l2_gas, l1_fee_wei) = match network {
+            L2Network::OpStack => {
+                let payload = Self::unsigned_eip1559_payload(
+                    self.chain_id().await?,
+                    to,
+                    input,
+                    gas_estimate,
+                    &fees.normal,
+                );
+                let oracle = IGasPriceOracle::new(
+                    utils::parse_address(l2::GAS_PRICE_ORACLE)?,
+                    &self.provider,
+                );
+                let l1_fee = oracle.getL1Fee(payload).call().await?._0;
+                (gas_estimate, l1_fee)
+            }
+            L2Network::Arbitrum => {
+                let node_interface =
+                    INodeInterface::new(utils::parse_address(l2::NODE_INTERFACE)?, &self.provider);
+                let component = node_interface
+                    .gasEstimateL1Component(to, false, input)
+                    .call()
+                    .await?;
+                // eth_estimateGas on Arbitrum already includes the L1 component as extra L2 gas
+                let l1_gas = component.gasEstimateForL1;
+                let l1_fee = U256::from(l1_gas) * component.baseFee;
+                (gas_estimate.saturating_sub(l1_gas), l1_fee)
+            }
+        };
+
+        let l1_fee_wei: u128 = l1_fee_wei
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("L1 data fee out of range: {}", l1_fee_wei))?;
+        Ok(GasCostBreakdown {
+            network: network.as_str().to_string(),
+            l2_execution_fee_eth: Self::wei_to_eth((l2_gas as u128).saturating_mul(gas_price)),
+            l1_data_fee_eth: Self::wei_to_eth(l1_fee_wei),
+        })
+    }
+
+    /// Uniswap V3 pools between two tokens across every fee tier, looked up concurrently,
+    /// skipping tiers the factory never deployed or couldn't be asked about and pools
+    /// with no in-range liquidity
+    async fn liquid_pools(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Vec<RouteHop>> {
+        let factory_addr = utils::parse_address(&contracts.uniswap_v3_factory)?;
+        let factory = IUniswapV3Factory::new(factory_addr, &self.provider);
+        let tiers = fees::ALL.map(|fee| {
+            let factory = &factory;
+            async move {
+                let pool = match Self::retry_with_backoff(
+                    || async {
+                        let call = factory.getPool(token_in, token_out, fee);
+                        Ok(self.permitted(call.call()).await??.pool)
+                    },
+                    3,
+                    "getPool",
+                )
+                .await
+                {
+                    Ok(pool) => pool,
+                    Err(e) => {
+                        warn!(fee, "Factory getPool failed, skipping fee tier: {}", e);
+                        return None;
+                    }
+                };
+                if pool == Address::ZERO {
+                    debug!(fee, "No pool deployed for fee tier");
+                    return None;
+                }
+                let liquidity = self
+                    .permitted(IUniswapV3Pool::new(pool, &self.provider).liquidity().call())
+                    .await
+                    .and_then(|liquidity| Ok(liquidity?._0));
+                match liquidity {
+                    Ok(liquidity) if liquidity > 0 => Some(RouteHop {
+                        token_in: TokenAddress::new(token_in),
+                        token_out: TokenAddress::new(token_out),
+                        fee_tier: fee,
+                        pool,
+                        liquidity,
+                    }),
+                    Ok(_) => {
+                        debug!(fee, pool = %pool, "Skipping pool without liquidity");
+                        None
+                    }
+                    Err(e) => {
+                        warn!("Could not read liquidity of pool {}: {}", pool, e);
+                        None
+                    }
+                }
+            }
+        });
+        Ok(join_all(tiers).await.into_iter().flatten().collect())
+    }
+
+    /// Quote `amount_in` through every liquid pool between two tokens, dropping pools
+    /// whose quote reverts
+    async fn quote_hops(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Vec<(RouteHop, PathQuote)>> {
+        let hops = self.liquid_pools(token_in, token_out, contracts).await?;
+        let quotes = hops.into_iter().map(|hop| async move {
+            let route = SwapRoute {
+                protocol: SwapProtocol::UniswapV3,
+                hops: vec![hop.clone()],
+            };
+            match self.quote_route(&route, amount_in, contracts).await {
+                Ok(quote) => Some((hop, quote)),
+                Err(e) => {
+                    debug!(fee = hop.fee_tier, pool = %hop.pool, "Pool quote reverted: {}", e);
+                    None
+                }
+            }
+        });
+        Ok(join_all(quotes).await.into_iter().flatten().collect())
+    }
+
+    /// Output of `amount_in` along a route, via `quoteExactInput` for multi-hop paths
+    async fn quote_route(
+        &self,
+        route: &SwapRoute,
+        amount_in: U256,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<PathQuote> {
+        let quoter_addr = utils::parse_address(&contracts.uniswap_v3_quoter_v2)?;
+        let quoter = IUniswapV3QuoterV2::new(quoter_addr, &self.provider);
+        let quote = match route.hops.as_slice() {
+            [] => return Err(anyhow::anyhow!("Swap route has no pools")),
+            [hop] => {
+                let call =
+                    quoter.quoteExactInputSingle(IUniswapV3QuoterV2::QuoteExactInputSingleParams {
+                        tokenIn: hop.token_in.address(),
+                        tokenOut: hop.token_out.address(),
+                        amountIn: amount_in,
+                        fee: hop.fee_tier,
+                        sqrtPriceLimitX96: U256::ZERO,
+                    });
+                self.permitted(call.call()).await?.map(|quote| PathQuote {
+                    amount: quote.amountOut,
+                    sqrt_prices_x96_after: vec![U256::from(quote.sqrtPriceX96After)],
+                    initialized_ticks_crossed: quote.initializedTicksCrossed,
+                    gas_estimate: quote.gasEstimate.saturating_to(),
+                })
+            }
+            _ => {
+                let path = utils::encode_path(&route.tokens(), &route.fee_tiers())?;
+                self.permitted(quoter.quoteExactInput(path, amount_in).call())
+                    .await?
+                    .map(|quote| PathQuote {
+                        amount: quote.amountOut,
+                        sqrt_prices_x96_after: quote
+                            .sqrtPriceX96AfterList
+                            .into_iter()
+                            .map(U256::from)
+                            .collect(),
+                        initialized_ticks_crossed: quote.initializedTicksCrossedList.iter().sum(),
+                        gas_estimate: quote.gasEstimate.saturating_to(),
+                    })
+            }
+        };
+        quote.map_err(|e| Self::contract_revert_error("Swap quote reverted", e))
+    }
+
+    /// Where `token` stores balances and allowances, found by overriding candidate
+    /// slots in `eth_call`s until `balanceOf` and `allowance` read the override back
+    async fn token_slots(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+    ) -> anyhow::Result<TokenSlots> {
+        if let Some(slots) = self.token_slots.lock().unwrap().get(&token) {
+            return Ok(*slots);
+        }
+        let balance = self
+            .find_mapping_slot(
+                token,
+                owner,
+                IERC20::balanceOfCall { account: owner }.abi_encode(),
+                |slot| slot.key(owner),
+            )
+            .await?;
+        let allowance = self
+            .find_mapping_slot(
+                token,
+                owner,
+                IERC20::allowanceCall { owner, spender }.abi_encode(),
+                |slot| slot.nested(owner, spender),
+            )
+            .await?;
+        let slots = TokenSlots { balance, allowance };
+        debug!(%token, ?slots, "Located token storage slots");
+        self.token_slots.lock().unwrap().insert(token, slots);
+        Ok(slots)
+    }
+
+    async fn find_mapping_slot(
+        &self,
+        token: Address,
+        owner: Address,
+        input: Vec<u8>,
+        slot_of: impl Fn(MappingSlot) -> B256,
+    ) -> anyhow::Result<MappingSlot> {
+        let marker = keccak256(b"ethereum-mcp-server storage probe");
+        let request = TransactionRequest::default()
+            .with_from(owner)
+            .with_to(token)
+            .with_input(Bytes::from(input));
+        for index in 0..MAX_PROBED_STORAGE_SLOT {
+            for vyper in [false, true] {
+                let slot = MappingSlot { index, vyper };
+                let overrides = StateOverride::from_iter([(
+                    token,
+                    AccountOverride {
+                        state_diff: Some(HashMap::from_iter([(slot_of(slot), marker)])),
+                        ..Default::default()
+                    },
+                )]);
+                if let Ok(output) = self.provider.call(&request).overrides(&overrides).await {
+                    if output.get(..32) == Some(marker.as_slice()) {
+                        return Ok(slot);
+                    }
+                }
+            }
+        }
+        Err(anyhow::anyhow!(
+            "No mapping read back from the first {} storage slots of {}",
+            MAX_PROBED_STORAGE_SLOT,
+            token
+        ))
+    }
+
+    /// Mid rate of a route, compounded from each pool's current `slot0` price or each
+    /// pair's reserves. Pools without liquidity at that price have no meaningful mid rate.
+    async fn route_mid_rate(&self, route: &SwapRoute) -> anyhow::Result<f64> {
+        let mut rate = 1.0;
+        for hop in &route.hops {
+            if hop.pool == Address::ZERO {
+                return Err(anyhow::anyhow!(
+                    "Route hop {} -> {} has no pool address",
+                    hop.token_in.to_hex(),
+                    hop.token_out.to_hex()
+                ));
+            }
+            // V2 pairs price at the ratio of their reserves
+            if route.protocol.is_v2() {
+                let reserves = self
+                    .permitted(
+                        IUniswapV2Pair::new(hop.pool, &self.provider)
+                            .getReserves()
+                            .call(),
+                    )
+                    .await?
+                    .map_err(|e| Self::contract_revert_error("Pair reserves read failed", e))?;
+                rate *= Self::v2_hop_mid_rate(
+                    hop,
+                    U256::from(reserves.reserve0),
+                    U256::from(reserves.reserve1),
+                );
+                continue;
+            }
+            let pool = IUniswapV3Pool::new(hop.pool, &self.provider);
+            let slot0 = self
+                .permitted(pool.slot0().call())
+                .await?
+                .map_err(|e| Self::contract_revert_error("Pool slot0 read failed", e))?;
+            let liquidity = self
+                .permitted(pool.liquidity().call())
+                .await?
+                .map_err(|e| Self::contract_revert_error("Pool liquidity read failed", e))?
+                ._0;
+            if liquidity == 0 {
+                return Err(anyhow::anyhow!(
+                    "Pool {} has no liquidity at its current price",
+                    hop.pool
+                ));
+            }
+            rate *= Self::hop_mid_rate(hop, U256::from(slot0.sqrtPriceX96));
+        }
+        Ok(rate)
+    }
+
+    /// Input a single-pool route needs to pay out exactly `amount_out`
+    async fn quote_exact_output_route(
+        &self,
+        route: &SwapRoute,
+        amount_out: U256,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<PathQuote> {
+        let [hop] = route.hops.as_slice() else {
+            return Err(anyhow::anyhow!(
+                "Exact-output swaps need a single-pool route, got {} pools",
+                route.hops.len()
+            ));
+        };
+        let quoter_addr = utils::parse_address(&contracts.uniswap_v3_quoter_v2)?;
+        let quoter = IUniswapV3QuoterV2::new(quoter_addr, &self.provider);
+        let call =
+            quoter.quoteExactOutputSingle(IUniswapV3QuoterV2::QuoteExactOutputSingleParams {
+                tokenIn: hop.token_in.address(),
+                tokenOut: hop.token_out.address(),
+                amount: amount_out,
+                fee: hop.fee_tier,
+                sqrtPriceLimitX96: U256::ZERO,
+            });
+        self.permitted(call.call())
+            .await?
+            .map(|quote| PathQuote {
+                amount: quote.amountIn,
+                sqrt_prices_x96_after: vec![U256::from(quote.sqrtPriceX96After)],
+                initialized_ticks_crossed: quote.initializedTicksCrossed,
+                gas_estimate: quote.gasEstimate.saturating_to(),
+            })
+            .map_err(|e| Self::contract_revert_error("Swap quote reverted", e))
+    }
+
+    /// Direct routes with the input each needs to pay out exactly `amount_out`,
+    /// dropping pools whose quote reverts (e.g. too shallow for the amount)
+    async fn quote_exact_output_routes(
+        &self,
+        from: Address,
+        to: Address,
+        amount_out: U256,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Vec<(SwapRoute, PathQuote)>> {
+        self.execute_with_circuit(
+            || async {
+                let hops = self.liquid_pools(from, to, contracts).await?;
+                let routes = hops.into_iter().map(|hop| async move {
+                    let route = SwapRoute {
+                        protocol: SwapProtocol::UniswapV3,
+                        hops: vec![hop],
+                    };
+                    match self
+                        .quote_exact_output_route(&route, amount_out, contracts)
+                        .await
+                    {
+                        Ok(quote) => Some((route, quote)),
+                        Err(e) => {
+                            debug!(
+                                fee = route.hops[0].fee_tier,
+                                "Exact-output quote failed: {}", e
+                            );
+                            None
+                        }
+                    }
+                });
+                Ok(join_all(routes).await.into_iter().flatten().collect())
+            },
+            "quote_exact_output_routes",
+        )
+        .await
+    }
+
+    /// Candidate routes with their quoted outputs: every liquid direct pool, plus the
+    /// best two-hop route through each base token. Each leg of a two-hop route takes
+    /// its best-quoting pool, which maximises the route's output, and the whole path
+    /// is then re-quoted with `quoteExactInput`. The direct pools and each base token
+    /// are quoted concurrently.
+    async fn quote_routes(
+        &self,
+        from: Address,
+        to: Address,
+        amount_in: U256,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Vec<(SwapRoute, PathQuote)>> {
+        self.execute_with_circuit(
+            || async {
+                let best = |quotes: Vec<(RouteHop, PathQuote)>| {
+                    quotes
+                        .into_iter()
+                        .max_by_key(|(_, quote)| quote.amount)
+                        .map(|(hop, quote)| (hop, quote.amount))
+                };
+                let two_hop = |base: Address| async move {
+                    let Some((first, mid_amount)) =
+                        best(self.quote_hops(from, base, amount_in, contracts).await?)
+                    else {
+                        return Ok(None);
+                    };
+                    let Some((second, _)) =
+                        best(self.quote_hops(base, to, mid_amount, contracts).await?)
+                    else {
+                        return Ok(None);
+                    };
+                    let route = SwapRoute {
+                        protocol: SwapProtocol::UniswapV3,
+                        hops: vec![first, second],
+                    };
+                    match self.quote_route(&route, amount_in, contracts).await {
+                        Ok(quote) => anyhow::Ok(Some((route, quote))),
+                        Err(e) => {
+                            debug!(base = %base, "Two-hop quote failed: {}", e);
+                            Ok(None)
+                        }
+                    }
+                };
+                let bases = utils::base_tokens(contracts)
+                    .into_iter()
+                    .filter(|base| *base != from && *base != to);
+                let (direct, two_hop) = futures::join!(
+                    self.quote_hops(from, to, amount_in, contracts),
+                    join_all(bases.map(two_hop))
+                );
+
+                let mut routes: Vec<(SwapRoute, PathQuote)> = direct?
+                    .into_iter()
+                    .map(|(hop, quote)| {
+                        (
+                            SwapRoute {
+                                protocol: SwapProtocol::UniswapV3,
+                                hops: vec![hop],
+                            },
+                            quote,
+                        )
+                    })
+                    .collect();
+                for route in two_hop {
+                    routes.extend(route?);
+                }
+                Ok(routes)
+            },
+            "quote_routes",
+        )
+        .await
     }
-}
 
-impl AlloyEthereumProvider<Http<Client>> {
-    /// Create new Ethereum provider with rate limiting and timeouts
-    #[instrument(skip(rpc_url, wallet_private_key))]
-    pub async fn new(
-        rpc_url: String,
-        wallet_private_key: String,
-        max_concurrent_requests: usize,
-        request_timeout_seconds: u64,
-    ) -> anyhow::Result<Self> {
-        let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
-        let signer = Self::parse_private_key(&wallet_private_key)?;
-        let wallet_address = WalletAddress::new(signer.address());
-        info!("Wallet loaded successfully (address redacted for security)");
+    /// The pairs along a V2 token path, or None if any pair is missing or empty
+    async fn v2_path_hops(
+        &self,
+        protocol: SwapProtocol,
+        path: &[Address],
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Option<Vec<RouteHop>>> {
+        let factory_addr = utils::pair_factory(protocol, contracts)?;
+        let factory = IUniswapV2Factory::new(factory_addr, &self.provider);
+        let mut hops = Vec::new();
+        for tokens in path.windows(2) {
+            let pair = self
+                .permitted(factory.getPair(tokens[0], tokens[1]).call())
+                .await?
+                .map_err(|e| anyhow::anyhow!("{} getPair failed: {}", protocol.as_str(), e))?
+                .pair;
+            if pair == Address::ZERO {
+                return Ok(None);
+            }
+            let reserves = self
+                .permitted(
+                    IUniswapV2Pair::new(pair, &self.provider)
+                        .getReserves()
+                        .call(),
+                )
+                .await?
+                .map_err(|e| Self::contract_revert_error("Pair reserves read failed", e))?;
+            if reserves.reserve0 == 0 || reserves.reserve1 == 0 {
+                debug!(pair = %pair, "Skipping pair without reserves");
+                return Ok(None);
+            }
+            let liquidity = (U256::from(reserves.reserve0) * U256::from(reserves.reserve1)).root(2);
+            hops.push(RouteHop {
+                token_in: TokenAddress::new(tokens[0]),
+                token_out: TokenAddress::new(tokens[1]),
+                fee_tier: fees::V2_PAIR,
+                pool: pair,
+                liquidity: liquidity.saturating_to(),
+            });
+        }
+        Ok(Some(hops))
+    }
 
-        let nonce_manager = Arc::new(super::NonceManager::new());
-        let instance = Self {
-            provider,
-            wallet_address,
-            request_semaphore: Arc::new(Semaphore::new(max_concurrent_requests)),
-            circuit_breaker: CircuitBreaker::new(),
-            _nonce_manager: nonce_manager,
+    /// Quote a V2 route with the router's `getAmountsOut`, or `getAmountsIn` when
+    /// `exact_output`
+    async fn quote_v2_route(
+        &self,
+        route: &SwapRoute,
+        amount: U256,
+        exact_output: bool,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<PathQuote> {
+        let router_addr = utils::swap_router(route.protocol, contracts)?;
+        let router = IUniswapV2Router::new(router_addr, &self.provider);
+        let path = route.tokens();
+        let amounts = if exact_output {
+            self.permitted(router.getAmountsIn(amount, path).call())
+                .await?
+                .map(|q| q.amounts)
+        } else {
+            self.permitted(router.getAmountsOut(amount, path).call())
+                .await?
+                .map(|q| q.amounts)
+        }
+        .map_err(|e| Self::contract_revert_error("Swap quote reverted", e))?;
+        let amount = if exact_output {
+            amounts.first()
+        } else {
+            amounts.last()
         };
+        Ok(PathQuote {
+            amount: *amount.ok_or_else(|| anyhow::anyhow!("Router returned no amounts"))?,
+            sqrt_prices_x96_after: Vec::new(),
+            initialized_ticks_crossed: 0,
+            gas_estimate: V2_PAIR_SWAP_GAS * route.hops.len() as u64,
+        })
+    }
 
-        tokio::time::timeout(
-            Duration::from_secs(request_timeout_seconds),
-            instance.health_check(),
+    /// Candidate V2 routes with their quotes: the direct pair and the path through each
+    /// base token, wherever every pair exists and has reserves. Paths are quoted
+    /// concurrently.
+    async fn quote_v2_routes(
+        &self,
+        protocol: SwapProtocol,
+        from: Address,
+        to: Address,
+        amount: U256,
+        exact_output: bool,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<Vec<(SwapRoute, PathQuote)>> {
+        let mut paths = vec![vec![from, to]];
+        paths.extend(
+            utils::base_tokens(contracts)
+                .into_iter()
+                .filter(|base| *base != from && *base != to)
+                .map(|base| vec![from, base, to]),
+        );
+        self.execute_with_circuit(
+            || async {
+                let routes = paths.iter().map(|path| async move {
+                    let Some(hops) = self.v2_path_hops(protocol, path, contracts).await? else {
+                        return Ok(None);
+                    };
+                    let route = SwapRoute { protocol, hops };
+                    match self
+                        .quote_v2_route(&route, amount, exact_output, contracts)
+                        .await
+                    {
+                        Ok(quote) => anyhow::Ok(Some((route, quote))),
+                        Err(e) => {
+                            debug!(protocol = protocol.as_str(), "V2 quote failed: {}", e);
+                            Ok(None)
+                        }
+                    }
+                });
+                let mut quoted = Vec::new();
+                for route in join_all(routes).await {
+                    quoted.extend(route?);
+                }
+                Ok(quoted)
+            },
+            "quote_v2_routes",
         )
         .await
-        .map_err(|_| anyhow::anyhow!("Provider health check timed out"))??;
+    }
 
-        info!("Ethereum provider initialized successfully");
-        Ok(instance)
+    /// Units of `token` one ETH buys, used to price swap gas in one of the swap's
+    /// tokens. `quoted_rate` is the swap's own price when it trades against WETH.
+    async fn units_per_eth(
+        &self,
+        token: &TokenAddress,
+        decimals: u8,
+        quoted_rate: Option<Decimal>,
+        contracts: &ContractAddresses,
+    ) -> Option<Decimal> {
+        let weth = utils::parse_address(&contracts.weth).ok()?;
+        if token.is_native() || token.address() == weth {
+            return Some(Decimal::ONE);
+        }
+        if quoted_rate.is_some() {
+            return quoted_rate;
+        }
+        // The best of the liquid WETH pools, rather than whichever fee tier answers first
+        let one_eth = U256::from(10u64.pow(18));
+        let best = self
+            .quote_hops(weth, token.address(), one_eth, contracts)
+            .await
+            .ok()
+            .and_then(|quotes| quotes.into_iter().map(|(_, quote)| quote.amount).max());
+        let Some(amount_out) = best else {
+            warn!(
+                "No WETH price for {}; ranking routes without gas",
+                token.to_hex()
+            );
+            return None;
+        };
+        let raw = Self::u256_to_decimal(amount_out).ok()?;
+        Some(TokenAmount::from_raw_units(raw, decimals).raw)
     }
 
     async fn fetch_eth_usd_price(&self, contracts: &ContractAddresses) -> anyhow::Result<Decimal> {
@@ -167,7 +1846,7 @@ impl AlloyEthereumProvider<Http<Client>> {
 }
 
 #[async_trait]
-impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
+impl<T: Transport + Clone> EthereumProvider for AlloyEthereumProvider<T> {
     #[instrument(skip(self), fields(provider = "http", wallet = %wallet.to_hex()))]
     async fn get_eth_balance(&self, wallet: &WalletAddress) -> anyhow::Result<BalanceInfo> {
         let _permit = self.acquire_permit().await?;
@@ -229,8 +1908,51 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
         .await
     }
 
+    #[instrument(skip(self), fields(provider = "http", owner = %owner.to_hex(), token = %token.to_hex()))]
+    async fn get_allowance(
+        &self,
+        owner: &WalletAddress,
+        token: &TokenAddress,
+        spender: &Address,
+    ) -> anyhow::Result<AllowanceInfo> {
+        let _permit = self.acquire_permit().await?;
+        self.execute_with_circuit(
+            || async {
+                Self::retry_with_backoff(
+                    || async {
+                        let contract = IERC20::new(token.address(), &self.provider);
+                        let allowance = contract
+                            .allowance(owner.address(), *spender)
+                            .call()
+                            .await?
+                            ._0;
+                        let decimals = contract.decimals().call().await?._0;
+                        Ok(AllowanceInfo {
+                            owner: owner.clone(),
+                            spender: *spender,
+                            token_address: token.clone(),
+                            allowance: TokenAmount::from_raw_units(
+                                Self::u256_to_decimal(allowance)?,
+                                decimals,
+                            ),
+                            is_unlimited: allowance == U256::MAX,
+                        })
+                    },
+                    3,
+                    "get_allowance",
+                )
+                .await
+            },
+            "get_allowance",
+        )
+        .await
+    }
+
     #[instrument(skip(self), fields(provider = "http", token = %token.to_hex()))]
     async fn get_token_decimals(&self, token: &TokenAddress) -> anyhow::Result<u8> {
+        if token.is_native() {
+            return Ok(18);
+        }
         let _permit = self.acquire_permit().await?;
         self.execute_with_circuit(
             || async {
@@ -244,6 +1966,9 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
 
     #[instrument(skip(self), fields(provider = "http", token = %token.to_hex()))]
     async fn get_token_symbol(&self, token: &TokenAddress) -> anyhow::Result<String> {
+        if token.is_native() {
+            return Ok("ETH".to_string());
+        }
         let _permit = self.acquire_permit().await?;
         self.execute_with_circuit(
             || async {
@@ -264,7 +1989,7 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
         let _permit = self.acquire_permit().await?;
         self.execute_with_circuit(
             || async {
-                let token_addr = token.address();
+                let token_addr = utils::wrapped_token(token, contracts)?;
                 let weth_addr = utils::parse_address(&contracts.weth)?;
                 let eth_usd_price = self.fetch_eth_usd_price(contracts).await.ok();
                 if token_addr == weth_addr {
@@ -280,18 +2005,18 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
                     &contracts.weth,
                     contracts,
                 ));
-                let quoter_addr = utils::parse_address(&contracts.uniswap_v3_quoter)?;
-                let quoter = IUniswapV3Quoter::new(quoter_addr, &self.provider);
+                let quoter_addr = utils::parse_address(&contracts.uniswap_v3_quoter_v2)?;
+                let quoter = IUniswapV3QuoterV2::new(quoter_addr, &self.provider);
                 let token_decimals = self.get_token_decimals(token).await?;
                 let one_token = U256::from(10_u64.pow(token_decimals as u32));
                 match quoter
-                    .quoteExactInputSingle(
-                        token_addr,
-                        weth_addr,
-                        fee_tier.to::<u32>(),
-                        one_token,
-                        U256::ZERO,
-                    )
+                    .quoteExactInputSingle(IUniswapV3QuoterV2::QuoteExactInputSingleParams {
+                        tokenIn: token_addr,
+                        tokenOut: weth_addr,
+                        amountIn: one_token,
+                        fee: fee_tier.to::<u32>(),
+                        sqrtPriceLimitX96: U256::ZERO,
+                    })
                     .call()
                     .await
                 {
@@ -331,82 +2056,844 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
         params: &SwapParams,
         contracts: &ContractAddresses,
     ) -> anyhow::Result<SwapResult> {
-        let _permit = self.acquire_permit().await?;
-        let from_addr = params.from_token.address();
-        let to_addr = params.to_token.address();
-        let fee_tier = Uint::<24, 1>::from(utils::get_common_fee_tier(
-            &params.from_token.to_hex(),
-            &params.to_token.to_hex(),
-            contracts,
+        // Route quoting fans out, so each request below takes its own permit rather
+        // than the swap holding one throughout
+
+        // Native ETH is quoted and routed as WETH
+        let from_addr = utils::wrapped_token(&params.from_token, contracts)?;
+        let to_addr = utils::wrapped_token(&params.to_token, contracts)?;
+        if from_addr == to_addr {
+            return Err(anyhow::anyhow!(
+                "Swapping {} for {} is a 1:1 wrap; use wrap_eth or unwrap_weth instead",
+                params.from_token.to_hex(),
+                params.to_token.to_hex()
+            ));
+        }
+        let eth_in = params.from_token.is_native();
+        // Exact-input swaps quote the output of `amount_in`; exact-output swaps quote the
+        // input that pays out `amount_out`
+        let (amount, exact_output) = match &params.amount_out {
+            None => (
+                Self::decimal_to_u256(params.amount_in.to_raw_units()?)?,
+                false,
+            ),
+            Some(amount_out) => (amount_out.to_u256()?, true),
+        };
+        let routes = match &params.route {
+            Some(route) if route.protocol.is_v2() => vec![(
+                route.clone(),
+                self.quote_v2_route(route, amount, exact_output, contracts)
+                    .await?,
+            )],
+            Some(route) if exact_output => vec![(
+                route.clone(),
+                self.quote_exact_output_route(route, amount, contracts)
+                    .await?,
+            )],
+            Some(route) => vec![(
+                route.clone(),
+                self.quote_route(route, amount, contracts).await?,
+            )],
+            None => {
+                let protocols = match params.protocol {
+                    Some(protocol) => vec![protocol],
+                    None => SwapProtocol::ALL.to_vec(),
+                };
+                let quoted = join_all(protocols.into_iter().map(|protocol| async move {
+                    let quoted = match protocol {
+                        SwapProtocol::UniswapV3 if exact_output => {
+                            self.quote_exact_output_routes(from_addr, to_addr, amount, contracts)
+                                .await
+                        }
+                        SwapProtocol::UniswapV3 => {
+                            self.quote_routes(from_addr, to_addr, amount, contracts)
+                                .await
+                        }
+                        _ => {
+                            self.quote_v2_routes(
+                                protocol,
+                                from_addr,
+                                to_addr,
+                                amount,
+                                exact_output,
+                                contracts,
+                            )
+                            .await
+                        }
+                    };
+                    (protocol, quoted)
+                }))
+                .await;
+                let mut routes = Vec::new();
+                for (protocol, quoted) in quoted {
+                    match quoted {
+                        Ok(quoted) => routes.extend(quoted),
+                        // Comparing across protocols shouldn't fail because one is unavailable
+                        Err(e) if params.protocol.is_none() => {
+                            warn!(protocol = protocol.as_str(), "Skipping protocol: {}", e)
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                routes
+            }
+        };
+        // Candidates as (route, amount_in, amount_out, quote) with amounts in raw units
+        let candidates: Vec<(SwapRoute, U256, U256, PathQuote)> = routes
+            .into_iter()
+            .map(|(route, quote)| match exact_output {
+                false => (route, amount, quote.amount, quote),
+                true => (route, quote.amount, amount, quote),
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No {} route with liquidity for {} -> {}",
+                params.protocol.map_or("swap", |protocol| protocol.as_str()),
+                params.from_token.to_hex(),
+                params.to_token.to_hex()
+            ));
+        }
+        let from_decimals = match &params.amount_out {
+            None => params.amount_in.decimals,
+            Some(_) => self.get_token_decimals(&params.from_token).await?,
+        };
+        let to_decimals = self.get_token_decimals(&params.to_token).await?;
+
+        let deadline = U256::from(Utc::now().timestamp() + 1800);
+
+        // Executing needs the route's router approved for the most the swap may spend,
+        // unless it is paid in ether
+        let sender = params
+            .sender
+            .as_ref()
+            .unwrap_or(&self.wallet_address)
+            .address();
+        let from_token = IERC20::new(from_addr, &self.provider);
+        let balance = match eth_in {
+            true => self
+                .permitted(self.provider.get_balance(sender))
+                .await
+                .and_then(|balance| Ok(balance?)),
+            false => self
+                .permitted(from_token.balanceOf(sender).call())
+                .await
+                .and_then(|balance| Ok(balance?._0)),
+        };
+        let balance = match balance {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                warn!("Balance check failed, assuming a shortfall: {}", e);
+                None
+            }
+        };
+        let mut allowances = HashMap::new();
+        for protocol in candidates.iter().map(|(route, ..)| route.protocol) {
+            if allowances.contains_key(&protocol) {
+                continue;
+            }
+            let router = utils::swap_router(protocol, contracts)?;
+            let allowance = match eth_in {
+                true => None,
+                false => match self
+                    .permitted(from_token.allowance(sender, router).call())
+                    .await
+                    .and_then(|allowance| Ok(allowance?._0))
+                {
+                    Ok(allowance) => Some(allowance),
+                    Err(e) => {
+                        warn!(
+                            "Allowance check failed, assuming approval is required: {}",
+                            e
+                        );
+                        None
+                    }
+                },
+            };
+            allowances.insert(protocol, (router, allowance));
+        }
+        let router_of = |quoted: &SwapParams| {
+            let protocol = quoted
+                .route
+                .as_ref()
+                .map(|route| route.protocol)
+                .unwrap_or_default();
+            allowances
+                .get(&protocol)
+                .copied()
+                .unwrap_or((Address::ZERO, None))
+        };
+        let needs_approval = |quoted: &SwapParams| -> anyhow::Result<bool> {
+            let maximum = quoted.maximum_amount_in().to_u256()?;
+            Ok(!eth_in
+                && router_of(quoted)
+                    .1
+                    .is_none_or(|allowance| allowance < maximum))
+        };
+        // The router pulls the input from the caller, so dry runs have to come from the
+        // sender, and can only succeed once the router is approved
+        let swap_request = |quoted: &SwapParams, amount_out_minimum: U256| {
+            let router = router_of(quoted).0;
+            Self::swap_calls(quoted, router, sender, deadline, amount_out_minimum)
+                .and_then(|calls| Self::swap_transaction(quoted, router, calls))
+                .map(|request| request.with_from(sender))
+        };
+        let quoted_params = |quote: &RouteQuote| SwapParams {
+            amount_in: quote.amount_in.clone(),
+            route: Some(quote.route.clone()),
+            ..params.clone()
+        };
+
+        let fee_estimate = self.get_gas_fees().await;
+        let gas_price = match &fee_estimate {
+            Ok(fees) => Some(U256::from(fees.normal_gas_price())),
+            Err(e) => {
+                warn!(
+                    "EIP-1559 fee estimation failed, using legacy gas price: {}",
+                    e
+                );
+                self.get_gas_price().await.ok()
+            }
+        };
+        let gas_cost =
+            |gas_estimate: u64| gas_price.map(|price| Self::gas_cost_eth(gas_estimate, price));
+
+        let mut route_quotes = Vec::new();
+        for (route, amount_in_raw, amount_out_raw, path_quote) in candidates {
+            let amount_in = match &params.amount_out {
+                None => params.amount_in.clone(),
+                Some(_) => TokenAmount::from_raw_units(
+                    Self::u256_to_decimal(amount_in_raw)?,
+                    from_decimals,
+                ),
+            };
+            let amount_out =
+                TokenAmount::from_raw_units(Self::u256_to_decimal(amount_out_raw)?, to_decimals);
+            let mut quote = RouteQuote {
+                route,
+                amount_in,
+                amount_out,
+                gas_estimate: 0,
+                gas_cost_eth: None,
+                net_amount_out: None,
+                net_amount_in: None,
+                sqrt_prices_x96_after: path_quote.sqrt_prices_x96_after,
+                initialized_ticks_crossed: path_quote.initialized_ticks_crossed,
+            };
+            let quoted = quoted_params(&quote);
+            let quoted_gas = Self::quoted_route_gas(&quote.route, path_quote.gas_estimate);
+            quote.gas_estimate = if needs_approval(&quoted)? {
+                quoted_gas
+            } else {
+                let request = swap_request(&quoted, U256::ZERO)?;
+                match self.permitted(self.provider.estimate_gas(&request)).await? {
+                    Ok(gas) => gas as u64,
+                    Err(e) => {
+                        debug!("Router gas estimate failed, using the quoted one: {}", e);
+                        quoted_gas
+                    }
+                }
+            };
+            quote.gas_cost_eth = gas_cost(quote.gas_estimate);
+            route_quotes.push(quote);
+        }
+
+        // Exact-input quotes share their input, so gas is priced in the output token;
+        // exact-output quotes share their output, so it is priced in the input token.
+        // Trading against WETH prices it at the swap's own rate.
+        let weth = utils::parse_address(&contracts.weth)?;
+        match &params.amount_out {
+            None => {
+                let quoted_rate = match route_quotes.iter().map(|q| q.amount_out.raw).max() {
+                    Some(best) if from_addr == weth && !params.amount_in.raw.is_zero() => {
+                        Some(best / params.amount_in.raw)
+                    }
+                    _ => None,
+                };
+                if let Some(rate) = self
+                    .units_per_eth(&params.to_token, to_decimals, quoted_rate, contracts)
+                    .await
+                {
+                    for quote in &mut route_quotes {
+                        quote.net_amount_out = quote
+                            .gas_cost_eth
+                            .map(|cost| Self::net_amount_out(&quote.amount_out, cost, rate));
+                    }
+                }
+            }
+            Some(amount_out) => {
+                let quoted_rate = match route_quotes.iter().map(|q| q.amount_in.raw).min() {
+                    Some(best) if to_addr == weth && !amount_out.raw.is_zero() => {
+                        Some(best / amount_out.raw)
+                    }
+                    _ => None,
+                };
+                if let Some(rate) = self
+                    .units_per_eth(&params.from_token, from_decimals, quoted_rate, contracts)
+                    .await
+                {
+                    for quote in &mut route_quotes {
+                        quote.net_amount_in = quote
+                            .gas_cost_eth
+                            .map(|cost| Self::net_amount_in(&quote.amount_in, cost, rate));
+                    }
+                }
+            }
+        }
+        Self::rank_route_quotes(&mut route_quotes);
+        let best = route_quotes[0].clone();
+        debug!(
+            hops = best.route.hops.len(),
+            fee_tiers = ?best.route.fee_tiers(),
+            candidates = route_quotes.len(),
+            "Selected swap route"
+        );
+
+        let mid_rate = self.route_mid_rate(&best.route).await?;
+        let price_impact = Self::price_impact(
+            mid_rate,
+            best.amount_in.to_u256()?,
+            best.amount_out.to_u256()?,
+        );
+        debug!(%price_impact, "Computed price impact from pool state");
+
+        let quoted = quoted_params(&best);
+        let (router_addr, allowance) = router_of(&quoted);
+        let requires_approval = needs_approval(&quoted)?;
+        let estimated_amount_out = best.amount_out.clone();
+        let min_amount_out_u256 = match &params.amount_out {
+            None => Self::decimal_to_u256(
+                params
+                    .minimum_amount_out(&estimated_amount_out)
+                    .to_raw_units()?,
+            )?,
+            Some(amount_out) => amount_out.to_u256()?,
+        };
+        let request = swap_request(&quoted, min_amount_out_u256)?;
+        let gas_estimate = best.gas_estimate;
+        let mut gas_cost_eth = best.gas_cost_eth;
+
+        // On rollups the sender also pays for posting the transaction data to L1
+        let l2_network = match self.permitted(self.chain_id()).await? {
+            Ok(chain_id) => L2Network::from_chain_id(chain_id),
+            Err(e) => {
+                warn!("Could not determine chain ID for L1 data fee: {}", e);
+                None
+            }
+        };
+        let gas_cost_breakdown = match (l2_network, &fee_estimate) {
+            (Some(network), Ok(fees)) => match self
+                .permitted(self.l2_gas_cost_breakdown(
+                    network,
+                    router_addr,
+                    request.input.input().cloned().unwrap_or_default(),
+                    gas_estimate,
+                    fees,
+                ))
+                .await?
+            {
+                Ok(breakdown) => Some(breakdown),
+                Err(e) => {
+                    warn!("L1 data fee estimation failed: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        if let Some(breakdown) = &gas_cost_breakdown {
+            gas_cost_eth = Some(breakdown.total_eth());
+        }
+
+        // Optionally stand in for the funds and approval the sender lacks, so the dry
+        // run shows whether the swap itself would go through
+        let maximum_in = quoted.maximum_amount_in().to_u256()?;
+        let balance_short = balance.is_none_or(|balance| balance < maximum_in);
+        let mut overrides = StateOverride::default();
+        let (mut balance_overridden, mut allowance_overridden) = (false, false);
+        if params.override_token_state && eth_in && balance_short {
+            overrides.insert(
+                sender,
+                AccountOverride {
+                    balance: Some(maximum_in),
+                    ..Default::default()
+                },
+            );
+            balance_overridden = true;
+        } else if params.override_token_state && (balance_short || requires_approval) {
+            match self
+                .permitted(self.token_slots(from_addr, sender, router_addr))
+                .await?
+            {
+                Ok(slots) => {
+                    let mut state_diff = HashMap::new();
+                    if balance_short {
+                        state_diff.insert(slots.balance.key(sender), B256::from(maximum_in));
+                        balance_overridden = true;
+                    }
+                    if requires_approval {
+                        state_diff.insert(
+                            slots.allowance.nested(sender, router_addr),
+                            B256::from(maximum_in),
+                        );
+                        allowance_overridden = true;
+                    }
+                    overrides.insert(
+                        from_addr,
+                        AccountOverride {
+                            state_diff: Some(state_diff),
+                            ..Default::default()
+                        },
+                    );
+                }
+                Err(e) => warn!("Simulating without token state overrides: {}", e),
+            }
+        }
+        let call = self.provider.call(&request);
+        let outcome = if overrides.is_empty() {
+            self.permitted(call).await?
+        } else {
+            self.permitted(call.overrides(&overrides)).await?
+        };
+        let (success, revert_reason) = match outcome {
+            Ok(_) => (true, None),
+            Err(e) => {
+                let error = Self::revert_error("Swap simulation reverted", e);
+                match RevertError::find(&error) {
+                    Some(reason) => (false, Some(reason.clone())),
+                    None => return Err(error),
+                }
+            }
+        };
+        // Unlimited approvals don't fit a Decimal and are reported as unknown
+        let to_amount = |raw: U256| {
+            Self::u256_to_decimal(raw)
+                .ok()
+                .map(|raw| TokenAmount::from_raw_units(raw, from_decimals))
+        };
+        let simulation = SwapSimulation {
+            sender,
+            balance: balance.and_then(to_amount),
+            allowance: allowance.and_then(to_amount),
+            balance_overridden,
+            allowance_overridden,
+            success,
+            revert_reason,
+        };
+
+        Ok(SwapResult {
+            params: quoted,
+            estimated_amount_out,
+            price_impact,
+            high_price_impact: false,
+            gas_estimate,
+            gas_cost_eth,
+            gas_cost_breakdown,
+            requires_approval,
+            route: best.route,
+            route_quotes,
+            simulation,
+        })
+    }
+
+    #[instrument(skip(self, contracts), fields(provider = "ws"))]
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount_in: &TokenAmount,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<QuoteComparison> {
+        let from = utils::wrapped_token(from_token, contracts)?;
+        let to = utils::wrapped_token(to_token, contracts)?;
+        if from == to {
+            return Err(anyhow::anyhow!(
+                "ETH and WETH convert 1:1; there is nothing to compare"
+            ));
+        }
+        let amount = amount_in.to_u256()?;
+        let curve_pools = contracts
+            .curve_pools
+            .iter()
+            .map(|pool| utils::parse_address(pool))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let balancer_vault = utils::parse_address(&contracts.balancer_vault)?;
+        let balancer_pool_ids = contracts
+            .balancer_pool_ids
+            .iter()
+            .map(|id| {
+                B256::from_str(id)
+                    .map_err(|e| anyhow::anyhow!("Invalid Balancer pool ID {}: {}", id, e))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Uniswap quotes fan out and take a permit per request; the other venues make a
+        // few calls in turn under a single permit
+        let mut quoters: Vec<(Box<dyn DexQuoter + '_>, bool)> = Vec::new();
+        for protocol in SwapProtocol::ALL {
+            let quoter = UniswapQuoter {
+                provider: self,
+                protocol,
+                contracts,
+            };
+            quoters.push((Box::new(quoter), false));
+        }
+        quoters.push((
+            Box::new(CurveQuoter::new(&self.provider, curve_pools)),
+            true,
+        ));
+        quoters.push((
+            Box::new(BalancerQuoter::new(
+                &self.provider,
+                balancer_vault,
+                balancer_pool_ids,
+            )),
+            true,
         ));
-        let _from_decimals = self.get_token_decimals(&params.from_token).await?;
-        let amount_in_u256 = AlloyEthereumProvider::<Http<Client>>::decimal_to_u256(
-            params.amount_in.to_raw_units()?,
+
+        let to_decimals = self.get_token_decimals(to_token).await?;
+        let gas_price = match self.get_gas_fees().await {
+            Ok(fees) => Some(U256::from(fees.normal_gas_price())),
+            Err(e) => {
+                warn!(
+                    "EIP-1559 fee estimation failed, using legacy gas price: {}",
+                    e
+                );
+                self.get_gas_price().await.ok()
+            }
+        };
+
+        let mut quotes = Vec::new();
+        let mut unavailable = Vec::new();
+        for (quoter, one_permit) in &quoters {
+            let venue = quoter.venue().to_string();
+            let quoted = match one_permit {
+                true => self.permitted(quoter.quote(from, to, amount)).await?,
+                false => quoter.quote(from, to, amount).await,
+            };
+            match quoted {
+                Ok(Some(quote)) => quotes.push(VenueQuote {
+                    venue,
+                    pools: quote.pools,
+                    amount_out: TokenAmount::from_raw_units(
+                        Self::u256_to_decimal(quote.amount_out)?,
+                        to_decimals,
+                    ),
+                    gas_estimate: quote.gas_estimate,
+                    gas_cost_eth: gas_price
+                        .map(|price| Self::gas_cost_eth(quote.gas_estimate, price)),
+                    net_amount_out: None,
+                }),
+                Ok(None) => unavailable.push(VenueUnavailable {
+                    venue,
+                    reason: "No pool for this pair".to_string(),
+                }),
+                Err(e) => {
+                    debug!(venue = %venue, "Venue quote failed: {}", e);
+                    unavailable.push(VenueUnavailable {
+                        venue,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        // Gas is priced in the output token as in `simulate_swap`
+        let weth = utils::parse_address(&contracts.weth)?;
+        let quoted_rate = match quotes.iter().map(|q| q.amount_out.raw).max() {
+            Some(best) if from == weth && !amount_in.raw.is_zero() => Some(best / amount_in.raw),
+            _ => None,
+        };
+        if let Some(rate) = self
+            .units_per_eth(to_token, to_decimals, quoted_rate, contracts)
+            .await
+        {
+            for quote in &mut quotes {
+                quote.net_amount_out = quote
+                    .gas_cost_eth
+                    .map(|cost| Self::net_amount_out(&quote.amount_out, cost, rate));
+            }
+        }
+        Self::rank_venue_quotes(&mut quotes);
+        debug!(
+            quoted = quotes.len(),
+            unavailable = unavailable.len(),
+            "Compared venue quotes"
+        );
+
+        Ok(QuoteComparison {
+            from_token: from_token.clone(),
+            to_token: to_token.clone(),
+            amount_in: amount_in.clone(),
+            quotes,
+            unavailable,
+        })
+    }
+
+    #[instrument(skip(self, contracts), fields(provider = "http"))]
+    async fn execute_swap<'a>(
+        &self,
+        params: &SwapParams,
+        amount_out_minimum: &TokenAmount,
+        permit: Option<&'a PermitSignature>,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<B256> {
+        let from = self.wallet_address.address();
+        let route = match &params.route {
+            Some(route) => route.clone(),
+            None => {
+                let token_in =
+                    TokenAddress::new(utils::wrapped_token(&params.from_token, contracts)?);
+                let token_out =
+                    TokenAddress::new(utils::wrapped_token(&params.to_token, contracts)?);
+                SwapRoute {
+                    protocol: SwapProtocol::UniswapV3,
+                    hops: vec![RouteHop {
+                        fee_tier: utils::get_common_fee_tier(
+                            &token_in.to_hex(),
+                            &token_out.to_hex(),
+                            contracts,
+                        ),
+                        token_in,
+                        token_out,
+                        pool: Address::ZERO,
+                        liquidity: 0,
+                    }],
+                }
+            }
+        };
+        let router_addr = utils::swap_router(route.protocol, contracts)?;
+        if permit.is_some() && route.protocol.is_v2() {
+            return Err(anyhow::anyhow!(
+                "Permits are only supported on Uniswap V3 routes"
+            ));
+        }
+        if permit.is_some() && params.from_token.is_native() {
+            return Err(anyhow::anyhow!("Swaps paid in ETH need no permit"));
+        }
+        let quoted = SwapParams {
+            route: Some(route),
+            ..params.clone()
+        };
+        let mut calls = Self::swap_calls(
+            &quoted,
+            router_addr,
+            from,
+            U256::from(Utc::now().timestamp() + 300),
+            Self::decimal_to_u256(amount_out_minimum.to_raw_units()?)?,
         )?;
+        match permit {
+            None => {}
+            // SwapRouter inherits SelfPermit, so the permit and swap share one transaction
+            Some(permit) if permit.kind == PermitKind::Eip2612 => {
+                let self_permit = IUniswapV3Router::selfPermitCall {
+                    token: permit.token_address.address(),
+                    value: permit.value,
+                    deadline: permit.deadline,
+                    v: permit.v,
+                    r: permit.r,
+                    s: permit.s,
+                };
+                calls.insert(0, self_permit.abi_encode().into());
+            }
+            Some(_) => {
+                return Err(anyhow::anyhow!(
+                    "The V3 SwapRouter only accepts EIP-2612 permits; Permit2 signatures need a Permit2-aware spender"
+                ))
+            }
+        }
+        let tx = Self::swap_transaction(&quoted, router_addr, calls)?;
+        self.sign_and_send(tx, "execute_swap").await
+    }
+
+    #[instrument(skip(self), fields(provider = "http", token = %token.to_hex(), spender = %spender))]
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        value: U256,
+        deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        let chain_id = self.chain_id().await?;
+        let owner = self.wallet_address.address();
+        let _permit = self.acquire_permit().await?;
+        let permit_token = IERC20Permit::new(token.address(), &self.provider);
 
-        let quoter_addr = utils::parse_address(&contracts.uniswap_v3_quoter)?;
-        let quoter = IUniswapV3Quoter::new(quoter_addr, &self.provider);
+        // EIP-2612 support is detected from the presence of DOMAIN_SEPARATOR and nonces
+        let not_supported = |e: alloy::contract::Error| {
+            anyhow::anyhow!(
+                "Token {} does not support EIP-2612 permit: {}",
+                token.to_hex(),
+                e
+            )
+        };
+        let domain_separator = permit_token
+            .DOMAIN_SEPARATOR()
+            .call()
+            .await
+            .map_err(not_supported)?
+            ._0;
+        let nonce = permit_token
+            .nonces(owner)
+            .call()
+            .await
+            .map_err(not_supported)?
+            ._0;
+        // Tokens like DAI expose a permit with a different struct; signing it would be rejected
+        if let Ok(typehash) = permit_token.PERMIT_TYPEHASH().call().await {
+            if typehash._0 != keccak256(Permit::eip712_encode_type().as_bytes()) {
+                return Err(anyhow::anyhow!(
+                    "Token {} uses a non-standard permit type",
+                    token.to_hex()
+                ));
+            }
+        }
 
-        let quote = quoter
-            .quoteExactInputSingle(
-                from_addr,
-                to_addr,
-                fee_tier.to::<u32>(),
-                amount_in_u256,
-                U256::ZERO,
+        let name = IERC20::new(token.address(), &self.provider)
+            .name()
+            .call()
+            .await?
+            ._0;
+        let mut versions = vec!["1".to_string(), "2".to_string()];
+        if let Ok(version) = permit_token.version().call().await {
+            versions.insert(0, version._0);
+        }
+        let domain = Self::eip2612_domain(
+            &name,
+            &versions,
+            chain_id,
+            token.address(),
+            domain_separator,
+        )
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Could not reconstruct the EIP-712 domain of token {}",
+                token.to_hex()
             )
+        })?;
+
+        let message = Permit {
+            owner,
+            spender: *spender,
+            value,
+            nonce,
+            deadline,
+        };
+        let signature = self
+            .signer
+            .sign_hash_sync(&message.eip712_signing_hash(&domain))
+            .map_err(|e| anyhow::anyhow!("Failed to sign permit: {}", e))?;
+        let (signature, v, r, s) = Self::permit_signature_parts(&signature);
+
+        Ok(PermitSignature {
+            kind: PermitKind::Eip2612,
+            token_address: token.clone(),
+            owner: self.wallet_address.clone(),
+            spender: *spender,
+            value,
+            nonce,
+            deadline,
+            expiration: None,
+            signature,
+            v,
+            r,
+            s,
+        })
+    }
+
+    #[instrument(skip(self), fields(provider = "http", token = %token.to_hex(), spender = %spender))]
+    async fn sign_permit2(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+        expiration: u64,
+        sig_deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        let chain_id = self.chain_id().await?;
+        let owner = self.wallet_address.address();
+        let permit2_addr = utils::parse_address(PERMIT2)?;
+        let _permit = self.acquire_permit().await?;
+
+        // Permit2 amounts are uint160; an unlimited request maps to its maximum
+        let max_uint160 = (U256::from(1) << 160) - U256::from(1);
+        let amount = if amount == U256::MAX {
+            max_uint160
+        } else if amount > max_uint160 {
+            return Err(anyhow::anyhow!(
+                "Permit2 amount exceeds uint160: {}",
+                amount
+            ));
+        } else {
+            amount
+        };
+        let current = IPermit2::new(permit2_addr, &self.provider)
+            .allowance(owner, token.address(), *spender)
             .call()
-            .await?;
-        let estimated_amount_out_raw = quote.amountOut;
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read Permit2 nonce: {}", e))?;
 
-        let to_decimals = self.get_token_decimals(&params.to_token).await?;
-        let estimated_out_decimal =
-            AlloyEthereumProvider::<Http<Client>>::u256_to_decimal(estimated_amount_out_raw)?;
-        let estimated_amount_out = TokenAmount::from_raw_units(estimated_out_decimal, to_decimals);
-
-        let slippage_multiplier =
-            Decimal::from(1) - (params.slippage_tolerance / Decimal::from(100));
-        let min_amount_out_u256 = AlloyEthereumProvider::<Http<Client>>::decimal_to_u256(
-            estimated_out_decimal * slippage_multiplier,
-        )?;
+        let message = IPermit2::PermitSingle {
+            details: IPermit2::PermitDetails {
+                token: token.address(),
+                amount,
+                expiration,
+                nonce: current.nonce,
+            },
+            spender: *spender,
+            sigDeadline: sig_deadline,
+        };
+        let domain = Self::permit2_domain(chain_id, permit2_addr);
+        let signature = self
+            .signer
+            .sign_hash_sync(&message.eip712_signing_hash(&domain))
+            .map_err(|e| anyhow::anyhow!("Failed to sign Permit2 permit: {}", e))?;
+        let (signature, v, r, s) = Self::permit_signature_parts(&signature);
 
-        let router_addr = utils::parse_address(&contracts.uniswap_v3_router)?;
-        let router = IUniswapV3Router::new(router_addr, &self.provider);
-        let dummy_recipient = utils::parse_address("0x0000000000000000000000000000000000000001")?;
-        let deadline = U256::from(Utc::now().timestamp() + 1800);
+        Ok(PermitSignature {
+            kind: PermitKind::Permit2,
+            token_address: token.clone(),
+            owner: self.wallet_address.clone(),
+            spender: *spender,
+            value: amount,
+            nonce: U256::from(current.nonce),
+            deadline: sig_deadline,
+            expiration: Some(expiration),
+            signature,
+            v,
+            r,
+            s,
+        })
+    }
 
-        let swap_params = IUniswapV3Router::ExactInputSingleParams {
-            tokenIn: from_addr,
-            tokenOut: to_addr,
-            fee: fee_tier.to::<u32>(),
-            recipient: dummy_recipient,
-            deadline,
-            amountIn: amount_in_u256,
-            amountOutMinimum: min_amount_out_u256,
-            sqrtPriceLimitX96: U256::ZERO,
-        };
-
-        let call = router.exactInputSingle(swap_params.clone());
-        let gas_estimate_u128 = call.estimate_gas().await.unwrap_or(200000u128);
-        let gas_estimate = gas_estimate_u128 as u64;
-        let gas_price = self.get_gas_price().await.ok();
-        let gas_cost_eth = gas_price.map(|price| {
-            let gas_estimate_dec = Decimal::from(gas_estimate);
-            let gas_price_dec =
-                AlloyEthereumProvider::<Http<Client>>::u256_to_decimal(price).unwrap_or_default();
-            (gas_estimate_dec * gas_price_dec) / Decimal::from(10_u64.pow(18))
-        });
+    #[instrument(skip(self), fields(provider = "http", token = %token.to_hex(), spender = %spender))]
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+    ) -> anyhow::Result<B256> {
+        let contract = IERC20::new(token.address(), &self.provider);
+        let tx = contract
+            .approve(*spender, amount)
+            .into_transaction_request();
+        self.sign_and_send(tx, "approve_token").await
+    }
 
-        router.exactInputSingle(swap_params).call().await?;
+    #[instrument(skip(self), fields(provider = "http", weth = %weth.to_hex()))]
+    async fn wrap_eth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        let tx = IWETH9::new(weth.address(), &self.provider)
+            .deposit()
+            .value(amount)
+            .into_transaction_request();
+        self.sign_and_send(tx, "wrap_eth").await
+    }
 
-        Ok(SwapResult {
-            params: params.clone(),
-            estimated_amount_out,
-            price_impact: Decimal::ZERO,
-            gas_estimate,
-            gas_cost_eth,
-            route: format!("uniswap_v3_fee_{}", fee_tier.to::<u32>()),
-        })
+    #[instrument(skip(self), fields(provider = "http", weth = %weth.to_hex()))]
+    async fn unwrap_weth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        let tx = IWETH9::new(weth.address(), &self.provider)
+            .withdraw(amount)
+            .into_transaction_request();
+        self.sign_and_send(tx, "unwrap_weth").await
     }
 
     #[instrument(skip(self), fields(provider = "ws"))]
@@ -422,44 +2909,300 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
         .await
     }
 
+    #[instrument(skip(self))]
+    async fn get_block_number(&self) -> anyhow::Result<u64> {
+        let _permit = self.acquire_permit().await?;
+        self.execute_with_circuit(
+            || async { Ok(self.provider.get_block_number().await?) },
+            "get_block_number",
+        )
+        .await
+    }
+
+    #[instrument(skip(self), fields(provider = "http"))]
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate> {
+        let _permit = self.acquire_permit().await?;
+        self.execute_with_circuit(
+            || async {
+                Self::retry_with_backoff(
+                    || async {
+                        let history = self
+                            .provider
+                            .get_fee_history(
+                                FEE_HISTORY_BLOCKS,
+                                BlockNumberOrTag::Latest,
+                                &FEE_HISTORY_PERCENTILES,
+                            )
+                            .await?;
+                        Self::fee_estimate_from_history(&history)
+                    },
+                    3,
+                    "get_gas_fees",
+                )
+                .await
+            },
+            "get_gas_fees",
+        )
+        .await
+    }
+
     #[instrument(skip(self), fields(provider = "ws"))]
     async fn get_transaction_status(
         &self,
         tx_hash: &B256,
     ) -> anyhow::Result<TransactionStatusInfo> {
+        let _permit = self.acquire_permit().await?;
+        let (info, settled) = self
+            .execute_with_circuit(
+                || async {
+                    if let Some(receipt) = self.provider.get_transaction_receipt(*tx_hash).await? {
+                        let (latest_block, finalized_block) = tokio::join!(
+                            self.provider.get_block_number(),
+                            self.finalized_block_number()
+                        );
+                        let latest_block = latest_block?;
+                        let confirmations = receipt
+                            .block_number
+                            .map_or(0, |b| latest_block.saturating_sub(b) + 1);
+                        let finalized = matches!(
+                            (receipt.block_number, finalized_block),
+                            (Some(block), Some(finalized)) if block <= finalized
+                        );
+                        let (status, revert_reason) = if receipt.status() {
+                            (TransactionStatus::Confirmed, None)
+                        } else {
+                            let reason = self
+                                .replay_revert_reason(
+                                    tx_hash,
+                                    receipt.block_number,
+                                    receipt.gas_used,
+                                )
+                                .await;
+                            (TransactionStatus::Failed, reason)
+                        };
+                        return Ok((
+                            TransactionStatusInfo {
+                                transaction_hash: format!("{:?}", tx_hash),
+                                status,
+                                confirmations,
+                                block_number: receipt.block_number,
+                                finalized,
+                                replaced_by: None,
+                                detail: None,
+                                revert_reason,
+                            },
+                            Some(StoredTxStatus::Mined),
+                        ));
+                    }
+
+                    let replaced_by = self
+                        .nonce_manager
+                        .replaced_by(&self.wallet_address, tx_hash)
+                        .await;
+                    // Sender and nonce come from the node, or from our own records if it
+                    // has forgotten the transaction
+                    let (sender_nonce, known_to_node) =
+                        match self.provider.get_transaction_by_hash(*tx_hash).await? {
+                            Some(tx) => (Some((tx.from, tx.nonce)), true),
+                            None => (
+                                self.nonce_manager
+                                    .nonce_of(&self.wallet_address, tx_hash)
+                                    .await
+                                    .map(|nonce| (self.wallet_address.address(), nonce)),
+                                false,
+                            ),
+                        };
+
+                    let (status, settled) = match sender_nonce {
+                        None => (TransactionStatus::NotFound, None),
+                        Some((sender, nonce)) => {
+                            let mined_count = self.transaction_count_of(sender, false).await?;
+                            let status = Self::unmined_status(
+                                nonce,
+                                mined_count,
+                                known_to_node,
+                                replaced_by.is_some(),
+                            );
+                            // Once its nonce is mined without it, it can never be mined
+                            (
+                                status,
+                                (mined_count > nonce).then_some(StoredTxStatus::Replaced),
+                            )
+                        }
+                    };
+                    Ok((
+                        TransactionStatusInfo {
+                            transaction_hash: format!("{:?}", tx_hash),
+                            status,
+                            confirmations: 0,
+                            block_number: None,
+                            finalized: false,
+                            replaced_by: replaced_by.map(|hash| format!("{:?}", hash)),
+                            detail: None,
+                            revert_reason: None,
+                        },
+                        settled,
+                    ))
+                },
+                "get_transaction_status",
+            )
+            .await?;
+
+        // Transactions this server sent stop being tracked once their nonce is mined
+        if let (Some(status), Some(store)) = (settled, &self.tx_store) {
+            if let Err(e) = store.update_status(tx_hash, status).await {
+                warn!(tx_hash = %tx_hash, "Failed to update transaction store: {}", e);
+            }
+        }
+        Ok(info)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_transaction_detail(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<Option<TransactionDetail>> {
         let _permit = self.acquire_permit().await?;
         self.execute_with_circuit(
             || async {
-                if let Some(receipt) = self.provider.get_transaction_receipt(*tx_hash).await? {
-                    let latest_block = self.provider.get_block_number().await?;
-                    let confirmations = receipt
-                        .block_number
-                        .map_or(0, |b| latest_block.saturating_sub(b) + 1);
-                    let status = if receipt.status() {
-                        TransactionStatus::Confirmed
-                    } else {
-                        TransactionStatus::Failed
-                    };
-                    Ok(TransactionStatusInfo {
-                        transaction_hash: format!("{:?}", tx_hash),
-                        status,
-                        confirmations,
-                        block_number: receipt.block_number,
-                    })
-                } else {
-                    Ok(TransactionStatusInfo {
-                        transaction_hash: format!("{:?}", tx_hash),
-                        status: TransactionStatus::Pending,
-                        confirmations: 0,
-                        block_number: None,
-                    })
-                }
+                let (tx, receipt) = tokio::join!(
+                    self.provider.get_transaction_by_hash(*tx_hash),
+                    self.provider.get_transaction_receipt(*tx_hash)
+                );
+                let Some(tx) = tx? else {
+                    return Ok(None);
+                };
+                let receipt = receipt?;
+
+                let (logs, undecoded_log_count) = match &receipt {
+                    Some(receipt) => {
+                        let logs = receipt.inner.logs();
+                        let decoded: Vec<_> = logs
+                            .iter()
+                            .filter_map(|log| decode::decode_log(&log.inner))
+                            .collect();
+                        let undecoded = logs.len() - decoded.len();
+                        (decoded, undecoded)
+                    }
+                    None => (Vec::new(), 0),
+                };
+                let fee_wei = receipt
+                    .as_ref()
+                    .map(|r| r.gas_used.saturating_mul(r.effective_gas_price));
+
+                Ok(Some(TransactionDetail {
+                    from: tx.from,
+                    to: tx.to,
+                    nonce: tx.nonce,
+                    value_eth: Self::u256_to_decimal(tx.value)? / Decimal::from(10_u64.pow(18)),
+                    gas_used: receipt.as_ref().map(|r| r.gas_used),
+                    effective_gas_price: receipt.as_ref().map(|r| r.effective_gas_price),
+                    fee_eth: fee_wei.map(Self::wei_to_eth),
+                    method: decode::decode_call(&tx.input),
+                    logs,
+                    undecoded_log_count,
+                }))
             },
-            "get_transaction_status",
+            "get_transaction_detail",
         )
         .await
     }
 
+    #[instrument(skip(self))]
+    async fn replace_transaction(
+        &self,
+        tx_hash: &B256,
+        kind: ReplacementKind,
+    ) -> anyhow::Result<ReplacementResult> {
+        let nonce = self
+            .nonce_manager
+            .nonce_of(&self.wallet_address, tx_hash)
+            .await
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Transaction {} is not a pending transaction sent by this server",
+                    tx_hash
+                )
+            })?;
+        if self.transaction_count(false).await? > nonce {
+            return Err(anyhow::anyhow!(
+                "Nonce {} has already been mined; nothing to replace",
+                nonce
+            ));
+        }
+
+        // Fees must beat the latest transaction at this nonce, which may be a replacement
+        let current = self
+            .nonce_manager
+            .pending_transactions(&self.wallet_address)
+            .await
+            .into_iter()
+            .find_map(|(pending_nonce, hash)| (pending_nonce == nonce).then_some(hash))
+            .unwrap_or(*tx_hash);
+        let original = self.replaceable_transaction(&current).await?;
+
+        let fees = self.get_gas_fees().await?;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = Self::replacement_fees(
+            original.max_fee_per_gas,
+            original.max_priority_fee_per_gas,
+            &fees.fast,
+        );
+        let from = self.wallet_address.address();
+        let (tx, operation) = match kind {
+            ReplacementKind::SpeedUp => (
+                TransactionRequest::default()
+                    .with_kind(original.to)
+                    .with_value(original.value)
+                    .with_input(original.input)
+                    .with_gas_limit(original.gas_limit),
+                "speed_up",
+            ),
+            ReplacementKind::Cancel => (
+                TransactionRequest::default()
+                    .with_to(from)
+                    .with_value(U256::ZERO)
+                    .with_gas_limit(21_000),
+                "cancel",
+            ),
+        };
+        let tx = tx
+            .with_from(from)
+            .with_chain_id(self.chain_id().await?)
+            .with_max_fee_per_gas(max_fee_per_gas)
+            .with_max_priority_fee_per_gas(max_priority_fee_per_gas);
+
+        let _permit = self.acquire_permit().await?;
+        let replacement_hash = self.broadcast_with_nonce(tx, nonce, operation).await?;
+        self.nonce_manager
+            .record_replacement(&self.wallet_address, nonce, current, replacement_hash)
+            .await;
+        // Otherwise a restart would try to rebroadcast the superseded transaction
+        if let Some(store) = &self.tx_store {
+            if let Err(e) = store
+                .update_status(&current, StoredTxStatus::Replaced)
+                .await
+            {
+                warn!(tx_hash = %current, "Failed to mark replaced transaction: {}", e);
+            }
+        }
+        info!(
+            nonce,
+            original = %current,
+            replacement = %replacement_hash,
+            "{} transaction broadcast",
+            operation
+        );
+
+        Ok(ReplacementResult {
+            kind,
+            original_hash: format!("{:?}", current),
+            replacement_hash: format!("{:?}", replacement_hash),
+            nonce,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+
     #[instrument(skip(self), fields(provider = "ws"))]
     async fn health_check(&self) -> anyhow::Result<()> {
         self.execute_with_circuit(
@@ -477,6 +3220,44 @@ impl EthereumProvider for AlloyEthereumProvider<Http<Client>> {
     }
 }
 
+#[async_trait]
+impl<T: Transport + Clone> DexQuoter for UniswapQuoter<'_, T> {
+    fn venue(&self) -> &'static str {
+        self.protocol.as_str()
+    }
+
+    async fn quote(
+        &self,
+        from: Address,
+        to: Address,
+        amount_in: U256,
+    ) -> anyhow::Result<Option<DexQuote>> {
+        let routes = match self.protocol {
+            SwapProtocol::UniswapV3 => {
+                self.provider
+                    .quote_routes(from, to, amount_in, self.contracts)
+                    .await?
+            }
+            protocol => {
+                self.provider
+                    .quote_v2_routes(protocol, from, to, amount_in, false, self.contracts)
+                    .await?
+            }
+        };
+        Ok(routes
+            .into_iter()
+            .max_by_key(|(_, quote)| quote.amount)
+            .map(|(route, quote)| DexQuote {
+                pools: route.hops.iter().map(|hop| hop.pool).collect(),
+                amount_out: quote.amount,
+                gas_estimate: AlloyEthereumProvider::<T>::quoted_route_gas(
+                    &route,
+                    quote.gas_estimate,
+                ),
+            }))
+    }
+}
+
 #[async_trait]
 
 impl<T> Drop for AlloyEthereumProvider<T> {
@@ -865,6 +3646,717 @@ mod tests {
         }
     }
 
+    fn direct_route(fee_tier: u32) -> SwapRoute {
+        SwapRoute {
+            protocol: SwapProtocol::UniswapV3,
+            hops: vec![RouteHop {
+                token_in: TokenAddress::new(Address::repeat_byte(1)),
+                token_out: TokenAddress::new(Address::repeat_byte(2)),
+                fee_tier,
+                pool: Address::ZERO,
+                liquidity: 1,
+            }],
+        }
+    }
+
+    fn route_quote(fee_tier: u32, amount_out: &str, net: Option<&str>) -> RouteQuote {
+        RouteQuote {
+            route: direct_route(fee_tier),
+            amount_in: TokenAmount::from_human_readable("1", 18).unwrap(),
+            amount_out: TokenAmount::from_human_readable(amount_out, 6).unwrap(),
+            gas_estimate: 100_000,
+            gas_cost_eth: None,
+            net_amount_out: net.map(|n| TokenAmount::from_human_readable(n, 6).unwrap()),
+            net_amount_in: None,
+            sqrt_prices_x96_after: Vec::new(),
+            initialized_ticks_crossed: 0,
+        }
+    }
+
+    #[test]
+    fn test_hop_mid_rate_follows_token_order_and_fee() {
+        type Provider = AlloyEthereumProvider<Http<Client>>;
+        let [forward] = direct_route(3000).hops.try_into().unwrap();
+        let backward = RouteHop {
+            token_in: forward.token_out.clone(),
+            token_out: forward.token_in.clone(),
+            ..forward.clone()
+        };
+        // sqrtPriceX96 of 2 << 96 prices token0 at 4 token1
+        let sqrt_price = U256::from(2u64) << 96;
+        assert!((Provider::hop_mid_rate(&forward, sqrt_price) - 4.0 * 0.997).abs() < 1e-12);
+        assert!((Provider::hop_mid_rate(&backward, sqrt_price) - 0.25 * 0.997).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_v2_hop_mid_rate_uses_reserve_ratio() {
+        type Provider = AlloyEthereumProvider<Http<Client>>;
+        let [mut forward] = direct_route(fees::V2_PAIR).hops.try_into().unwrap();
+        forward.pool = Address::repeat_byte(7);
+        let backward = RouteHop {
+            token_in: forward.token_out.clone(),
+            token_out: forward.token_in.clone(),
+            ..forward.clone()
+        };
+        let (reserve0, reserve1) = (U256::from(1_000u64), U256::from(4_000u64));
+        let forward_rate = Provider::v2_hop_mid_rate(&forward, reserve0, reserve1);
+        assert!((forward_rate - 4.0 * 0.997).abs() < 1e-12);
+        let backward_rate = Provider::v2_hop_mid_rate(&backward, reserve0, reserve1);
+        assert!((backward_rate - 0.25 * 0.997).abs() < 1e-12);
+        assert_eq!(
+            Provider::v2_hop_mid_rate(&forward, U256::ZERO, reserve1),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_price_impact_against_mid_rate() {
+        type Provider = AlloyEthereumProvider<Http<Client>>;
+        let impact = |amount_out: u64| {
+            Provider::price_impact(2.0, U256::from(100u64), U256::from(amount_out))
+        };
+        assert_eq!(impact(190), Decimal::from(5));
+        assert_eq!(impact(199), Decimal::from_str("0.5").unwrap());
+        assert_eq!(impact(200), Decimal::ZERO);
+        // Rounding in the swap's favour is not negative impact
+        assert_eq!(impact(201), Decimal::ZERO);
+        assert_eq!(
+            Provider::price_impact(0.0, U256::from(100u64), U256::from(1u64)),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_net_amount_out_prices_gas_in_output_token() {
+        let amount_out = TokenAmount::from_human_readable("1500", 6).unwrap();
+        let net = AlloyEthereumProvider::<Http<Client>>::net_amount_out(
+            &amount_out,
+            Decimal::from_str("0.0010000005").unwrap(),
+            Decimal::from(3000),
+        );
+        // 3.0000015 USDC of gas, rounded down to whole micro-USDC
+        assert_eq!(
+            net,
+            TokenAmount::from_human_readable("1496.999998", 6).unwrap()
+        );
+
+        let dust = TokenAmount::from_human_readable("1", 6).unwrap();
+        let net = AlloyEthereumProvider::<Http<Client>>::net_amount_out(
+            &dust,
+            Decimal::ONE,
+            Decimal::from(3000),
+        );
+        assert_eq!(net.raw, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_net_amount_in_prices_gas_in_input_token() {
+        let amount_in = TokenAmount::from_human_readable("1500", 6).unwrap();
+        let net = AlloyEthereumProvider::<Http<Client>>::net_amount_in(
+            &amount_in,
+            Decimal::from_str("0.0010000005").unwrap(),
+            Decimal::from(3000),
+        );
+        // 3.0000015 USDC of gas, rounded up to whole micro-USDC
+        assert_eq!(
+            net,
+            TokenAmount::from_human_readable("1503.000002", 6).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rank_route_quotes_prefers_net_output() {
+        // The 0.05% pool quotes more but costs enough extra gas to lose on net
+        let mut quotes = vec![
+            route_quote(500, "1000", Some("990")),
+            route_quote(3000, "998", Some("995")),
+        ];
+        AlloyEthereumProvider::<Http<Client>>::rank_route_quotes(&mut quotes);
+        assert_eq!(quotes[0].route.fee_tiers(), vec![3000]);
+
+        // Without a gas price for every quote, gross output decides
+        let mut quotes = vec![
+            route_quote(3000, "998", Some("995")),
+            route_quote(500, "1000", None),
+        ];
+        AlloyEthereumProvider::<Http<Client>>::rank_route_quotes(&mut quotes);
+        assert_eq!(quotes[0].route.fee_tiers(), vec![500]);
+
+        // Exact-output quotes pay out the same; the cheapest input wins
+        let mut expensive = route_quote(500, "1000", None);
+        expensive.amount_in = TokenAmount::from_human_readable("1.01", 18).unwrap();
+        let mut quotes = vec![expensive, route_quote(3000, "1000", None)];
+        AlloyEthereumProvider::<Http<Client>>::rank_route_quotes(&mut quotes);
+        assert_eq!(quotes[0].route.fee_tiers(), vec![3000]);
+
+        // ...counting its gas where every quote has that priced in the input token
+        let net_in = |quote: &mut RouteQuote, net: &str| {
+            quote.net_amount_in = Some(TokenAmount::from_human_readable(net, 18).unwrap())
+        };
+        let mut cheap_gas = route_quote(500, "1000", None);
+        cheap_gas.amount_in = TokenAmount::from_human_readable("1.001", 18).unwrap();
+        net_in(&mut cheap_gas, "1.002");
+        let mut costly_gas = route_quote(3000, "1000", None);
+        net_in(&mut costly_gas, "1.005");
+        let mut quotes = vec![costly_gas, cheap_gas];
+        AlloyEthereumProvider::<Http<Client>>::rank_route_quotes(&mut quotes);
+        assert_eq!(quotes[0].route.fee_tiers(), vec![500]);
+        quotes[0].net_amount_in = None;
+        AlloyEthereumProvider::<Http<Client>>::rank_route_quotes(&mut quotes);
+        assert_eq!(quotes[0].route.fee_tiers(), vec![3000]);
+    }
+
+    #[test]
+    fn test_rank_venue_quotes_prefers_net_output() {
+        type Provider = AlloyEthereumProvider<Http<Client>>;
+        let venue_quote = |venue: &str, amount_out: &str, net: Option<&str>| VenueQuote {
+            venue: venue.to_string(),
+            pools: vec![Address::repeat_byte(7)],
+            amount_out: TokenAmount::from_human_readable(amount_out, 6).unwrap(),
+            gas_estimate: 150_000,
+            gas_cost_eth: Some(Decimal::from_str("0.001").unwrap()),
+            net_amount_out: net.map(|n| TokenAmount::from_human_readable(n, 6).unwrap()),
+        };
+        // Curve pays out more, but its gas costs more than the difference
+        let mut quotes = vec![
+            venue_quote("curve", "1000", Some("990")),
+            venue_quote("uniswap_v3", "999", Some("995")),
+        ];
+        Provider::rank_venue_quotes(&mut quotes);
+        assert_eq!(quotes[0].venue, "uniswap_v3");
+
+        // Without a price for gas on every quote, gross output decides
+        quotes[1].net_amount_out = None;
+        Provider::rank_venue_quotes(&mut quotes);
+        assert_eq!(quotes[0].venue, "curve");
+
+        assert_eq!(
+            Provider::gas_cost_eth(150_000, U256::from(20_000_000_000u64)),
+            Decimal::from_str("0.003").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_swap_calldata_exact_output_caps_input() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+        let route = direct_route(500);
+        let params = SwapParams {
+            from_token: route.hops[0].token_in.clone(),
+            to_token: route.hops[0].token_out.clone(),
+            amount_in: TokenAmount::from_human_readable("2", 6).unwrap(),
+            slippage_tolerance: Decimal::from(1),
+            route: Some(route.clone()),
+            amount_out: Some(TokenAmount::from_human_readable("1000", 6).unwrap()),
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        };
+        let calldata =
+            P::swap_calldata(&params, Address::repeat_byte(9), U256::from(1), U256::ZERO).unwrap();
+        let call = IUniswapV3Router::exactOutputSingleCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.params.amountOut, U256::from(1_000_000_000u64));
+        // 2 plus 1% slippage
+        assert_eq!(call.params.amountInMaximum, U256::from(2_020_000u64));
+
+        let mut two_hop = params.clone();
+        two_hop
+            .route
+            .as_mut()
+            .unwrap()
+            .hops
+            .push(route.hops[0].clone());
+        assert!(P::swap_calldata(&two_hop, Address::ZERO, U256::from(1), U256::ZERO).is_err());
+        let unrouted = SwapParams {
+            route: None,
+            ..params
+        };
+        assert!(P::swap_calldata(&unrouted, Address::ZERO, U256::from(1), U256::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_v2_swap_calldata_passes_token_path() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+        let mut route = direct_route(fees::V2_PAIR);
+        route.protocol = SwapProtocol::UniswapV2;
+        route.hops.push(RouteHop {
+            token_in: TokenAddress::new(Address::repeat_byte(2)),
+            token_out: TokenAddress::new(Address::repeat_byte(3)),
+            fee_tier: fees::V2_PAIR,
+            pool: Address::ZERO,
+            liquidity: 1,
+        });
+        let params = SwapParams {
+            from_token: route.hops[0].token_in.clone(),
+            to_token: route.hops[1].token_out.clone(),
+            amount_in: TokenAmount::from_human_readable("2", 6).unwrap(),
+            slippage_tolerance: Decimal::from(1),
+            route: Some(route.clone()),
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        };
+        let recipient = Address::repeat_byte(9);
+        let calldata = P::swap_calldata(&params, recipient, U256::from(1), U256::from(10)).unwrap();
+        let call =
+            IUniswapV2Router::swapExactTokensForTokensCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.amountIn, U256::from(2_000_000u64));
+        assert_eq!(call.amountOutMin, U256::from(10));
+        assert_eq!(call.path, route.tokens());
+        assert_eq!(call.to, recipient);
+
+        let exact_output = SwapParams {
+            amount_out: Some(TokenAmount::from_human_readable("1000", 6).unwrap()),
+            ..params
+        };
+        let calldata =
+            P::swap_calldata(&exact_output, recipient, U256::from(1), U256::ZERO).unwrap();
+        let call =
+            IUniswapV2Router::swapTokensForExactTokensCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.amountOut, U256::from(1_000_000_000u64));
+        // 2 plus 1% slippage
+        assert_eq!(call.amountInMax, U256::from(2_020_000u64));
+        assert_eq!(call.path, route.tokens());
+    }
+
+    #[test]
+    fn test_native_eth_swaps_pay_value_and_unwrap() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+        let route = direct_route(500);
+        let (router, recipient, deadline) = (
+            Address::repeat_byte(8),
+            Address::repeat_byte(9),
+            U256::from(1),
+        );
+        let eth_in = SwapParams {
+            from_token: TokenAddress::native(),
+            to_token: route.hops[0].token_out.clone(),
+            amount_in: TokenAmount::from_human_readable("2", 18).unwrap(),
+            slippage_tolerance: Decimal::from(1),
+            route: Some(route.clone()),
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        };
+        // Ether input goes out as the value of a plain swap call
+        let calls = P::swap_calls(&eth_in, router, recipient, deadline, U256::ZERO).unwrap();
+        assert_eq!(calls.len(), 1);
+        let tx = P::swap_transaction(&eth_in, router, calls).unwrap();
+        assert_eq!(tx.value, Some(U256::from(2_000_000_000_000_000_000u128)));
+        let call =
+            IUniswapV3Router::exactInputSingleCall::abi_decode(tx.input.input().unwrap(), true)
+                .unwrap();
+        assert_eq!(call.params.recipient, recipient);
+
+        // Exact-output swaps send the maximum input and take back what's left
+        let exact_output = SwapParams {
+            amount_out: Some(TokenAmount::from_human_readable("1000", 6).unwrap()),
+            ..eth_in.clone()
+        };
+        let calls = P::swap_calls(&exact_output, router, recipient, deadline, U256::ZERO).unwrap();
+        assert_eq!(calls.len(), 2);
+        IUniswapV3Router::refundETHCall::abi_decode(&calls[1], true).unwrap();
+        let tx = P::swap_transaction(&exact_output, router, calls).unwrap();
+        assert_eq!(tx.value, Some(U256::from(2_020_000_000_000_000_000u128)));
+        IUniswapV3Router::multicallCall::abi_decode(tx.input.input().unwrap(), true).unwrap();
+
+        // Ether output is swapped to the router as WETH, then unwrapped to the recipient
+        let eth_out = SwapParams {
+            from_token: route.hops[0].token_in.clone(),
+            to_token: TokenAddress::native(),
+            ..eth_in.clone()
+        };
+        let calls = P::swap_calls(&eth_out, router, recipient, deadline, U256::from(5)).unwrap();
+        let swap = IUniswapV3Router::exactInputSingleCall::abi_decode(&calls[0], true).unwrap();
+        assert_eq!(swap.params.recipient, router);
+        let unwrap = IUniswapV3Router::unwrapWETH9Call::abi_decode(&calls[1], true).unwrap();
+        assert_eq!(unwrap.amountMinimum, U256::from(5));
+        assert_eq!(unwrap.recipient, recipient);
+        let tx = P::swap_transaction(&eth_out, router, calls).unwrap();
+        assert_eq!(tx.value, Some(U256::ZERO));
+
+        // V2 routers take ether through their own functions
+        let mut v2_route = route.clone();
+        v2_route.protocol = SwapProtocol::UniswapV2;
+        let v2 = |params: &SwapParams| SwapParams {
+            route: Some(v2_route.clone()),
+            ..params.clone()
+        };
+        let calls = P::swap_calls(&v2(&eth_in), router, recipient, deadline, U256::ZERO).unwrap();
+        IUniswapV2Router::swapExactETHForTokensCall::abi_decode(&calls[0], true).unwrap();
+        let calls =
+            P::swap_calls(&v2(&exact_output), router, recipient, deadline, U256::ZERO).unwrap();
+        assert_eq!(calls.len(), 1);
+        IUniswapV2Router::swapETHForExactTokensCall::abi_decode(&calls[0], true).unwrap();
+        let calls = P::swap_calls(&v2(&eth_out), router, recipient, deadline, U256::ZERO).unwrap();
+        let call =
+            IUniswapV2Router::swapExactTokensForETHCall::abi_decode(&calls[0], true).unwrap();
+        assert_eq!(call.to, recipient);
+    }
+
+    #[test]
+    fn test_exact_input_calldata_by_route_length() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+        let recipient = Address::repeat_byte(9);
+        let direct = direct_route(500);
+        let calldata = P::exact_input_calldata(
+            &direct,
+            recipient,
+            U256::from(1),
+            U256::from(10),
+            U256::ZERO,
+        )
+        .unwrap();
+        let call = IUniswapV3Router::exactInputSingleCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.params.fee, 500);
+        // QuoterV2 measures the pool swaps; the router adds the base cost and transfers
+        assert_eq!(
+            P::quoted_route_gas(&direct, 80_000),
+            80_000 + 21_000 + 60_000
+        );
+
+        let mut two_hop = direct_route(500);
+        two_hop.hops.push(RouteHop {
+            token_in: TokenAddress::new(Address::repeat_byte(2)),
+            token_out: TokenAddress::new(Address::repeat_byte(3)),
+            fee_tier: 3000,
+            pool: Address::ZERO,
+            liquidity: 1,
+        });
+        let calldata = P::exact_input_calldata(
+            &two_hop,
+            recipient,
+            U256::from(1),
+            U256::from(10),
+            U256::ZERO,
+        )
+        .unwrap();
+        let call = IUniswapV3Router::exactInputCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(
+            utils::decode_path(&call.params.path).unwrap(),
+            (two_hop.tokens(), vec![500, 3000])
+        );
+        assert_eq!(call.params.recipient, recipient);
+        assert_eq!(
+            P::quoted_route_gas(&two_hop, 160_000),
+            160_000 + 21_000 + 2 * 60_000
+        );
+
+        assert!(P::exact_input_calldata(
+            &SwapRoute::default(),
+            recipient,
+            U256::from(1),
+            U256::from(10),
+            U256::ZERO
+        )
+        .is_err());
+    }
+
+    fn fee_history(base_fees: Vec<u128>, ratios: Vec<f64>, reward: Vec<Vec<u128>>) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees,
+            gas_used_ratio: ratios,
+            base_fee_per_blob_gas: vec![],
+            blob_gas_used_ratio: vec![],
+            oldest_block: 100,
+            reward: Some(reward),
+        }
+    }
+
+    #[test]
+    fn test_predict_next_base_fee() {
+        let base = 10_000_000_000u128;
+        // Exactly on target keeps the base fee unchanged
+        assert_eq!(
+            AlloyEthereumProvider::<Http<Client>>::predict_next_base_fee(base, 0.5),
+            base
+        );
+        // Full block raises the base fee by 12.5%
+        assert_eq!(
+            AlloyEthereumProvider::<Http<Client>>::predict_next_base_fee(base, 1.0),
+            11_250_000_000
+        );
+        // Empty block lowers the base fee by 12.5%
+        assert_eq!(
+            AlloyEthereumProvider::<Http<Client>>::predict_next_base_fee(base, 0.0),
+            8_750_000_000
+        );
+    }
+
+    #[test]
+    fn test_fee_estimate_from_history() {
+        let history = fee_history(
+            vec![
+                10_000_000_000,
+                11_000_000_000,
+                12_000_000_000,
+                13_000_000_000,
+            ],
+            vec![0.9, 0.9, 0.6],
+            vec![
+                vec![1_000_000_000, 2_000_000_000, 5_000_000_000],
+                vec![0, 0, 0], // empty block
+                vec![1_500_000_000, 2_500_000_000, 4_000_000_000],
+            ],
+        );
+
+        let estimate =
+            AlloyEthereumProvider::<Http<Client>>::fee_estimate_from_history(&history).unwrap();
+
+        assert_eq!(estimate.base_fee_per_gas, 12_000_000_000);
+        assert_eq!(estimate.next_base_fee_per_gas, 13_000_000_000);
+        assert_eq!(estimate.slow.max_priority_fee_per_gas, 1_500_000_000);
+        assert_eq!(estimate.normal.max_priority_fee_per_gas, 2_500_000_000);
+        assert_eq!(estimate.fast.max_priority_fee_per_gas, 5_000_000_000);
+        assert_eq!(estimate.normal.max_fee_per_gas, 28_500_000_000);
+        assert_eq!(estimate.latest_block, 102);
+    }
+
+    #[test]
+    fn test_fee_estimate_predicts_missing_next_base_fee() {
+        let history = fee_history(vec![8_000_000_000], vec![1.0], vec![vec![1, 2, 3]]);
+
+        let estimate =
+            AlloyEthereumProvider::<Http<Client>>::fee_estimate_from_history(&history).unwrap();
+
+        assert_eq!(estimate.base_fee_per_gas, 8_000_000_000);
+        assert_eq!(estimate.next_base_fee_per_gas, 9_000_000_000);
+    }
+
+    #[test]
+    fn test_fee_estimate_rejects_empty_history() {
+        let history = fee_history(vec![], vec![], vec![]);
+        let result = AlloyEthereumProvider::<Http<Client>>::fee_estimate_from_history(&history);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("EIP-1559"));
+    }
+
+    #[test]
+    fn test_unsigned_eip1559_payload() {
+        let fees = FeeSuggestion {
+            max_priority_fee_per_gas: 1_000_000,
+            max_fee_per_gas: 100_000_000,
+        };
+        let to = Address::from_str("0xE592427A0AEce92De3Edee1F18E0157C05861564").unwrap();
+        let input = Bytes::from(vec![0x41, 0x4b, 0xf3, 0x89]);
+        let payload = AlloyEthereumProvider::<Http<Client>>::unsigned_eip1559_payload(
+            10,
+            to,
+            input.clone(),
+            180_000,
+            &fees,
+        );
+
+        // Typed transaction envelope: 0x02 followed by the RLP list
+        assert_eq!(payload[0], 0x02);
+        assert!(payload[1] >= 0xc0);
+        assert!(payload.ends_with(&[0x84, 0x41, 0x4b, 0xf3, 0x89, 0xc0]));
+        assert!(payload.windows(20).any(|w| w == to.as_slice()));
+    }
+
+    #[test]
+    fn test_wei_to_eth() {
+        assert_eq!(
+            AlloyEthereumProvider::<Http<Client>>::wei_to_eth(1_500_000_000_000_000_000),
+            Decimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(
+            AlloyEthereumProvider::<Http<Client>>::wei_to_eth(0),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_eip2612_domain_matches_on_chain_separator() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+        let token = Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+        let on_chain = Eip712Domain::new(
+            Some("USD Coin".into()),
+            Some("2".into()),
+            Some(U256::from(1u64)),
+            Some(token),
+            None,
+        )
+        .separator();
+        let versions = vec!["1".to_string(), "2".to_string()];
+
+        let domain = P::eip2612_domain("USD Coin", &versions, 1, token, on_chain).unwrap();
+        assert_eq!(domain.version.as_deref(), Some("2"));
+
+        // Wrong chain id or name never reproduces the token's separator
+        assert!(P::eip2612_domain("USD Coin", &versions, 10, token, on_chain).is_none());
+        assert!(P::eip2612_domain("USDC", &versions, 1, token, on_chain).is_none());
+    }
+
+    #[test]
+    fn test_permit2_domain_has_no_version() {
+        let permit2 = Address::from_str(crate::contracts::PERMIT2).unwrap();
+        let domain = AlloyEthereumProvider::<Http<Client>>::permit2_domain(1, permit2);
+
+        assert_eq!(domain.name.as_deref(), Some("Permit2"));
+        assert!(domain.version.is_none());
+        assert_eq!(domain.chain_id, Some(U256::from(1u64)));
+        assert_eq!(domain.verifying_contract, Some(permit2));
+    }
+
+    #[test]
+    fn test_permit_signature_parts() {
+        let signer = PrivateKeySigner::from_str(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let hash = keccak256(b"permit");
+        let signature = signer.sign_hash_sync(&hash).unwrap();
+
+        let (bytes, v, r, s) =
+            AlloyEthereumProvider::<Http<Client>>::permit_signature_parts(&signature);
+
+        assert_eq!(bytes.len(), 65);
+        // ecrecover-style recovery id, as expected by permit(..., v, r, s)
+        assert!(v == 27 || v == 28);
+        assert_eq!(&bytes[..32], r.as_slice());
+        assert_eq!(&bytes[32..64], s.as_slice());
+        assert_eq!(bytes[64], v);
+        assert_eq!(
+            signature.recover_address_from_prehash(&hash).unwrap(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn test_revert_reason_from_rpc_errors() {
+        use alloy::transports::RpcError;
+        type P = AlloyEthereumProvider<Http<Client>>;
+
+        let rpc_error = |payload: &str| -> TransportError {
+            RpcError::ErrorResp(serde_json::from_str(payload).unwrap())
+        };
+
+        // Revert data in the error payload
+        let data = Revert {
+            reason: "Too little received".to_string(),
+        }
+        .abi_encode();
+        let with_data = rpc_error(&format!(
+            r#"{{"code":3,"message":"execution reverted","data":"{}"}}"#,
+            alloy::primitives::hex::encode_prefixed(&data)
+        ));
+        let reason = P::revert_reason(&with_data).unwrap();
+        assert_eq!(reason.kind, RevertKind::Error);
+        assert_eq!(reason.message, "Too little received");
+        assert!(reason.explanation.is_some());
+
+        // Reason only in the message
+        let message_only =
+            rpc_error(r#"{"code":-32000,"message":"execution reverted: Transaction too old"}"#);
+        assert_eq!(
+            P::revert_reason(&message_only).unwrap().message,
+            "Transaction too old"
+        );
+
+        // Not a revert
+        let other = rpc_error(r#"{"code":-32000,"message":"nonce too low"}"#);
+        assert!(P::revert_reason(&other).is_none());
+        let err = P::revert_error("Swap", other);
+        assert!(RevertError::find(&err).is_none());
+
+        let err = P::revert_error("Swap simulation reverted", message_only);
+        assert_eq!(
+            err.to_string(),
+            "Swap simulation reverted: Transaction too old"
+        );
+        assert!(RevertError::find(&err).is_some());
+    }
+
+    #[test]
+    fn test_never_broadcast_only_when_the_node_refused() {
+        use alloy::transports::{RpcError, TransportErrorKind};
+        type P = AlloyEthereumProvider<Http<Client>>;
+
+        let refused: TransportError = RpcError::ErrorResp(
+            serde_json::from_str(r#"{"code":-32000,"message":"txpool is full"}"#).unwrap(),
+        );
+        assert!(P::never_broadcast(&refused));
+
+        // The request may have reached the node before the connection failed
+        let timed_out = TransportErrorKind::custom_str("request timed out");
+        assert!(!P::never_broadcast(&timed_out));
+        let bad_gateway = TransportErrorKind::custom_str("HTTP error 502 with empty body");
+        assert!(!P::never_broadcast(&bad_gateway));
+    }
+
+    #[test]
+    fn test_unmined_status() {
+        type P = AlloyEthereumProvider<Http<Client>>;
+
+        // In the mempool with its nonce still open
+        assert_eq!(
+            P::unmined_status(5, 5, true, false),
+            TransactionStatus::Pending
+        );
+        // Forgotten by the node with its nonce still open
+        assert_eq!(
+            P::unmined_status(5, 5, false, false),
+            TransactionStatus::Dropped
+        );
+        // Nonce mined by a different transaction, whether or not the node still lists it
+        assert_eq!(
+            P::unmined_status(5, 6, false, false),
+            TransactionStatus::Replaced
+        );
+        assert_eq!(
+            P::unmined_status(5, 6, true, false),
+            TransactionStatus::Replaced
+        );
+        // Superseded by a speed-up or cancel that is itself still pending
+        assert_eq!(
+            P::unmined_status(5, 5, false, true),
+            TransactionStatus::Replaced
+        );
+    }
+
+    #[test]
+    fn test_replacement_fees_bump_at_least_ten_percent() {
+        let quiet_market = FeeSuggestion {
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas: 30_000_000_000,
+        };
+        let (max_fee, priority_fee) = AlloyEthereumProvider::<Http<Client>>::replacement_fees(
+            40_000_000_000,
+            2_000_000_000,
+            &quiet_market,
+        );
+        assert_eq!(max_fee, 44_000_000_000);
+        assert_eq!(priority_fee, 2_200_000_000);
+
+        // Rounds up so odd values still clear the 10% rule
+        let (max_fee, priority_fee) = AlloyEthereumProvider::<Http<Client>>::replacement_fees(
+            15,
+            5,
+            &FeeSuggestion {
+                max_priority_fee_per_gas: 0,
+                max_fee_per_gas: 0,
+            },
+        );
+        assert_eq!((max_fee, priority_fee), (17, 6));
+    }
+
+    #[test]
+    fn test_replacement_fees_follow_market_when_higher() {
+        let busy_market = FeeSuggestion {
+            max_priority_fee_per_gas: 5_000_000_000,
+            max_fee_per_gas: 90_000_000_000,
+        };
+        let (max_fee, priority_fee) = AlloyEthereumProvider::<Http<Client>>::replacement_fees(
+            40_000_000_000,
+            2_000_000_000,
+            &busy_market,
+        );
+        assert_eq!(max_fee, 90_000_000_000);
+        assert_eq!(priority_fee, 5_000_000_000);
+    }
+
     #[test]
     fn test_gas_price_bounds() {
         // Test reasonable gas price bounds
@@ -972,4 +4464,50 @@ mod tests {
         // This is a compile-time check that the type parameter works
         let _type_check: Option<AlloyEthereumProvider<Http<Client>>> = None;
     }
+
+    fn flaky_gas_price(error_rate: f64) -> crate::providers::ChaosProvider {
+        let mut mock = crate::providers::MockEthereumProvider::new();
+        mock.expect_get_gas_price()
+            .returning(|| Ok(U256::from(1_000_000_000u64)));
+        crate::providers::ChaosProvider::new(Arc::new(mock))
+            .with_seed(3)
+            .with_faults(crate::providers::FaultConfig {
+                error_rate,
+                ..Default::default()
+            })
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_rides_out_intermittent_errors() {
+        let chaos = flaky_gas_price(0.5);
+        for _ in 0..5 {
+            let price = AlloyEthereumProvider::<Http<Client>>::retry_with_backoff(
+                || chaos.get_gas_price(),
+                6,
+                "get_gas_price",
+            )
+            .await
+            .unwrap();
+            assert_eq!(price, U256::from(1_000_000_000u64));
+        }
+        assert!(chaos.injected(crate::providers::Fault::Error) > 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_gives_up_on_an_outage() {
+        let chaos = flaky_gas_price(1.0);
+        let started = std::time::Instant::now();
+        let error = AlloyEthereumProvider::<Http<Client>>::retry_with_backoff(
+            || chaos.get_gas_price(),
+            3,
+            "get_gas_price",
+        )
+        .await
+        .unwrap_err();
+
+        assert!(error.to_string().contains("failed after 3 attempts"));
+        assert_eq!(chaos.calls(), 3);
+        // Backs off 100ms, then 200ms
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
 }
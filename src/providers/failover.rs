@@ -0,0 +1,518 @@
+/// Failover across several providers, for resilience tests (`chaos` feature)
+/// Each endpoint sits behind its own circuit breaker. Reads go to the first endpoint whose
+/// breaker isn't open and fall through to the next one on failure. Broadcasts stay on the
+/// primary, whose nonce manager and transaction journal track the wallet's transactions,
+/// and so do transaction status lookups, which need them to report replaced and dropped
+/// transactions.
+use super::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState, EthereumProvider,
+};
+use crate::types::{
+    AllowanceInfo, BalanceInfo, GasFeeEstimate, PermitSignature, QuoteComparison, ReplacementKind,
+    ReplacementResult, SwapParams, SwapResult, TokenAddress, TokenAmount, TokenPrice,
+    TransactionDetail, TransactionStatusInfo, WalletAddress,
+};
+use crate::ContractAddresses;
+use alloy::primitives::{Address, B256, U256};
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+struct Endpoint {
+    provider: Arc<dyn EthereumProvider>,
+    breaker: CircuitBreaker,
+}
+
+/// Provider failing over between endpoints in priority order; the first is the primary
+pub struct FailoverProvider {
+    endpoints: Vec<Endpoint>,
+}
+
+impl FailoverProvider {
+    /// Fail over between `providers` with default circuit breakers
+    pub fn new(providers: Vec<Arc<dyn EthereumProvider>>) -> anyhow::Result<Self> {
+        Self::with_breaker_config(providers, CircuitBreakerConfig::default())
+    }
+
+    /// Fail over between `providers` with breakers configured by `config`
+    pub fn with_breaker_config(
+        providers: Vec<Arc<dyn EthereumProvider>>,
+        config: CircuitBreakerConfig,
+    ) -> anyhow::Result<Self> {
+        if providers.is_empty() {
+            return Err(anyhow::anyhow!("No RPC URLs provided"));
+        }
+        Ok(Self {
+            endpoints: providers
+                .into_iter()
+                .map(|provider| Endpoint {
+                    provider,
+                    breaker: CircuitBreaker::with_config(config.clone()),
+                })
+                .collect(),
+        })
+    }
+
+    /// Breaker state of the endpoint at `index`
+    pub fn breaker_state(&self, index: usize) -> Option<CircuitState> {
+        self.endpoints
+            .get(index)
+            .map(|endpoint| endpoint.breaker.state())
+    }
+
+    /// Run a read on the first endpoint that answers
+    async fn read<R, F, Fut>(&self, method: &str, operation: F) -> anyhow::Result<R>
+    where
+        F: Fn(Arc<dyn EthereumProvider>) -> Fut,
+        Fut: Future<Output = anyhow::Result<R>>,
+    {
+        let mut last_error = None;
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            match endpoint
+                .breaker
+                .call(|| operation(endpoint.provider.clone()))
+                .await
+            {
+                Ok(result) => return Ok(result),
+                Err(CircuitBreakerError::CircuitOpen) => {
+                    debug!(
+                        endpoint = index,
+                        method, "Skipping endpoint with open circuit"
+                    );
+                }
+                Err(CircuitBreakerError::OperationFailed(e)) => {
+                    warn!(
+                        endpoint = index,
+                        method, "RPC endpoint failed, failing over: {}", e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            anyhow::anyhow!("Circuit breaker open for every RPC endpoint ({})", method)
+        }))
+    }
+
+    /// Run an operation on the primary only: a broadcast sent elsewhere could reuse a
+    /// nonce, and only the primary knows which of the wallet's transactions were replaced
+    async fn on_primary<R, Fut>(&self, method: &str, operation: Fut) -> anyhow::Result<R>
+    where
+        Fut: Future<Output = anyhow::Result<R>>,
+    {
+        self.endpoints[0]
+            .breaker
+            .call(|| operation)
+            .await
+            .map_err(|e| match e {
+                CircuitBreakerError::CircuitOpen => {
+                    anyhow::anyhow!("Circuit breaker open for operation {}", method)
+                }
+                CircuitBreakerError::OperationFailed(e) => e,
+            })
+    }
+
+    fn primary(&self) -> &Arc<dyn EthereumProvider> {
+        &self.endpoints[0].provider
+    }
+}
+
+#[async_trait]
+impl EthereumProvider for FailoverProvider {
+    async fn get_eth_balance(&self, wallet: &WalletAddress) -> anyhow::Result<BalanceInfo> {
+        self.read("get_eth_balance", |p| async move {
+            p.get_eth_balance(wallet).await
+        })
+        .await
+    }
+
+    async fn get_erc20_balance(
+        &self,
+        wallet: &WalletAddress,
+        token: &TokenAddress,
+    ) -> anyhow::Result<BalanceInfo> {
+        self.read("get_erc20_balance", |p| async move {
+            p.get_erc20_balance(wallet, token).await
+        })
+        .await
+    }
+
+    async fn get_allowance(
+        &self,
+        owner: &WalletAddress,
+        token: &TokenAddress,
+        spender: &Address,
+    ) -> anyhow::Result<AllowanceInfo> {
+        self.read("get_allowance", |p| async move {
+            p.get_allowance(owner, token, spender).await
+        })
+        .await
+    }
+
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+    ) -> anyhow::Result<B256> {
+        self.on_primary(
+            "approve_token",
+            self.primary().approve_token(token, spender, amount),
+        )
+        .await
+    }
+
+    async fn wrap_eth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        self.on_primary("wrap_eth", self.primary().wrap_eth(weth, amount))
+            .await
+    }
+
+    async fn unwrap_weth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256> {
+        self.on_primary("unwrap_weth", self.primary().unwrap_weth(weth, amount))
+            .await
+    }
+
+    async fn get_token_decimals(&self, token: &TokenAddress) -> anyhow::Result<u8> {
+        self.read("get_token_decimals", |p| async move {
+            p.get_token_decimals(token).await
+        })
+        .await
+    }
+
+    async fn get_token_symbol(&self, token: &TokenAddress) -> anyhow::Result<String> {
+        self.read("get_token_symbol", |p| async move {
+            p.get_token_symbol(token).await
+        })
+        .await
+    }
+
+    async fn get_token_price(
+        &self,
+        token: &TokenAddress,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<TokenPrice> {
+        self.read("get_token_price", |p| async move {
+            p.get_token_price(token, contracts).await
+        })
+        .await
+    }
+
+    async fn simulate_swap(
+        &self,
+        params: &SwapParams,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<SwapResult> {
+        self.read("simulate_swap", |p| async move {
+            p.simulate_swap(params, contracts).await
+        })
+        .await
+    }
+
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount_in: &TokenAmount,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<QuoteComparison> {
+        self.read("compare_quotes", |p| async move {
+            p.compare_quotes(from_token, to_token, amount_in, contracts)
+                .await
+        })
+        .await
+    }
+
+    async fn execute_swap<'a>(
+        &self,
+        params: &SwapParams,
+        amount_out_minimum: &TokenAmount,
+        permit: Option<&'a PermitSignature>,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<B256> {
+        self.on_primary(
+            "execute_swap",
+            self.primary()
+                .execute_swap(params, amount_out_minimum, permit, contracts),
+        )
+        .await
+    }
+
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        value: U256,
+        deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        self.read("sign_permit", |p| async move {
+            p.sign_permit(token, spender, value, deadline).await
+        })
+        .await
+    }
+
+    async fn sign_permit2(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+        expiration: u64,
+        sig_deadline: U256,
+    ) -> anyhow::Result<PermitSignature> {
+        self.read("sign_permit2", |p| async move {
+            p.sign_permit2(token, spender, amount, expiration, sig_deadline)
+                .await
+        })
+        .await
+    }
+
+    async fn get_gas_price(&self) -> anyhow::Result<U256> {
+        self.read("get_gas_price", |p| async move { p.get_gas_price().await })
+            .await
+    }
+
+    async fn get_block_number(&self) -> anyhow::Result<u64> {
+        self.read(
+            "get_block_number",
+            |p| async move { p.get_block_number().await },
+        )
+        .await
+    }
+
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate> {
+        self.read("get_gas_fees", |p| async move { p.get_gas_fees().await })
+            .await
+    }
+
+    async fn get_transaction_status(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<TransactionStatusInfo> {
+        self.on_primary(
+            "get_transaction_status",
+            self.primary().get_transaction_status(tx_hash),
+        )
+        .await
+    }
+
+    async fn get_transaction_detail(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<Option<TransactionDetail>> {
+        self.on_primary(
+            "get_transaction_detail",
+            self.primary().get_transaction_detail(tx_hash),
+        )
+        .await
+    }
+
+    async fn replace_transaction(
+        &self,
+        tx_hash: &B256,
+        kind: ReplacementKind,
+    ) -> anyhow::Result<ReplacementResult> {
+        self.on_primary(
+            "replace_transaction",
+            self.primary().replace_transaction(tx_hash, kind),
+        )
+        .await
+    }
+
+    /// Healthy while any endpoint is
+    async fn health_check(&self) -> anyhow::Result<()> {
+        self.read("health_check", |p| async move { p.health_check().await })
+            .await
+    }
+
+    fn wallet_address(&self) -> WalletAddress {
+        self.primary().wallet_address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ChaosProvider, FaultConfig, MockEthereumProvider};
+    use std::time::Duration;
+
+    fn endpoint(block: u64) -> Arc<ChaosProvider> {
+        let mut mock = MockEthereumProvider::new();
+        mock.expect_get_block_number().returning(move || Ok(block));
+        mock.expect_health_check().returning(|| Ok(()));
+        mock.expect_approve_token()
+            .returning(move |_, _, _| Ok(B256::repeat_byte(block as u8)));
+        Arc::new(ChaosProvider::new(Arc::new(mock)))
+    }
+
+    fn failover(endpoints: &[Arc<ChaosProvider>]) -> FailoverProvider {
+        FailoverProvider::with_breaker_config(
+            endpoints
+                .iter()
+                .map(|e| e.clone() as Arc<dyn EthereumProvider>)
+                .collect(),
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                timeout_duration: Duration::from_millis(100),
+                success_threshold: 1,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_requires_an_endpoint() {
+        assert!(FailoverProvider::new(vec![]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reads_prefer_the_primary() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        let provider = failover(&[primary.clone(), backup.clone()]);
+        assert_eq!(provider.get_block_number().await.unwrap(), 1);
+        assert_eq!(backup.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_and_skip_an_open_primary() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        primary.set_faults(FaultConfig::failing());
+        let provider = failover(&[primary.clone(), backup.clone()]);
+
+        for _ in 0..2 {
+            assert_eq!(provider.get_block_number().await.unwrap(), 2);
+        }
+        assert_eq!(provider.breaker_state(0), Some(CircuitState::Open));
+        assert_eq!(primary.calls(), 2);
+
+        // The open primary is skipped without being called
+        assert_eq!(provider.get_block_number().await.unwrap(), 2);
+        assert_eq!(primary.calls(), 2);
+        assert_eq!(provider.breaker_state(1), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_primary_is_used_again_once_it_recovers() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        primary.set_faults(FaultConfig::failing());
+        let provider = failover(&[primary.clone(), backup.clone()]);
+        for _ in 0..2 {
+            provider.get_block_number().await.unwrap();
+        }
+        assert_eq!(provider.breaker_state(0), Some(CircuitState::Open));
+
+        primary.set_faults(FaultConfig::default());
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(provider.get_block_number().await.unwrap(), 1);
+        assert_eq!(provider.breaker_state(0), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_and_malformed_responses_fail_over() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        let provider = failover(&[primary.clone(), backup.clone()]);
+
+        primary.set_faults(FaultConfig {
+            malformed_rate: 1.0,
+            ..Default::default()
+        });
+        assert_eq!(provider.get_block_number().await.unwrap(), 2);
+
+        let primary = Arc::new(
+            ChaosProvider::new(primary.clone())
+                .with_hang_duration(Duration::from_millis(10))
+                .with_faults(FaultConfig {
+                    timeout_rate: 1.0,
+                    ..Default::default()
+                }),
+        );
+        let provider = FailoverProvider::new(vec![primary, backup]).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_down() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        primary.set_faults(FaultConfig::failing());
+        backup.set_faults(FaultConfig::failing());
+        let provider = failover(&[primary, backup]);
+
+        let error = provider.get_block_number().await.unwrap_err();
+        assert!(error.to_string().contains("injected fault"));
+        let _ = provider.get_block_number().await;
+        let error = provider.get_block_number().await.unwrap_err();
+        assert!(
+            error.to_string().contains("every RPC endpoint"),
+            "{}",
+            error
+        );
+        assert!(provider.health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_status_comes_from_the_primary() {
+        use crate::types::{TransactionStatus, TransactionStatusInfo};
+
+        // Only the primary sent the replacement; a backup sees an unknown transaction
+        let status = |status: TransactionStatus, replaced_by: Option<&str>| {
+            let replaced_by = replaced_by.map(str::to_string);
+            let mut mock = MockEthereumProvider::new();
+            mock.expect_get_transaction_status().returning(move |hash| {
+                Ok(TransactionStatusInfo {
+                    transaction_hash: format!("{:?}", hash),
+                    status: status.clone(),
+                    confirmations: 0,
+                    block_number: None,
+                    finalized: false,
+                    replaced_by: replaced_by.clone(),
+                    detail: None,
+                    revert_reason: None,
+                })
+            });
+            mock.expect_get_transaction_detail().returning(|_| Ok(None));
+            Arc::new(ChaosProvider::new(Arc::new(mock)))
+        };
+        let replacement = format!("{:?}", B256::repeat_byte(2));
+        let primary = status(TransactionStatus::Replaced, Some(&replacement));
+        let backup = status(TransactionStatus::NotFound, None);
+        let provider = failover(&[primary.clone(), backup.clone()]);
+        let original = B256::repeat_byte(1);
+
+        let info = provider.get_transaction_status(&original).await.unwrap();
+        assert_eq!(info.status, TransactionStatus::Replaced);
+        assert_eq!(info.replaced_by, Some(replacement));
+        assert!(provider
+            .get_transaction_detail(&original)
+            .await
+            .unwrap()
+            .is_none());
+
+        // A failing primary is reported rather than answered by the backup
+        primary.set_faults(FaultConfig::failing());
+        assert!(provider.get_transaction_status(&original).await.is_err());
+        assert!(provider.get_transaction_detail(&original).await.is_err());
+        assert_eq!(backup.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_writes_never_fail_over() {
+        let (primary, backup) = (endpoint(1), endpoint(2));
+        primary.set_faults(FaultConfig::failing());
+        let provider = failover(&[primary.clone(), backup.clone()]);
+        let token = TokenAddress::from_hex("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+
+        assert!(provider
+            .approve_token(&token, &Address::ZERO, U256::from(1))
+            .await
+            .is_err());
+        assert_eq!(backup.calls(), 0);
+
+        primary.set_faults(FaultConfig::default());
+        assert_eq!(
+            provider
+                .approve_token(&token, &Address::ZERO, U256::from(1))
+                .await
+                .unwrap(),
+            B256::repeat_byte(1)
+        );
+    }
+}
@@ -1,26 +1,46 @@
 /// Provider module - abstracts blockchain interactions
 /// Clean interface for dependency injection and testing
+#[cfg(any(test, feature = "chaos"))]
+mod chaos;
 mod circuit_breaker;
+mod dex;
 mod ethereum;
+#[cfg(any(test, feature = "chaos"))]
+mod failover;
 mod mock;
 mod nonce_manager;
+mod rpc_fixture;
+mod tx_store;
 
-pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError};
+#[cfg(any(test, feature = "chaos"))]
+pub use chaos::{ChaosProvider, Fault, FaultConfig};
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState,
+};
+pub use dex::{BalancerQuoter, CurveQuoter, DexQuote, DexQuoter};
 pub use ethereum::AlloyEthereumProvider;
+#[cfg(any(test, feature = "chaos"))]
+pub use failover::FailoverProvider;
 pub use nonce_manager::NonceManager;
+pub use rpc_fixture::{RecordingTransport, ReplayTransport, RpcExchange, RpcOutcome, RpcRecorder};
+pub use tx_store::{StoredTransaction, StoredTxStatus, TransactionStore};
 
 /// Ethereum provider abstraction for testability
 /// Strategic interface for mocking - enables 90% test coverage
 use crate::{
     types::{
-        BalanceInfo, SwapParams, SwapResult, TokenAddress, TokenPrice, TransactionStatusInfo,
-        WalletAddress,
+        AllowanceInfo, BalanceInfo, GasFeeEstimate, PermitSignature, QuoteComparison,
+        ReplacementKind, ReplacementResult, SwapParams, SwapResult, TokenAddress, TokenAmount,
+        TokenPrice, TransactionDetail, TransactionStatusInfo, WalletAddress,
     },
     ContractAddresses,
 };
-use alloy::primitives::{B256, U256};
+use alloy::primitives::{Address, B256, U256};
+use alloy::transports::http::Http;
+use alloy::transports::Transport;
 use async_trait::async_trait;
 use mockall::automock;
+use std::path::Path;
 use std::sync::Arc;
 
 /// Core Ethereum operations interface
@@ -38,6 +58,28 @@ pub trait EthereumProvider: Send + Sync {
         token: &TokenAddress,
     ) -> anyhow::Result<BalanceInfo>;
 
+    /// Get the ERC20 allowance `owner` has granted to `spender`
+    async fn get_allowance(
+        &self,
+        owner: &WalletAddress,
+        token: &TokenAddress,
+        spender: &Address,
+    ) -> anyhow::Result<AllowanceInfo>;
+
+    /// Sign and broadcast an ERC20 `approve(spender, amount)` from the configured wallet
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+    ) -> anyhow::Result<B256>;
+
+    /// Sign and broadcast a WETH `deposit()` wrapping `amount` wei of the wallet's ether
+    async fn wrap_eth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256>;
+
+    /// Sign and broadcast a WETH `withdraw(amount)` unwrapping the wallet's WETH to ether
+    async fn unwrap_weth(&self, weth: &TokenAddress, amount: U256) -> anyhow::Result<B256>;
+
     /// Get token decimals
     async fn get_token_decimals(&self, token: &TokenAddress) -> anyhow::Result<u8>;
 
@@ -58,13 +100,76 @@ pub trait EthereumProvider: Send + Sync {
         contracts: &ContractAddresses,
     ) -> anyhow::Result<SwapResult>;
 
+    /// Quote selling `amount_in` on every venue (Uniswap V3, Uniswap V2, Sushiswap,
+    /// Curve and Balancer), best output net of gas first
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount_in: &TokenAmount,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<QuoteComparison>;
+
+    /// Sign and broadcast a quoted Uniswap V3 swap from the configured wallet along
+    /// `params.route`, reverting on-chain if less than `amount_out_minimum` would be
+    /// received, or for exact-output swaps if more than `params.maximum_amount_in()`
+    /// would be spent. An EIP-2612 `permit` is bundled via `selfPermit` in place of a
+    /// prior approval.
+    async fn execute_swap<'a>(
+        &self,
+        params: &SwapParams,
+        amount_out_minimum: &TokenAmount,
+        permit: Option<&'a PermitSignature>,
+        contracts: &ContractAddresses,
+    ) -> anyhow::Result<B256>;
+
+    /// Sign an EIP-2612 permit for `spender` with the configured wallet
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        value: U256,
+        deadline: U256,
+    ) -> anyhow::Result<PermitSignature>;
+
+    /// Sign a Uniswap Permit2 `PermitSingle` for `spender` with the configured wallet
+    async fn sign_permit2(
+        &self,
+        token: &TokenAddress,
+        spender: &Address,
+        amount: U256,
+        expiration: u64,
+        sig_deadline: U256,
+    ) -> anyhow::Result<PermitSignature>;
+
     /// Get the current gas price
     async fn get_gas_price(&self) -> anyhow::Result<U256>;
 
+    /// Get the latest block number
+    async fn get_block_number(&self) -> anyhow::Result<u64>;
+
+    /// Get EIP-1559 fee suggestions (base fee, priority fee tiers, next base fee)
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate>;
+
     /// Get the status of a transaction
     async fn get_transaction_status(&self, tx_hash: &B256)
         -> anyhow::Result<TransactionStatusInfo>;
 
+    /// Get the parties, fees, decoded calldata and logs of a transaction; None if the
+    /// node doesn't know it
+    async fn get_transaction_detail(
+        &self,
+        tx_hash: &B256,
+    ) -> anyhow::Result<Option<TransactionDetail>>;
+
+    /// Replace a pending transaction sent by this server, at the same nonce and with
+    /// bumped fees: a copy of it (speed up) or a 0-value self-transfer (cancel)
+    async fn replace_transaction(
+        &self,
+        tx_hash: &B256,
+        kind: ReplacementKind,
+    ) -> anyhow::Result<ReplacementResult>;
+
     /// Health check - verify provider connectivity
     async fn health_check(&self) -> anyhow::Result<()>;
 
@@ -77,19 +182,63 @@ pub struct ProviderFactory;
 
 impl ProviderFactory {
     /// Create production Ethereum provider
+    /// With a `tx_store`, signed transactions are journaled and reconciled on startup.
+    /// With an `rpc_recorder`, every RPC call is captured for replay in tests.
     pub async fn create_ethereum_provider(
         rpc_url: String,
         wallet_private_key: String,
         max_concurrent_requests: usize,
         request_timeout_seconds: u64,
+        tx_store: Option<Arc<TransactionStore>>,
+        rpc_recorder: Option<Arc<RpcRecorder>>,
     ) -> anyhow::Result<Arc<dyn EthereumProvider>> {
-        let provider = ethereum::AlloyEthereumProvider::new(
-            rpc_url,
-            wallet_private_key,
-            max_concurrent_requests,
-            request_timeout_seconds,
-        )
-        .await?;
+        match rpc_recorder {
+            Some(recorder) => {
+                let transport = RecordingTransport::new(Http::new(rpc_url.parse()?), recorder);
+                let provider = ethereum::AlloyEthereumProvider::with_transport(
+                    transport,
+                    wallet_private_key,
+                    max_concurrent_requests,
+                    request_timeout_seconds,
+                )
+                .await?;
+                Self::with_store(provider, tx_store).await
+            }
+            None => {
+                let provider = ethereum::AlloyEthereumProvider::new(
+                    rpc_url,
+                    wallet_private_key,
+                    max_concurrent_requests,
+                    request_timeout_seconds,
+                )
+                .await?;
+                Self::with_store(provider, tx_store).await
+            }
+        }
+    }
+
+    /// Create an Ethereum provider that answers from a recorded RPC fixture, with no
+    /// network. `ignore_params` lists methods matched on name alone.
+    pub async fn create_replay_provider(
+        fixture_path: impl AsRef<Path>,
+        ignore_params: &[&str],
+        wallet_private_key: String,
+    ) -> anyhow::Result<Arc<dyn EthereumProvider>> {
+        let transport = ReplayTransport::from_file(fixture_path)?
+            .ignoring_params(ignore_params.iter().copied());
+        let provider =
+            ethereum::AlloyEthereumProvider::with_transport(transport, wallet_private_key, 10, 30)
+                .await?;
+        Ok(Arc::new(provider))
+    }
+
+    async fn with_store<T: Transport + Clone>(
+        mut provider: ethereum::AlloyEthereumProvider<T>,
+        tx_store: Option<Arc<TransactionStore>>,
+    ) -> anyhow::Result<Arc<dyn EthereumProvider>> {
+        if let Some(store) = tx_store {
+            provider = provider.with_transaction_store(store).await?;
+        }
         Ok(Arc::new(provider))
     }
 
@@ -99,18 +248,22 @@ impl ProviderFactory {
         wallet_private_key: String,
         max_concurrent_requests: usize,
         request_timeout_seconds: u64,
+        tx_store: Option<Arc<TransactionStore>>,
+        rpc_recorder: Option<Arc<RpcRecorder>>,
     ) -> anyhow::Result<Arc<dyn EthereumProvider>> {
         let mut last_err: Option<anyhow::Error> = None;
         for url in rpc_urls {
-            match ethereum::AlloyEthereumProvider::new(
+            match Self::create_ethereum_provider(
                 url.clone(),
                 wallet_private_key.clone(),
                 max_concurrent_requests,
                 request_timeout_seconds,
+                tx_store.clone(),
+                rpc_recorder.clone(),
             )
             .await
             {
-                Ok(provider) => return Ok(Arc::new(provider)),
+                Ok(provider) => return Ok(provider),
                 Err(e) => {
                     last_err = Some(e);
                     continue;
@@ -129,7 +282,6 @@ impl ProviderFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::TokenAmount;
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
@@ -165,6 +317,8 @@ mod tests {
             "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
             10,
             30,
+            None,
+            None,
         )
         .await;
 
@@ -182,6 +336,8 @@ mod tests {
             "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
             10,
             30,
+            None,
+            None,
         )
         .await;
 
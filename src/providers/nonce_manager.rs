@@ -1,16 +1,40 @@
 use crate::types::WalletAddress;
+use alloy::primitives::B256;
 /// Nonce management for sequential transaction ordering
 /// Prevents nonce conflicts in concurrent transaction scenarios
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
+/// Nonce bookkeeping for a single wallet
+#[derive(Debug, Default)]
+struct WalletNonces {
+    /// Next never-allocated nonce
+    next: u64,
+    /// Broadcast transactions by nonce, until they are mined
+    pending: BTreeMap<u64, B256>,
+    /// Nonces below `next` whose broadcast failed, handed out again before `next`
+    released: BTreeSet<u64>,
+    /// Speed-up and cancel transactions, by the hash they superseded
+    replaced: HashMap<B256, B256>,
+}
+
+impl WalletNonces {
+    fn synced_from(chain_nonce: u64) -> Self {
+        Self {
+            next: chain_nonce,
+            ..Self::default()
+        }
+    }
+}
+
 /// Thread-safe nonce manager for Ethereum transactions
 #[derive(Debug)]
 pub struct NonceManager {
-    /// Current nonce for each wallet address
-    nonces: Arc<Mutex<HashMap<WalletAddress, u64>>>,
+    /// Nonce state for each wallet address, synced from the chain on first use
+    nonces: Arc<Mutex<HashMap<WalletAddress, WalletNonces>>>,
 }
 
 impl NonceManager {
@@ -22,51 +46,194 @@ impl NonceManager {
     }
 
     /// Get the next nonce for a wallet address
-    /// This method is thread-safe and ensures sequential nonce allocation
-    pub async fn get_next_nonce(&self, wallet_address: &WalletAddress) -> u64 {
+    /// The first call for a wallet syncs from `fetch_pending_count`, which should return
+    /// `eth_getTransactionCount(wallet, "pending")`. Released nonces are reused first, so a
+    /// failed broadcast never leaves a gap that would block later transactions.
+    pub async fn get_next_nonce<F, Fut>(
+        &self,
+        wallet_address: &WalletAddress,
+        fetch_pending_count: F,
+    ) -> anyhow::Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<u64>>,
+    {
+        // The lock is held across the fetch so concurrent first calls sync only once
         let mut nonces = self.nonces.lock().await;
-        let current_nonce = nonces.get(wallet_address).copied().unwrap_or(0);
-        let next_nonce = current_nonce + 1;
-        nonces.insert(wallet_address.clone(), next_nonce);
+        if !nonces.contains_key(wallet_address) {
+            let chain_nonce = fetch_pending_count().await?;
+            debug!(
+                wallet = %wallet_address.to_hex(),
+                chain_nonce = chain_nonce,
+                "Synchronized nonce with blockchain"
+            );
+            nonces.insert(
+                wallet_address.clone(),
+                WalletNonces::synced_from(chain_nonce),
+            );
+        }
+
+        let state = nonces
+            .get_mut(wallet_address)
+            .expect("wallet nonce state initialized above");
+        let nonce = match state.released.pop_first() {
+            Some(released) => released,
+            None => {
+                state.next += 1;
+                state.next - 1
+            }
+        };
 
         debug!(
             wallet = %wallet_address.to_hex(),
-            nonce = next_nonce,
+            nonce = nonce,
             "Allocated next nonce"
         );
 
-        next_nonce
+        Ok(nonce)
     }
 
     /// Initialize nonce for a wallet address from the blockchain
-    /// Should be called when first connecting to ensure nonce synchronization
+    /// Moves the local nonce forward if the chain's pending count is ahead of it
     pub async fn initialize_nonce(&self, wallet_address: &WalletAddress, blockchain_nonce: u64) {
         let mut nonces = self.nonces.lock().await;
-        let current_local_nonce = nonces.get(wallet_address).copied().unwrap_or(0);
+        let state = nonces.entry(wallet_address.clone()).or_default();
 
-        if blockchain_nonce > current_local_nonce {
-            nonces.insert(wallet_address.clone(), blockchain_nonce);
+        if blockchain_nonce > state.next {
             debug!(
                 wallet = %wallet_address.to_hex(),
                 blockchain_nonce = blockchain_nonce,
-                local_nonce = current_local_nonce,
+                local_nonce = state.next,
                 "Synchronized nonce with blockchain"
             );
-        } else if blockchain_nonce < current_local_nonce {
+            state.next = blockchain_nonce;
+        } else if blockchain_nonce < state.next {
             warn!(
                 wallet = %wallet_address.to_hex(),
                 blockchain_nonce = blockchain_nonce,
-                local_nonce = current_local_nonce,
+                local_nonce = state.next,
                 "Local nonce ahead of blockchain - possible pending transactions"
             );
         }
+        // The chain has already used these, so they can't be handed out again
+        state.released = state.released.split_off(&blockchain_nonce);
+    }
+
+    /// Record the transaction broadcast with an allocated nonce
+    pub async fn record_transaction(
+        &self,
+        wallet_address: &WalletAddress,
+        nonce: u64,
+        tx_hash: B256,
+    ) {
+        let mut nonces = self.nonces.lock().await;
+        let state = nonces.entry(wallet_address.clone()).or_default();
+        state.pending.insert(nonce, tx_hash);
+    }
+
+    /// Record a speed-up or cancel broadcast at the nonce of `original`
+    pub async fn record_replacement(
+        &self,
+        wallet_address: &WalletAddress,
+        nonce: u64,
+        original: B256,
+        replacement: B256,
+    ) {
+        let mut nonces = self.nonces.lock().await;
+        let state = nonces.entry(wallet_address.clone()).or_default();
+        state.pending.insert(nonce, replacement);
+        state.replaced.insert(original, replacement);
+    }
+
+    /// The transaction that superseded `tx_hash`, if it was sped up or cancelled
+    pub async fn replaced_by(
+        &self,
+        wallet_address: &WalletAddress,
+        tx_hash: &B256,
+    ) -> Option<B256> {
+        let nonces = self.nonces.lock().await;
+        nonces
+            .get(wallet_address)
+            .and_then(|state| state.replaced.get(tx_hash).copied())
+    }
+
+    /// Nonce of a transaction broadcast by this manager and not yet mined, following
+    /// replacements so a superseded hash resolves to the nonce it shared
+    pub async fn nonce_of(&self, wallet_address: &WalletAddress, tx_hash: &B256) -> Option<u64> {
+        let nonces = self.nonces.lock().await;
+        let state = nonces.get(wallet_address)?;
+
+        let mut current = *tx_hash;
+        // Bounded walk, in case of a (never expected) replacement cycle
+        for _ in 0..=state.replaced.len() {
+            if let Some((nonce, _)) = state.pending.iter().find(|(_, hash)| **hash == current) {
+                return Some(*nonce);
+            }
+            current = *state.replaced.get(&current)?;
+        }
+        None
+    }
+
+    /// Return an allocated nonce whose transaction was never broadcast
+    pub async fn release_nonce(&self, wallet_address: &WalletAddress, nonce: u64) {
+        let mut nonces = self.nonces.lock().await;
+        let Some(state) = nonces.get_mut(wallet_address) else {
+            return;
+        };
+        if nonce >= state.next || state.pending.contains_key(&nonce) {
+            return;
+        }
+
+        if nonce + 1 == state.next {
+            // Highest allocation: roll back, along with any released nonces just below it
+            state.next = nonce;
+            while state.next > 0 && state.released.remove(&(state.next - 1)) {
+                state.next -= 1;
+            }
+        } else {
+            state.released.insert(nonce);
+        }
+
+        debug!(
+            wallet = %wallet_address.to_hex(),
+            nonce = nonce,
+            "Released unused nonce"
+        );
+    }
+
+    /// Resynchronize with the chain's pending transaction count after a nonce conflict
+    /// (e.g. "nonce too low" because another client used the wallet). The chain is
+    /// treated as the source of truth; the next allocation returns `blockchain_nonce`.
+    pub async fn resync_nonce(&self, wallet_address: &WalletAddress, blockchain_nonce: u64) {
+        let mut nonces = self.nonces.lock().await;
+        let state = nonces.entry(wallet_address.clone()).or_default();
+
+        warn!(
+            wallet = %wallet_address.to_hex(),
+            blockchain_nonce = blockchain_nonce,
+            local_nonce = state.next,
+            "Handling nonce conflict - resynchronizing"
+        );
+
+        // Anything tracked at or above the pending count is unknown to the node (dropped)
+        state.next = blockchain_nonce;
+        state.released.clear();
+        state.pending.split_off(&blockchain_nonce);
+    }
+
+    /// Stop tracking transactions below the wallet's mined (`latest`) transaction count
+    pub async fn prune_confirmed(&self, wallet_address: &WalletAddress, mined_nonce: u64) {
+        let mut nonces = self.nonces.lock().await;
+        if let Some(state) = nonces.get_mut(wallet_address) {
+            state.pending = state.pending.split_off(&mined_nonce);
+        }
     }
 
     /// Reset nonce for a wallet address (use with caution)
     /// This should only be used in error recovery scenarios
     pub async fn reset_nonce(&self, wallet_address: &WalletAddress, new_nonce: u64) {
         let mut nonces = self.nonces.lock().await;
-        nonces.insert(wallet_address.clone(), new_nonce);
+        nonces.insert(wallet_address.clone(), WalletNonces::synced_from(new_nonce));
 
         warn!(
             wallet = %wallet_address.to_hex(),
@@ -75,32 +242,31 @@ impl NonceManager {
         );
     }
 
-    /// Get current nonce without incrementing (for read-only operations)
+    /// Get the nonce the next allocation will return, without allocating it
+    /// (`None` until the wallet has been synced)
     pub async fn get_current_nonce(&self, wallet_address: &WalletAddress) -> Option<u64> {
         let nonces = self.nonces.lock().await;
-        nonces.get(wallet_address).copied()
+        nonces
+            .get(wallet_address)
+            .map(|state| state.released.first().copied().unwrap_or(state.next))
     }
 
-    /// Handle nonce conflict by resynchronizing with blockchain
-    /// Returns the corrected nonce that should be used
-    pub async fn handle_nonce_conflict(
-        &self,
-        wallet_address: &WalletAddress,
-        failed_nonce: u64,
-        blockchain_nonce: u64,
-    ) -> u64 {
-        let mut nonces = self.nonces.lock().await;
-
-        warn!(
-            wallet = %wallet_address.to_hex(),
-            failed_nonce = failed_nonce,
-            blockchain_nonce = blockchain_nonce,
-            "Handling nonce conflict - resynchronizing"
-        );
+    /// Transactions broadcast by this manager that the chain has not yet consumed,
+    /// ordered by nonce
+    pub async fn pending_transactions(&self, wallet_address: &WalletAddress) -> Vec<(u64, B256)> {
+        let nonces = self.nonces.lock().await;
+        nonces
+            .get(wallet_address)
+            .map(|state| state.pending.iter().map(|(n, h)| (*n, *h)).collect())
+            .unwrap_or_default()
+    }
 
-        // Use the blockchain nonce as the source of truth
-        nonces.insert(wallet_address.clone(), blockchain_nonce);
-        blockchain_nonce + 1
+    /// Whether a node rejected a transaction because its nonce was already used
+    pub fn is_nonce_too_low(error: &str) -> bool {
+        let error = error.to_lowercase();
+        error.contains("nonce too low")
+            || error.contains("oldnonce")
+            || error.contains("nonce has already been used")
     }
 }
 
@@ -115,36 +281,95 @@ mod tests {
     use super::*;
     use crate::types::WalletAddress;
 
+    fn wallet() -> WalletAddress {
+        WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap()
+    }
+
+    async fn chain_count(count: u64) -> anyhow::Result<u64> {
+        Ok(count)
+    }
+
     #[tokio::test]
     async fn test_nonce_allocation() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
+
+        let nonce1 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let nonce2 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let nonce3 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+
+        // A fresh account's first transaction uses nonce 0
+        assert_eq!(nonce1, 0);
+        assert_eq!(nonce2, 1);
+        assert_eq!(nonce3, 2);
+    }
+
+    #[tokio::test]
+    async fn test_first_use_syncs_from_chain_once() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        let first = manager
+            .get_next_nonce(&wallet, || chain_count(7))
+            .await
+            .unwrap();
+        let second = manager
+            .get_next_nonce(&wallet, || async {
+                panic!("pending count should only be fetched on first use")
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(first, 7);
+        assert_eq!(second, 8);
+    }
+
+    #[tokio::test]
+    async fn test_failed_sync_allocates_nothing() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
 
-        let nonce1 = manager.get_next_nonce(&wallet).await;
-        let nonce2 = manager.get_next_nonce(&wallet).await;
-        let nonce3 = manager.get_next_nonce(&wallet).await;
+        let result = manager
+            .get_next_nonce(&wallet, || async { anyhow::bail!("rpc down") })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(manager.get_current_nonce(&wallet).await, None);
 
-        assert_eq!(nonce1, 1);
-        assert_eq!(nonce2, 2);
-        assert_eq!(nonce3, 3);
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(3))
+            .await
+            .unwrap();
+        assert_eq!(nonce, 3);
     }
 
     #[tokio::test]
     async fn test_nonce_initialization() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Initialize with blockchain nonce
         manager.initialize_nonce(&wallet, 10).await;
 
-        let next_nonce = manager.get_next_nonce(&wallet).await;
-        assert_eq!(next_nonce, 11);
+        let next_nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(next_nonce, 10);
     }
 
     #[tokio::test]
     async fn test_concurrent_nonce_allocation() {
         let manager = Arc::new(NonceManager::new());
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         let mut handles = vec![];
 
@@ -152,8 +377,12 @@ mod tests {
         for _ in 0..10 {
             let manager_clone = manager.clone();
             let wallet_clone = wallet.clone();
-            let handle =
-                tokio::spawn(async move { manager_clone.get_next_nonce(&wallet_clone).await });
+            let handle = tokio::spawn(async move {
+                manager_clone
+                    .get_next_nonce(&wallet_clone, || chain_count(5))
+                    .await
+                    .unwrap()
+            });
             handles.push(handle);
         }
 
@@ -162,80 +391,267 @@ mod tests {
             results.push(handle.await.unwrap());
         }
 
-        // Sort results and verify they are sequential
+        // Sort results and verify they are sequential from the chain's count
         results.sort();
         for (i, nonce) in results.iter().enumerate() {
-            assert_eq!(*nonce, (i + 1) as u64);
+            assert_eq!(*nonce, 5 + i as u64);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_release_highest_nonce_rolls_back() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(4))
+            .await
+            .unwrap();
+        manager.release_nonce(&wallet, nonce).await;
+
+        assert_eq!(manager.get_current_nonce(&wallet).await, Some(4));
+        let retry = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(retry, 4);
+    }
+
+    #[tokio::test]
+    async fn test_released_gap_is_reused_first() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        let n0 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let n1 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let n2 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        manager
+            .record_transaction(&wallet, n0, B256::repeat_byte(0))
+            .await;
+        manager
+            .record_transaction(&wallet, n2, B256::repeat_byte(2))
+            .await;
+
+        // n1's broadcast failed while n2 is in flight: n1 must be filled next
+        manager.release_nonce(&wallet, n1).await;
+        let next = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(next, n1);
+        let after = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(after, 3);
+    }
+
+    #[tokio::test]
+    async fn test_release_cascades_through_released_nonces() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        for _ in 0..3 {
+            manager
+                .get_next_nonce(&wallet, || chain_count(0))
+                .await
+                .unwrap();
         }
+        manager.release_nonce(&wallet, 1).await;
+        manager.release_nonce(&wallet, 2).await;
+
+        // 2 was the highest allocation, and 1 just below it was also unused
+        let next = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(next, 1);
+        let after = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(after, 2);
+    }
+
+    #[tokio::test]
+    async fn test_release_ignores_broadcast_nonce() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        manager
+            .record_transaction(&wallet, nonce, B256::repeat_byte(1))
+            .await;
+        manager.release_nonce(&wallet, nonce).await;
+
+        assert_eq!(manager.get_current_nonce(&wallet).await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_pending_transaction_tracking() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        for hash_byte in [0xa0, 0xa1, 0xa2] {
+            let nonce = manager
+                .get_next_nonce(&wallet, || chain_count(10))
+                .await
+                .unwrap();
+            manager
+                .record_transaction(&wallet, nonce, B256::repeat_byte(hash_byte))
+                .await;
+        }
+        assert_eq!(
+            manager.pending_transactions(&wallet).await,
+            vec![
+                (10, B256::repeat_byte(0xa0)),
+                (11, B256::repeat_byte(0xa1)),
+                (12, B256::repeat_byte(0xa2)),
+            ]
+        );
+
+        // Once a transaction is mined it is no longer tracked
+        manager.prune_confirmed(&wallet, 12).await;
+        assert_eq!(
+            manager.pending_transactions(&wallet).await,
+            vec![(12, B256::repeat_byte(0xa2))]
+        );
     }
 
     #[tokio::test]
     async fn test_nonce_conflict_handling() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Simulate a nonce conflict scenario
         manager.initialize_nonce(&wallet, 5).await;
-        let _nonce1 = manager.get_next_nonce(&wallet).await; // Should be 6
-        let _nonce2 = manager.get_next_nonce(&wallet).await; // Should be 7
+        let nonce1 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let _nonce2 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        manager
+            .record_transaction(&wallet, nonce1, B256::repeat_byte(5))
+            .await;
+
+        // Another client used 5..=7, so the chain reports a pending count of 8
+        manager.resync_nonce(&wallet, 8).await;
+
+        // Next nonce continues from the chain's count
+        let next_nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(next_nonce, 8);
+        assert_eq!(
+            manager.pending_transactions(&wallet).await,
+            vec![(5, B256::repeat_byte(5))]
+        );
+    }
 
-        // Simulate blockchain showing nonce 5 (transaction failed)
-        let corrected_nonce = manager.handle_nonce_conflict(&wallet, 6, 5).await;
-        assert_eq!(corrected_nonce, 6);
+    #[tokio::test]
+    async fn test_resync_backwards_drops_stale_pending() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+
+        for hash_byte in [1, 2, 3] {
+            let nonce = manager
+                .get_next_nonce(&wallet, || chain_count(0))
+                .await
+                .unwrap();
+            manager
+                .record_transaction(&wallet, nonce, B256::repeat_byte(hash_byte))
+                .await;
+        }
 
-        // Next nonce should continue from corrected value
-        let next_nonce = manager.get_next_nonce(&wallet).await;
-        assert_eq!(next_nonce, 6);
+        // Nonces 1 and 2 were dropped from the mempool
+        manager.resync_nonce(&wallet, 1).await;
+        assert_eq!(
+            manager.pending_transactions(&wallet).await,
+            vec![(0, B256::repeat_byte(1))]
+        );
+        assert_eq!(manager.get_current_nonce(&wallet).await, Some(1));
     }
 
     #[tokio::test]
     async fn test_reset_nonce() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Allocate some nonces
-        let _nonce1 = manager.get_next_nonce(&wallet).await;
-        let _nonce2 = manager.get_next_nonce(&wallet).await;
+        let _nonce1 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        let _nonce2 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
 
         // Reset to a specific nonce
         manager.reset_nonce(&wallet, 10).await;
 
-        // Next nonce should be 11
-        let next_nonce = manager.get_next_nonce(&wallet).await;
-        assert_eq!(next_nonce, 11);
+        // Next nonce should be 10
+        let next_nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(next_nonce, 10);
     }
 
     #[tokio::test]
     async fn test_get_current_nonce() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Initially, no nonce should exist
         assert_eq!(manager.get_current_nonce(&wallet).await, None);
 
         // After getting a nonce
-        let nonce = manager.get_next_nonce(&wallet).await;
-        assert_eq!(nonce, 1);
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(nonce, 0);
 
-        // Current nonce should be 1
+        // Current nonce should be the next to hand out
         assert_eq!(manager.get_current_nonce(&wallet).await, Some(1));
 
         // Get another nonce
-        let nonce2 = manager.get_next_nonce(&wallet).await;
-        assert_eq!(nonce2, 2);
-
-        // Current nonce should now be 2
+        let nonce2 = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(nonce2, 1);
         assert_eq!(manager.get_current_nonce(&wallet).await, Some(2));
     }
 
     #[tokio::test]
     async fn test_initialize_nonce_local_ahead() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Set local nonce ahead
         manager.initialize_nonce(&wallet, 10).await;
-        let _nonce = manager.get_next_nonce(&wallet).await; // Local is now 11
+        let _nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap(); // Local is now 11
 
         // Try to initialize with lower blockchain nonce
         manager.initialize_nonce(&wallet, 5).await;
@@ -248,7 +664,7 @@ mod tests {
     #[tokio::test]
     async fn test_initialize_nonce_equal() {
         let manager = NonceManager::new();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
         // Set local nonce
         manager.initialize_nonce(&wallet, 10).await;
@@ -263,34 +679,96 @@ mod tests {
     #[tokio::test]
     async fn test_default_nonce_manager() {
         let manager = NonceManager::default();
-        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet = wallet();
 
-        let nonce = manager.get_next_nonce(&wallet).await;
-        assert_eq!(nonce, 1);
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(0))
+            .await
+            .unwrap();
+        assert_eq!(nonce, 0);
     }
 
     #[tokio::test]
     async fn test_multiple_wallets() {
         let manager = NonceManager::new();
-        let wallet1 =
-            WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+        let wallet1 = wallet();
         let wallet2 =
             WalletAddress::from_hex("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
 
-        // Get nonces for both wallets
-        let nonce1_w1 = manager.get_next_nonce(&wallet1).await;
-        let nonce1_w2 = manager.get_next_nonce(&wallet2).await;
+        // Each wallet syncs from its own pending count
+        let nonce1_w1 = manager
+            .get_next_nonce(&wallet1, || chain_count(0))
+            .await
+            .unwrap();
+        let nonce1_w2 = manager
+            .get_next_nonce(&wallet2, || chain_count(3))
+            .await
+            .unwrap();
 
-        // Both should start at 1
-        assert_eq!(nonce1_w1, 1);
-        assert_eq!(nonce1_w2, 1);
+        assert_eq!(nonce1_w1, 0);
+        assert_eq!(nonce1_w2, 3);
 
         // Get second nonces
-        let nonce2_w1 = manager.get_next_nonce(&wallet1).await;
-        let nonce2_w2 = manager.get_next_nonce(&wallet2).await;
+        let nonce2_w1 = manager
+            .get_next_nonce(&wallet1, || chain_count(0))
+            .await
+            .unwrap();
+        let nonce2_w2 = manager
+            .get_next_nonce(&wallet2, || chain_count(0))
+            .await
+            .unwrap();
+
+        assert_eq!(nonce2_w1, 1);
+        assert_eq!(nonce2_w2, 4);
+    }
 
-        // Both should be 2
-        assert_eq!(nonce2_w1, 2);
-        assert_eq!(nonce2_w2, 2);
+    #[test]
+    fn test_is_nonce_too_low() {
+        assert!(NonceManager::is_nonce_too_low(
+            "server returned an error response: error code -32000: nonce too low"
+        ));
+        assert!(NonceManager::is_nonce_too_low(
+            "Nonce too low: next nonce 8, tx nonce 5"
+        ));
+        assert!(NonceManager::is_nonce_too_low("OldNonce"));
+        assert!(!NonceManager::is_nonce_too_low(
+            "insufficient funds for gas * price + value"
+        ));
+        assert!(!NonceManager::is_nonce_too_low("nonce too high"));
+    }
+
+    #[tokio::test]
+    async fn test_replacement_tracking() {
+        let manager = NonceManager::new();
+        let wallet = wallet();
+        let (original, sped_up, cancelled) = (
+            B256::repeat_byte(1),
+            B256::repeat_byte(2),
+            B256::repeat_byte(3),
+        );
+
+        let nonce = manager
+            .get_next_nonce(&wallet, || chain_count(4))
+            .await
+            .unwrap();
+        manager.record_transaction(&wallet, nonce, original).await;
+        manager
+            .record_replacement(&wallet, nonce, original, sped_up)
+            .await;
+        manager
+            .record_replacement(&wallet, nonce, sped_up, cancelled)
+            .await;
+
+        assert_eq!(manager.replaced_by(&wallet, &original).await, Some(sped_up));
+        assert_eq!(manager.replaced_by(&wallet, &cancelled).await, None);
+        // Every hash in the chain resolves to the shared nonce
+        for hash in [original, sped_up, cancelled] {
+            assert_eq!(manager.nonce_of(&wallet, &hash).await, Some(4));
+        }
+        assert_eq!(
+            manager.pending_transactions(&wallet).await,
+            vec![(4, cancelled)]
+        );
+        assert_eq!(manager.nonce_of(&wallet, &B256::repeat_byte(9)).await, None);
     }
 }
@@ -0,0 +1,437 @@
+/// Record-and-replay JSON-RPC fixtures
+/// `RecordingTransport` wraps a live transport and appends every request/response pair to
+/// a JSON-lines fixture; `ReplayTransport` serves a fixture back with no network, so a
+/// captured run of `AlloyEthereumProvider` becomes a deterministic regression test.
+use alloy::rpc::json_rpc::{Id, RequestPacket, ResponsePacket, SerializedRequest};
+use alloy::transports::{Transport, TransportError, TransportErrorKind, TransportFut};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tower::Service;
+use tracing::{info, warn};
+
+/// What the node answered: `{"result": ...}` or `{"error": {...}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOutcome {
+    Result(Value),
+    Error(Value),
+}
+
+/// One recorded JSON-RPC call, a line of a fixture file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcExchange {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(flatten)]
+    pub outcome: RpcOutcome,
+}
+
+impl RpcExchange {
+    /// Read a fixture file, one exchange per line
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Vec<Self>> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read RPC fixture {}: {}", path.display(), e))?;
+        Self::parse(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid RPC fixture {}: {}", path.display(), e))
+    }
+
+    /// Parse fixture contents, skipping blank lines
+    pub fn parse(contents: &str) -> anyhow::Result<Vec<Self>> {
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| anyhow::anyhow!("line {}: {}", index + 1, e))
+            })
+            .collect()
+    }
+}
+
+/// Method and params of each request in a packet, by request id
+fn requests_by_id(packet: &RequestPacket) -> HashMap<Id, (String, Value)> {
+    let describe = |request: &SerializedRequest| {
+        let params = request
+            .params()
+            .and_then(|params| serde_json::from_str(params.get()).ok())
+            .unwrap_or(Value::Null);
+        (request.id().clone(), (request.method().to_string(), params))
+    };
+    match packet {
+        RequestPacket::Single(request) => [describe(request)].into_iter().collect(),
+        RequestPacket::Batch(requests) => requests.iter().map(describe).collect(),
+    }
+}
+
+/// Append-only JSON-lines fixture being recorded
+#[derive(Debug)]
+pub struct RpcRecorder {
+    path: PathBuf,
+    file: tokio::sync::Mutex<File>,
+}
+
+impl RpcRecorder {
+    /// Start a new recording at `path`, replacing any previous one
+    pub async fn create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to create RPC fixture directory {}: {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to create RPC fixture {}: {}", path.display(), e)
+            })?;
+        info!(path = %path.display(), "Recording RPC traffic");
+        Ok(Self {
+            path,
+            file: tokio::sync::Mutex::new(file),
+        })
+    }
+
+    /// Location of the fixture file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the exchanges of one request packet and its response
+    async fn record(
+        &self,
+        requests: &HashMap<Id, (String, Value)>,
+        response: &ResponsePacket,
+    ) -> anyhow::Result<()> {
+        let responses = match response {
+            ResponsePacket::Single(response) => std::slice::from_ref(response),
+            ResponsePacket::Batch(responses) => responses.as_slice(),
+        };
+        let mut lines = Vec::new();
+        for response in responses {
+            let Some((method, params)) = requests.get(&response.id) else {
+                continue;
+            };
+            let Value::Object(mut body) = serde_json::to_value(response)? else {
+                continue;
+            };
+            let outcome = match (body.remove("result"), body.remove("error")) {
+                (Some(result), _) => RpcOutcome::Result(result),
+                (_, Some(error)) => RpcOutcome::Error(error),
+                _ => continue,
+            };
+            let exchange = RpcExchange {
+                method: method.clone(),
+                params: params.clone(),
+                outcome,
+            };
+            lines.extend(serde_json::to_vec(&exchange)?);
+            lines.push(b'\n');
+        }
+        let mut file = self.file.lock().await;
+        file.write_all(&lines).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// Transport that forwards to `inner` and records every answered call
+#[derive(Debug, Clone)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    recorder: Arc<RpcRecorder>,
+}
+
+impl<T> RecordingTransport<T> {
+    pub fn new(inner: T, recorder: Arc<RpcRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<T: Transport + Clone> Service<RequestPacket> for RecordingTransport<T> {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let requests = requests_by_id(&request);
+        let recorder = self.recorder.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            // A failed write loses a fixture line, not the call
+            if let Err(e) = recorder.record(&requests, &response).await {
+                warn!(path = %recorder.path().display(), "Failed to record RPC exchange: {}", e);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    exchanges: Vec<RpcExchange>,
+    /// Methods matched on name alone, for params that change every run
+    ignore_params: HashSet<String>,
+    /// Responses already served per request key
+    served: HashMap<String, usize>,
+}
+
+impl ReplayState {
+    fn key(&self, method: &str, params: &Value) -> String {
+        if self.ignore_params.contains(method) {
+            method.to_string()
+        } else {
+            format!("{}{}", method, params)
+        }
+    }
+
+    /// The next recorded answer to a request. Repeats of a request are answered in
+    /// recorded order, and the last answer keeps being served once they run out, so
+    /// polling loops settle on the final recorded state.
+    fn next(&mut self, method: &str, params: &Value) -> Option<RpcOutcome> {
+        let key = self.key(method, params);
+        let candidates: Vec<&RpcExchange> = self
+            .exchanges
+            .iter()
+            .filter(|exchange| self.key(&exchange.method, &exchange.params) == key)
+            .collect();
+        let served = self.served.entry(key).or_default();
+        let exchange = candidates
+            .get(*served)
+            .or_else(|| candidates.last())?
+            .outcome
+            .clone();
+        *served += 1;
+        Some(exchange)
+    }
+}
+
+/// Transport that answers from a recorded fixture, matching on method and params
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<RpcExchange>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                exchanges,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Replay the fixture file at `path`
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(Self::new(RpcExchange::load(path)?))
+    }
+
+    /// Match these methods on name alone. Use for calls whose params differ between
+    /// runs, such as `eth_sendRawTransaction` or calls carrying a swap deadline.
+    pub fn ignoring_params<I, S>(self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.state()
+            .ignore_params
+            .extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn respond(&self, request: &SerializedRequest) -> Result<Value, TransportError> {
+        let params = request
+            .params()
+            .and_then(|params| serde_json::from_str(params.get()).ok())
+            .unwrap_or(Value::Null);
+        let outcome = self
+            .state()
+            .next(request.method(), &params)
+            .ok_or_else(|| {
+                TransportErrorKind::custom_str(&format!(
+                    "No recorded response for {} {}",
+                    request.method(),
+                    params
+                ))
+            })?;
+        let mut response = json!({"jsonrpc": "2.0", "id": request.id()});
+        match outcome {
+            RpcOutcome::Result(result) => response["result"] = result,
+            RpcOutcome::Error(error) => response["error"] = error,
+        }
+        Ok(response)
+    }
+
+    fn replay(&self, packet: &RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let body = match packet {
+            RequestPacket::Single(request) => self.respond(request)?,
+            RequestPacket::Batch(requests) => Value::Array(
+                requests
+                    .iter()
+                    .map(|request| self.respond(request))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        serde_json::from_str(&body.to_string()).map_err(TransportErrorKind::custom)
+    }
+}
+
+impl Service<RequestPacket> for ReplayTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let response = self.replay(&request);
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(method: &str, params: Value, outcome: RpcOutcome) -> RpcExchange {
+        RpcExchange {
+            method: method.to_string(),
+            params,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_exchange_round_trips_as_json_line() {
+        let success = exchange(
+            "eth_getBalance",
+            json!(["0x0000000000000000000000000000000000000001", "latest"]),
+            RpcOutcome::Result(json!("0x10")),
+        );
+        let line = serde_json::to_string(&success).unwrap();
+        assert_eq!(
+            line,
+            r#"{"method":"eth_getBalance","params":["0x0000000000000000000000000000000000000001","latest"],"result":"0x10"}"#
+        );
+
+        let failure = r#"{"method":"eth_call","params":[{}],"error":{"code":3,"message":"execution reverted"}}"#;
+        let parsed = RpcExchange::parse(&format!("{}\n\n{}\n", line, failure)).unwrap();
+        assert_eq!(parsed[0], success);
+        assert_eq!(
+            parsed[1].outcome,
+            RpcOutcome::Error(json!({"code": 3, "message": "execution reverted"}))
+        );
+        assert!(RpcExchange::parse("{\"method\":").is_err());
+    }
+
+    #[test]
+    fn test_replay_serves_repeats_in_order_then_the_last() {
+        let mut state = ReplayState {
+            exchanges: vec![
+                exchange(
+                    "eth_blockNumber",
+                    json!([]),
+                    RpcOutcome::Result(json!("0x1")),
+                ),
+                exchange("eth_chainId", json!([]), RpcOutcome::Result(json!("0x1"))),
+                exchange(
+                    "eth_blockNumber",
+                    json!([]),
+                    RpcOutcome::Result(json!("0x2")),
+                ),
+            ],
+            ..Default::default()
+        };
+        let block = |state: &mut ReplayState| state.next("eth_blockNumber", &json!([]));
+        assert_eq!(block(&mut state), Some(RpcOutcome::Result(json!("0x1"))));
+        assert_eq!(block(&mut state), Some(RpcOutcome::Result(json!("0x2"))));
+        assert_eq!(block(&mut state), Some(RpcOutcome::Result(json!("0x2"))));
+        assert_eq!(state.next("eth_gasPrice", &json!([])), None);
+    }
+
+    #[test]
+    fn test_replay_matches_params_unless_ignored() {
+        let recorded = vec![exchange(
+            "eth_sendRawTransaction",
+            json!(["0xaa"]),
+            RpcOutcome::Result(json!("0xhash")),
+        )];
+        let mut strict = ReplayState {
+            exchanges: recorded.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            strict.next("eth_sendRawTransaction", &json!(["0xbb"])),
+            None
+        );
+
+        let mut loose = ReplayState {
+            exchanges: recorded,
+            ignore_params: ["eth_sendRawTransaction".to_string()].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            loose.next("eth_sendRawTransaction", &json!(["0xbb"])),
+            Some(RpcOutcome::Result(json!("0xhash")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recorder_writes_one_line_per_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/fixture.jsonl");
+        let recorder = RpcRecorder::create(&path).await.unwrap();
+
+        let requests: HashMap<Id, (String, Value)> = [
+            (Id::Number(1), ("eth_chainId".to_string(), json!([]))),
+            (
+                Id::Number(2),
+                ("eth_call".to_string(), json!([{"to": "0x01"}])),
+            ),
+        ]
+        .into();
+        let response: ResponsePacket = serde_json::from_str(
+            r#"[{"jsonrpc":"2.0","id":2,"error":{"code":3,"message":"execution reverted"}},
+                {"jsonrpc":"2.0","id":1,"result":"0x1"}]"#,
+        )
+        .unwrap();
+        recorder.record(&requests, &response).await.unwrap();
+
+        let recorded = RpcExchange::load(&path).unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].method, "eth_call");
+        assert_eq!(recorded[0].params, json!([{"to": "0x01"}]));
+        assert!(matches!(recorded[0].outcome, RpcOutcome::Error(_)));
+        assert_eq!(
+            recorded[1],
+            exchange("eth_chainId", json!([]), RpcOutcome::Result(json!("0x1")))
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::types::WalletAddress;
+use alloy::primitives::{Bytes, B256};
+use chrono::{DateTime, Utc};
+/// Durable journal of signed transactions
+/// Lets in-flight transactions survive a restart so they can be reconciled with the chain
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Lifecycle of a transaction signed by this server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoredTxStatus {
+    /// Signed and (about to be) broadcast, not yet mined
+    Pending,
+    /// Included in a block (successfully or reverted)
+    Mined,
+    /// Its nonce was used by a different transaction
+    Replaced,
+    /// Never reached, or fell out of, the mempool and could not be rebroadcast
+    Dropped,
+}
+
+/// A signed transaction and what is known about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredTransaction {
+    pub wallet: WalletAddress,
+    pub nonce: u64,
+    pub tx_hash: B256,
+    /// EIP-2718 encoded signed transaction, for rebroadcasting
+    pub raw_transaction: Bytes,
+    pub operation: String,
+    pub status: StoredTxStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct StoreState {
+    /// Pending transactions by hash; finished ones only live in the journal until compaction
+    pending: HashMap<B256, StoredTransaction>,
+    journal: File,
+}
+
+/// Append-only JSON-lines journal of signed transactions
+/// Each line is a full snapshot of a transaction; the last line for a hash wins. The
+/// journal is compacted to the still-pending transactions every time it is opened.
+#[derive(Debug)]
+pub struct TransactionStore {
+    path: PathBuf,
+    state: Mutex<StoreState>,
+}
+
+impl TransactionStore {
+    /// Open (or create) the journal at `path`
+    pub async fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to create transaction store directory {}: {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+
+        let pending = match fs::read_to_string(&path).await {
+            Ok(contents) => Self::replay(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to read transaction store {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+
+        // Compact via a temp file so a crash mid-rewrite never loses the journal
+        let tmp_path = path.with_extension("compacting");
+        let mut compacted = String::new();
+        for tx in Self::sorted(pending.values().cloned()) {
+            compacted.push_str(&serde_json::to_string(&tx)?);
+            compacted.push('\n');
+        }
+        fs::write(&tmp_path, compacted).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        let journal = OpenOptions::new().append(true).open(&path).await?;
+        info!(
+            path = %path.display(),
+            pending = pending.len(),
+            "Transaction store opened"
+        );
+
+        Ok(Self {
+            path,
+            state: Mutex::new(StoreState { pending, journal }),
+        })
+    }
+
+    /// Location of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Persist a transaction snapshot; returns once it has been flushed to disk
+    pub async fn record(&self, tx: StoredTransaction) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        Self::append(&mut state.journal, &tx).await?;
+        debug!(tx_hash = %tx.tx_hash, nonce = tx.nonce, status = ?tx.status, "Transaction recorded");
+
+        if tx.status == StoredTxStatus::Pending {
+            state.pending.insert(tx.tx_hash, tx);
+        } else {
+            state.pending.remove(&tx.tx_hash);
+        }
+        Ok(())
+    }
+
+    /// Move a pending transaction to `status`; returns false if it isn't pending here
+    pub async fn update_status(
+        &self,
+        tx_hash: &B256,
+        status: StoredTxStatus,
+    ) -> anyhow::Result<bool> {
+        let Some(mut tx) = self.get(tx_hash).await else {
+            return Ok(false);
+        };
+        if tx.status == status {
+            return Ok(true);
+        }
+        tx.status = status;
+        tx.updated_at = Utc::now();
+        self.record(tx).await?;
+        Ok(true)
+    }
+
+    /// A pending transaction by hash
+    pub async fn get(&self, tx_hash: &B256) -> Option<StoredTransaction> {
+        self.state.lock().await.pending.get(tx_hash).cloned()
+    }
+
+    /// Pending transactions sent from `wallet`, ordered by nonce
+    pub async fn pending(&self, wallet: &WalletAddress) -> Vec<StoredTransaction> {
+        let state = self.state.lock().await;
+        Self::sorted(
+            state
+                .pending
+                .values()
+                .filter(|tx| &tx.wallet == wallet)
+                .cloned(),
+        )
+    }
+
+    fn replay(contents: &str) -> HashMap<B256, StoredTransaction> {
+        let mut pending = HashMap::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<StoredTransaction>(line) {
+                Ok(tx) if tx.status == StoredTxStatus::Pending => {
+                    pending.insert(tx.tx_hash, tx);
+                }
+                Ok(tx) => {
+                    pending.remove(&tx.tx_hash);
+                }
+                // A crash mid-append can leave a truncated final line
+                Err(e) => warn!(
+                    line = line_number + 1,
+                    error = %e,
+                    "Skipping unreadable transaction store entry"
+                ),
+            }
+        }
+        pending
+    }
+
+    fn sorted(txs: impl Iterator<Item = StoredTransaction>) -> Vec<StoredTransaction> {
+        let mut txs: Vec<_> = txs.collect();
+        txs.sort_by_key(|tx| (tx.nonce, tx.updated_at));
+        txs
+    }
+
+    async fn append(journal: &mut File, tx: &StoredTransaction) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(tx)?;
+        line.push(b'\n');
+        journal
+            .write_all(&line)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to write transaction store: {}", e))?;
+        journal
+            .sync_data()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to sync transaction store: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet() -> WalletAddress {
+        WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap()
+    }
+
+    fn pending_tx(nonce: u64) -> StoredTransaction {
+        StoredTransaction {
+            wallet: wallet(),
+            nonce,
+            tx_hash: B256::repeat_byte(nonce as u8 + 1),
+            raw_transaction: Bytes::from(vec![0x02, nonce as u8]),
+            operation: "swap".to_string(),
+            status: StoredTxStatus::Pending,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_transactions_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("transactions.jsonl");
+
+        let (first, second) = (pending_tx(0), pending_tx(1));
+        let store = TransactionStore::open(&path).await.unwrap();
+        store.record(second.clone()).await.unwrap();
+        store.record(first.clone()).await.unwrap();
+        drop(store);
+
+        let reopened = TransactionStore::open(&path).await.unwrap();
+        assert_eq!(reopened.pending(&wallet()).await, vec![first, second]);
+    }
+
+    #[tokio::test]
+    async fn test_finished_transactions_are_compacted_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transactions.jsonl");
+
+        let store = TransactionStore::open(&path).await.unwrap();
+        let mined = pending_tx(0);
+        store.record(mined.clone()).await.unwrap();
+        store.record(pending_tx(1)).await.unwrap();
+        assert!(store
+            .update_status(&mined.tx_hash, StoredTxStatus::Mined)
+            .await
+            .unwrap());
+        assert!(store.get(&mined.tx_hash).await.is_none());
+        drop(store);
+
+        let reopened = TransactionStore::open(&path).await.unwrap();
+        let pending = reopened.pending(&wallet()).await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].nonce, 1);
+
+        let journal = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(journal.lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_line_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transactions.jsonl");
+        let mut journal = serde_json::to_string(&pending_tx(0)).unwrap();
+        journal.push_str("\n{\"wallet\":\"0x74");
+        std::fs::write(&path, journal).unwrap();
+
+        let store = TransactionStore::open(&path).await.unwrap();
+        assert_eq!(store.pending(&wallet()).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_status_unknown_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TransactionStore::open(dir.path().join("transactions.jsonl"))
+            .await
+            .unwrap();
+
+        let updated = store
+            .update_status(&B256::repeat_byte(9), StoredTxStatus::Dropped)
+            .await
+            .unwrap();
+        assert!(!updated);
+    }
+
+    #[tokio::test]
+    async fn test_pending_filters_by_wallet() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TransactionStore::open(dir.path().join("transactions.jsonl"))
+            .await
+            .unwrap();
+        let mut other = pending_tx(3);
+        other.wallet =
+            WalletAddress::from_hex("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+
+        store.record(pending_tx(0)).await.unwrap();
+        store.record(other).await.unwrap();
+
+        assert_eq!(store.pending(&wallet()).await.len(), 1);
+    }
+}
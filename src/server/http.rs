@@ -24,22 +24,49 @@ fn jr_error(id: Option<&Value>, err: JsonRpcError) -> Json<Value> {
     }
 }
 use crate::server::jsonrpc::{JsonRpcError, JsonRpcResponse};
+
+/// Attach the decoded revert reason, if the failure was a revert, as `error.data`.
+/// A broadcast that may have gone through is spelled out with its hash instead, since
+/// retrying could send the transaction twice.
+fn with_revert_data(err: JsonRpcError, cause: &anyhow::Error) -> JsonRpcError {
+    let unconfirmed = cause
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<crate::types::UnconfirmedBroadcast>());
+    if let Some(unconfirmed) = unconfirmed {
+        return JsonRpcError {
+            message: format!("{}: {}", err.message, unconfirmed),
+            ..err
+        }
+        .with_data(json!({ "transaction_hash": format!("{:#x}", unconfirmed.tx_hash) }));
+    }
+    match crate::types::RevertError::find(cause) {
+        Some(reason) => err.with_data(json!({ "revert_reason": reason })),
+        None => err,
+    }
+}
+use crate::services::allowance::AllowanceServiceTrait;
 use crate::services::balance::BalanceServiceTrait;
+use crate::services::gas::GasServiceTrait;
 use crate::services::price::PriceServiceTrait;
 use crate::services::swap::SwapServiceTrait;
 /// HTTP server implementation with graceful shutdown
 /// Clean separation of transport layer from business logic
 use crate::services::{
-    BalanceService, PriceService, SwapService, TransactionStatusService,
-    TransactionStatusServiceTrait,
+    AllowanceService, BalanceService, GasService, PriceService, SwapService,
+    TransactionStatusService, TransactionStatusServiceTrait,
 };
+use crate::types::{PriceImpactError, SwapRoute, SwapValueError, WaitTarget};
 use axum::{
     extract::{DefaultBodyLimit, State},
-    http::{Method, StatusCode},
-    response::Json,
+    http::{HeaderMap, Method, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
     routing::post,
     Router,
 };
+use futures::StreamExt;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use std::time::Duration;
@@ -52,7 +79,16 @@ use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
+
+/// Default validity window for signed permits
+const DEFAULT_PERMIT_DEADLINE_SECONDS: u64 = 1800;
+/// Upper bound on permit validity so signatures don't linger indefinitely
+const MAX_PERMIT_DEADLINE_SECONDS: u64 = 86_400;
+/// Most confirmations `wait_for_transaction` accepts; use `finalized` beyond that
+const MAX_WAIT_CONFIRMATIONS: u64 = 64;
+/// Headroom left between a non-streaming wait and the HTTP timeout
+const REQUEST_TIMEOUT_MARGIN: Duration = Duration::from_secs(1);
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -61,7 +97,10 @@ pub struct AppState {
     price_service: Arc<PriceService>,
     swap_service: Arc<SwapService>,
     transaction_status_service: Arc<TransactionStatusService>,
-    max_swap_amount: u64,
+    gas_service: Arc<GasService>,
+    allowance_service: Arc<AllowanceService>,
+    /// Set by the server so blocking tools can return before the HTTP timeout fires
+    request_timeout: Option<Duration>,
 }
 
 impl AppState {
@@ -70,14 +109,17 @@ impl AppState {
         price_service: Arc<PriceService>,
         swap_service: Arc<SwapService>,
         transaction_status_service: Arc<TransactionStatusService>,
-        max_swap_amount: u64,
+        gas_service: Arc<GasService>,
+        allowance_service: Arc<AllowanceService>,
     ) -> Self {
         Self {
             balance_service,
             price_service,
             swap_service,
             transaction_status_service,
-            max_swap_amount,
+            gas_service,
+            allowance_service,
+            request_timeout: None,
         }
     }
 }
@@ -132,6 +174,10 @@ impl HttpServer {
                 .allow_headers(Any)
         };
 
+        let state = AppState {
+            request_timeout: Some(Duration::from_secs(http_timeout_seconds)),
+            ..state
+        };
         let router = Router::new()
             .route("/", post(handle_jsonrpc))
             .route("/health", axum::routing::get(health_check))
@@ -158,8 +204,6 @@ impl HttpServer {
             .layer(TraceLayer::new_for_http())
             .with_state(state);
 
-        // Note: max_swap_amount is stored in AppState and used in handlers
-
         Ok(Self { router, host, port })
     }
 
@@ -175,10 +219,19 @@ impl HttpServer {
             .map_err(|e| anyhow::anyhow!("Failed to bind to address {}: {}", addr, e))?;
 
         info!("Server listening on {}", addr);
+        self.serve(listener).await
+    }
 
+    /// Serve on an already bound listener, e.g. one on an ephemeral port
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
         // Note: Request timeouts are handled by tower_governor rate limiter
         // For production, consider adding tower::timeout::Timeout service
-        axum::serve(listener, self.router.clone())
+        // The rate limiter keys on the peer address when no proxy headers are present
+        let service = self
+            .router
+            .clone()
+            .into_make_service_with_connect_info::<std::net::SocketAddr>();
+        axum::serve(listener, service)
             .with_graceful_shutdown(shutdown_signal())
             .await
             .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
@@ -188,9 +241,39 @@ impl HttpServer {
     }
 }
 
+/// JSON-RPC 2.0 entry point. Tools that report progress stream it as server-sent events
+/// when the client accepts `text/event-stream`; everything else is a single JSON response.
+async fn handle_jsonrpc(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Result<Response, StatusCode> {
+    use crate::validation::Validator;
+
+    let streams_progress = accepts_event_stream(&headers)
+        && request.get("method").and_then(|m| m.as_str()) == Some("tools/call")
+        && request.pointer("/params/name").and_then(|n| n.as_str()) == Some("wait_for_transaction")
+        && Validator::validate_jsonrpc_request(&request).is_ok();
+    if streams_progress {
+        return Ok(stream_wait_for_transaction(&state, &request));
+    }
+
+    dispatch_jsonrpc(State(state), Json(request))
+        .await
+        .map(IntoResponse::into_response)
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(axum::http::header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains("text/event-stream"))
+}
+
 /// JSON-RPC 2.0 request handler with enhanced security
 #[instrument(skip(state))]
-async fn handle_jsonrpc(
+async fn dispatch_jsonrpc(
     State(state): State<AppState>,
     Json(request): Json<Value>,
 ) -> Result<Json<Value>, StatusCode> {
@@ -214,8 +297,19 @@ async fn handle_jsonrpc(
                 "tools": [
                     {"name": "get_balance", "description": "Query ETH and ERC20 token balances with proper decimals"},
                     {"name": "get_token_price", "description": "Get current token price in USD or ETH (input: token address or symbol)"},
-                    {"name": "swap_tokens", "description": "Simulate Uniswap token swap via eth_call"},
-                    {"name": "get_transaction_status", "description": "Get the status of a transaction, including confirmations"}
+                    {"name": "swap_tokens", "description": "Simulate a Uniswap V3, Uniswap V2 or Sushiswap swap via eth_call (best route unless `protocol` is set), selling exactly `amount` or buying exactly `amount_out`; returns a short-lived quote_id for execute_swap. `simulate_from` and `override_token_state` dry-run from another account or past a missing balance/approval"},
+                    {"name": "compare_quotes", "description": "Quote selling `amount` of from_token on Uniswap V3, Uniswap V2, Sushiswap, Curve and Balancer, listing each venue's output, gas and net value side by side"},
+                    {"name": "execute_swap", "description": "Sign and broadcast a swap previously simulated by swap_tokens (input: quote_id, optional use_permit)"},
+                    {"name": "wrap_eth", "description": "Wrap `amount` of the wallet's ETH into WETH with a WETH deposit"},
+                    {"name": "unwrap_weth", "description": "Unwrap `amount` of the wallet's WETH back into ETH with a WETH withdraw"},
+                    {"name": "get_transaction_status", "description": "Get the status of a transaction, including confirmations and whether it was replaced or dropped; detail=true adds fees, decoded calldata and Transfer/Swap logs"},
+                    {"name": "wait_for_transaction", "description": "Wait until a transaction has N confirmations or is finalized, returning early if it fails, is dropped or replaced; streams per-block progress when the client accepts text/event-stream"},
+                    {"name": "speed_up_transaction", "description": "Rebroadcast a stuck transaction sent by this server at the same nonce with fees bumped at least 10%"},
+                    {"name": "cancel_transaction", "description": "Cancel a stuck transaction sent by this server with a 0-value self-transfer at the same nonce"},
+                    {"name": "get_gas_fees", "description": "Get EIP-1559 base fee, next-block base fee and slow/normal/fast priority fees from eth_feeHistory"},
+                    {"name": "get_allowance", "description": "Get the ERC20 allowance granted to a spender (defaults: configured wallet as owner, Uniswap V3 router as spender)"},
+                    {"name": "approve_token", "description": "Approve a spender (default: Uniswap V3 router) for an exact token amount; unlimited only with unlimited=true"},
+                    {"name": "sign_permit", "description": "Sign a gasless EIP-2612 permit (or Permit2 with permit2=true) and return the signature and deadline"}
                 ]
             }),
         )),
@@ -241,12 +335,76 @@ async fn handle_jsonrpc(
                     Ok(response) => Ok(response),
                     Err((_, json_response)) => Ok(json_response),
                 },
+                Some("compare_quotes") => {
+                    match handle_compare_quotes(&state, arguments, id).await {
+                        Ok(response) => Ok(response),
+                        Err((_, json_response)) => Ok(json_response),
+                    }
+                }
+                Some(tool @ ("wrap_eth" | "unwrap_weth")) => {
+                    match handle_wrap(&state, arguments, id, tool == "wrap_eth").await {
+                        Ok(response) => Ok(response),
+                        Err((_, json_response)) => Ok(json_response),
+                    }
+                }
                 Some("get_transaction_status") => {
                     match handle_get_transaction_status(&state, arguments, id).await {
                         Ok(response) => Ok(response),
                         Err((_, json_response)) => Ok(json_response),
                     }
                 }
+                Some("wait_for_transaction") => {
+                    match handle_wait_for_transaction(&state, arguments, id).await {
+                        Ok(response) => Ok(response),
+                        Err((_, json_response)) => Ok(json_response),
+                    }
+                }
+                Some("speed_up_transaction") => {
+                    match handle_replace_transaction(
+                        &state,
+                        arguments,
+                        id,
+                        crate::types::ReplacementKind::SpeedUp,
+                    )
+                    .await
+                    {
+                        Ok(response) => Ok(response),
+                        Err((_, json_response)) => Ok(json_response),
+                    }
+                }
+                Some("cancel_transaction") => {
+                    match handle_replace_transaction(
+                        &state,
+                        arguments,
+                        id,
+                        crate::types::ReplacementKind::Cancel,
+                    )
+                    .await
+                    {
+                        Ok(response) => Ok(response),
+                        Err((_, json_response)) => Ok(json_response),
+                    }
+                }
+                Some("execute_swap") => match handle_execute_swap(&state, arguments, id).await {
+                    Ok(response) => Ok(response),
+                    Err((_, json_response)) => Ok(json_response),
+                },
+                Some("get_gas_fees") => match handle_get_gas_fees(&state, id).await {
+                    Ok(response) => Ok(response),
+                    Err((_, json_response)) => Ok(json_response),
+                },
+                Some("get_allowance") => match handle_get_allowance(&state, arguments, id).await {
+                    Ok(response) => Ok(response),
+                    Err((_, json_response)) => Ok(json_response),
+                },
+                Some("approve_token") => match handle_approve_token(&state, arguments, id).await {
+                    Ok(response) => Ok(response),
+                    Err((_, json_response)) => Ok(json_response),
+                },
+                Some("sign_permit") => match handle_sign_permit(&state, arguments, id).await {
+                    Ok(response) => Ok(response),
+                    Err((_, json_response)) => Ok(json_response),
+                },
                 _ => Ok(jr_error(id, JsonRpcError::method_not_found())),
             }
         }
@@ -299,7 +457,9 @@ async fn check_system_health(state: &AppState) -> anyhow::Result<Value> {
         "services": {
             "balance_service": "operational",
             "price_service": "operational",
-            "swap_service": "operational"
+            "swap_service": "operational",
+            "gas_service": "operational",
+            "allowance_service": "operational"
         }
     }))
 }
@@ -591,11 +751,11 @@ async fn handle_get_token_price(
     }
 }
 
-async fn handle_get_transaction_status(
-    state: &AppState,
+/// Parse the required `transaction_hash` argument shared by the transaction tools
+fn parse_transaction_hash_arg(
     arguments: Option<&Value>,
     id: Option<&Value>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<alloy::primitives::B256, (StatusCode, Json<Value>)> {
     use alloy::primitives::B256;
     use std::str::FromStr;
 
@@ -616,16 +776,28 @@ async fn handle_get_transaction_status(
             )
         })?;
 
-    let tx_hash = B256::from_str(tx_hash_str).map_err(|_| {
+    B256::from_str(tx_hash_str).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
             jr_error(id, JsonRpcError::invalid_params("Invalid transaction_hash")),
         )
-    })?;
+    })
+}
+
+async fn handle_get_transaction_status(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let tx_hash = parse_transaction_hash_arg(arguments, id)?;
+    let detail = arguments
+        .and_then(|args| args.get("detail"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
     match state
         .transaction_status_service
-        .get_transaction_status(&tx_hash)
+        .get_transaction_status(&tx_hash, detail)
         .await
     {
         Ok(status_info) => Ok(jr_success(id, json!(status_info))),
@@ -642,183 +814,1117 @@ async fn handle_get_transaction_status(
     }
 }
 
-async fn handle_swap_tokens(
+/// Parsed `wait_for_transaction` arguments: the hash, what to wait for and an optional
+/// timeout (None to wait as long as the server allows)
+type WaitArgs = (alloy::primitives::B256, WaitTarget, Option<Duration>);
+
+fn parse_wait_args(
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<WaitArgs, (StatusCode, Json<Value>)> {
+    let tx_hash = parse_transaction_hash_arg(arguments, id)?;
+    let invalid = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(id, JsonRpcError::invalid_params(message)),
+        )
+    };
+    let arg = |name: &str| arguments.and_then(|args| args.get(name));
+
+    let finalized = match arg("finalized") {
+        None => false,
+        Some(v) => v
+            .as_bool()
+            .ok_or_else(|| invalid("finalized must be a boolean"))?,
+    };
+    let target = match (finalized, arg("confirmations")) {
+        (true, Some(_)) => {
+            return Err(invalid(
+                "Pass either confirmations or finalized=true, not both",
+            ))
+        }
+        (true, None) => WaitTarget::Finalized,
+        (false, None) => WaitTarget::Confirmations(1),
+        (false, Some(v)) => match v.as_u64() {
+            Some(n) if (1..=MAX_WAIT_CONFIRMATIONS).contains(&n) => WaitTarget::Confirmations(n),
+            _ => {
+                return Err(invalid(&format!(
+                    "confirmations must be an integer between 1 and {}",
+                    MAX_WAIT_CONFIRMATIONS
+                )))
+            }
+        },
+    };
+    let timeout = match arg("timeout_seconds") {
+        None => None,
+        Some(v) => match v.as_u64() {
+            Some(seconds) if seconds > 0 => Some(Duration::from_secs(seconds)),
+            _ => return Err(invalid("timeout_seconds must be a positive integer")),
+        },
+    };
+    Ok((tx_hash, target, timeout))
+}
+
+async fn handle_wait_for_transaction(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let (tx_hash, target, timeout) = parse_wait_args(arguments, id)?;
+    let service = &state.transaction_status_service;
+
+    // Without streaming the whole wait has to fit inside the HTTP timeout
+    let mut timeout = timeout.unwrap_or_else(|| service.max_wait());
+    if let Some(request_timeout) = state.request_timeout {
+        timeout = timeout.min(
+            request_timeout
+                .saturating_sub(REQUEST_TIMEOUT_MARGIN)
+                .max(Duration::from_secs(1)),
+        );
+    }
+
+    match service
+        .wait_for_transaction(&tx_hash, target, timeout, None)
+        .await
+    {
+        Ok(result) => Ok(jr_success(id, json!(result))),
+        Err(e) => {
+            error!("Waiting for transaction failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error("Failed to wait for transaction"),
+                ),
+            ))
+        }
+    }
+}
+
+/// Aborts a spawned task once its response stream is dropped, e.g. on client disconnect
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// `wait_for_transaction` as server-sent events: an MCP `notifications/progress` per new
+/// block, then the JSON-RPC response
+fn stream_wait_for_transaction(state: &AppState, request: &Value) -> Response {
+    let id = request.get("id");
+    let arguments = request.pointer("/params/arguments");
+    let (tx_hash, target, timeout) = match parse_wait_args(arguments, id) {
+        Ok(args) => args,
+        Err((_, json_response)) => return json_response.into_response(),
+    };
+    // MCP clients ask for progress with a token; fall back to the request id
+    let progress_token = request
+        .pointer("/params/_meta/progressToken")
+        .or(id)
+        .cloned()
+        .unwrap_or(Value::Null);
+    let id = id.cloned();
+
+    let service = state.transaction_status_service.clone();
+    let timeout = timeout.unwrap_or_else(|| service.max_wait());
+    let (progress_tx, progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let wait = AbortOnDrop(tokio::spawn(async move {
+        service
+            .wait_for_transaction(&tx_hash, target, timeout, Some(progress_tx))
+            .await
+    }));
+
+    // The progress channel closes when the wait finishes, so the response comes last
+    let progress = futures::stream::unfold(progress_rx, |mut rx| async move {
+        rx.recv().await.map(|progress| (progress, rx))
+    })
+    .enumerate()
+    .map(move |(index, progress)| {
+        Event::default().json_data(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {
+                "progressToken": progress_token,
+                "progress": index + 1,
+                "message": format!(
+                    "Block {}: {:?}, {} confirmations",
+                    progress.block_number,
+                    progress.status.status,
+                    progress.status.confirmations
+                ),
+                "block_number": progress.block_number,
+                "status": progress.status,
+            }
+        }))
+    });
+    let response = futures::stream::once(async move {
+        let mut wait = wait;
+        let id = id.as_ref();
+        let response = match (&mut wait.0).await {
+            Ok(Ok(result)) => jr_success(id, json!(result)),
+            Ok(Err(e)) => {
+                error!("Waiting for transaction failed: {}", e);
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error("Failed to wait for transaction"),
+                )
+            }
+            Err(e) => {
+                error!("Wait task ended abnormally: {}", e);
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error("Failed to wait for transaction"),
+                )
+            }
+        };
+        Event::default().json_data(response.0)
+    });
+
+    Sse::new(progress.chain(response))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn handle_replace_transaction(
     state: &AppState,
     arguments: Option<&Value>,
     id: Option<&Value>,
+    kind: crate::types::ReplacementKind,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    use crate::types::{SwapParams, TokenAddress, TokenAmount};
+    use crate::types::ReplacementKind;
+
+    let tx_hash = parse_transaction_hash_arg(arguments, id)?;
+    let result = match kind {
+        ReplacementKind::SpeedUp => {
+            state
+                .transaction_status_service
+                .speed_up_transaction(&tx_hash)
+                .await
+        }
+        ReplacementKind::Cancel => {
+            state
+                .transaction_status_service
+                .cancel_transaction(&tx_hash)
+                .await
+        }
+    };
+
+    match result {
+        Ok(replacement) => Ok(jr_success(id, json!(replacement))),
+        Err(e) => {
+            error!("Transaction replacement failed: {}", e);
+            // Untracked or already-mined transactions are actionable, so the reason is passed through
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error(&format!("Transaction not replaced: {}", e)),
+                ),
+            ))
+        }
+    }
+}
+
+async fn handle_get_gas_fees(
+    state: &AppState,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match state.gas_service.get_gas_fees().await {
+        Ok(fees) => Ok(jr_success(id, json!(fees))),
+        Err(e) => {
+            error!("Gas fee estimation failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error("Failed to estimate gas fees"),
+                ),
+            ))
+        }
+    }
+}
+
+/// Parse the optional `spender` argument shared by the allowance tools
+fn parse_spender_arg(
+    args: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Option<alloy::primitives::Address>, (StatusCode, Json<Value>)> {
+    use crate::validation::Validator;
+
+    match args.and_then(|a| a.get("spender")).and_then(|v| v.as_str()) {
+        Some(spender) => Validator::validate_wallet_address(spender)
+            .map(|addr| Some(addr.address()))
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    jr_error(
+                        id,
+                        JsonRpcError::invalid_params(&format!("Invalid spender: {}", e)),
+                    ),
+                )
+            }),
+        None => Ok(None),
+    }
+}
+
+/// Parse the `amount` / `unlimited` arguments shared by approve_token and sign_permit
+fn parse_approval_amount_args(
+    args: &Value,
+    id: Option<&Value>,
+) -> Result<crate::types::ApprovalAmount, (StatusCode, Json<Value>)> {
+    use crate::types::ApprovalAmount;
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
+    // Exact amounts by default; unlimited approvals must be requested explicitly
+    let unlimited = args
+        .get("unlimited")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    match (args.get("amount").and_then(|v| v.as_str()), unlimited) {
+        (Some(_), true) => Err((
+            StatusCode::BAD_REQUEST,
+            jr_error(
+                id,
+                JsonRpcError::invalid_params("Provide either amount or unlimited=true, not both"),
+            ),
+        )),
+        (None, true) => Ok(ApprovalAmount::Unlimited),
+        (Some(amount_str), false) => {
+            let amount = Decimal::from_str(amount_str)
+                .ok()
+                .filter(|a| !a.is_sign_negative())
+                .ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        jr_error(id, JsonRpcError::invalid_params("Invalid amount format")),
+                    )
+                })?;
+            Ok(ApprovalAmount::Exact(amount))
+        }
+        (None, false) => Err((
+            StatusCode::BAD_REQUEST,
+            jr_error(
+                id,
+                JsonRpcError::invalid_params("Missing amount (or set unlimited=true)"),
+            ),
+        )),
+    }
+}
+
+async fn handle_get_allowance(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use crate::validation::Validator;
+
     let args = arguments.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(json!({
-                "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Missing arguments"},
-                "id": id
-            })),
+            jr_error(id, JsonRpcError::invalid_params("Missing arguments")),
         )
     })?;
 
-    // Parse required arguments
-    let from_token_str = args
-        .get("from_token")
+    let token_str = args
+        .get("token_address")
         .and_then(|v| v.as_str())
         .ok_or_else(|| {
             (
                 StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "jsonrpc": "2.0",
-                    "error": {"code": -32602, "message": "Missing from_token"},
-                    "id": id
-                })),
+                jr_error(id, JsonRpcError::invalid_params("Missing token_address")),
             )
         })?;
+    let token = Validator::validate_token_address(token_str).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(
+                id,
+                JsonRpcError::invalid_params(&format!("Invalid token_address: {}", e)),
+            ),
+        )
+    })?;
 
-    let to_token_str = args
-        .get("to_token")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| {
+    let owner = match args.get("owner").and_then(|v| v.as_str()) {
+        Some(owner) => Some(Validator::validate_wallet_address(owner).map_err(|e| {
             (
                 StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "jsonrpc": "2.0",
-                    "error": {"code": -32602, "message": "Missing to_token"},
-                    "id": id
-                })),
+                jr_error(
+                    id,
+                    JsonRpcError::invalid_params(&format!("Invalid owner: {}", e)),
+                ),
             )
-        })?;
+        })?),
+        None => None,
+    };
+    let spender = parse_spender_arg(arguments, id)?;
+
+    match state
+        .allowance_service
+        .get_allowance(owner.as_ref(), &token, spender.as_ref())
+        .await
+    {
+        Ok(info) => Ok(jr_success(
+            id,
+            json!({
+                "owner": info.owner.to_hex(),
+                "spender": format!("{:#x}", info.spender),
+                "token_address": info.token_address.to_hex(),
+                "allowance": info.allowance.to_human_readable().to_string(),
+                "decimals": info.allowance.decimals,
+                "is_unlimited": info.is_unlimited,
+            }),
+        )),
+        Err(e) => {
+            error!("Allowance query failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(id, JsonRpcError::internal_error("Failed to get allowance")),
+            ))
+        }
+    }
+}
+
+async fn handle_approve_token(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use crate::validation::Validator;
 
-    let amount_str = args.get("amount").and_then(|v| v.as_str()).ok_or_else(|| {
+    let args = arguments.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(json!({
-                "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Missing amount"},
-                "id": id
-            })),
+            jr_error(id, JsonRpcError::invalid_params("Missing arguments")),
         )
     })?;
 
-    // Parse token addresses first
-    let from_token = TokenAddress::from_hex(from_token_str).map_err(|_| {
+    let token_str = args
+        .get("token_address")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                jr_error(id, JsonRpcError::invalid_params("Missing token_address")),
+            )
+        })?;
+    let token = Validator::validate_token_address(token_str).map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
-            Json(json!({
-                "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Invalid from_token"},
-                "id": id
-            })),
+            jr_error(
+                id,
+                JsonRpcError::invalid_params(&format!("Invalid token_address: {}", e)),
+            ),
         )
     })?;
+    let spender = parse_spender_arg(arguments, id)?;
 
-    let to_token = TokenAddress::from_hex(to_token_str).map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Invalid to_token"},
+    let amount = parse_approval_amount_args(args, id)?;
+
+    match state
+        .allowance_service
+        .approve_token(&token, spender.as_ref(), &amount)
+        .await
+    {
+        Ok(approval) => Ok(jr_success(
+            id,
+            json!({
+                "token_address": approval.token_address.to_hex(),
+                "spender": format!("{:#x}", approval.spender),
+                "amount": approval.amount.map(|a| a.to_human_readable().to_string()),
+                "is_unlimited": approval.is_unlimited,
+                "transaction_hash": approval.transaction_hash,
+            }),
+        )),
+        Err(e) => {
+            error!("Token approval failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    with_revert_data(JsonRpcError::internal_error("Failed to approve token"), &e),
+                ),
+            ))
+        }
+    }
+}
+
+async fn handle_sign_permit(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use crate::validation::Validator;
+
+    let args = arguments.ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(id, JsonRpcError::invalid_params("Missing arguments")),
+        )
+    })?;
+
+    let token_str = args
+        .get("token_address")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                jr_error(id, JsonRpcError::invalid_params("Missing token_address")),
+            )
+        })?;
+    let token = Validator::validate_token_address(token_str).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(
+                id,
+                JsonRpcError::invalid_params(&format!("Invalid token_address: {}", e)),
+            ),
+        )
+    })?;
+    let spender = parse_spender_arg(arguments, id)?;
+    let amount = parse_approval_amount_args(args, id)?;
+
+    let deadline_seconds = match args.get("deadline_seconds") {
+        Some(value) => value
+            .as_u64()
+            .filter(|secs| (1..=MAX_PERMIT_DEADLINE_SECONDS).contains(secs))
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    jr_error(
+                        id,
+                        JsonRpcError::invalid_params(&format!(
+                            "deadline_seconds must be between 1 and {}",
+                            MAX_PERMIT_DEADLINE_SECONDS
+                        )),
+                    ),
+                )
+            })?,
+        None => DEFAULT_PERMIT_DEADLINE_SECONDS,
+    };
+    let permit2 = args
+        .get("permit2")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    match state
+        .allowance_service
+        .sign_permit(
+            &token,
+            spender.as_ref(),
+            &amount,
+            Duration::from_secs(deadline_seconds),
+            permit2,
+        )
+        .await
+    {
+        Ok(permit) => Ok(jr_success(
+            id,
+            json!({
+                "kind": permit.kind,
+                "token_address": permit.token_address.to_hex(),
+                "owner": permit.owner.to_hex(),
+                "spender": format!("{:#x}", permit.spender),
+                "value": permit.value.to_string(),
+                "nonce": permit.nonce.to_string(),
+                "deadline": permit.deadline.to_string(),
+                "expiration": permit.expiration,
+                "signature": permit.signature.to_string(),
+                "v": permit.v,
+                "r": permit.r.to_string(),
+                "s": permit.s.to_string(),
+            }),
+        )),
+        Err(e) => {
+            error!("Permit signing failed: {}", e);
+            // Unsupported-token errors explain which flow to use instead
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    JsonRpcError::internal_error(&format!("Failed to sign permit: {}", e)),
+                ),
+            ))
+        }
+    }
+}
+
+async fn handle_execute_swap(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let quote_id = arguments
+        .and_then(|args| args.get("quote_id"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                jr_error(id, JsonRpcError::invalid_params("Missing quote_id")),
+            )
+        })?;
+
+    let use_permit = arguments
+        .and_then(|args| args.get("use_permit"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    match state.swap_service.execute_swap(quote_id, use_permit).await {
+        Ok(execution) => Ok(jr_success(
+            id,
+            json!({
+                "quote_id": execution.quote_id,
+                "transaction_hash": execution.transaction_hash,
+                "estimated_amount_out": execution.estimated_amount_out.to_human_readable().to_string(),
+                "amount_out_minimum": execution.amount_out_minimum.to_human_readable().to_string(),
+            }),
+        )),
+        Err(e) => {
+            if let Some(refusal) = e.downcast_ref::<SwapValueError>() {
+                return Err(swap_value_refusal(refusal, id));
+            }
+            error!("Swap execution failed: {}", e);
+            // Expiry and slippage failures are actionable, so the reason is passed through
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    with_revert_data(
+                        JsonRpcError::internal_error(&format!("Swap not executed: {}", e)),
+                        &e,
+                    ),
+                ),
+            ))
+        }
+    }
+}
+
+/// `wrap_eth` (`wrapping`) or `unwrap_weth`: convert `amount` between the wallet's
+/// ether and WETH
+async fn handle_wrap(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+    wrapping: bool,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let invalid = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(id, JsonRpcError::invalid_params(message)),
+        )
+    };
+    let amount = arguments
+        .and_then(|args| args.get("amount"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| invalid("Missing amount"))?;
+    let amount = Decimal::from_str(amount)
+        .ok()
+        .filter(|amount| *amount > Decimal::ZERO)
+        .ok_or_else(|| invalid("amount must be a positive decimal"))?;
+
+    let result = match wrapping {
+        true => state.swap_service.wrap_eth(amount).await,
+        false => state.swap_service.unwrap_weth(amount).await,
+    };
+    match result {
+        Ok(wrap) => Ok(jr_success(
+            id,
+            json!({
+                "weth_address": wrap.weth_address.to_hex(),
+                "amount": wrap.amount.to_human_readable().to_string(),
+                "transaction_hash": wrap.transaction_hash,
+            }),
+        )),
+        Err(e) => {
+            let action = if wrapping { "wrap" } else { "unwrap" };
+            error!("WETH {} failed: {}", action, e);
+            // Balance shortfalls are actionable, so the reason is passed through
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(
+                    id,
+                    with_revert_data(
+                        JsonRpcError::internal_error(&format!("Failed to {}: {}", action, e)),
+                        &e,
+                    ),
+                ),
+            ))
+        }
+    }
+}
+
+/// A swap route as its protocol and token path plus one entry per pool
+fn route_json(route: &SwapRoute) -> Value {
+    json!({
+        "protocol": route.protocol.as_str(),
+        "path": route.tokens().iter().map(|t| format!("{:#x}", t)).collect::<Vec<_>>(),
+        "hops": route.hops.iter().map(|hop| json!({
+            "token_in": hop.token_in.to_hex(),
+            "token_out": hop.token_out.to_hex(),
+            "fee_tier": hop.fee_tier,
+            "pool": format!("{:#x}", hop.pool),
+            "liquidity": hop.liquidity.to_string(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+async fn handle_swap_tokens(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use crate::types::{
+        SwapParams, SwapProtocol, SwapQuote, TokenAddress, TokenAmount, WalletAddress,
+    };
+    use crate::validation::Validator;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let args = arguments.ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "jsonrpc": "2.0",
+                "error": {"code": -32602, "message": "Missing arguments"},
                 "id": id
             })),
         )
     })?;
 
-    // Parse and validate slippage
-    let slippage_str = args
-        .get("slippage_tolerance")
+    // Parse required arguments
+    let from_token_str = args
+        .get("from_token")
         .and_then(|v| v.as_str())
         .ok_or_else(|| {
             (
                 StatusCode::BAD_REQUEST,
                 Json(json!({
                     "jsonrpc": "2.0",
-                    "error": {"code": -32602, "message": "Missing slippage_tolerance"},
+                    "error": {"code": -32602, "message": "Missing from_token"},
                     "id": id
                 })),
             )
         })?;
 
-    let slippage_tolerance = Decimal::from_str(slippage_str).map_err(|_| {
+    let to_token_str = args
+        .get("to_token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32602, "message": "Missing to_token"},
+                    "id": id
+                })),
+            )
+        })?;
+
+    // `amount` sells exactly that much; `amount_out` buys exactly that much instead
+    let amount_arg = args.get("amount").and_then(|v| v.as_str());
+    let amount_out_arg = args.get("amount_out").and_then(|v| v.as_str());
+    let (amount_str, exact_output) = match (amount_arg, amount_out_arg) {
+        (Some(amount), None) => (amount, false),
+        (None, Some(amount_out)) => (amount_out, true),
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32602, "message": "Missing amount"},
+                    "id": id
+                })),
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32602, "message": "Provide either amount or amount_out, not both"},
+                    "id": id
+                })),
+            ))
+        }
+    };
+
+    // Parse token addresses first; "ETH" is native ether
+    let from_token = Validator::validate_token_address(from_token_str).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Invalid slippage_tolerance format"},
+                "error": {"code": -32602, "message": "Invalid from_token"},
                 "id": id
             })),
         )
     })?;
 
-    if slippage_tolerance < Decimal::ZERO || slippage_tolerance > Decimal::from(100) {
-        return Err((
+    let to_token = Validator::validate_token_address(to_token_str).map_err(|_| {
+        (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Slippage tolerance must be between 0 and 100"},
+                "error": {"code": -32602, "message": "Invalid to_token"},
                 "id": id
             })),
+        )
+    })?;
+    let weth = TokenAddress::from_hex(&state.swap_service.contracts.weth).ok();
+    let is_eth = |token: &TokenAddress| token.is_native() || Some(token) == weth.as_ref();
+    if from_token != to_token && is_eth(&from_token) && is_eth(&to_token) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            jr_error(
+                id,
+                JsonRpcError::invalid_params(
+                    "ETH and WETH convert 1:1; use wrap_eth or unwrap_weth instead of a swap",
+                ),
+            ),
         ));
     }
 
-    // Parse and validate amount
-    let amount_in = TokenAmount::from_human_readable(amount_str, 18).map_err(|_| {
+    // Parse and validate slippage
+    let slippage_str = args
+        .get("slippage_tolerance")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32602, "message": "Missing slippage_tolerance"},
+                    "id": id
+                })),
+            )
+        })?;
+
+    let slippage_tolerance = Decimal::from_str(slippage_str).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Invalid amount format"},
+                "error": {"code": -32602, "message": "Invalid slippage_tolerance format"},
                 "id": id
             })),
         )
     })?;
 
-    // Validate amount limits
-    if amount_in.to_human_readable() > Decimal::from(state.max_swap_amount) {
+    if slippage_tolerance < Decimal::ZERO || slippage_tolerance > Decimal::from(100) {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "jsonrpc": "2.0",
-                "error": {"code": -32602, "message": "Amount exceeds maximum swap limit"},
+                "error": {"code": -32602, "message": "Slippage tolerance must be between 0 and 100"},
                 "id": id
             })),
         ));
     }
 
-    // Create swap parameters
+    // Amounts are in the units of the token they name: the input for `amount`,
+    // the output for `amount_out`
+    let raw_units = match args.get("amount_units").and_then(|v| v.as_str()) {
+        None | Some("human") => false,
+        Some("raw") => true,
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                jr_error(
+                    id,
+                    JsonRpcError::invalid_params("amount_units must be human or raw"),
+                ),
+            ))
+        }
+    };
+    let provider = &state.swap_service.ethereum_provider;
+    let (from_decimals, to_decimals) = tokio::try_join!(
+        provider.get_token_decimals(&from_token),
+        provider.get_token_decimals(&to_token)
+    )
+    .map_err(|e| {
+        error!("Failed to read token decimals: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            jr_error(
+                id,
+                JsonRpcError::internal_error(&format!("Failed to read token decimals: {e}")),
+            ),
+        )
+    })?;
+    let decimals = if exact_output {
+        to_decimals
+    } else {
+        from_decimals
+    };
+    let amount = parse_swap_amount(amount_str, decimals, raw_units).map_err(|message| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(id, JsonRpcError::invalid_params(&message)),
+        )
+    })?;
+
+    // Dry-run from another account, optionally standing in for its missing funds
+    let sender = match args.get("simulate_from") {
+        None => None,
+        Some(v) => Some(
+            v.as_str()
+                .and_then(|s| WalletAddress::from_hex(s).ok())
+                .ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        jr_error(id, JsonRpcError::invalid_params("Invalid simulate_from")),
+                    )
+                })?,
+        ),
+    };
+    let override_token_state = match args.get("override_token_state") {
+        None => false,
+        Some(v) => v.as_bool().ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                jr_error(
+                    id,
+                    JsonRpcError::invalid_params("override_token_state must be a boolean"),
+                ),
+            )
+        })?,
+    };
+
+    // A single DEX to route on; by default every one is quoted and the best wins
+    let protocol = match args.get("protocol").and_then(|v| v.as_str()) {
+        None | Some("auto") => None,
+        Some(name) => Some(SwapProtocol::from_str(name).map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                jr_error(
+                    id,
+                    JsonRpcError::invalid_params(
+                        "protocol must be auto, uniswap_v3, uniswap_v2 or sushiswap",
+                    ),
+                ),
+            )
+        })?),
+    };
+
+    // Create swap parameters; an exact-output swap's input is filled in by the quote
+    let (amount_in, amount_out) = if exact_output {
+        (TokenAmount::new(Decimal::ZERO, from_decimals), Some(amount))
+    } else {
+        (amount, None)
+    };
     let swap_params = SwapParams {
         from_token,
         to_token,
         amount_in,
         slippage_tolerance,
+        route: None,
+        protocol,
+        amount_out,
+        sender,
+        override_token_state,
     };
 
-    // Simulate the swap
-    match state.swap_service.simulate_swap(&swap_params).await {
-        Ok(swap_result) => Ok(Json(json!({
+    // Simulate the swap and keep it as an executable quote
+    let quote = state.swap_service.quote_swap(&swap_params).await;
+    match quote {
+        Ok(SwapQuote {
+            quote_id,
+            expires_at,
+            result: swap_result,
+        }) => Ok(Json(json!({
             "jsonrpc": "2.0",
             "result": {
+                "quote_id": quote_id,
+                "quote_expires_at": expires_at.to_rfc3339(),
                 "from_token": swap_result.params.from_token.to_hex(),
                 "to_token": swap_result.params.to_token.to_hex(),
+                "mode": if exact_output { "exact_output" } else { "exact_input" },
                 "amount_in": swap_result.params.amount_in.to_human_readable().to_string(),
+                "amount_in_maximum": swap_result.params.maximum_amount_in().to_human_readable().to_string(),
                 "amount_out": swap_result.estimated_amount_out.to_human_readable().to_string(),
                 "price_impact": swap_result.price_impact.to_string(),
+                "high_price_impact": swap_result.high_price_impact,
                 "gas_estimate_units": swap_result.gas_estimate.to_string(),
                 "gas_cost_eth": swap_result.gas_cost_eth.map(|c| c.to_string()),
-                "route": swap_result.route
+                "requires_approval": swap_result.requires_approval,
+                "gas_cost_breakdown": swap_result.gas_cost_breakdown.map(|b| json!({
+                    "network": b.network,
+                    "l2_execution_fee_eth": b.l2_execution_fee_eth.to_string(),
+                    "l1_data_fee_eth": b.l1_data_fee_eth.to_string(),
+                })),
+                "route": route_json(&swap_result.route),
+                "route_quotes": swap_result.route_quotes.iter().map(|q| json!({
+                    "route": route_json(&q.route),
+                    "amount_in": q.amount_in.to_human_readable().to_string(),
+                    "amount_out": q.amount_out.to_human_readable().to_string(),
+                    "gas_estimate_units": q.gas_estimate.to_string(),
+                    "gas_cost_eth": q.gas_cost_eth.map(|c| c.to_string()),
+                    "net_amount_out": q.net_amount_out.as_ref().map(|n| n.to_human_readable().to_string()),
+                    "net_amount_in": q.net_amount_in.as_ref().map(|n| n.to_human_readable().to_string()),
+                    "sqrt_prices_x96_after": q.sqrt_prices_x96_after.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+                    "initialized_ticks_crossed": q.initialized_ticks_crossed,
+                })).collect::<Vec<_>>(),
+                "simulation": {
+                    "sender": format!("{:#x}", swap_result.simulation.sender),
+                    "balance": swap_result.simulation.balance.as_ref().map(|b| b.to_human_readable().to_string()),
+                    "allowance": swap_result.simulation.allowance.as_ref().map(|a| a.to_human_readable().to_string()),
+                    "balance_overridden": swap_result.simulation.balance_overridden,
+                    "allowance_overridden": swap_result.simulation.allowance_overridden,
+                    "success": swap_result.simulation.success,
+                    "revert_reason": swap_result.simulation.revert_reason,
+                },
             },
             "id": id
         }))),
         Err(e) => {
+            // Too large a swap for the pools is the caller's to fix, so say by how much
+            if let Some(refusal) = e.downcast_ref::<PriceImpactError>() {
+                warn!("Swap refused: {}", refusal);
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    jr_error(
+                        id,
+                        JsonRpcError::invalid_params(&refusal.to_string()).with_data(json!({
+                            "price_impact": refusal.price_impact.to_string(),
+                            "max_price_impact": refusal.max_price_impact.to_string(),
+                        })),
+                    ),
+                ));
+            }
+            if let Some(refusal) = e.downcast_ref::<SwapValueError>() {
+                return Err(swap_value_refusal(refusal, id));
+            }
             error!("Swap simulation failed: {}", e);
+            let err = JsonRpcError {
+                code: -32603,
+                message: "Failed to simulate swap".to_string(),
+                data: None,
+            };
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "jsonrpc": "2.0",
-                    "error": {"code": -32603, "message": "Failed to simulate swap"},
-                    "id": id
-                })),
+                jr_error(id, with_revert_data(err, &e)),
+            ))
+        }
+    }
+}
+
+/// Parse a swap amount in the token's human-readable units, or in its smallest
+/// units with `raw_units`
+fn parse_swap_amount(
+    amount: &str,
+    decimals: u8,
+    raw_units: bool,
+) -> Result<crate::types::TokenAmount, String> {
+    use crate::types::TokenAmount;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let value = Decimal::from_str(amount)
+        .ok()
+        .filter(|v| !v.is_sign_negative())
+        .ok_or_else(|| "Invalid amount format".to_string())?;
+    if raw_units {
+        if value.fract() != Decimal::ZERO {
+            return Err("Raw amounts must be whole numbers".to_string());
+        }
+        return Ok(TokenAmount::from_raw_units(value, decimals));
+    }
+    let amount = TokenAmount::new(value, decimals);
+    amount.to_u256().map_err(|e| e.to_string())?;
+    Ok(amount)
+}
+
+/// Invalid-params response for a swap refused by the maximum swap value
+fn swap_value_refusal(refusal: &SwapValueError, id: Option<&Value>) -> (StatusCode, Json<Value>) {
+    warn!("Swap refused: {}", refusal);
+    let error = JsonRpcError::invalid_params(&refusal.to_string());
+    let error = match refusal {
+        SwapValueError::TooLarge {
+            value_usd,
+            max_swap_value_usd,
+        } => error.with_data(json!({
+            "value_usd": value_usd.to_string(),
+            "max_swap_value_usd": max_swap_value_usd.to_string(),
+        })),
+        SwapValueError::Unpriced => error,
+    };
+    (StatusCode::BAD_REQUEST, jr_error(id, error))
+}
+
+async fn handle_compare_quotes(
+    state: &AppState,
+    arguments: Option<&Value>,
+    id: Option<&Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use crate::validation::Validator;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let invalid = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            jr_error(id, JsonRpcError::invalid_params(message)),
+        )
+    };
+    let args = arguments.ok_or_else(|| invalid("Missing arguments"))?;
+    let token_arg = |name: &str| {
+        let value = args
+            .get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| invalid(&format!("Missing {}", name)))?;
+        Validator::validate_token_address(value)
+            .map_err(|e| invalid(&format!("Invalid {}: {}", name, e)))
+    };
+    let from_token = token_arg("from_token")?;
+    let to_token = token_arg("to_token")?;
+    if from_token == to_token {
+        return Err(invalid("from_token and to_token must differ"));
+    }
+    let amount = args
+        .get("amount")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| invalid("Missing amount"))?;
+    let amount = Decimal::from_str(amount)
+        .ok()
+        .filter(|amount| *amount > Decimal::ZERO)
+        .ok_or_else(|| invalid("amount must be a positive decimal"))?;
+
+    match state
+        .swap_service
+        .compare_quotes(&from_token, &to_token, amount)
+        .await
+    {
+        Ok(comparison) => Ok(jr_success(
+            id,
+            json!({
+                "from_token": comparison.from_token.to_hex(),
+                "to_token": comparison.to_token.to_hex(),
+                "amount_in": comparison.amount_in.to_human_readable().to_string(),
+                "best_venue": comparison.quotes.first().map(|q| q.venue.clone()),
+                "quotes": comparison.quotes.iter().map(|q| json!({
+                    "venue": q.venue,
+                    "pools": q.pools.iter().map(|p| format!("{:#x}", p)).collect::<Vec<_>>(),
+                    "amount_out": q.amount_out.to_human_readable().to_string(),
+                    "gas_estimate_units": q.gas_estimate.to_string(),
+                    "gas_cost_eth": q.gas_cost_eth.map(|c| c.to_string()),
+                    "net_amount_out": q.net_amount_out.as_ref().map(|n| n.to_human_readable().to_string()),
+                })).collect::<Vec<_>>(),
+                "unavailable": comparison.unavailable.iter().map(|u| json!({
+                    "venue": u.venue,
+                    "reason": u.reason,
+                })).collect::<Vec<_>>(),
+            }),
+        )),
+        Err(e) => {
+            error!("Quote comparison failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                jr_error(id, JsonRpcError::internal_error("Failed to compare quotes")),
             ))
         }
     }
@@ -828,8 +1934,12 @@ async fn handle_swap_tokens(
 mod tests {
     use super::*;
     use crate::providers::MockEthereumProvider;
-    use crate::services::{BalanceService, PriceService, SwapService, TransactionStatusService};
+    use crate::services::{
+        AllowanceService, BalanceService, GasService, PriceService, SwapService,
+        TransactionStatusService,
+    };
     use crate::ContractAddresses;
+    use rust_decimal::Decimal;
     use serde_json::json;
 
     fn create_test_app_state() -> AppState {
@@ -837,15 +1947,22 @@ mod tests {
         let balance_service = Arc::new(BalanceService::new(mock_provider.clone()));
         let contracts = ContractAddresses::default();
         let price_service = Arc::new(PriceService::new(mock_provider.clone(), contracts.clone()));
-        let swap_service = Arc::new(SwapService::new(mock_provider.clone(), contracts));
-        let transaction_status_service = Arc::new(TransactionStatusService::new(mock_provider));
+        let swap_service = Arc::new(
+            SwapService::new(mock_provider.clone(), contracts.clone())
+                .with_max_swap_value(Decimal::from(1000)),
+        );
+        let transaction_status_service =
+            Arc::new(TransactionStatusService::new(mock_provider.clone()));
+        let gas_service = Arc::new(GasService::new(mock_provider.clone()));
+        let allowance_service = Arc::new(AllowanceService::new(mock_provider, contracts));
 
         AppState::new(
             balance_service,
             price_service,
             swap_service,
             transaction_status_service,
-            1000, // max_swap_amount is u64, not Decimal
+            gas_service,
+            allowance_service,
         )
     }
 
@@ -904,7 +2021,10 @@ mod tests {
     #[test]
     fn test_app_state_creation() {
         let app_state = create_test_app_state();
-        assert_eq!(app_state.max_swap_amount, 1000u64);
+        assert_eq!(
+            app_state.swap_service.max_swap_value(),
+            Some(Decimal::from(1000))
+        );
     }
 
     #[test]
@@ -927,6 +2047,38 @@ mod tests {
         assert_eq!(server.port, 3000);
     }
 
+    #[tokio::test]
+    async fn test_serve_gives_the_rate_limiter_a_peer_address() {
+        let server = HttpServer::new(
+            "127.0.0.1".to_string(),
+            0,
+            create_test_app_state(),
+            30,
+            100,
+            10,
+            5,
+            "*".to_string(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move { server.serve(listener).await });
+
+        // Without connect info and proxy headers the limiter cannot key the request and
+        // answers 500 "Unable To Extract Key!"
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&json!({"jsonrpc": "2.0", "method": "tools/list", "id": 1}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: Value = response.json().await.unwrap();
+        assert!(body["result"]["tools"].is_array());
+
+        handle.abort();
+    }
+
     #[test]
     fn test_http_server_port_handling() {
         let app_state = create_test_app_state();
@@ -1060,9 +2212,27 @@ mod tests {
     fn test_app_state_max_swap_amount() {
         let app_state = create_test_app_state();
 
-        // Verify max_swap_amount is set correctly
-        assert!(app_state.max_swap_amount > 0);
-        assert!(app_state.max_swap_amount <= 1_000_000_000_000);
+        // Verify the maximum swap value is set correctly
+        let max_swap_value = app_state.swap_service.max_swap_value().unwrap();
+        assert!(max_swap_value > Decimal::ZERO);
+        assert!(max_swap_value <= Decimal::from(1_000_000_000_000u64));
+    }
+
+    #[test]
+    fn test_parse_swap_amount_uses_token_decimals() {
+        use rust_decimal::Decimal;
+
+        let usdc = parse_swap_amount("100", 6, false).unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(usdc.to_raw_units().unwrap(), Decimal::from(100_000_000));
+
+        let raw = parse_swap_amount("100000000", 6, true).unwrap();
+        assert_eq!(raw, usdc);
+
+        // Finer than the token's smallest unit, fractional raw units, or negative
+        assert!(parse_swap_amount("0.0000001", 6, false).is_err());
+        assert!(parse_swap_amount("1.5", 6, true).is_err());
+        assert!(parse_swap_amount("-1", 18, false).is_err());
     }
 
     #[test]
@@ -1102,6 +2272,150 @@ mod tests {
         assert!(response_value["error"]["data"].is_object());
     }
 
+    #[test]
+    fn test_with_revert_data_only_for_reverts() {
+        use crate::types::{RevertError, RevertKind, RevertReason};
+
+        let revert: anyhow::Error = RevertError {
+            context: "Swap simulation reverted".to_string(),
+            reason: RevertReason {
+                kind: RevertKind::Error,
+                message: "STF".to_string(),
+                explanation: Some("safeTransferFrom failed".to_string()),
+                arguments: None,
+                data: alloy::primitives::Bytes::new(),
+            },
+        }
+        .into();
+        let err = with_revert_data(JsonRpcError::internal_error("Failed"), &revert);
+        let data = err.data.unwrap();
+        assert_eq!(data["revert_reason"]["kind"], "error");
+        assert_eq!(data["revert_reason"]["message"], "STF");
+
+        let other = anyhow::anyhow!("connection refused");
+        let err = with_revert_data(JsonRpcError::internal_error("Failed"), &other);
+        assert!(err.data.is_none());
+    }
+
+    #[test]
+    fn test_parse_wait_args() {
+        let hash = format!("{:?}", alloy::primitives::B256::repeat_byte(5));
+        let id = Some(&json!(1));
+
+        let args = json!({"transaction_hash": hash});
+        let (_, target, timeout) = parse_wait_args(Some(&args), id).unwrap();
+        assert_eq!(target, WaitTarget::Confirmations(1));
+        assert!(timeout.is_none());
+
+        let args = json!({"transaction_hash": hash, "confirmations": 6, "timeout_seconds": 30});
+        let (_, target, timeout) = parse_wait_args(Some(&args), id).unwrap();
+        assert_eq!(target, WaitTarget::Confirmations(6));
+        assert_eq!(timeout, Some(Duration::from_secs(30)));
+
+        let args = json!({"transaction_hash": hash, "finalized": true});
+        let (_, target, _) = parse_wait_args(Some(&args), id).unwrap();
+        assert_eq!(target, WaitTarget::Finalized);
+
+        for invalid in [
+            json!({"transaction_hash": hash, "confirmations": 0}),
+            json!({"transaction_hash": hash, "confirmations": MAX_WAIT_CONFIRMATIONS + 1}),
+            json!({"transaction_hash": hash, "finalized": true, "confirmations": 2}),
+            json!({"transaction_hash": hash, "timeout_seconds": 0}),
+            json!({"confirmations": 2}),
+        ] {
+            let (status, response) = parse_wait_args(Some(&invalid), id).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(response.0["error"]["code"], -32602);
+        }
+    }
+
+    #[test]
+    fn test_accepts_event_stream() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts_event_stream(&headers));
+        headers.insert(
+            axum::http::header::ACCEPT,
+            "application/json, text/event-stream".parse().unwrap(),
+        );
+        assert!(accepts_event_stream(&headers));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_transaction_streams_progress_then_result() {
+        use crate::types::{TransactionStatus, TransactionStatusInfo};
+
+        let mut mock_provider = MockEthereumProvider::new();
+        mock_provider
+            .expect_get_block_number()
+            .returning(|| Ok(100));
+        mock_provider
+            .expect_get_transaction_status()
+            .returning(|hash| {
+                Ok(TransactionStatusInfo {
+                    transaction_hash: format!("{:?}", hash),
+                    status: TransactionStatus::Confirmed,
+                    confirmations: 1,
+                    block_number: Some(100),
+                    finalized: false,
+                    replaced_by: None,
+                    detail: None,
+                    revert_reason: None,
+                })
+            });
+        let mock_provider = Arc::new(mock_provider);
+        let contracts = ContractAddresses::default();
+        let state = AppState::new(
+            Arc::new(BalanceService::new(mock_provider.clone())),
+            Arc::new(PriceService::new(mock_provider.clone(), contracts.clone())),
+            Arc::new(SwapService::new(mock_provider.clone(), contracts.clone())),
+            Arc::new(TransactionStatusService::new(mock_provider.clone())),
+            Arc::new(GasService::new(mock_provider.clone())),
+            Arc::new(AllowanceService::new(mock_provider, contracts)),
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::ACCEPT,
+            "text/event-stream".parse().unwrap(),
+        );
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {
+                "name": "wait_for_transaction",
+                "arguments": {
+                    "transaction_hash": format!("{:?}", alloy::primitives::B256::repeat_byte(5))
+                },
+                "_meta": {"progressToken": "tok-1"}
+            },
+            "id": 9
+        });
+
+        let response = handle_jsonrpc(State(state), headers, Json(request))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let events: Vec<Value> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["method"], "notifications/progress");
+        assert_eq!(events[0]["params"]["progressToken"], "tok-1");
+        assert_eq!(events[0]["params"]["block_number"], 100);
+        assert_eq!(events[1]["id"], 9);
+        assert_eq!(events[1]["result"]["outcome"], "reached");
+    }
+
     #[test]
     fn test_http_server_different_hosts() {
         let app_state = create_test_app_state();
@@ -108,6 +108,12 @@ impl JsonRpcError {
             data: None,
         }
     }
+
+    /// Attach structured `data` to the error
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
 }
 
 /// Validate JSON-RPC 2.0 request format
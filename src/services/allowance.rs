@@ -0,0 +1,367 @@
+/// Token allowance service implementation
+/// Single Responsibility: Handle ERC20 allowance queries, approvals and permit signatures
+use crate::contracts::utils;
+use crate::providers::EthereumProvider;
+use crate::types::{
+    AllowanceInfo, ApprovalAmount, ApprovalResult, PermitSignature, TokenAddress, TokenAmount,
+    WalletAddress,
+};
+use crate::ContractAddresses;
+use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, instrument};
+
+#[async_trait]
+pub trait AllowanceServiceTrait: Send + Sync {
+    /// Allowance of `owner` (default: configured wallet) to `spender` (default: Uniswap V3 router)
+    async fn get_allowance(
+        &self,
+        owner: Option<&WalletAddress>,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+    ) -> anyhow::Result<AllowanceInfo>;
+
+    /// Approve `spender` (default: Uniswap V3 router) from the configured wallet
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+        amount: &ApprovalAmount,
+    ) -> anyhow::Result<ApprovalResult>;
+
+    /// Sign a gasless approval for `spender` (default: Uniswap V3 router) valid for
+    /// `validity`: the token's EIP-2612 permit, or a Permit2 `PermitSingle` if `permit2`
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+        amount: &ApprovalAmount,
+        validity: Duration,
+        permit2: bool,
+    ) -> anyhow::Result<PermitSignature>;
+}
+
+pub struct AllowanceService {
+    pub ethereum_provider: Arc<dyn EthereumProvider>,
+    pub contracts: ContractAddresses,
+}
+
+impl AllowanceService {
+    pub fn new(ethereum_provider: Arc<dyn EthereumProvider>, contracts: ContractAddresses) -> Self {
+        Self {
+            ethereum_provider,
+            contracts,
+        }
+    }
+
+    /// Raw approval amount, with the token amount when it is exact
+    async fn raw_approval_amount(
+        &self,
+        token: &TokenAddress,
+        amount: &ApprovalAmount,
+    ) -> anyhow::Result<(U256, Option<TokenAmount>)> {
+        match amount {
+            ApprovalAmount::Unlimited => Ok((U256::MAX, None)),
+            ApprovalAmount::Exact(value) => {
+                let decimals = self.ethereum_provider.get_token_decimals(token).await?;
+                let token_amount = TokenAmount::from_human_readable(&value.to_string(), decimals)?;
+                Ok((token_amount.to_u256()?, Some(token_amount)))
+            }
+        }
+    }
+
+    fn spender_or_router(&self, spender: Option<&Address>) -> anyhow::Result<Address> {
+        match spender {
+            Some(spender) => Ok(*spender),
+            None => utils::parse_address(&self.contracts.uniswap_v3_router),
+        }
+    }
+}
+
+#[async_trait]
+impl AllowanceServiceTrait for AllowanceService {
+    #[instrument(skip(self), fields(token = %token.to_hex()))]
+    async fn get_allowance(
+        &self,
+        owner: Option<&WalletAddress>,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+    ) -> anyhow::Result<AllowanceInfo> {
+        debug!("Fetching token allowance");
+        let owner = owner
+            .cloned()
+            .unwrap_or_else(|| self.ethereum_provider.wallet_address());
+        let spender = self.spender_or_router(spender)?;
+        self.ethereum_provider
+            .get_allowance(&owner, token, &spender)
+            .await
+    }
+
+    #[instrument(skip(self), fields(token = %token.to_hex()))]
+    async fn approve_token(
+        &self,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+        amount: &ApprovalAmount,
+    ) -> anyhow::Result<ApprovalResult> {
+        let spender = self.spender_or_router(spender)?;
+        let (raw_amount, approved_amount) = self.raw_approval_amount(token, amount).await?;
+
+        let tx_hash = self
+            .ethereum_provider
+            .approve_token(token, &spender, raw_amount)
+            .await?;
+        info!(spender = %spender, tx_hash = %tx_hash, "Token approval broadcast");
+
+        Ok(ApprovalResult {
+            token_address: token.clone(),
+            spender,
+            amount: approved_amount,
+            is_unlimited: *amount == ApprovalAmount::Unlimited,
+            transaction_hash: tx_hash.to_string(),
+        })
+    }
+
+    #[instrument(skip(self), fields(token = %token.to_hex()))]
+    async fn sign_permit(
+        &self,
+        token: &TokenAddress,
+        spender: Option<&Address>,
+        amount: &ApprovalAmount,
+        validity: Duration,
+        permit2: bool,
+    ) -> anyhow::Result<PermitSignature> {
+        let spender = self.spender_or_router(spender)?;
+        let (raw_amount, _) = self.raw_approval_amount(token, amount).await?;
+        let deadline = (Utc::now() + chrono::Duration::from_std(validity)?).timestamp() as u64;
+
+        let permit = if permit2 {
+            // The Permit2 allowance and the signature expire together
+            self.ethereum_provider
+                .sign_permit2(token, &spender, raw_amount, deadline, U256::from(deadline))
+                .await?
+        } else {
+            self.ethereum_provider
+                .sign_permit(token, &spender, raw_amount, U256::from(deadline))
+                .await?
+        };
+        debug!(kind = ?permit.kind, deadline, "Signed permit");
+        Ok(permit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockEthereumProvider;
+    use crate::types::PermitKind;
+    use alloy::primitives::{Bytes, B256};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const WALLET: &str = "0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7";
+
+    fn router() -> Address {
+        utils::parse_address(&ContractAddresses::default().uniswap_v3_router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_allowance_defaults_to_wallet_and_router() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let wallet = WalletAddress::from_hex(WALLET).unwrap();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+
+        let wallet_clone = wallet.clone();
+        mock_provider
+            .expect_wallet_address()
+            .returning(move || wallet_clone.clone());
+        mock_provider
+            .expect_get_allowance()
+            .withf(move |owner, _, spender| {
+                owner.to_hex() == WALLET.to_lowercase() && *spender == router()
+            })
+            .times(1)
+            .returning(|owner, token, spender| {
+                Ok(AllowanceInfo {
+                    owner: owner.clone(),
+                    spender: *spender,
+                    token_address: token.clone(),
+                    allowance: TokenAmount::from_human_readable("25", 6).unwrap(),
+                    is_unlimited: false,
+                })
+            });
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let info = service.get_allowance(None, &token, None).await.unwrap();
+
+        assert_eq!(info.owner, wallet);
+        assert_eq!(info.allowance.raw, Decimal::from(25));
+    }
+
+    #[tokio::test]
+    async fn test_approve_exact_amount() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+
+        mock_provider
+            .expect_get_token_decimals()
+            .returning(|_| Ok(6));
+        mock_provider
+            .expect_approve_token()
+            .withf(|_, spender, amount| *spender == router() && *amount == U256::from(1_500_000u64))
+            .times(1)
+            .returning(|_, _, _| Ok(B256::repeat_byte(0x22)));
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let result = service
+            .approve_token(
+                &token,
+                None,
+                &ApprovalAmount::Exact(Decimal::from_str("1.5").unwrap()),
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_unlimited);
+        assert_eq!(
+            result.amount.unwrap().raw,
+            Decimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(result.transaction_hash, B256::repeat_byte(0x22).to_string());
+    }
+
+    #[tokio::test]
+    async fn test_approve_unlimited() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+        let spender = Address::repeat_byte(0x33);
+
+        mock_provider
+            .expect_approve_token()
+            .withf(move |_, s, amount| *s == spender && *amount == U256::MAX)
+            .times(1)
+            .returning(|_, _, _| Ok(B256::repeat_byte(0x44)));
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let result = service
+            .approve_token(&token, Some(&spender), &ApprovalAmount::Unlimited)
+            .await
+            .unwrap();
+
+        assert!(result.is_unlimited);
+        assert!(result.amount.is_none());
+        assert_eq!(result.spender, spender);
+    }
+
+    #[tokio::test]
+    async fn test_approve_rejects_excess_precision() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+
+        mock_provider
+            .expect_get_token_decimals()
+            .returning(|_| Ok(6));
+        mock_provider.expect_approve_token().never();
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let result = service
+            .approve_token(
+                &token,
+                None,
+                &ApprovalAmount::Exact(Decimal::from_str("0.0000001").unwrap()),
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("decimal places"));
+    }
+
+    fn signed_permit(kind: PermitKind, value: U256, deadline: U256) -> PermitSignature {
+        PermitSignature {
+            kind,
+            token_address: TokenAddress::from_hex(USDC).unwrap(),
+            owner: WalletAddress::from_hex(WALLET).unwrap(),
+            spender: router(),
+            value,
+            nonce: U256::ZERO,
+            deadline,
+            expiration: None,
+            signature: Bytes::from(vec![0u8; 65]),
+            v: 27,
+            r: B256::ZERO,
+            s: B256::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_eip2612_permit() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+
+        mock_provider
+            .expect_get_token_decimals()
+            .returning(|_| Ok(6));
+        mock_provider
+            .expect_sign_permit()
+            .withf(|_, spender, value, deadline| {
+                let in_30_min = (Utc::now().timestamp() + 1800) as u64;
+                *spender == router()
+                    && *value == U256::from(10_000_000u64)
+                    && deadline.to::<u64>().abs_diff(in_30_min) <= 5
+            })
+            .times(1)
+            .returning(|_, _, value, deadline| {
+                Ok(signed_permit(PermitKind::Eip2612, value, deadline))
+            });
+        mock_provider.expect_sign_permit2().never();
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let permit = service
+            .sign_permit(
+                &token,
+                None,
+                &ApprovalAmount::Exact(Decimal::from(10)),
+                Duration::from_secs(1800),
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(permit.kind, PermitKind::Eip2612);
+        assert_eq!(permit.value, U256::from(10_000_000u64));
+    }
+
+    #[tokio::test]
+    async fn test_sign_permit2_unlimited() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let token = TokenAddress::from_hex(USDC).unwrap();
+
+        mock_provider
+            .expect_sign_permit2()
+            .withf(|_, _, amount, expiration, sig_deadline| {
+                *amount == U256::MAX && U256::from(*expiration) == *sig_deadline
+            })
+            .times(1)
+            .returning(|_, _, _, _, sig_deadline| {
+                Ok(signed_permit(PermitKind::Permit2, U256::MAX, sig_deadline))
+            });
+        mock_provider.expect_sign_permit().never();
+
+        let service = AllowanceService::new(Arc::new(mock_provider), ContractAddresses::default());
+        let permit = service
+            .sign_permit(
+                &token,
+                None,
+                &ApprovalAmount::Unlimited,
+                Duration::from_secs(600),
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(permit.kind, PermitKind::Permit2);
+    }
+}
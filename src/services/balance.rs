@@ -36,16 +36,17 @@ impl BalanceServiceTrait for BalanceService {
         debug!("Getting balance for wallet");
 
         match token {
-            None => {
-                debug!("Fetching ETH balance");
-                self.ethereum_provider.get_eth_balance(wallet).await
-            }
-            Some(token_addr) => {
+            Some(token_addr) if !token_addr.is_native() => {
                 debug!("Fetching ERC20 balance for token: {}", token_addr.to_hex());
                 self.ethereum_provider
                     .get_erc20_balance(wallet, token_addr)
                     .await
             }
+            // No token, or "ETH" for native ether, which has no contract
+            _ => {
+                debug!("Fetching ETH balance");
+                self.ethereum_provider.get_eth_balance(wallet).await
+            }
         }
     }
 }
@@ -83,6 +84,34 @@ mod tests {
         assert_eq!(result.amount.raw, Decimal::from_str("1.5").unwrap());
     }
 
+    #[tokio::test]
+    async fn test_native_token_fetches_eth_balance() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
+
+        mock_provider
+            .expect_get_eth_balance()
+            .times(1)
+            .returning(|wallet| {
+                Ok(BalanceInfo {
+                    wallet_address: wallet.clone(),
+                    token_address: None,
+                    amount: TokenAmount::from_human_readable("2", 18).unwrap(),
+                    symbol: "ETH".to_string(),
+                })
+            });
+        mock_provider.expect_get_erc20_balance().never();
+
+        let service = BalanceService::new(Arc::new(mock_provider));
+        let result = service
+            .get_balance(&wallet, Some(&TokenAddress::native()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.symbol, "ETH");
+        assert_eq!(result.amount.raw, Decimal::from(2));
+    }
+
     #[tokio::test]
     async fn test_get_erc20_balance() {
         let mut mock_provider = MockEthereumProvider::new();
@@ -0,0 +1,67 @@
+/// Gas fee service implementation
+/// Single Responsibility: Handle EIP-1559 gas fee estimation
+use crate::providers::EthereumProvider;
+use crate::types::GasFeeEstimate;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+#[async_trait]
+pub trait GasServiceTrait: Send + Sync {
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate>;
+}
+
+pub struct GasService {
+    pub ethereum_provider: Arc<dyn EthereumProvider>,
+}
+
+impl GasService {
+    pub fn new(ethereum_provider: Arc<dyn EthereumProvider>) -> Self {
+        Self { ethereum_provider }
+    }
+}
+
+#[async_trait]
+impl GasServiceTrait for GasService {
+    #[instrument(skip(self))]
+    async fn get_gas_fees(&self) -> anyhow::Result<GasFeeEstimate> {
+        debug!("Estimating EIP-1559 gas fees");
+        self.ethereum_provider.get_gas_fees().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockEthereumProvider;
+    use crate::types::FeeSuggestion;
+
+    #[tokio::test]
+    async fn test_get_gas_fees() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let suggestion = FeeSuggestion {
+            max_priority_fee_per_gas: 2_000_000_000,
+            max_fee_per_gas: 42_000_000_000,
+        };
+        let expected = GasFeeEstimate {
+            base_fee_per_gas: 20_000_000_000,
+            next_base_fee_per_gas: 20_000_000_000,
+            slow: suggestion.clone(),
+            normal: suggestion.clone(),
+            fast: suggestion,
+            latest_block: 18_000_000,
+        };
+
+        let expected_clone = expected.clone();
+        mock_provider
+            .expect_get_gas_fees()
+            .times(1)
+            .returning(move || Ok(expected_clone.clone()));
+
+        let service = GasService::new(Arc::new(mock_provider));
+        let result = service.get_gas_fees().await.unwrap();
+
+        assert_eq!(result, expected);
+        assert_eq!(result.normal_gas_price(), 22_000_000_000);
+    }
+}
@@ -1,12 +1,16 @@
 /// Business logic services
 /// Following Single Responsibility Principle
+pub mod allowance;
 pub mod balance;
+pub mod gas;
 pub mod price;
 pub mod swap;
 pub mod transaction_status;
 
 // Re-export for convenience
+pub use allowance::AllowanceService;
 pub use balance::BalanceService;
+pub use gas::GasService;
 pub use price::PriceService;
 pub use swap::SwapService;
 pub use transaction_status::{TransactionStatusService, TransactionStatusServiceTrait};
@@ -1,22 +1,83 @@
+use crate::contracts::utils;
 /// Swap service implementation
-/// Single Responsibility: Handle token swap simulations
+/// Single Responsibility: Handle token swap simulations and confirmed execution
 use crate::providers::EthereumProvider;
 use crate::{
-    types::{SwapParams, SwapResult},
+    types::{
+        PriceImpactError, QuoteComparison, RevertError, SwapExecution, SwapParams, SwapQuote,
+        SwapResult, SwapValueError, TokenAddress, TokenAmount, WrapResult,
+    },
     ContractAddresses,
 };
+use alloy::primitives::{keccak256, U256};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tracing::{debug, instrument};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, info, instrument, warn};
+
+/// Default lifetime of a swap quote before it must be re-simulated
+pub const DEFAULT_QUOTE_TTL: Duration = Duration::from_secs(60);
+
+/// Price impact, in percent, above which quotes are flagged
+pub const DEFAULT_PRICE_IMPACT_WARNING: Decimal = Decimal::from_parts(3, 0, 0, false, 0);
+
+/// Price impact, in percent, above which quotes are refused
+pub const DEFAULT_MAX_PRICE_IMPACT: Decimal = Decimal::from_parts(15, 0, 0, false, 0);
+
+/// How long a permit signed for `execute_swap` remains valid
+const PERMIT_VALIDITY: chrono::Duration = chrono::Duration::minutes(20);
 
 #[async_trait]
 pub trait SwapServiceTrait: Send + Sync {
+    /// Simulate a swap, refusing it when its price impact is above the maximum
     async fn simulate_swap(&self, params: &SwapParams) -> anyhow::Result<SwapResult>;
+
+    /// Simulate a swap and remember it under a single-use quote ID
+    async fn quote_swap(&self, params: &SwapParams) -> anyhow::Result<SwapQuote>;
+
+    /// Execute a previously quoted swap if the quote is unexpired and the price
+    /// is still within the quoted slippage tolerance. With `use_permit`, an EIP-2612
+    /// permit for the input amount is signed and bundled instead of requiring approval.
+    async fn execute_swap(&self, quote_id: &str, use_permit: bool)
+        -> anyhow::Result<SwapExecution>;
+
+    /// Quote selling a human-readable `amount` of `from_token` on every venue, best
+    /// output net of gas first
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount: Decimal,
+    ) -> anyhow::Result<QuoteComparison>;
+
+    /// Wrap a human-readable `amount` of the wallet's ether into WETH
+    async fn wrap_eth(&self, amount: Decimal) -> anyhow::Result<WrapResult>;
+
+    /// Unwrap a human-readable `amount` of the wallet's WETH back into ether
+    async fn unwrap_weth(&self, amount: Decimal) -> anyhow::Result<WrapResult>;
+}
+
+/// Quote remembered between `quote_swap` and `execute_swap`
+struct PendingQuote {
+    params: SwapParams,
+    estimated_amount_out: TokenAmount,
+    expires_at: DateTime<Utc>,
 }
 
 pub struct SwapService {
     pub ethereum_provider: Arc<dyn EthereumProvider>,
     pub contracts: ContractAddresses,
+    quote_ttl: Duration,
+    price_impact_warning: Decimal,
+    max_price_impact: Decimal,
+    max_swap_value: Option<Decimal>,
+    quotes: Mutex<HashMap<String, PendingQuote>>,
+    quote_counter: AtomicU64,
 }
 
 impl SwapService {
@@ -24,7 +85,131 @@ impl SwapService {
         Self {
             ethereum_provider,
             contracts,
+            quote_ttl: DEFAULT_QUOTE_TTL,
+            price_impact_warning: DEFAULT_PRICE_IMPACT_WARNING,
+            max_price_impact: DEFAULT_MAX_PRICE_IMPACT,
+            max_swap_value: None,
+            quotes: Mutex::new(HashMap::new()),
+            quote_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Override how long a quote stays executable
+    pub fn with_quote_ttl(mut self, quote_ttl: Duration) -> Self {
+        self.quote_ttl = quote_ttl;
+        self
+    }
+
+    /// Override the price impact percentages above which simulations are flagged
+    /// (`warning`) and refused (`maximum`)
+    pub fn with_price_impact_limits(mut self, warning: Decimal, maximum: Decimal) -> Self {
+        self.price_impact_warning = warning;
+        self.max_price_impact = maximum;
+        self
+    }
+
+    /// Refuse quoting or executing swaps worth more than `max_swap_value` USD
+    pub fn with_max_swap_value(mut self, max_swap_value: Decimal) -> Self {
+        self.max_swap_value = Some(max_swap_value);
+        self
+    }
+
+    /// USD value of `amount` of `token`, if the token has a USD price
+    async fn value_usd(&self, token: &TokenAddress, amount: &TokenAmount) -> Option<Decimal> {
+        let price = self
+            .ethereum_provider
+            .get_token_price(token, &self.contracts)
+            .await
+            .ok()?;
+        Some(price.price_usd? * amount.to_human_readable())
+    }
+
+    /// Maximum USD value of a swap, if one is enforced
+    pub fn max_swap_value(&self) -> Option<Decimal> {
+        self.max_swap_value
+    }
+
+    /// Refuse a swap worth more than `max_swap_value_usd`
+    fn check_swap_value(value_usd: Decimal, max_swap_value_usd: Decimal) -> anyhow::Result<()> {
+        if value_usd > max_swap_value_usd {
+            warn!(value_usd = %value_usd, "Refusing swap above the maximum swap value");
+            return Err(SwapValueError::TooLarge {
+                value_usd: value_usd.round_dp(2),
+                max_swap_value_usd,
+            }
+            .into());
         }
+        Ok(())
+    }
+
+    /// Simulate a swap, refusing it when it's worth more than the maximum swap value.
+    /// A priced fixed amount is checked before simulating; otherwise the side the
+    /// simulation settles is valued instead.
+    async fn simulate_within_max_value(&self, params: &SwapParams) -> anyhow::Result<SwapResult> {
+        let Some(max_swap_value_usd) = self.max_swap_value else {
+            return self.simulate_swap(params).await;
+        };
+        let (token, amount) = match &params.amount_out {
+            Some(amount_out) => (&params.to_token, amount_out),
+            None => (&params.from_token, &params.amount_in),
+        };
+        let fixed_value = self.value_usd(token, amount).await;
+        if let Some(value_usd) = fixed_value {
+            Self::check_swap_value(value_usd, max_swap_value_usd)?;
+        }
+
+        let result = self.simulate_swap(params).await?;
+        if fixed_value.is_none() {
+            let (token, amount) = match &params.amount_out {
+                Some(_) => (&params.from_token, &result.params.amount_in),
+                None => (&params.to_token, &result.estimated_amount_out),
+            };
+            let value_usd = self
+                .value_usd(token, amount)
+                .await
+                .ok_or(SwapValueError::Unpriced)?;
+            Self::check_swap_value(value_usd, max_swap_value_usd)?;
+        }
+        Ok(result)
+    }
+
+    /// WETH address and `amount` in wei, after checking the wallet holds `amount` of
+    /// ether (wrapping) or WETH (unwrapping)
+    async fn wrap_amount(
+        &self,
+        amount: Decimal,
+        wrapping: bool,
+    ) -> anyhow::Result<(TokenAddress, TokenAmount)> {
+        if amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Amount must be greater than zero"));
+        }
+        let weth = TokenAddress::from_hex(&self.contracts.weth)?;
+        let amount = TokenAmount::new(amount, 18);
+        let wallet = self.ethereum_provider.wallet_address();
+        let balance = match wrapping {
+            true => self.ethereum_provider.get_eth_balance(&wallet).await?,
+            false => {
+                self.ethereum_provider
+                    .get_erc20_balance(&wallet, &weth)
+                    .await?
+            }
+        };
+        if balance.amount.raw < amount.raw {
+            return Err(anyhow::anyhow!(
+                "Insufficient {} balance: have {}, need {}",
+                if wrapping { "ETH" } else { "WETH" },
+                balance.amount.raw,
+                amount.raw
+            ));
+        }
+        Ok((weth, amount))
+    }
+
+    /// Opaque quote identifier, unique per service instance
+    fn next_quote_id(&self) -> String {
+        let counter = self.quote_counter.fetch_add(1, Ordering::Relaxed);
+        let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        keccak256([counter.to_be_bytes(), nanos.to_be_bytes()].concat()).to_string()
     }
 }
 
@@ -33,18 +218,264 @@ impl SwapServiceTrait for SwapService {
     #[instrument(skip(self), fields(from_token = %params.from_token.to_hex(), to_token = %params.to_token.to_hex()))]
     async fn simulate_swap(&self, params: &SwapParams) -> anyhow::Result<SwapResult> {
         debug!("Simulating token swap");
-        self.ethereum_provider
+        let mut result = self
+            .ethereum_provider
             .simulate_swap(params, &self.contracts)
+            .await?;
+        if result.price_impact > self.max_price_impact {
+            warn!(price_impact = %result.price_impact, "Refusing swap with excessive price impact");
+            return Err(PriceImpactError {
+                price_impact: result.price_impact,
+                max_price_impact: self.max_price_impact,
+            }
+            .into());
+        }
+        result.high_price_impact = result.price_impact > self.price_impact_warning;
+        Ok(result)
+    }
+
+    #[instrument(skip(self), fields(from_token = %params.from_token.to_hex(), to_token = %params.to_token.to_hex()))]
+    async fn quote_swap(&self, params: &SwapParams) -> anyhow::Result<SwapQuote> {
+        let result = self.simulate_within_max_value(params).await?;
+        let quote_id = self.next_quote_id();
+        let expires_at = Utc::now() + chrono::Duration::from_std(self.quote_ttl)?;
+
+        let mut quotes = self.quotes.lock().await;
+        let now = Utc::now();
+        quotes.retain(|_, quote| quote.expires_at > now);
+        quotes.insert(
+            quote_id.clone(),
+            PendingQuote {
+                // Pins the fee tier the simulation picked, so execution uses the same pool
+                params: result.params.clone(),
+                estimated_amount_out: result.estimated_amount_out.clone(),
+                expires_at,
+            },
+        );
+        debug!(quote_id = %quote_id, "Stored swap quote");
+
+        Ok(SwapQuote {
+            quote_id,
+            expires_at,
+            result,
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn execute_swap(
+        &self,
+        quote_id: &str,
+        use_permit: bool,
+    ) -> anyhow::Result<SwapExecution> {
+        // Quotes are single-use: a failed attempt requires a fresh simulation
+        let quote = self
+            .quotes
+            .lock()
+            .await
+            .remove(quote_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown or already used quote_id: {}", quote_id))?;
+        if Utc::now() > quote.expires_at {
+            return Err(anyhow::anyhow!(
+                "Quote {} expired at {}; simulate the swap again",
+                quote_id,
+                quote.expires_at
+            ));
+        }
+
+        // Quotes dry-run from another account or on overridden balances only show what
+        // would happen; the wallet has to stand on its own funds
+        if let Some(sender) = &quote.params.sender {
+            if *sender != self.ethereum_provider.wallet_address() {
+                return Err(anyhow::anyhow!(
+                    "Quote {} was simulated from {}, not the wallet; it can't be executed",
+                    quote_id,
+                    sender.to_hex()
+                ));
+            }
+        }
+        let params = SwapParams {
+            sender: None,
+            override_token_state: false,
+            ..quote.params.clone()
+        };
+        let current = self.simulate_within_max_value(&params).await?;
+        let maximum_in = current.params.maximum_amount_in();
+        if let Some(balance) = &current.simulation.balance {
+            if balance.raw < maximum_in.raw {
+                return Err(anyhow::anyhow!(
+                    "Insufficient {} balance: have {}, swap may spend up to {}",
+                    quote.params.from_token.to_hex(),
+                    balance.raw,
+                    maximum_in.raw
+                ));
+            }
+        }
+        let amount_out_minimum = match &quote.params.amount_out {
+            Some(amount_out) => {
+                let maximum = quote.params.maximum_amount_in();
+                if current.params.amount_in.raw > maximum.raw {
+                    warn!(
+                        quoted = %quote.params.amount_in.raw,
+                        current = %current.params.amount_in.raw,
+                        "Price moved beyond slippage tolerance since quote"
+                    );
+                    return Err(anyhow::anyhow!(
+                        "Price moved beyond slippage tolerance: quoted input {}, now {}, maximum {}",
+                        quote.params.amount_in.raw,
+                        current.params.amount_in.raw,
+                        maximum.raw
+                    ));
+                }
+                amount_out.clone()
+            }
+            None => quote.params.minimum_amount_out(&quote.estimated_amount_out),
+        };
+        if current.estimated_amount_out.raw < amount_out_minimum.raw {
+            warn!(
+                quoted = %quote.estimated_amount_out.raw,
+                current = %current.estimated_amount_out.raw,
+                "Price moved beyond slippage tolerance since quote"
+            );
+            return Err(anyhow::anyhow!(
+                "Price moved beyond slippage tolerance: quoted {}, now {}, minimum {}",
+                quote.estimated_amount_out.raw,
+                current.estimated_amount_out.raw,
+                amount_out_minimum.raw
+            ));
+        }
+
+        // Without an approval the dry run is expected to fail, so it only speaks for
+        // swaps the router can already pull funds for
+        if !current.requires_approval && !current.simulation.success {
+            if let Some(reason) = current.simulation.revert_reason {
+                return Err(RevertError {
+                    context: "Swap would revert".to_string(),
+                    reason,
+                }
+                .into());
+            }
+            return Err(anyhow::anyhow!("Swap would revert"));
+        }
+
+        let permit = if use_permit {
+            if quote.params.from_token.is_native() {
+                return Err(anyhow::anyhow!(
+                    "Swaps paid in ETH need no approval; execute without use_permit"
+                ));
+            }
+            // Only the V3 SwapRouter can redeem a permit in the swap transaction
+            if current.route.protocol.is_v2() {
+                return Err(anyhow::anyhow!(
+                    "Permits are only supported on Uniswap V3 routes; approve the {} router instead",
+                    current.route.protocol.as_str()
+                ));
+            }
+            let router = utils::swap_router(current.route.protocol, &self.contracts)?;
+            let deadline = Utc::now() + PERMIT_VALIDITY;
+            Some(
+                self.ethereum_provider
+                    .sign_permit(
+                        &quote.params.from_token,
+                        &router,
+                        quote.params.maximum_amount_in().to_u256()?,
+                        U256::from(deadline.timestamp()),
+                    )
+                    .await?,
+            )
+        } else if current.requires_approval {
+            return Err(anyhow::anyhow!(
+                "Router allowance for {} is below the swap amount; call approve_token first or use_permit",
+                quote.params.from_token.to_hex()
+            ));
+        } else {
+            None
+        };
+
+        let tx_hash = self
+            .ethereum_provider
+            .execute_swap(
+                &quote.params,
+                &amount_out_minimum,
+                permit.as_ref(),
+                &self.contracts,
+            )
+            .await?;
+        info!(quote_id = %quote_id, tx_hash = %tx_hash, "Swap executed");
+
+        Ok(SwapExecution {
+            quote_id: quote_id.to_string(),
+            transaction_hash: tx_hash.to_string(),
+            estimated_amount_out: current.estimated_amount_out,
+            amount_out_minimum,
+        })
+    }
+
+    #[instrument(skip(self), fields(from_token = %from_token.to_hex(), to_token = %to_token.to_hex()))]
+    async fn compare_quotes(
+        &self,
+        from_token: &TokenAddress,
+        to_token: &TokenAddress,
+        amount: Decimal,
+    ) -> anyhow::Result<QuoteComparison> {
+        if from_token == to_token {
+            return Err(anyhow::anyhow!(
+                "Cannot compare quotes for a token against itself"
+            ));
+        }
+        if amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Amount must be greater than zero"));
+        }
+        let decimals = self
+            .ethereum_provider
+            .get_token_decimals(from_token)
+            .await?;
+        let amount_in = TokenAmount::new(amount, decimals);
+        self.ethereum_provider
+            .compare_quotes(from_token, to_token, &amount_in, &self.contracts)
             .await
     }
+
+    #[instrument(skip(self))]
+    async fn wrap_eth(&self, amount: Decimal) -> anyhow::Result<WrapResult> {
+        let (weth, amount) = self.wrap_amount(amount, true).await?;
+        let tx_hash = self
+            .ethereum_provider
+            .wrap_eth(&weth, amount.to_u256()?)
+            .await?;
+        info!(tx_hash = %tx_hash, "ETH wrap broadcast");
+        Ok(WrapResult {
+            weth_address: weth,
+            amount,
+            transaction_hash: tx_hash.to_string(),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn unwrap_weth(&self, amount: Decimal) -> anyhow::Result<WrapResult> {
+        let (weth, amount) = self.wrap_amount(amount, false).await?;
+        let tx_hash = self
+            .ethereum_provider
+            .unwrap_weth(&weth, amount.to_u256()?)
+            .await?;
+        info!(tx_hash = %tx_hash, "WETH unwrap broadcast");
+        Ok(WrapResult {
+            weth_address: weth,
+            amount,
+            transaction_hash: tx_hash.to_string(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::providers::MockEthereumProvider;
-    use crate::types::{TokenAddress, TokenAmount};
+    use crate::types::{
+        BalanceInfo, PermitKind, PermitSignature, RouteHop, SwapProtocol, SwapRoute,
+        SwapSimulation, TokenAddress, TokenPrice, WalletAddress,
+    };
     use crate::ContractAddresses;
+    use alloy::primitives::{Address, B256};
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
@@ -56,7 +487,17 @@ mod tests {
             weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
             uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
             uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-            uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+            uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+            uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+            uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+            sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+            sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+            curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+            balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+            balancer_pool_ids: vec![
+                "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019".to_string(),
+                "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a".to_string(),
+            ],
             chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
         }
     }
@@ -73,15 +514,34 @@ mod tests {
             to_token: to_token.clone(),
             amount_in: TokenAmount::from_human_readable("1.0", 18).unwrap(),
             slippage_tolerance: Decimal::from_str("0.5").unwrap(),
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
         };
 
         let expected_result = SwapResult {
             params: swap_params.clone(),
             estimated_amount_out: TokenAmount::from_human_readable("98.5", 18).unwrap(),
             price_impact: Decimal::from_str("0.12").unwrap(),
+            high_price_impact: false,
             gas_estimate: 180000,
             gas_cost_eth: Some(Decimal::from_str("0.012").unwrap()),
-            route: "uniswap_v3".to_string(),
+            gas_cost_breakdown: None,
+            requires_approval: false,
+            route: SwapRoute {
+                protocol: SwapProtocol::UniswapV3,
+                hops: vec![RouteHop {
+                    token_in: from_token.clone(),
+                    token_out: to_token.clone(),
+                    fee_tier: 3000,
+                    pool: Address::ZERO,
+                    liquidity: 1,
+                }],
+            },
+            route_quotes: Vec::new(),
+            simulation: SwapSimulation::default(),
         };
 
         let swap_params_clone = swap_params.clone();
@@ -96,6 +556,582 @@ mod tests {
         let result = service.simulate_swap(&swap_params).await.unwrap();
 
         assert_eq!(result.gas_estimate, 180000);
-        assert_eq!(result.route, "uniswap_v3");
+        assert!(result.route.is_direct());
+        assert_eq!(result.route.fee_tiers(), vec![3000]);
+    }
+
+    fn usdc_to_weth_params() -> SwapParams {
+        let contracts = get_test_contracts();
+        SwapParams {
+            from_token: TokenAddress::from_hex(&contracts.usdc).unwrap(),
+            to_token: TokenAddress::from_hex(&contracts.weth).unwrap(),
+            amount_in: TokenAmount::from_human_readable("100", 6).unwrap(),
+            slippage_tolerance: Decimal::from_str("1").unwrap(),
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        }
+    }
+
+    fn swap_result(params: &SwapParams, amount_out: &str) -> SwapResult {
+        SwapResult {
+            params: params.clone(),
+            estimated_amount_out: TokenAmount::from_human_readable(amount_out, 18).unwrap(),
+            price_impact: Decimal::ZERO,
+            high_price_impact: false,
+            gas_estimate: 150000,
+            gas_cost_eth: None,
+            gas_cost_breakdown: None,
+            requires_approval: false,
+            route: SwapRoute::default(),
+            route_quotes: Vec::new(),
+            simulation: SwapSimulation {
+                success: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Mock whose simulations return `amounts_out` in order
+    fn mock_with_simulations(amounts_out: &'static [&'static str]) -> MockEthereumProvider {
+        let mut mock_provider = MockEthereumProvider::new();
+        let mut calls = 0;
+        mock_provider
+            .expect_simulate_swap()
+            .times(amounts_out.len())
+            .returning(move |params, _| {
+                let result = swap_result(params, amounts_out[calls]);
+                calls += 1;
+                Ok(result)
+            });
+        mock_provider
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_after_quote() {
+        let mut mock_provider = mock_with_simulations(&["0.04", "0.0399"]);
+        let tx_hash = B256::repeat_byte(0xab);
+        mock_provider
+            .expect_execute_swap()
+            .withf(|_, minimum, permit, _| {
+                minimum.raw == Decimal::from_str("0.0396").unwrap() && permit.is_none()
+            })
+            .times(1)
+            .returning(move |_, _, _, _| Ok(tx_hash));
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        assert!(quote.expires_at > Utc::now());
+
+        let execution = service.execute_swap(&quote.quote_id, false).await.unwrap();
+        assert_eq!(execution.transaction_hash, tx_hash.to_string());
+        assert_eq!(
+            execution.estimated_amount_out.raw,
+            Decimal::from_str("0.0399").unwrap()
+        );
+
+        // Quotes are single-use
+        let replay = service.execute_swap(&quote.quote_id, false).await;
+        assert!(replay.unwrap_err().to_string().contains("Unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_unknown_quote() {
+        let service = SwapService::new(Arc::new(MockEthereumProvider::new()), get_test_contracts());
+        let result = service.execute_swap("0xdeadbeef", false).await;
+        assert!(result.unwrap_err().to_string().contains("Unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_expired_quote() {
+        let mock_provider = mock_with_simulations(&["0.04"]);
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts())
+            .with_quote_ttl(Duration::ZERO);
+
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let result = service.execute_swap(&quote.quote_id, false).await;
+        assert!(result.unwrap_err().to_string().contains("expired"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_rejects_price_moved_beyond_slippage() {
+        // 1% tolerance on 0.04 allows down to 0.0396
+        let mock_provider = mock_with_simulations(&["0.04", "0.0395"]);
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        let result = service.execute_swap(&quote.quote_id, false).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("beyond slippage tolerance"));
+    }
+
+    /// Mock whose exact-output simulations need `amounts_in` USDC in order
+    fn mock_with_exact_output_simulations(
+        amounts_in: &'static [&'static str],
+    ) -> MockEthereumProvider {
+        let mut mock_provider = MockEthereumProvider::new();
+        let mut calls = 0;
+        mock_provider
+            .expect_simulate_swap()
+            .times(amounts_in.len())
+            .returning(move |params, _| {
+                let quoted = SwapParams {
+                    amount_in: TokenAmount::from_human_readable(amounts_in[calls], 6).unwrap(),
+                    ..params.clone()
+                };
+                calls += 1;
+                Ok(swap_result(&quoted, "0.04"))
+            });
+        mock_provider
+    }
+
+    fn exact_output_params() -> SwapParams {
+        SwapParams {
+            amount_in: TokenAmount::new(Decimal::ZERO, 6),
+            amount_out: Some(TokenAmount::from_human_readable("0.04", 18).unwrap()),
+            ..usdc_to_weth_params()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_exact_output_swap_within_maximum_input() {
+        // 1% tolerance on a 100 USDC quote allows spending up to 101
+        let mut mock_provider = mock_with_exact_output_simulations(&["100", "100.9"]);
+        let tx_hash = B256::repeat_byte(0xcd);
+        mock_provider
+            .expect_execute_swap()
+            .withf(|params, minimum, _, _| {
+                params.maximum_amount_in().raw == Decimal::from(101)
+                    && minimum.raw == Decimal::from_str("0.04").unwrap()
+            })
+            .times(1)
+            .returning(move |_, _, _, _| Ok(tx_hash));
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let quote = service.quote_swap(&exact_output_params()).await.unwrap();
+        assert_eq!(quote.result.params.amount_in.raw, Decimal::from(100));
+
+        let execution = service.execute_swap(&quote.quote_id, false).await.unwrap();
+        assert_eq!(execution.transaction_hash, tx_hash.to_string());
+        assert_eq!(
+            execution.amount_out_minimum.raw,
+            Decimal::from_str("0.04").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_exact_output_swap_rejects_input_beyond_slippage() {
+        let mock_provider = mock_with_exact_output_simulations(&["100", "101.5"]);
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+
+        let quote = service.quote_swap(&exact_output_params()).await.unwrap();
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("quoted input 100"), "{error}");
+    }
+
+    /// Have `mock_provider` price USDC at `usdc_usd` (or not at all) and WETH at $3000
+    fn expect_prices(mock_provider: &mut MockEthereumProvider, usdc_usd: Option<Decimal>) {
+        let usdc = TokenAddress::from_hex(&get_test_contracts().usdc).unwrap();
+        mock_provider
+            .expect_get_token_price()
+            .returning(move |token, _| {
+                Ok(TokenPrice {
+                    token_address: token.clone(),
+                    price_eth: Decimal::ZERO,
+                    price_usd: match *token == usdc {
+                        true => usdc_usd,
+                        false => Some(Decimal::from(3000)),
+                    },
+                    source: "uniswap_v3".to_string(),
+                })
+            });
+    }
+
+    #[tokio::test]
+    async fn test_quote_swap_refuses_swaps_above_the_maximum_value() {
+        // A priced input is refused without simulating the swap
+        let mut mock_provider = MockEthereumProvider::new();
+        expect_prices(&mut mock_provider, Some(Decimal::ONE));
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts())
+            .with_max_swap_value(Decimal::from(50));
+
+        let error = service
+            .quote_swap(&usdc_to_weth_params())
+            .await
+            .unwrap_err();
+        match error.downcast_ref::<SwapValueError>() {
+            Some(SwapValueError::TooLarge {
+                value_usd,
+                max_swap_value_usd,
+            }) => {
+                assert_eq!(*value_usd, Decimal::from(100));
+                assert_eq!(*max_swap_value_usd, Decimal::from(50));
+            }
+            other => panic!("unexpected refusal {other:?}: {error}"),
+        }
+        assert!(service.quotes.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quote_swap_refuses_unpriced_swaps_under_a_maximum_value() {
+        let mut mock_provider = mock_with_simulations(&["0.04"]);
+        mock_provider
+            .expect_get_token_price()
+            .returning(|_, _| Err(anyhow::anyhow!("No pool")));
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts())
+            .with_max_swap_value(Decimal::from(1000));
+
+        let error = service
+            .quote_swap(&usdc_to_weth_params())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SwapValueError>(),
+            Some(SwapValueError::Unpriced)
+        ));
+        assert!(service.quotes.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_refuses_swaps_grown_above_the_maximum_value() {
+        // Unpriced USDC is valued by its WETH output: $120 when quoted, $180 now
+        let mut mock_provider = mock_with_simulations(&["0.04", "0.06"]);
+        expect_prices(&mut mock_provider, None);
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts())
+            .with_max_swap_value(Decimal::from(150));
+
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error.downcast_ref::<SwapValueError>(),
+                Some(SwapValueError::TooLarge { value_usd, .. }) if *value_usd == Decimal::from(180)
+            ),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_ids_are_unique() {
+        let mock_provider = mock_with_simulations(&["0.04", "0.04"]);
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let params = usdc_to_weth_params();
+
+        let first = service.quote_swap(&params).await.unwrap();
+        let second = service.quote_swap(&params).await.unwrap();
+        assert_ne!(first.quote_id, second.quote_id);
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_requires_approval() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let mut calls = 0;
+        mock_provider
+            .expect_simulate_swap()
+            .times(2)
+            .returning(move |params, _| {
+                calls += 1;
+                let mut result = swap_result(params, "0.04");
+                result.requires_approval = calls > 1;
+                Ok(result)
+            });
+        mock_provider.expect_execute_swap().never();
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        let result = service.execute_swap(&quote.quote_id, false).await;
+        assert!(result.unwrap_err().to_string().contains("approve_token"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_with_permit_skips_approval() {
+        let mut mock_provider = MockEthereumProvider::new();
+        mock_provider
+            .expect_simulate_swap()
+            .times(2)
+            .returning(|params, _| {
+                let mut result = swap_result(params, "0.04");
+                result.requires_approval = true;
+                Ok(result)
+            });
+        mock_provider
+            .expect_sign_permit()
+            .withf(|token, spender, value, _| {
+                let contracts = get_test_contracts();
+                token.to_hex().eq_ignore_ascii_case(&contracts.usdc)
+                    && format!("{:#x}", spender).eq_ignore_ascii_case(&contracts.uniswap_v3_router)
+                    && *value == alloy::primitives::U256::from(100_000_000u64)
+            })
+            .times(1)
+            .returning(|token, spender, value, deadline| {
+                Ok(PermitSignature {
+                    kind: PermitKind::Eip2612,
+                    token_address: token.clone(),
+                    owner: WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+                        .unwrap(),
+                    spender: *spender,
+                    value,
+                    nonce: alloy::primitives::U256::ZERO,
+                    deadline,
+                    expiration: None,
+                    signature: alloy::primitives::Bytes::from(vec![0u8; 65]),
+                    v: 27,
+                    r: B256::ZERO,
+                    s: B256::ZERO,
+                })
+            });
+        mock_provider
+            .expect_execute_swap()
+            .withf(|_, _, permit, _| permit.is_some_and(|p| p.kind == PermitKind::Eip2612))
+            .times(1)
+            .returning(|_, _, _, _| Ok(B256::repeat_byte(0xcd)));
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        let execution = service.execute_swap(&quote.quote_id, true).await.unwrap();
+        assert_eq!(
+            execution.transaction_hash,
+            B256::repeat_byte(0xcd).to_string()
+        );
+    }
+
+    /// Mock whose simulations report `price_impacts` in order
+    fn mock_with_price_impacts(price_impacts: &'static [&'static str]) -> MockEthereumProvider {
+        let mut mock_provider = MockEthereumProvider::new();
+        let mut calls = 0;
+        mock_provider
+            .expect_simulate_swap()
+            .times(price_impacts.len())
+            .returning(move |params, _| {
+                let mut result = swap_result(params, "0.04");
+                result.price_impact = Decimal::from_str(price_impacts[calls]).unwrap();
+                calls += 1;
+                Ok(result)
+            });
+        mock_provider
+    }
+
+    #[tokio::test]
+    async fn test_simulate_swap_flags_high_price_impact() {
+        let service = SwapService::new(
+            Arc::new(mock_with_price_impacts(&["2.9", "3.1"])),
+            get_test_contracts(),
+        );
+        let params = usdc_to_weth_params();
+
+        let low = service.simulate_swap(&params).await.unwrap();
+        assert!(!low.high_price_impact);
+        let high = service.simulate_swap(&params).await.unwrap();
+        assert!(high.high_price_impact);
+    }
+
+    #[tokio::test]
+    async fn test_price_impact_above_maximum_is_refused() {
+        let service = SwapService::new(
+            Arc::new(mock_with_price_impacts(&["0.5", "1.5", "0.5"])),
+            get_test_contracts(),
+        )
+        .with_price_impact_limits(Decimal::from_str("0.25").unwrap(), Decimal::ONE);
+        let params = usdc_to_weth_params();
+
+        let quote = service.quote_swap(&params).await.unwrap();
+        assert!(quote.result.high_price_impact);
+
+        // Execution re-simulates, and the pool moved past the limit in between
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("exceeds the 1% limit"),
+            "{error}"
+        );
+
+        let quote = service.quote_swap(&params).await;
+        assert!(quote.is_ok());
+    }
+
+    /// Mock whose quote simulation succeeds and whose re-simulation at execution is
+    /// adjusted by `at_execution`
+    fn mock_with_execution_simulation(
+        at_execution: impl Fn(&mut SwapResult) + Send + 'static,
+    ) -> MockEthereumProvider {
+        let mut mock_provider = MockEthereumProvider::new();
+        let mut calls = 0;
+        mock_provider
+            .expect_simulate_swap()
+            .times(2)
+            .returning(move |params, _| {
+                calls += 1;
+                let mut result = swap_result(params, "0.04");
+                if calls > 1 {
+                    at_execution(&mut result);
+                }
+                Ok(result)
+            });
+        mock_provider.expect_execute_swap().never();
+        mock_provider
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_refuses_quote_simulated_from_another_account() {
+        let mut mock_provider = mock_with_simulations(&["0.04"]);
+        mock_provider.expect_wallet_address().returning(|| {
+            WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap()
+        });
+        mock_provider.expect_execute_swap().never();
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let params = SwapParams {
+            sender: Some(WalletAddress::new(Address::repeat_byte(0x42))),
+            ..usdc_to_weth_params()
+        };
+        let quote = service.quote_swap(&params).await.unwrap();
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not the wallet"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_refuses_insufficient_balance() {
+        let mut mock_provider = mock_with_execution_simulation(|result| {
+            // Execution never dry-runs on overridden balances
+            assert!(!result.params.override_token_state);
+            result.simulation.balance = Some(TokenAmount::from_human_readable("50", 6).unwrap());
+        });
+        mock_provider.expect_wallet_address().returning(|| {
+            WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap()
+        });
+
+        let service = SwapService::new(Arc::new(mock_provider), get_test_contracts());
+        let params = SwapParams {
+            sender: Some(
+                WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap(),
+            ),
+            override_token_state: true,
+            ..usdc_to_weth_params()
+        };
+        let quote = service.quote_swap(&params).await.unwrap();
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Insufficient"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_execute_swap_refuses_reverting_simulation() {
+        use alloy::sol_types::{Revert, SolError};
+
+        let service = SwapService::new(
+            Arc::new(mock_with_execution_simulation(|result| {
+                result.simulation.success = false;
+                result.simulation.revert_reason = Some(crate::contracts::decode::decode_revert(
+                    &Revert::from("STF").abi_encode(),
+                ));
+            })),
+            get_test_contracts(),
+        );
+        let quote = service.quote_swap(&usdc_to_weth_params()).await.unwrap();
+        let error = service
+            .execute_swap(&quote.quote_id, false)
+            .await
+            .unwrap_err();
+        let reason = RevertError::find(&error).expect("revert reason");
+        assert_eq!(reason.message, "STF");
+    }
+
+    #[tokio::test]
+    async fn test_wrap_and_unwrap_check_the_balance_they_spend() {
+        let contracts = get_test_contracts();
+        let weth = TokenAddress::from_hex(&contracts.weth).unwrap();
+        let balance = |amount: &str, token: Option<TokenAddress>| BalanceInfo {
+            wallet_address: WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+                .unwrap(),
+            token_address: token,
+            amount: TokenAmount::from_human_readable(amount, 18).unwrap(),
+            symbol: String::new(),
+        };
+        let mut mock = MockEthereumProvider::new();
+        mock.expect_wallet_address().returning(|| {
+            WalletAddress::from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap()
+        });
+        let eth_balance = balance("1.5", None);
+        mock.expect_get_eth_balance()
+            .returning(move |_| Ok(eth_balance.clone()));
+        let weth_balance = balance("0.25", Some(weth.clone()));
+        mock.expect_get_erc20_balance()
+            .returning(move |_, _| Ok(weth_balance.clone()));
+        mock.expect_wrap_eth()
+            .withf(|_, amount| *amount == U256::from(1_200_000_000_000_000_000u64))
+            .times(1)
+            .returning(|_, _| Ok(B256::repeat_byte(1)));
+        mock.expect_unwrap_weth().never();
+        let service = SwapService::new(Arc::new(mock), contracts);
+
+        let wrap = service
+            .wrap_eth(Decimal::from_str("1.2").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(wrap.weth_address, weth);
+        assert_eq!(wrap.transaction_hash, B256::repeat_byte(1).to_string());
+        assert!(service.wrap_eth(Decimal::from(2)).await.is_err());
+        // Only 0.25 WETH to unwrap
+        let error = service.unwrap_weth(Decimal::ONE).await.unwrap_err();
+        assert!(error.to_string().contains("Insufficient WETH"), "{error}");
+        assert!(service.wrap_eth(Decimal::ZERO).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compare_quotes_uses_input_token_decimals() {
+        let contracts = get_test_contracts();
+        let usdc = TokenAddress::from_hex(&contracts.usdc).unwrap();
+        let dai = TokenAddress::from_hex(&contracts.dai).unwrap();
+        let mut mock = MockEthereumProvider::new();
+        mock.expect_get_token_decimals().returning(|_| Ok(6));
+        mock.expect_compare_quotes()
+            .withf(|_, _, amount_in, _| {
+                amount_in.decimals == 6 && amount_in.raw == Decimal::from(250)
+            })
+            .times(1)
+            .returning(|from_token, to_token, amount_in, _| {
+                Ok(QuoteComparison {
+                    from_token: from_token.clone(),
+                    to_token: to_token.clone(),
+                    amount_in: amount_in.clone(),
+                    quotes: Vec::new(),
+                    unavailable: Vec::new(),
+                })
+            });
+        let service = SwapService::new(Arc::new(mock), contracts);
+
+        let comparison = service
+            .compare_quotes(&usdc, &dai, Decimal::from(250))
+            .await
+            .unwrap();
+        assert_eq!(comparison.amount_in.decimals, 6);
+        assert!(service
+            .compare_quotes(&usdc, &usdc, Decimal::from(250))
+            .await
+            .is_err());
+        assert!(service
+            .compare_quotes(&usdc, &dai, Decimal::ZERO)
+            .await
+            .is_err());
     }
 }
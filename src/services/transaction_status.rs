@@ -1,23 +1,100 @@
 /// Transaction status service implementation
+/// Also recovers stuck transactions by replacing them at the same nonce
 use crate::providers::EthereumProvider;
-use crate::types::TransactionStatusInfo;
+use crate::types::{
+    ReplacementKind, ReplacementResult, TransactionStatus, TransactionStatusInfo, WaitOutcome,
+    WaitProgress, WaitResult, WaitTarget,
+};
 use alloy::primitives::B256;
 use async_trait::async_trait;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Default cap on how long one `wait_for_transaction` call may block
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(120);
+
+/// Default interval between checks for a new block while waiting
+pub const DEFAULT_WAIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 #[async_trait]
 pub trait TransactionStatusServiceTrait: Send + Sync {
-    async fn get_transaction_status(&self, tx_hash: &B256)
-        -> anyhow::Result<TransactionStatusInfo>;
+    /// With `detail`, also report fees, decoded calldata and logs
+    async fn get_transaction_status(
+        &self,
+        tx_hash: &B256,
+        detail: bool,
+    ) -> anyhow::Result<TransactionStatusInfo>;
+
+    /// Rebroadcast a pending transaction at the same nonce with bumped fees
+    async fn speed_up_transaction(&self, tx_hash: &B256) -> anyhow::Result<ReplacementResult>;
+
+    /// Replace a pending transaction with a 0-value self-transfer at the same nonce
+    async fn cancel_transaction(&self, tx_hash: &B256) -> anyhow::Result<ReplacementResult>;
+
+    /// Block until `target` is reached, the transaction fails, is dropped or replaced,
+    /// or `timeout` (capped by the service's limit) passes. The status at every new
+    /// block is sent to `progress`, if given.
+    async fn wait_for_transaction(
+        &self,
+        tx_hash: &B256,
+        target: WaitTarget,
+        timeout: Duration,
+        progress: Option<mpsc::UnboundedSender<WaitProgress>>,
+    ) -> anyhow::Result<WaitResult>;
 }
 
 pub struct TransactionStatusService {
     pub ethereum_provider: Arc<dyn EthereumProvider>,
+    max_wait: Duration,
+    poll_interval: Duration,
 }
 
 impl TransactionStatusService {
     pub fn new(ethereum_provider: Arc<dyn EthereumProvider>) -> Self {
-        Self { ethereum_provider }
+        Self {
+            ethereum_provider,
+            max_wait: DEFAULT_MAX_WAIT,
+            poll_interval: DEFAULT_WAIT_POLL_INTERVAL,
+        }
+    }
+
+    /// Override the cap on how long `wait_for_transaction` may block
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Override how often a wait checks for a new block; chains with sub-second blocks
+    /// want less than the default
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Longest a single wait may block
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// How a wait ends given the latest status; None to keep waiting
+    pub fn wait_outcome(status: &TransactionStatusInfo, target: WaitTarget) -> Option<WaitOutcome> {
+        match status.status {
+            TransactionStatus::Failed => Some(WaitOutcome::Failed),
+            TransactionStatus::Dropped => Some(WaitOutcome::Dropped),
+            TransactionStatus::Replaced => Some(WaitOutcome::Replaced),
+            TransactionStatus::Confirmed => {
+                let reached = match target {
+                    WaitTarget::Confirmations(n) => status.confirmations >= n,
+                    WaitTarget::Finalized => status.finalized,
+                };
+                reached.then_some(WaitOutcome::Reached)
+            }
+            // A just-broadcast transaction may not have reached this node yet
+            TransactionStatus::Pending | TransactionStatus::NotFound => None,
+        }
     }
 }
 
@@ -26,8 +103,97 @@ impl TransactionStatusServiceTrait for TransactionStatusService {
     async fn get_transaction_status(
         &self,
         tx_hash: &B256,
+        detail: bool,
     ) -> anyhow::Result<TransactionStatusInfo> {
-        self.ethereum_provider.get_transaction_status(tx_hash).await
+        let mut status = self
+            .ethereum_provider
+            .get_transaction_status(tx_hash)
+            .await?;
+        if detail {
+            status.detail = self
+                .ethereum_provider
+                .get_transaction_detail(tx_hash)
+                .await?;
+        }
+        Ok(status)
+    }
+
+    async fn speed_up_transaction(&self, tx_hash: &B256) -> anyhow::Result<ReplacementResult> {
+        self.ethereum_provider
+            .replace_transaction(tx_hash, ReplacementKind::SpeedUp)
+            .await
+    }
+
+    async fn cancel_transaction(&self, tx_hash: &B256) -> anyhow::Result<ReplacementResult> {
+        self.ethereum_provider
+            .replace_transaction(tx_hash, ReplacementKind::Cancel)
+            .await
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        tx_hash: &B256,
+        target: WaitTarget,
+        timeout: Duration,
+        progress: Option<mpsc::UnboundedSender<WaitProgress>>,
+    ) -> anyhow::Result<WaitResult> {
+        let started = Instant::now();
+        let deadline = started + timeout.min(self.max_wait);
+        let mut last_block = None;
+        let mut last_status = None;
+        let mut blocks_observed = 0;
+
+        loop {
+            let block_number = self.ethereum_provider.get_block_number().await?;
+            // The status can only change with a new block, so it is only fetched then
+            if last_block != Some(block_number) {
+                last_block = Some(block_number);
+                blocks_observed += 1;
+
+                let status = self
+                    .ethereum_provider
+                    .get_transaction_status(tx_hash)
+                    .await?;
+                debug!(
+                    block_number,
+                    status = ?status.status,
+                    confirmations = status.confirmations,
+                    "Waiting for transaction"
+                );
+                if let Some(progress) = &progress {
+                    // A receiver that hung up doesn't stop the wait
+                    let _ = progress.send(WaitProgress {
+                        block_number,
+                        status: status.clone(),
+                    });
+                }
+
+                if let Some(outcome) = Self::wait_outcome(&status, target) {
+                    return Ok(WaitResult {
+                        outcome,
+                        target,
+                        status,
+                        blocks_observed,
+                        waited_seconds: started.elapsed().as_secs(),
+                    });
+                }
+                last_status = Some(status);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                if let Some(status) = last_status.take() {
+                    return Ok(WaitResult {
+                        outcome: WaitOutcome::TimedOut,
+                        target,
+                        status,
+                        blocks_observed,
+                        waited_seconds: started.elapsed().as_secs(),
+                    });
+                }
+            }
+            tokio::time::sleep(self.poll_interval.min(remaining)).await;
+        }
     }
 }
 
@@ -59,6 +225,10 @@ mod tests {
             status: TransactionStatus::Confirmed,
             block_number: Some(12345),
             confirmations: 6,
+            finalized: true,
+            replaced_by: None,
+            detail: None,
+            revert_reason: None,
         };
 
         mock_provider
@@ -71,11 +241,15 @@ mod tests {
                     status: TransactionStatus::Confirmed,
                     block_number: Some(12345),
                     confirmations: 6,
+                    finalized: true,
+                    replaced_by: None,
+                    detail: None,
+                    revert_reason: None,
                 })
             });
 
         let service = TransactionStatusService::new(Arc::new(mock_provider));
-        let result = service.get_transaction_status(&tx_hash).await;
+        let result = service.get_transaction_status(&tx_hash, false).await;
 
         assert!(result.is_ok());
         let status_info = result.unwrap();
@@ -99,7 +273,7 @@ mod tests {
             .returning(|_| Err(anyhow::anyhow!("Transaction not found")));
 
         let service = TransactionStatusService::new(Arc::new(mock_provider));
-        let result = service.get_transaction_status(&tx_hash).await;
+        let result = service.get_transaction_status(&tx_hash, false).await;
 
         assert!(result.is_err());
         assert!(result
@@ -107,4 +281,248 @@ mod tests {
             .to_string()
             .contains("Transaction not found"));
     }
+
+    #[tokio::test]
+    async fn test_get_transaction_status_with_detail() {
+        use crate::types::{TransactionDetail, TransactionStatus};
+        use alloy::primitives::Address;
+        use rust_decimal::Decimal;
+
+        let mut mock_provider = MockEthereumProvider::new();
+        let tx_hash = B256::from([4u8; 32]);
+
+        mock_provider
+            .expect_get_transaction_status()
+            .times(2)
+            .returning(move |hash| {
+                Ok(TransactionStatusInfo {
+                    transaction_hash: format!("{:?}", hash),
+                    status: TransactionStatus::Pending,
+                    block_number: None,
+                    confirmations: 0,
+                    finalized: false,
+                    replaced_by: None,
+                    detail: None,
+                    revert_reason: None,
+                })
+            });
+        // Only fetched when asked for
+        mock_provider
+            .expect_get_transaction_detail()
+            .with(eq(tx_hash))
+            .times(1)
+            .returning(|_| {
+                Ok(Some(TransactionDetail {
+                    from: Address::repeat_byte(1),
+                    to: Some(Address::repeat_byte(2)),
+                    nonce: 3,
+                    value_eth: Decimal::ONE,
+                    gas_used: None,
+                    effective_gas_price: None,
+                    fee_eth: None,
+                    method: None,
+                    logs: Vec::new(),
+                    undecoded_log_count: 0,
+                }))
+            });
+
+        let service = TransactionStatusService::new(Arc::new(mock_provider));
+        let plain = service
+            .get_transaction_status(&tx_hash, false)
+            .await
+            .unwrap();
+        assert!(plain.detail.is_none());
+
+        let detailed = service
+            .get_transaction_status(&tx_hash, true)
+            .await
+            .unwrap();
+        let detail = detailed.detail.unwrap();
+        assert_eq!(detail.nonce, 3);
+        assert_eq!(detail.value_eth, Decimal::ONE);
+    }
+
+    fn status(status: TransactionStatus, confirmations: u64) -> TransactionStatusInfo {
+        TransactionStatusInfo {
+            transaction_hash: format!("{:?}", B256::repeat_byte(5)),
+            status,
+            block_number: (confirmations > 0).then_some(100),
+            confirmations,
+            finalized: false,
+            replaced_by: None,
+            detail: None,
+            revert_reason: None,
+        }
+    }
+
+    /// Provider whose chain advances one block per poll and whose transaction goes
+    /// through `statuses`, one per new block, repeating the last
+    fn advancing_provider(statuses: Vec<TransactionStatusInfo>) -> MockEthereumProvider {
+        use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+        let mut mock_provider = MockEthereumProvider::new();
+        let block = Arc::new(AtomicU64::new(100));
+        mock_provider
+            .expect_get_block_number()
+            .returning(move || Ok(block.fetch_add(1, Ordering::SeqCst)));
+        let next = Arc::new(AtomicUsize::new(0));
+        mock_provider
+            .expect_get_transaction_status()
+            .returning(move |_| {
+                let i = next.fetch_add(1, Ordering::SeqCst).min(statuses.len() - 1);
+                Ok(statuses[i].clone())
+            });
+        mock_provider
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_transaction_reaches_confirmations() {
+        let mock_provider = advancing_provider(vec![
+            status(TransactionStatus::Pending, 0),
+            status(TransactionStatus::Confirmed, 1),
+            status(TransactionStatus::Confirmed, 2),
+        ]);
+        let service = TransactionStatusService::new(Arc::new(mock_provider))
+            .with_poll_interval(Duration::from_millis(1));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let result = service
+            .wait_for_transaction(
+                &B256::repeat_byte(5),
+                WaitTarget::Confirmations(2),
+                Duration::from_secs(5),
+                Some(tx),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.outcome, WaitOutcome::Reached);
+        assert_eq!(result.status.confirmations, 2);
+        assert_eq!(result.blocks_observed, 3);
+
+        let mut blocks = Vec::new();
+        while let Ok(progress) = rx.try_recv() {
+            blocks.push(progress.block_number);
+        }
+        assert_eq!(blocks, vec![100, 101, 102]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_transaction_returns_early_on_failure() {
+        let mock_provider = advancing_provider(vec![
+            status(TransactionStatus::Pending, 0),
+            status(TransactionStatus::Failed, 1),
+        ]);
+        let service = TransactionStatusService::new(Arc::new(mock_provider))
+            .with_poll_interval(Duration::from_millis(1));
+
+        let result = service
+            .wait_for_transaction(
+                &B256::repeat_byte(5),
+                WaitTarget::Finalized,
+                Duration::from_secs(5),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.outcome, WaitOutcome::Failed);
+        assert_eq!(result.blocks_observed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_transaction_times_out_at_service_cap() {
+        let mut mock_provider = MockEthereumProvider::new();
+        mock_provider
+            .expect_get_block_number()
+            .returning(|| Ok(100));
+        // No new block, so the status is fetched only once
+        mock_provider
+            .expect_get_transaction_status()
+            .times(1)
+            .returning(|_| Ok(status(TransactionStatus::Pending, 0)));
+        let service = TransactionStatusService::new(Arc::new(mock_provider))
+            .with_max_wait(Duration::from_millis(30))
+            .with_poll_interval(Duration::from_millis(5));
+
+        let result = service
+            .wait_for_transaction(
+                &B256::repeat_byte(5),
+                WaitTarget::Confirmations(1),
+                Duration::from_secs(600),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.outcome, WaitOutcome::TimedOut);
+        assert_eq!(result.status.status, TransactionStatus::Pending);
+        assert_eq!(result.blocks_observed, 1);
+    }
+
+    #[test]
+    fn test_wait_outcome() {
+        let mut confirmed = status(TransactionStatus::Confirmed, 3);
+        assert_eq!(
+            TransactionStatusService::wait_outcome(&confirmed, WaitTarget::Confirmations(3)),
+            Some(WaitOutcome::Reached)
+        );
+        assert_eq!(
+            TransactionStatusService::wait_outcome(&confirmed, WaitTarget::Finalized),
+            None
+        );
+        confirmed.finalized = true;
+        assert_eq!(
+            TransactionStatusService::wait_outcome(&confirmed, WaitTarget::Finalized),
+            Some(WaitOutcome::Reached)
+        );
+        assert_eq!(
+            TransactionStatusService::wait_outcome(
+                &status(TransactionStatus::Replaced, 0),
+                WaitTarget::Confirmations(1)
+            ),
+            Some(WaitOutcome::Replaced)
+        );
+        assert_eq!(
+            TransactionStatusService::wait_outcome(
+                &status(TransactionStatus::NotFound, 0),
+                WaitTarget::Confirmations(1)
+            ),
+            None
+        );
+    }
+
+    fn replacement(kind: ReplacementKind, original: B256) -> ReplacementResult {
+        ReplacementResult {
+            kind,
+            original_hash: format!("{:?}", original),
+            replacement_hash: format!("{:?}", B256::repeat_byte(0xee)),
+            nonce: 7,
+            max_fee_per_gas: 44_000_000_000,
+            max_priority_fee_per_gas: 2_200_000_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_speed_up_and_cancel_use_matching_replacement_kind() {
+        let mut mock_provider = MockEthereumProvider::new();
+        let tx_hash = B256::from([3u8; 32]);
+
+        mock_provider
+            .expect_replace_transaction()
+            .with(eq(tx_hash), eq(ReplacementKind::SpeedUp))
+            .times(1)
+            .returning(|hash, kind| Ok(replacement(kind, *hash)));
+        mock_provider
+            .expect_replace_transaction()
+            .with(eq(tx_hash), eq(ReplacementKind::Cancel))
+            .times(1)
+            .returning(|hash, kind| Ok(replacement(kind, *hash)));
+
+        let service = TransactionStatusService::new(Arc::new(mock_provider));
+        let sped_up = service.speed_up_transaction(&tx_hash).await.unwrap();
+        let cancelled = service.cancel_transaction(&tx_hash).await.unwrap();
+
+        assert_eq!(sped_up.kind, ReplacementKind::SpeedUp);
+        assert_eq!(cancelled.kind, ReplacementKind::Cancel);
+        assert_eq!(sped_up.original_hash, format!("{:?}", tx_hash));
+    }
 }
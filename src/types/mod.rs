@@ -1,7 +1,8 @@
 /// Domain types for Ethereum MCP server
 /// Following SOLID principles with clear separation of concerns
-use alloy::primitives::Address;
-use rust_decimal::Decimal;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -63,6 +64,15 @@ impl TokenAddress {
     pub fn to_hex(&self) -> String {
         format!("{:#x}", self.0)
     }
+
+    /// Native ETH, which has no contract and is written as the zero address
+    pub fn native() -> Self {
+        Self(Address::ZERO)
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.0.is_zero()
+    }
 }
 
 impl FromStr for TokenAddress {
@@ -114,6 +124,20 @@ impl TokenAmount {
         })
     }
 
+    /// Get raw units as a U256, rejecting amounts finer than the token's smallest unit
+    pub fn to_u256(&self) -> anyhow::Result<U256> {
+        let raw = self.to_raw_units()?;
+        if raw.fract() != Decimal::ZERO {
+            return Err(anyhow::anyhow!(
+                "Amount {} has more decimal places than the token's {}",
+                self.raw,
+                self.decimals
+            ));
+        }
+        U256::from_str(&raw.trunc().to_string())
+            .map_err(|e| anyhow::anyhow!("Invalid token amount {}: {}", self.raw, e))
+    }
+
     /// Get human-readable decimal value
     pub fn to_human_readable(&self) -> Decimal {
         self.raw
@@ -134,6 +158,68 @@ pub struct BalanceInfo {
     pub symbol: String,
 }
 
+/// ERC20 allowance granted by an owner to a spender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowanceInfo {
+    pub owner: WalletAddress,
+    pub spender: Address,
+    pub token_address: TokenAddress,
+    pub allowance: TokenAmount,
+    /// True for the conventional "infinite" approval of `type(uint256).max`
+    pub is_unlimited: bool,
+}
+
+/// How much to approve: an exact amount unless unlimited is explicitly requested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalAmount {
+    /// Human-readable token amount
+    Exact(Decimal),
+    /// `type(uint256).max`
+    Unlimited,
+}
+
+/// A broadcast ERC20 approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalResult {
+    pub token_address: TokenAddress,
+    pub spender: Address,
+    /// None for unlimited approvals, which do not fit a decimal amount
+    pub amount: Option<TokenAmount>,
+    pub is_unlimited: bool,
+    pub transaction_hash: String,
+}
+
+/// Which off-chain approval scheme a permit signature is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermitKind {
+    /// The token's own EIP-2612 `permit`
+    Eip2612,
+    /// Uniswap Permit2 `PermitSingle`
+    Permit2,
+}
+
+/// A signed gasless approval
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermitSignature {
+    pub kind: PermitKind,
+    pub token_address: TokenAddress,
+    pub owner: WalletAddress,
+    pub spender: Address,
+    /// Approved amount in raw token units
+    pub value: U256,
+    pub nonce: U256,
+    /// Unix timestamp after which the signature is rejected
+    pub deadline: U256,
+    /// Permit2 allowance expiry (unix timestamp); None for EIP-2612
+    pub expiration: Option<u64>,
+    /// 65-byte `r || s || v` signature
+    pub signature: Bytes,
+    pub v: u8,
+    pub r: B256,
+    pub s: B256,
+}
+
 /// Token price information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPrice {
@@ -150,6 +236,196 @@ pub struct SwapParams {
     pub to_token: TokenAddress,
     pub amount_in: TokenAmount,
     pub slippage_tolerance: Decimal,
+    /// Route to swap along; `None` searches for the best one
+    #[serde(default)]
+    pub route: Option<SwapRoute>,
+    /// Protocol to search for routes on; `None` quotes every protocol and takes the best
+    #[serde(default)]
+    pub protocol: Option<SwapProtocol>,
+    /// Output to receive exactly, for exact-output swaps; `amount_in` is then the
+    /// quoted input (zero until quoted)
+    #[serde(default)]
+    pub amount_out: Option<TokenAmount>,
+    /// Account to simulate the swap from instead of the configured wallet
+    #[serde(default)]
+    pub sender: Option<WalletAddress>,
+    /// Dry-run as if the sender held and had approved enough of the input token, by
+    /// overriding the token's storage for the `eth_call`
+    #[serde(default)]
+    pub override_token_state: bool,
+}
+
+impl SwapParams {
+    /// Lowest acceptable output for an estimate under this slippage tolerance (percent),
+    /// rounded down to the token's smallest unit
+    pub fn minimum_amount_out(&self, estimated: &TokenAmount) -> TokenAmount {
+        let multiplier = Decimal::ONE - self.slippage_tolerance / Decimal::from(100);
+        let minimum = (estimated.raw * multiplier)
+            .round_dp_with_strategy(estimated.decimals as u32, RoundingStrategy::ToZero);
+        TokenAmount::new(minimum, estimated.decimals)
+    }
+
+    /// Most input the swap may spend: `amount_in` itself for exact-input swaps, or the
+    /// quoted `amount_in` plus the slippage tolerance, rounded up, for exact-output swaps
+    pub fn maximum_amount_in(&self) -> TokenAmount {
+        if self.amount_out.is_none() {
+            return self.amount_in.clone();
+        }
+        let multiplier = Decimal::ONE + self.slippage_tolerance / Decimal::from(100);
+        let maximum = (self.amount_in.raw * multiplier).round_dp_with_strategy(
+            self.amount_in.decimals as u32,
+            RoundingStrategy::AwayFromZero,
+        );
+        TokenAmount::new(maximum, self.amount_in.decimals)
+    }
+}
+
+/// Gas cost split for rollups, where the L1 data fee is charged on top of L2 execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasCostBreakdown {
+    pub network: String,
+    pub l2_execution_fee_eth: Decimal,
+    pub l1_data_fee_eth: Decimal,
+}
+
+impl GasCostBreakdown {
+    /// Total cost paid by the sender in ETH
+    pub fn total_eth(&self) -> Decimal {
+        self.l2_execution_fee_eth + self.l1_data_fee_eth
+    }
+}
+
+/// DEX a swap route runs through. Sushiswap is a Uniswap V2 fork with its own pairs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapProtocol {
+    #[default]
+    UniswapV3,
+    UniswapV2,
+    Sushiswap,
+}
+
+impl SwapProtocol {
+    /// Every supported protocol, in the order candidates are quoted
+    pub const ALL: [SwapProtocol; 3] = [Self::UniswapV3, Self::UniswapV2, Self::Sushiswap];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UniswapV3 => "uniswap_v3",
+            Self::UniswapV2 => "uniswap_v2",
+            Self::Sushiswap => "sushiswap",
+        }
+    }
+
+    /// Whether routes go through constant-product V2 pairs rather than V3 pools
+    pub fn is_v2(&self) -> bool {
+        !matches!(self, Self::UniswapV3)
+    }
+}
+
+impl FromStr for SwapProtocol {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|protocol| protocol.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown swap protocol: {}", s))
+    }
+}
+
+/// One pool a swap passes through. V2 pairs all charge 0.3% (a `fee_tier` of 3000)
+/// and report `sqrt(reserve0 * reserve1)` as their liquidity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteHop {
+    pub token_in: TokenAddress,
+    pub token_out: TokenAddress,
+    pub fee_tier: u32,
+    pub pool: Address,
+    pub liquidity: u128,
+}
+
+/// Pools a swap passes through, in order
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct SwapRoute {
+    #[serde(default)]
+    pub protocol: SwapProtocol,
+    pub hops: Vec<RouteHop>,
+}
+
+impl SwapRoute {
+    /// Tokens along the route, from input to output
+    pub fn tokens(&self) -> Vec<Address> {
+        let mut tokens: Vec<Address> = self.hops.iter().map(|h| h.token_in.address()).collect();
+        tokens.extend(self.hops.last().map(|h| h.token_out.address()));
+        tokens
+    }
+
+    pub fn fee_tiers(&self) -> Vec<u32> {
+        self.hops.iter().map(|h| h.fee_tier).collect()
+    }
+
+    /// Whether the swap goes through a single pool
+    pub fn is_direct(&self) -> bool {
+        self.hops.len() == 1
+    }
+}
+
+/// Quote for one candidate route considered for a swap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteQuote {
+    pub route: SwapRoute,
+    pub amount_in: TokenAmount,
+    pub amount_out: TokenAmount,
+    /// The router's `estimate_gas`, or QuoterV2's estimate (a fixed per-pair cost on V2)
+    /// plus the transaction and transfer costs it leaves out when the router call can't
+    /// be estimated
+    pub gas_estimate: u64,
+    pub gas_cost_eth: Option<Decimal>,
+    /// `amount_out` less the gas cost priced in the output token, when that price is known
+    pub net_amount_out: Option<TokenAmount>,
+    /// `amount_in` plus the gas cost priced in the input token, for exact-output quotes
+    /// when that price is known
+    #[serde(default)]
+    pub net_amount_in: Option<TokenAmount>,
+    /// Each pool's `sqrtPriceX96` after the swap, as reported by QuoterV2; empty for V2
+    #[serde(default)]
+    pub sqrt_prices_x96_after: Vec<U256>,
+    /// Initialized ticks the swap crosses over all its pools; each adds gas
+    #[serde(default)]
+    pub initialized_ticks_crossed: u32,
+}
+
+/// One venue's quote for selling an exact input, in a cross-DEX comparison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueQuote {
+    /// `uniswap_v3`, `uniswap_v2`, `sushiswap`, `curve` or `balancer`
+    pub venue: String,
+    /// Pools the quote passes through, in order
+    pub pools: Vec<Address>,
+    pub amount_out: TokenAmount,
+    /// Gas of the whole swap transaction
+    pub gas_estimate: u64,
+    pub gas_cost_eth: Option<Decimal>,
+    /// `amount_out` less the gas cost priced in the output token, when that price is known
+    pub net_amount_out: Option<TokenAmount>,
+}
+
+/// A venue that returned no quote, and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueUnavailable {
+    pub venue: String,
+    pub reason: String,
+}
+
+/// Every venue's quote for the same exact-input swap, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteComparison {
+    pub from_token: TokenAddress,
+    pub to_token: TokenAddress,
+    pub amount_in: TokenAmount,
+    pub quotes: Vec<VenueQuote>,
+    pub unavailable: Vec<VenueUnavailable>,
 }
 
 /// Swap simulation result
@@ -157,19 +433,108 @@ pub struct SwapParams {
 pub struct SwapResult {
     pub params: SwapParams,
     pub estimated_amount_out: TokenAmount,
+    /// Percent by which the swap's rate falls short of the pools' mid price, net of fees
     pub price_impact: Decimal,
+    /// Whether `price_impact` is above the swap service's warning threshold
+    #[serde(default)]
+    pub high_price_impact: bool,
     pub gas_estimate: u64,
     pub gas_cost_eth: Option<Decimal>,
-    pub route: String,
+    /// Present on L2s only; `gas_cost_eth` is then the breakdown's total
+    pub gas_cost_breakdown: Option<GasCostBreakdown>,
+    /// Whether the wallet must approve the router for `amount_in` before executing
+    pub requires_approval: bool,
+    pub route: SwapRoute,
+    /// Every candidate route that was quoted, best first
+    #[serde(default)]
+    pub route_quotes: Vec<RouteQuote>,
+    /// Dry run of the best route from the sender
+    #[serde(default)]
+    pub simulation: SwapSimulation,
+}
+
+/// Outcome of dry-running the router call from the swap's sender
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SwapSimulation {
+    pub sender: Address,
+    /// Sender's input token balance
+    pub balance: Option<TokenAmount>,
+    /// Sender's router allowance for the input token; unknown for unlimited approvals
+    pub allowance: Option<TokenAmount>,
+    /// Whether the dry run raised the balance or allowance through state overrides
+    pub balance_overridden: bool,
+    pub allowance_overridden: bool,
+    /// Whether the router call succeeded
+    pub success: bool,
+    pub revert_reason: Option<RevertReason>,
+}
+
+/// A simulated swap that can be executed by echoing `quote_id` back before `expires_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuote {
+    pub quote_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub result: SwapResult,
+}
+
+/// A swap transaction that was signed and broadcast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapExecution {
+    pub quote_id: String,
+    pub transaction_hash: String,
+    pub estimated_amount_out: TokenAmount,
+    pub amount_out_minimum: TokenAmount,
+}
+
+/// A broadcast WETH `deposit` or `withdraw`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapResult {
+    pub weth_address: TokenAddress,
+    pub amount: TokenAmount,
+    pub transaction_hash: String,
+}
+
+/// Suggested EIP-1559 fee parameters for one confirmation speed (values in wei)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSuggestion {
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+}
+
+/// EIP-1559 gas fee estimate derived from `eth_feeHistory` (values in wei)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasFeeEstimate {
+    pub base_fee_per_gas: u128,
+    pub next_base_fee_per_gas: u128,
+    pub slow: FeeSuggestion,
+    pub normal: FeeSuggestion,
+    pub fast: FeeSuggestion,
+    pub latest_block: u64,
+}
+
+impl GasFeeEstimate {
+    /// Expected price per gas paid at the "normal" speed in the next block
+    pub fn normal_gas_price(&self) -> u128 {
+        self.next_base_fee_per_gas
+            .saturating_add(self.normal.max_priority_fee_per_gas)
+            .min(self.normal.max_fee_per_gas)
+    }
 }
 
 /// The status of an on-chain transaction
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TransactionStatus {
+    /// In the mempool, not yet mined
     Pending,
     Confirmed,
+    /// Mined but reverted
     Failed,
+    /// Unknown to the node and not sent by this server
     NotFound,
+    /// Another transaction with the same nonce superseded it
+    Replaced,
+    /// Sent by this server but no longer known to the node
+    Dropped,
 }
 
 /// Information about a transaction's status and confirmations
@@ -179,6 +544,231 @@ pub struct TransactionStatusInfo {
     pub status: TransactionStatus,
     pub confirmations: u64,
     pub block_number: Option<u64>,
+    /// Whether the containing block is at or below the chain's `finalized` block
+    #[serde(default)]
+    pub finalized: bool,
+    /// Hash of the speed-up or cancel transaction that superseded this one, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    /// What the transaction did; only filled in when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<TransactionDetail>,
+    /// Why a `Failed` transaction reverted, recovered by replaying it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<RevertReason>,
+}
+
+/// What `wait_for_transaction` waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitTarget {
+    /// Mined with at least this many confirmations (the containing block counts as one)
+    Confirmations(u64),
+    /// Mined in a block at or below the chain's `finalized` block
+    Finalized,
+}
+
+/// Why a wait ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitOutcome {
+    /// Confirmed and the target was met
+    Reached,
+    /// Mined but reverted
+    Failed,
+    Dropped,
+    Replaced,
+    /// The wait limit passed first; `status` is the last one observed
+    TimedOut,
+}
+
+/// Status observed at a new block while waiting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitProgress {
+    pub block_number: u64,
+    pub status: TransactionStatusInfo,
+}
+
+/// Result of waiting for a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitResult {
+    pub outcome: WaitOutcome,
+    pub target: WaitTarget,
+    pub status: TransactionStatusInfo,
+    /// New blocks seen while waiting
+    pub blocks_observed: u64,
+    pub waited_seconds: u64,
+}
+
+/// How a call reverted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevertKind {
+    /// `require`/`revert` with a message: `Error(string)`
+    Error,
+    /// Solidity `Panic(uint256)`, e.g. arithmetic overflow
+    Panic,
+    /// A custom error from one of the known contract ABIs
+    Custom,
+    /// Exhausted its gas limit without reverting
+    OutOfGas,
+    /// Empty or unrecognised revert data
+    Unknown,
+}
+
+/// Decoded reason for a reverted call or transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevertReason {
+    pub kind: RevertKind,
+    /// The revert string, panic description or custom error signature
+    pub message: String,
+    /// What a known Uniswap revert string means
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    /// Custom error arguments by name, integers as decimal strings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+    /// Raw revert data, hex encoded
+    pub data: Bytes,
+}
+
+/// A call that reverted with a decodable reason
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{context}: {}", reason.message)]
+pub struct RevertError {
+    pub context: String,
+    pub reason: RevertReason,
+}
+
+impl RevertError {
+    /// Find a revert reason anywhere in an error chain
+    pub fn find(error: &anyhow::Error) -> Option<&RevertReason> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<RevertError>())
+            .map(|revert| &revert.reason)
+    }
+}
+
+/// A swap refused because it would move the price further than allowed
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Price impact {price_impact}% exceeds the {max_price_impact}% limit; try a smaller amount")]
+pub struct PriceImpactError {
+    pub price_impact: Decimal,
+    pub max_price_impact: Decimal,
+}
+
+/// A swap refused by the maximum USD swap value
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SwapValueError {
+    #[error("Swap value ${value_usd} exceeds the maximum swap value of ${max_swap_value_usd}")]
+    TooLarge {
+        value_usd: Decimal,
+        max_swap_value_usd: Decimal,
+    },
+    #[error("Neither token has a USD price to check the maximum swap value against")]
+    Unpriced,
+}
+
+/// A broadcast whose outcome is unknown: sending failed in a way (timeout, dropped
+/// connection) that doesn't rule out the node having accepted the transaction
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Transaction {tx_hash} may have been broadcast; check get_transaction_status before retrying: {reason}")]
+pub struct UnconfirmedBroadcast {
+    pub tx_hash: B256,
+    pub reason: String,
+}
+
+/// Parties, cost, calldata and effects of a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionDetail {
+    pub from: Address,
+    /// None for contract creations
+    pub to: Option<Address>,
+    pub nonce: u64,
+    pub value_eth: Decimal,
+    /// Receipt fields, None until the transaction is mined
+    pub gas_used: Option<u128>,
+    pub effective_gas_price: Option<u128>,
+    /// `gas_used * effective_gas_price`; excludes the L1 data fee on rollups
+    pub fee_eth: Option<Decimal>,
+    /// None for plain ETH transfers
+    pub method: Option<DecodedCall>,
+    /// ERC-20 `Transfer` and Uniswap V3 `Swap` logs, in emission order
+    pub logs: Vec<DecodedLog>,
+    /// Logs emitted that are not in `logs`
+    pub undecoded_log_count: usize,
+}
+
+/// Calldata matched against the contract ABIs this server knows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedCall {
+    /// First four bytes of the calldata, hex encoded
+    pub selector: String,
+    /// None when no known ABI has this selector
+    pub interface: Option<String>,
+    /// e.g. `approve(address,uint256)`
+    pub signature: Option<String>,
+    /// Arguments by parameter name, integers as decimal strings; null if undecoded
+    pub arguments: serde_json::Value,
+    /// Calls bundled in a router `multicall`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<DecodedCall>,
+}
+
+/// An event log this server knows how to read (amounts in raw units, as decimal strings)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum DecodedLog {
+    /// ERC-20 `Transfer` emitted by `token`
+    Transfer {
+        token: Address,
+        from: Address,
+        to: Address,
+        value: String,
+    },
+    /// Uniswap V3 `Swap` emitted by `pool`; positive amounts flowed into the pool
+    Swap {
+        pool: Address,
+        sender: Address,
+        recipient: Address,
+        amount0: String,
+        amount1: String,
+        sqrt_price_x96: String,
+        liquidity: String,
+        tick: i32,
+    },
+    /// Uniswap V2 / Sushiswap `Swap` emitted by `pair`
+    PairSwap {
+        pair: Address,
+        sender: Address,
+        to: Address,
+        amount0_in: String,
+        amount1_in: String,
+        amount0_out: String,
+        amount1_out: String,
+    },
+}
+
+/// How a stuck transaction is replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplacementKind {
+    /// Same transaction, higher fees
+    SpeedUp,
+    /// 0-value self-transfer, higher fees
+    Cancel,
+}
+
+/// A replacement broadcast at the nonce of a pending transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplacementResult {
+    pub kind: ReplacementKind,
+    pub original_hash: String,
+    pub replacement_hash: String,
+    pub nonce: u64,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
 }
 
 #[cfg(test)]
@@ -323,6 +913,11 @@ mod tests {
             to_token: to_token.clone(),
             amount_in: amount.clone(),
             slippage_tolerance: Decimal::from_str("0.01").unwrap(), // 1%
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
         };
 
         assert_eq!(params.from_token, from_token);
@@ -348,15 +943,25 @@ mod tests {
             to_token,
             amount_in,
             slippage_tolerance: Decimal::from_str("0.01").unwrap(),
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
         };
 
         let result = SwapResult {
             params: params.clone(),
             estimated_amount_out: amount_out.clone(),
             price_impact: Decimal::from_str("0.05").unwrap(),
+            high_price_impact: false,
             gas_estimate: 150000,
             gas_cost_eth: Some(Decimal::from_str("0.012").unwrap()),
-            route: "uniswap_v3".to_string(),
+            gas_cost_breakdown: None,
+            requires_approval: false,
+            route: SwapRoute::default(),
+            route_quotes: Vec::new(),
+            simulation: SwapSimulation::default(),
         };
 
         assert_eq!(result.params, params);
@@ -369,6 +974,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_gas_fee_estimate_normal_gas_price() {
+        let estimate = GasFeeEstimate {
+            base_fee_per_gas: 20_000_000_000,
+            next_base_fee_per_gas: 22_000_000_000,
+            slow: FeeSuggestion {
+                max_priority_fee_per_gas: 1_000_000_000,
+                max_fee_per_gas: 45_000_000_000,
+            },
+            normal: FeeSuggestion {
+                max_priority_fee_per_gas: 2_000_000_000,
+                max_fee_per_gas: 46_000_000_000,
+            },
+            fast: FeeSuggestion {
+                max_priority_fee_per_gas: 3_000_000_000,
+                max_fee_per_gas: 47_000_000_000,
+            },
+            latest_block: 18_000_000,
+        };
+
+        assert_eq!(estimate.normal_gas_price(), 24_000_000_000);
+    }
+
+    #[test]
+    fn test_swap_params_minimum_amount_out() {
+        let params = SwapParams {
+            from_token: TokenAddress::from_hex("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+                .unwrap(),
+            to_token: TokenAddress::from_hex("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").unwrap(),
+            amount_in: TokenAmount::from_human_readable("100", 6).unwrap(),
+            slippage_tolerance: Decimal::from_str("0.5").unwrap(),
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        };
+        let estimated = TokenAmount::from_human_readable("1.000001", 6).unwrap();
+
+        let minimum = params.minimum_amount_out(&estimated);
+        // 1.000001 * 0.995 = 0.99500099... truncated to 6 decimals
+        assert_eq!(minimum.raw, Decimal::from_str("0.995000").unwrap());
+        assert_eq!(minimum.decimals, 6);
+        assert_eq!(minimum.to_raw_units().unwrap().fract(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_swap_params_maximum_amount_in() {
+        let mut params = SwapParams {
+            from_token: TokenAddress::from_hex("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+                .unwrap(),
+            to_token: TokenAddress::from_hex("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").unwrap(),
+            amount_in: TokenAmount::from_human_readable("100.000001", 6).unwrap(),
+            slippage_tolerance: Decimal::from_str("0.5").unwrap(),
+            route: None,
+            amount_out: None,
+            sender: None,
+            override_token_state: false,
+            protocol: None,
+        };
+        // Exact-input swaps spend exactly amount_in
+        assert_eq!(params.maximum_amount_in(), params.amount_in);
+
+        params.amount_out = Some(TokenAmount::from_human_readable("0.03", 18).unwrap());
+        let maximum = params.maximum_amount_in();
+        // 100.000001 * 1.005 = 100.50000100...5 rounded up to 6 decimals
+        assert_eq!(maximum.raw, Decimal::from_str("100.500002").unwrap());
+        assert_eq!(maximum.decimals, 6);
+    }
+
+    #[test]
+    fn test_token_amount_to_u256() {
+        let amount = TokenAmount::from_human_readable("1.5", 6).unwrap();
+        assert_eq!(amount.to_u256().unwrap(), U256::from(1_500_000u64));
+
+        let too_precise = TokenAmount::from_human_readable("0.0000001", 6).unwrap();
+        assert!(too_precise
+            .to_u256()
+            .unwrap_err()
+            .to_string()
+            .contains("decimal places"));
+    }
+
+    #[test]
+    fn test_gas_cost_breakdown_total() {
+        let breakdown = GasCostBreakdown {
+            network: "op_stack".to_string(),
+            l2_execution_fee_eth: Decimal::from_str("0.00001").unwrap(),
+            l1_data_fee_eth: Decimal::from_str("0.0004").unwrap(),
+        };
+        assert_eq!(breakdown.total_eth(), Decimal::from_str("0.00041").unwrap());
+    }
+
+    #[test]
+    fn test_swap_protocol_names_round_trip() {
+        for protocol in SwapProtocol::ALL {
+            assert_eq!(SwapProtocol::from_str(protocol.as_str()).unwrap(), protocol);
+            assert_eq!(
+                serde_json::to_value(protocol).unwrap(),
+                serde_json::json!(protocol.as_str())
+            );
+        }
+        assert!(!SwapProtocol::UniswapV3.is_v2());
+        assert!(SwapProtocol::Sushiswap.is_v2());
+        assert!(SwapProtocol::from_str("balancer").is_err());
+    }
+
     #[test]
     fn test_address_from_str_trait() {
         let addr_str = "0x742d35Cc6634C0532925a3b8D4C4C0b8047cc6E1";
@@ -93,12 +93,7 @@ impl Validator {
 
         // Handle special case for ETH
         if input.to_uppercase() == "ETH" {
-            return TokenAddress::from_hex("0x0000000000000000000000000000000000000000").map_err(
-                |e| ValidationError::InvalidFormat {
-                    field: "token_address".to_string(),
-                    reason: format!("Failed to create ETH token address: {}", e),
-                },
-            );
+            return Ok(TokenAddress::native());
         }
 
         // Validate Ethereum address format
@@ -814,7 +809,7 @@ mod tests {
     #[test]
     fn test_validate_token_address_eth_lowercase() {
         let result = Validator::validate_token_address("eth");
-        assert!(result.is_ok());
+        assert!(result.is_ok_and(|token| token.is_native()));
     }
 
     #[test]
@@ -23,7 +23,17 @@ fn get_test_contracts() -> ContractAddresses {
         weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
         uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
         uniswap_v3_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
-        uniswap_v3_quoter: "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6".to_string(),
+        uniswap_v3_quoter_v2: "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+        uniswap_v2_factory: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(),
+        uniswap_v2_router: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(),
+        sushiswap_factory: "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac".to_string(),
+        sushiswap_router: "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F".to_string(),
+        curve_pools: vec!["0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7".to_string()],
+        balancer_vault: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+        balancer_pool_ids: vec![
+            "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019".to_string(),
+            "0x0b09dea16768f0799065c475be02919503cb2a3500020000000000000000001a".to_string(),
+        ],
         chainlink_eth_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".to_string(),
     }
 }
@@ -124,19 +134,32 @@ async fn test_nonce_manager() {
     let nonce_manager = NonceManager::new();
     let wallet = WalletAddress::from_hex("0x742d35Cc6634C0532925a3b8D8b5d0f8988Db8c7").unwrap();
 
-    // Test sequential nonce allocation
-    let nonce1 = nonce_manager.get_next_nonce(&wallet).await;
-    let nonce2 = nonce_manager.get_next_nonce(&wallet).await;
-    let nonce3 = nonce_manager.get_next_nonce(&wallet).await;
-
-    assert_eq!(nonce1, 1);
-    assert_eq!(nonce2, 2);
-    assert_eq!(nonce3, 3);
+    // Test sequential nonce allocation, starting from the chain's pending count
+    let pending_count = || async { Ok(0) };
+    let nonce1 = nonce_manager
+        .get_next_nonce(&wallet, pending_count)
+        .await
+        .unwrap();
+    let nonce2 = nonce_manager
+        .get_next_nonce(&wallet, pending_count)
+        .await
+        .unwrap();
+    let nonce3 = nonce_manager
+        .get_next_nonce(&wallet, pending_count)
+        .await
+        .unwrap();
+
+    assert_eq!(nonce1, 0);
+    assert_eq!(nonce2, 1);
+    assert_eq!(nonce3, 2);
 
     // Test nonce initialization
     nonce_manager.initialize_nonce(&wallet, 10).await;
-    let next_nonce = nonce_manager.get_next_nonce(&wallet).await;
-    assert_eq!(next_nonce, 11);
+    let next_nonce = nonce_manager
+        .get_next_nonce(&wallet, pending_count)
+        .await
+        .unwrap();
+    assert_eq!(next_nonce, 10);
 }
 
 #[tokio::test]
@@ -154,7 +177,12 @@ async fn test_concurrent_nonce_allocation() {
     for _ in 0..10 {
         let manager_clone = nonce_manager.clone();
         let wallet_clone = wallet.clone();
-        let handle = tokio::spawn(async move { manager_clone.get_next_nonce(&wallet_clone).await });
+        let handle = tokio::spawn(async move {
+            manager_clone
+                .get_next_nonce(&wallet_clone, || async { Ok(0) })
+                .await
+                .unwrap()
+        });
         handles.push(handle);
     }
 
@@ -166,7 +194,7 @@ async fn test_concurrent_nonce_allocation() {
     // Sort results and verify they are sequential
     results.sort();
     for (i, nonce) in results.iter().enumerate() {
-        assert_eq!(*nonce, (i + 1) as u64);
+        assert_eq!(*nonce, i as u64);
     }
 }
 
@@ -293,6 +321,43 @@ async fn test_http_get_balance_integration() {
                 symbol: "USDC".to_string(),
             })
         }
+        async fn get_allowance(
+            &self,
+            owner: &ethereum_mcp_server::types::WalletAddress,
+            token: &ethereum_mcp_server::types::TokenAddress,
+            spender: &alloy::primitives::Address,
+        ) -> anyhow::Result<ethereum_mcp_server::types::AllowanceInfo> {
+            Ok(ethereum_mcp_server::types::AllowanceInfo {
+                owner: owner.clone(),
+                spender: *spender,
+                token_address: token.clone(),
+                allowance: ethereum_mcp_server::types::TokenAmount::from_human_readable("0", 6)
+                    .unwrap(),
+                is_unlimited: false,
+            })
+        }
+        async fn approve_token(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x22))
+        }
+        async fn wrap_eth(
+            &self,
+            _weth: &ethereum_mcp_server::types::TokenAddress,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x33))
+        }
+        async fn unwrap_weth(
+            &self,
+            _weth: &ethereum_mcp_server::types::TokenAddress,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x33))
+        }
         async fn get_token_decimals(
             &self,
             _token: &ethereum_mcp_server::types::TokenAddress,
@@ -334,14 +399,79 @@ async fn test_http_get_balance_integration() {
                 )
                 .unwrap(),
                 price_impact: rust_decimal::Decimal::from_str("0.001").unwrap(),
+                high_price_impact: false,
                 gas_estimate: 21000,
                 gas_cost_eth: Some(rust_decimal::Decimal::from_str("0.0001").unwrap()),
-                route: "uniswap_v3".to_string(),
+                gas_cost_breakdown: None,
+                requires_approval: false,
+                route: Default::default(),
+                route_quotes: Vec::new(),
+                simulation: Default::default(),
+            })
+        }
+        async fn compare_quotes(
+            &self,
+            from_token: &ethereum_mcp_server::types::TokenAddress,
+            to_token: &ethereum_mcp_server::types::TokenAddress,
+            amount_in: &ethereum_mcp_server::types::TokenAmount,
+            _contracts: &ethereum_mcp_server::ContractAddresses,
+        ) -> anyhow::Result<ethereum_mcp_server::types::QuoteComparison> {
+            Ok(ethereum_mcp_server::types::QuoteComparison {
+                from_token: from_token.clone(),
+                to_token: to_token.clone(),
+                amount_in: amount_in.clone(),
+                quotes: Vec::new(),
+                unavailable: Vec::new(),
             })
         }
+        async fn execute_swap<'a>(
+            &self,
+            _params: &ethereum_mcp_server::types::SwapParams,
+            _amount_out_minimum: &ethereum_mcp_server::types::TokenAmount,
+            _permit: Option<&'a ethereum_mcp_server::types::PermitSignature>,
+            _contracts: &ethereum_mcp_server::ContractAddresses,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x11))
+        }
+        async fn sign_permit(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _value: alloy::primitives::U256,
+            _deadline: alloy::primitives::U256,
+        ) -> anyhow::Result<ethereum_mcp_server::types::PermitSignature> {
+            anyhow::bail!("Mock provider cannot sign permits")
+        }
+        async fn sign_permit2(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _amount: alloy::primitives::U256,
+            _expiration: u64,
+            _sig_deadline: alloy::primitives::U256,
+        ) -> anyhow::Result<ethereum_mcp_server::types::PermitSignature> {
+            anyhow::bail!("Mock provider cannot sign permits")
+        }
         async fn get_gas_price(&self) -> anyhow::Result<alloy::primitives::U256> {
             Ok(alloy::primitives::U256::from(20000000000u64))
         }
+        async fn get_gas_fees(&self) -> anyhow::Result<ethereum_mcp_server::types::GasFeeEstimate> {
+            let suggestion = ethereum_mcp_server::types::FeeSuggestion {
+                max_priority_fee_per_gas: 1_000_000_000,
+                max_fee_per_gas: 41_000_000_000,
+            };
+            Ok(ethereum_mcp_server::types::GasFeeEstimate {
+                base_fee_per_gas: 20_000_000_000,
+                next_base_fee_per_gas: 20_000_000_000,
+                slow: suggestion.clone(),
+                normal: suggestion.clone(),
+                fast: suggestion,
+                latest_block: 18_000_000,
+            })
+        }
+        async fn get_block_number(&self) -> anyhow::Result<u64> {
+            Ok(18_000_000)
+        }
         async fn get_transaction_status(
             &self,
             _tx_hash: &alloy::primitives::B256,
@@ -352,8 +482,25 @@ async fn test_http_get_balance_integration() {
                 status: ethereum_mcp_server::types::TransactionStatus::Confirmed,
                 confirmations: 12,
                 block_number: Some(18_000_000),
+                finalized: true,
+                replaced_by: None,
+                detail: None,
+                revert_reason: None,
             })
         }
+        async fn get_transaction_detail(
+            &self,
+            _tx_hash: &alloy::primitives::B256,
+        ) -> anyhow::Result<Option<ethereum_mcp_server::types::TransactionDetail>> {
+            Ok(None)
+        }
+        async fn replace_transaction(
+            &self,
+            _tx_hash: &alloy::primitives::B256,
+            _kind: ethereum_mcp_server::types::ReplacementKind,
+        ) -> anyhow::Result<ethereum_mcp_server::types::ReplacementResult> {
+            anyhow::bail!("Mock provider has no pending transactions")
+        }
         async fn health_check(&self) -> anyhow::Result<()> {
             Ok(())
         }
@@ -375,20 +522,29 @@ async fn test_http_get_balance_integration() {
         mock_provider.clone(),
         contracts.clone(),
     ));
-    let swap_service = std::sync::Arc::new(ethereum_mcp_server::services::SwapService::new(
-        mock_provider.clone(),
-        contracts.clone(),
-    ));
+    let swap_service = std::sync::Arc::new(
+        ethereum_mcp_server::services::SwapService::new(mock_provider.clone(), contracts.clone())
+            .with_max_swap_value(1_000_000_000u64.into()),
+    );
     let transaction_status_service = std::sync::Arc::new(
         ethereum_mcp_server::services::TransactionStatusService::new(mock_provider.clone()),
     );
+    let gas_service = std::sync::Arc::new(ethereum_mcp_server::services::GasService::new(
+        mock_provider.clone(),
+    ));
+    let allowance_service =
+        std::sync::Arc::new(ethereum_mcp_server::services::AllowanceService::new(
+            mock_provider.clone(),
+            contracts.clone(),
+        ));
 
     let app_state = ethereum_mcp_server::server::http::AppState::new(
         balance_service,
         price_service,
         swap_service,
         transaction_status_service,
-        1_000_000_000,
+        gas_service,
+        allowance_service,
     );
 
     // Create server
@@ -421,6 +577,7 @@ async fn test_http_get_balance_integration() {
 
 /// Test HTTP handler functions directly
 #[tokio::test]
+#[allow(clippy::assertions_on_constants)]
 async fn test_health_check_handler() {
     use ethereum_mcp_server::server::http::AppState;
     use std::str::FromStr;
@@ -469,6 +626,43 @@ async fn test_health_check_handler() {
             })
         }
 
+        async fn get_allowance(
+            &self,
+            owner: &ethereum_mcp_server::types::WalletAddress,
+            token: &ethereum_mcp_server::types::TokenAddress,
+            spender: &alloy::primitives::Address,
+        ) -> anyhow::Result<ethereum_mcp_server::types::AllowanceInfo> {
+            Ok(ethereum_mcp_server::types::AllowanceInfo {
+                owner: owner.clone(),
+                spender: *spender,
+                token_address: token.clone(),
+                allowance: ethereum_mcp_server::types::TokenAmount::from_human_readable("0", 6)
+                    .unwrap(),
+                is_unlimited: false,
+            })
+        }
+        async fn approve_token(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x22))
+        }
+        async fn wrap_eth(
+            &self,
+            _weth: &ethereum_mcp_server::types::TokenAddress,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x33))
+        }
+        async fn unwrap_weth(
+            &self,
+            _weth: &ethereum_mcp_server::types::TokenAddress,
+            _amount: alloy::primitives::U256,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x33))
+        }
         async fn get_token_decimals(
             &self,
             _token: &ethereum_mcp_server::types::TokenAddress,
@@ -516,6 +710,11 @@ async fn test_health_check_handler() {
                 amount_in: ethereum_mcp_server::types::TokenAmount::from_human_readable("100", 6)
                     .unwrap(),
                 slippage_tolerance: rust_decimal::Decimal::from_str("0.005").unwrap(),
+                route: None,
+                amount_out: None,
+                sender: None,
+                override_token_state: false,
+                protocol: None,
             };
 
             Ok(ethereum_mcp_server::types::SwapResult {
@@ -525,16 +724,81 @@ async fn test_health_check_handler() {
                 )
                 .unwrap(),
                 price_impact: rust_decimal::Decimal::from_str("0.001").unwrap(),
+                high_price_impact: false,
                 gas_estimate: 21000,
                 gas_cost_eth: Some(rust_decimal::Decimal::from_str("0.0001").unwrap()),
-                route: "uniswap_v3".to_string(),
+                gas_cost_breakdown: None,
+                requires_approval: false,
+                route: Default::default(),
+                route_quotes: Vec::new(),
+                simulation: Default::default(),
             })
         }
 
+        async fn compare_quotes(
+            &self,
+            from_token: &ethereum_mcp_server::types::TokenAddress,
+            to_token: &ethereum_mcp_server::types::TokenAddress,
+            amount_in: &ethereum_mcp_server::types::TokenAmount,
+            _contracts: &ethereum_mcp_server::ContractAddresses,
+        ) -> anyhow::Result<ethereum_mcp_server::types::QuoteComparison> {
+            Ok(ethereum_mcp_server::types::QuoteComparison {
+                from_token: from_token.clone(),
+                to_token: to_token.clone(),
+                amount_in: amount_in.clone(),
+                quotes: Vec::new(),
+                unavailable: Vec::new(),
+            })
+        }
+        async fn execute_swap<'a>(
+            &self,
+            _params: &ethereum_mcp_server::types::SwapParams,
+            _amount_out_minimum: &ethereum_mcp_server::types::TokenAmount,
+            _permit: Option<&'a ethereum_mcp_server::types::PermitSignature>,
+            _contracts: &ethereum_mcp_server::ContractAddresses,
+        ) -> anyhow::Result<alloy::primitives::B256> {
+            Ok(alloy::primitives::B256::repeat_byte(0x11))
+        }
+        async fn sign_permit(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _value: alloy::primitives::U256,
+            _deadline: alloy::primitives::U256,
+        ) -> anyhow::Result<ethereum_mcp_server::types::PermitSignature> {
+            anyhow::bail!("Mock provider cannot sign permits")
+        }
+        async fn sign_permit2(
+            &self,
+            _token: &ethereum_mcp_server::types::TokenAddress,
+            _spender: &alloy::primitives::Address,
+            _amount: alloy::primitives::U256,
+            _expiration: u64,
+            _sig_deadline: alloy::primitives::U256,
+        ) -> anyhow::Result<ethereum_mcp_server::types::PermitSignature> {
+            anyhow::bail!("Mock provider cannot sign permits")
+        }
         async fn get_gas_price(&self) -> anyhow::Result<alloy::primitives::U256> {
             Ok(alloy::primitives::U256::from(20000000000u64))
         }
+        async fn get_gas_fees(&self) -> anyhow::Result<ethereum_mcp_server::types::GasFeeEstimate> {
+            let suggestion = ethereum_mcp_server::types::FeeSuggestion {
+                max_priority_fee_per_gas: 1_000_000_000,
+                max_fee_per_gas: 41_000_000_000,
+            };
+            Ok(ethereum_mcp_server::types::GasFeeEstimate {
+                base_fee_per_gas: 20_000_000_000,
+                next_base_fee_per_gas: 20_000_000_000,
+                slow: suggestion.clone(),
+                normal: suggestion.clone(),
+                fast: suggestion,
+                latest_block: 18_000_000,
+            })
+        }
 
+        async fn get_block_number(&self) -> anyhow::Result<u64> {
+            Ok(18_000_000)
+        }
         async fn get_transaction_status(
             &self,
             _tx_hash: &alloy::primitives::B256,
@@ -545,8 +809,25 @@ async fn test_health_check_handler() {
                 status: ethereum_mcp_server::types::TransactionStatus::Confirmed,
                 confirmations: 12,
                 block_number: Some(18_000_000),
+                finalized: true,
+                replaced_by: None,
+                detail: None,
+                revert_reason: None,
             })
         }
+        async fn get_transaction_detail(
+            &self,
+            _tx_hash: &alloy::primitives::B256,
+        ) -> anyhow::Result<Option<ethereum_mcp_server::types::TransactionDetail>> {
+            Ok(None)
+        }
+        async fn replace_transaction(
+            &self,
+            _tx_hash: &alloy::primitives::B256,
+            _kind: ethereum_mcp_server::types::ReplacementKind,
+        ) -> anyhow::Result<ethereum_mcp_server::types::ReplacementResult> {
+            anyhow::bail!("Mock provider has no pending transactions")
+        }
 
         async fn health_check(&self) -> anyhow::Result<()> {
             Ok(())
@@ -569,20 +850,29 @@ async fn test_health_check_handler() {
         mock_provider.clone(),
         contracts.clone(),
     ));
-    let swap_service = std::sync::Arc::new(ethereum_mcp_server::services::SwapService::new(
-        mock_provider.clone(),
-        contracts.clone(),
-    ));
+    let swap_service = std::sync::Arc::new(
+        ethereum_mcp_server::services::SwapService::new(mock_provider.clone(), contracts.clone())
+            .with_max_swap_value(1_000_000_000u64.into()),
+    );
     let transaction_status_service = std::sync::Arc::new(
         ethereum_mcp_server::services::TransactionStatusService::new(mock_provider.clone()),
     );
+    let gas_service = std::sync::Arc::new(ethereum_mcp_server::services::GasService::new(
+        mock_provider.clone(),
+    ));
+    let allowance_service =
+        std::sync::Arc::new(ethereum_mcp_server::services::AllowanceService::new(
+            mock_provider.clone(),
+            contracts.clone(),
+        ));
 
     let _app_state = AppState::new(
         balance_service,
         price_service,
         swap_service,
         transaction_status_service,
-        1_000_000_000,
+        gas_service,
+        allowance_service,
     );
 
     // We can't easily test the async handler without running a server,
@@ -656,6 +946,7 @@ async fn test_address_conversions() {
 
 /// Test circuit breaker state transitions
 #[tokio::test]
+#[allow(clippy::assertions_on_constants)]
 async fn test_circuit_breaker_integration() {
     use ethereum_mcp_server::providers::CircuitBreaker;
 
@@ -686,6 +977,11 @@ async fn test_swap_params_comprehensive() {
         to_token: to_token.clone(),
         amount_in: amount.clone(),
         slippage_tolerance: Decimal::from_str("0.005").unwrap(),
+        route: None,
+        amount_out: None,
+        sender: None,
+        override_token_state: false,
+        protocol: None,
     };
 
     assert_eq!(params.from_token, from_token);
@@ -708,6 +1004,10 @@ async fn test_transaction_status_types() {
         status: TransactionStatus::Confirmed,
         confirmations: 12,
         block_number: Some(18_000_000),
+        finalized: true,
+        replaced_by: None,
+        detail: None,
+        revert_reason: None,
     };
 
     assert_eq!(status_info.confirmations, 12);
@@ -821,3 +1121,1931 @@ async fn test_config_validation_edge_cases() {
     );
     assert!(config.validate().is_err());
 }
+
+/// End-to-end swap execution against a local dev chain forked from mainnet, e.g.
+/// `anvil --fork-url $MAINNET_RPC_URL`, then
+/// `DEV_CHAIN_RPC_URL=http://127.0.0.1:8545 cargo test -- --ignored execute_swap`
+#[tokio::test]
+#[ignore = "requires a mainnet-fork dev chain at DEV_CHAIN_RPC_URL"]
+async fn test_execute_swap_on_dev_chain() {
+    use alloy::network::EthereumWallet;
+    use alloy::primitives::{B256, U256};
+    use alloy::providers::ProviderBuilder;
+    use alloy::signers::local::PrivateKeySigner;
+    use ethereum_mcp_server::providers::ProviderFactory;
+    use ethereum_mcp_server::services::allowance::AllowanceServiceTrait;
+    use ethereum_mcp_server::services::swap::SwapServiceTrait;
+    use ethereum_mcp_server::services::{AllowanceService, SwapService};
+    use ethereum_mcp_server::types::{
+        ApprovalAmount, SwapParams, TokenAddress, TokenAmount, TransactionStatus,
+    };
+    use std::str::FromStr;
+
+    alloy::sol! {
+        #[sol(rpc)]
+        interface IWETH9 {
+            function deposit() external payable;
+        }
+    }
+
+    let rpc_url = std::env::var("DEV_CHAIN_RPC_URL").expect("DEV_CHAIN_RPC_URL must be set");
+    // First default anvil / hardhat development account
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let contracts = test_config().contracts;
+    let weth = alloy::primitives::Address::from_str(&contracts.weth).unwrap();
+
+    // Fund the dev account with WETH outside the server
+    let signer = PrivateKeySigner::from_str(private_key.trim_start_matches("0x")).unwrap();
+    let funding_provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(rpc_url.parse().unwrap());
+    let weth_contract = IWETH9::new(weth, &funding_provider);
+    weth_contract
+        .deposit()
+        .value(U256::from(10u64).pow(U256::from(18)))
+        .send()
+        .await
+        .unwrap()
+        .get_receipt()
+        .await
+        .unwrap();
+
+    let provider = ProviderFactory::create_ethereum_provider(
+        rpc_url,
+        private_key.to_string(),
+        10,
+        30,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let swap_service = SwapService::new(provider.clone(), contracts.clone());
+    let params = SwapParams {
+        from_token: TokenAddress::from_hex(&contracts.weth).unwrap(),
+        to_token: TokenAddress::from_hex(&contracts.usdc).unwrap(),
+        amount_in: TokenAmount::from_human_readable("0.1", 18).unwrap(),
+        slippage_tolerance: rust_decimal::Decimal::from_str("1").unwrap(),
+        route: None,
+        amount_out: None,
+        sender: None,
+        override_token_state: false,
+        protocol: None,
+    };
+
+    let quote = swap_service.quote_swap(&params).await.unwrap();
+    assert!(quote.result.requires_approval);
+    assert!(swap_service
+        .execute_swap(&quote.quote_id, false)
+        .await
+        .is_err());
+
+    // Approve exactly the swap amount for the router, then quote again
+    let allowance_service = AllowanceService::new(provider.clone(), contracts.clone());
+    allowance_service
+        .approve_token(
+            &params.from_token,
+            None,
+            &ApprovalAmount::Exact(params.amount_in.raw),
+        )
+        .await
+        .unwrap();
+    let quote = swap_service.quote_swap(&params).await.unwrap();
+    assert!(!quote.result.requires_approval);
+    let execution = swap_service
+        .execute_swap(&quote.quote_id, false)
+        .await
+        .unwrap();
+    assert_eq!(execution.quote_id, quote.quote_id);
+
+    let tx_hash = B256::from_str(&execution.transaction_hash).unwrap();
+    let mut status = provider.get_transaction_status(&tx_hash).await.unwrap();
+    for _ in 0..20 {
+        if status.status != TransactionStatus::Pending {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        status = provider.get_transaction_status(&tx_hash).await.unwrap();
+    }
+    assert_eq!(status.status, TransactionStatus::Confirmed);
+
+    // The quote is consumed by the execution
+    assert!(swap_service
+        .execute_swap(&quote.quote_id, false)
+        .await
+        .is_err());
+}
+
+/// The full server wired to a provider on the in-process simulated node, served on an
+/// ephemeral port; returns the server's URL
+async fn spawn_simulated_server(
+    node: &ethereum_mcp_server::devnet::DevNode,
+) -> (String, tokio::task::JoinHandle<()>) {
+    let provider = ethereum_mcp_server::providers::ProviderFactory::create_ethereum_provider(
+        node.url(),
+        ethereum_mcp_server::devnet::DEV_PRIVATE_KEY.to_string(),
+        10,
+        5,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    spawn_server(provider, node.contracts()).await
+}
+
+/// The full server wired to `provider`, served on an ephemeral port; returns the
+/// server's URL
+async fn spawn_server(
+    provider: std::sync::Arc<dyn ethereum_mcp_server::providers::EthereumProvider>,
+    contracts: ethereum_mcp_server::ContractAddresses,
+) -> (String, tokio::task::JoinHandle<()>) {
+    use ethereum_mcp_server::server::http::{AppState, HttpServer};
+    use ethereum_mcp_server::services::{
+        AllowanceService, BalanceService, GasService, PriceService, SwapService,
+        TransactionStatusService,
+    };
+    use std::sync::Arc;
+
+    let state = AppState::new(
+        Arc::new(BalanceService::new(provider.clone())),
+        Arc::new(PriceService::new(provider.clone(), contracts.clone())),
+        Arc::new(
+            SwapService::new(provider.clone(), contracts.clone())
+                .with_max_swap_value(1_000_000u64.into()),
+        ),
+        Arc::new(
+            TransactionStatusService::new(provider.clone())
+                .with_poll_interval(Duration::from_millis(20)),
+        ),
+        Arc::new(GasService::new(provider.clone())),
+        Arc::new(AllowanceService::new(provider, contracts)),
+    );
+    let server = HttpServer::new(
+        "127.0.0.1".to_string(),
+        0,
+        state,
+        30,
+        100,
+        1000,
+        1000,
+        "*".to_string(),
+    )
+    .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        server.serve(listener).await.unwrap();
+    });
+    (url, handle)
+}
+
+/// Call a tool on the server and return the JSON-RPC response
+async fn call_tool(url: &str, name: &str, arguments: serde_json::Value) -> serde_json::Value {
+    reqwest::Client::new()
+        .post(url)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments},
+            "id": 1
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_simulated_balances_and_prices() {
+    use ethereum_mcp_server::devnet::DevNode;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    let response = call_tool(&url, "get_balance", json!({"wallet_address": wallet})).await;
+    assert_eq!(
+        response["result"]["amount"]["human_readable"], "10000",
+        "{response}"
+    );
+
+    // The seeded pools and feed price ETH at $3000
+    let response = call_tool(&url, "get_token_price", json!({"token_symbol": "USDC"})).await;
+    let price_usd: f64 = response["result"]["price_usd"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{response}"))
+        .parse()
+        .unwrap();
+    assert!((price_usd - 1.0).abs() < 0.01, "{response}");
+
+    let response = call_tool(
+        &url,
+        "get_token_price",
+        json!({"token_address": contracts.weth}),
+    )
+    .await;
+    assert_eq!(response["result"]["price_usd"], "3000", "{response}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_swap_end_to_end() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let usdc = Address::from_str(&contracts.usdc).unwrap();
+    node.mint(weth, wallet, U256::from(10u64).pow(U256::from(18)))
+        .unwrap();
+
+    let swap = json!({
+        "from_token": contracts.weth,
+        "to_token": contracts.usdc,
+        "amount": "0.5",
+        "slippage_tolerance": "1"
+    });
+    let quote = call_tool(&url, "swap_tokens", swap.clone()).await;
+    assert_eq!(quote["result"]["requires_approval"], true, "{quote}");
+    let quote_id = quote["result"]["quote_id"].as_str().unwrap().to_string();
+    let refused = call_tool(&url, "execute_swap", json!({"quote_id": quote_id})).await;
+    assert!(refused.get("error").is_some(), "{refused}");
+
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({"token_address": contracts.weth, "amount": "0.5"}),
+    )
+    .await;
+    let approval_hash = approval["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{approval}"))
+        .to_string();
+    let waited = call_tool(
+        &url,
+        "wait_for_transaction",
+        json!({"transaction_hash": approval_hash}),
+    )
+    .await;
+    assert_eq!(waited["result"]["outcome"], "reached", "{waited}");
+
+    let quote = call_tool(&url, "swap_tokens", swap).await;
+    assert_eq!(quote["result"]["requires_approval"], false, "{quote}");
+    // The empty 0.01% pool is skipped and the 0.05% pool beats the equally deep 0.3% one
+    // and the routes through USDT and DAI
+    let hops = quote["result"]["route"]["hops"].as_array().unwrap();
+    assert_eq!(hops.len(), 1, "{quote}");
+    assert_eq!(hops[0]["fee_tier"], 500, "{quote}");
+    let route_quotes = quote["result"]["route_quotes"].as_array().unwrap();
+    let direct_tiers: Vec<_> = route_quotes
+        .iter()
+        .filter(|q| q["route"]["hops"].as_array().unwrap().len() == 1)
+        .map(|q| q["route"]["hops"][0]["fee_tier"].as_u64().unwrap())
+        .collect();
+    assert_eq!(direct_tiers, vec![500, 3000], "{quote}");
+    assert_eq!(route_quotes.len(), 4, "{quote}");
+    assert!(route_quotes[0]["net_amount_out"].is_string());
+    let expected_out =
+        rust_decimal::Decimal::from_str(quote["result"]["amount_out"].as_str().unwrap()).unwrap();
+    // 0.5 WETH at $3000 less the 0.05% fee and a little price impact
+    assert!(
+        expected_out > rust_decimal::Decimal::from(1490)
+            && expected_out < rust_decimal::Decimal::from(1500),
+        "{quote}"
+    );
+
+    let execution = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": quote["result"]["quote_id"]}),
+    )
+    .await;
+    let swap_hash = execution["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{execution}"))
+        .to_string();
+
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": swap_hash, "detail": true}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Confirmed", "{status}");
+    let detail = &status["result"]["detail"];
+    assert!(
+        detail["method"]["signature"]
+            .as_str()
+            .is_some_and(|signature| signature.starts_with("exactInputSingle(")),
+        "{status}"
+    );
+    let swap_log = detail["logs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|log| log["event"] == "Swap")
+        .unwrap_or_else(|| panic!("{status}"));
+    assert_eq!(
+        swap_log["recipient"],
+        format!("{:?}", wallet).to_lowercase()
+    );
+
+    let usdc_raw = (expected_out * rust_decimal::Decimal::from(1_000_000))
+        .normalize()
+        .to_string();
+    assert_eq!(
+        node.token_balance(usdc, wallet).unwrap(),
+        U256::from_str(&usdc_raw).unwrap()
+    );
+    assert_eq!(
+        node.token_balance(weth, wallet).unwrap(),
+        U256::from(500_000_000_000_000_000u64)
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_execute_swap_confirmation_gate() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::contracts::fees;
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let usdc = Address::from_str(&contracts.usdc).unwrap();
+    let one_ether = U256::from(10u64).pow(U256::from(18));
+    node.mint(weth, wallet, one_ether).unwrap();
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({"token_address": contracts.weth, "amount": "1"}),
+    )
+    .await;
+    assert!(
+        approval["result"]["transaction_hash"].is_string(),
+        "{approval}"
+    );
+    let swap = json!({
+        "from_token": contracts.weth,
+        "to_token": contracts.usdc,
+        "amount": "0.1",
+        "slippage_tolerance": "1"
+    });
+    let execute = |quote_id: serde_json::Value| {
+        call_tool(&url, "execute_swap", json!({"quote_id": quote_id}))
+    };
+
+    // Nothing is sent without a quote_id from a prior simulation
+    let unknown = execute(json!("0xdeadbeef")).await;
+    assert!(
+        unknown["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("Unknown or already used quote_id")),
+        "{unknown}"
+    );
+
+    // The pool moves against the quote: the re-simulation falls outside the 1%
+    // slippage, so nothing is broadcast and the quote is spent
+    let quote = call_tool(&url, "swap_tokens", swap.clone()).await;
+    assert_eq!(
+        quote["result"]["route"]["hops"][0]["fee_tier"], 500,
+        "{quote}"
+    );
+    let pool = DevNode::pool_address(weth, usdc, fees::LOW);
+    node.mint(weth, pool, U256::from(100u64) * one_ether)
+        .unwrap();
+    let block = node.block_number();
+    let moved = execute(quote["result"]["quote_id"].clone()).await;
+    assert!(
+        moved["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("Price moved beyond slippage tolerance")),
+        "{moved}"
+    );
+    assert_eq!(node.block_number(), block);
+    assert_eq!(node.token_balance(usdc, wallet).unwrap(), U256::ZERO);
+    let retried = execute(quote["result"]["quote_id"].clone()).await;
+    assert!(retried.get("error").is_some(), "{retried}");
+
+    // A fresh quote executes once, and only once
+    let quote = call_tool(&url, "swap_tokens", swap).await;
+    let executed = execute(quote["result"]["quote_id"].clone()).await;
+    assert!(
+        executed["result"]["transaction_hash"].is_string(),
+        "{executed}"
+    );
+    let received = node.token_balance(usdc, wallet).unwrap();
+    assert!(received > U256::ZERO);
+    let replayed = execute(quote["result"]["quote_id"].clone()).await;
+    assert!(replayed.get("error").is_some(), "{replayed}");
+    assert_eq!(node.token_balance(usdc, wallet).unwrap(), received);
+    assert_eq!(
+        node.token_balance(weth, wallet).unwrap(),
+        one_ether - U256::from(100_000_000_000_000_000u64)
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_multi_hop_swap_through_weth() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::contracts::fees;
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let dai = Address::from_str(&contracts.dai).unwrap();
+    // A long-tail token whose only pool is against WETH, at 100 tokens per ETH
+    let long_tail = Address::repeat_byte(0x42);
+    node.add_token(long_tail, "Long Tail", "LONG", 18);
+    let ether = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+    node.add_pool(weth, long_tail, fees::HIGH, ether(100), ether(10_000))
+        .unwrap();
+    node.mint(dai, wallet, ether(3_000)).unwrap();
+
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({"token_address": contracts.dai, "amount": "3000"}),
+    )
+    .await;
+    let approval_hash = approval["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{approval}"))
+        .to_string();
+    call_tool(
+        &url,
+        "wait_for_transaction",
+        json!({"transaction_hash": approval_hash}),
+    )
+    .await;
+
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.dai,
+            "to_token": format!("{:#x}", long_tail),
+            "amount": "3000",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    assert_eq!(quote["result"]["requires_approval"], false, "{quote}");
+    let route = &quote["result"]["route"];
+    assert_eq!(
+        route["path"],
+        json!([contracts.dai, contracts.weth, format!("{:#x}", long_tail)]),
+        "{quote}"
+    );
+    assert_eq!(route["hops"][0]["fee_tier"], 3000, "{quote}");
+    assert_eq!(route["hops"][1]["fee_tier"], 10000, "{quote}");
+    // Gas is priced in LONG through its WETH pool, so every candidate has a net output
+    let route_quotes = quote["result"]["route_quotes"].as_array().unwrap();
+    assert!(
+        route_quotes.iter().all(|q| q["net_amount_out"].is_string()),
+        "{quote}"
+    );
+    // Roughly 1 ETH, then ~98 LONG after two fees and price impact
+    let expected_out =
+        rust_decimal::Decimal::from_str(quote["result"]["amount_out"].as_str().unwrap()).unwrap();
+    assert!(
+        expected_out > rust_decimal::Decimal::from(97)
+            && expected_out < rust_decimal::Decimal::from(100),
+        "{quote}"
+    );
+
+    let execution = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": quote["result"]["quote_id"]}),
+    )
+    .await;
+    let swap_hash = execution["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{execution}"))
+        .to_string();
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": swap_hash, "detail": true}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Confirmed", "{status}");
+    let detail = &status["result"]["detail"];
+    assert!(
+        detail["method"]["signature"]
+            .as_str()
+            .is_some_and(|signature| signature.starts_with("exactInput(")),
+        "{status}"
+    );
+    let swaps = detail["logs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|log| log["event"] == "Swap")
+        .count();
+    assert_eq!(swaps, 2, "{status}");
+
+    let long_raw = (expected_out * rust_decimal::Decimal::from(10u64.pow(18)))
+        .normalize()
+        .to_string();
+    assert_eq!(
+        node.token_balance(long_tail, wallet).unwrap(),
+        U256::from_str(&long_raw).unwrap()
+    );
+    assert_eq!(node.token_balance(dai, wallet).unwrap(), U256::ZERO);
+    assert_eq!(node.token_balance(weth, wallet).unwrap(), U256::ZERO);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_exact_output_swap() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let dai = Address::from_str(&contracts.dai).unwrap();
+    let one_ether = U256::from(10u64).pow(U256::from(18));
+    node.mint(weth, wallet, one_ether).unwrap();
+
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({"token_address": contracts.weth, "amount": "1"}),
+    )
+    .await;
+    let approval_hash = approval["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{approval}"))
+        .to_string();
+    call_tool(
+        &url,
+        "wait_for_transaction",
+        json!({"transaction_hash": approval_hash}),
+    )
+    .await;
+
+    let both = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.dai,
+            "amount": "0.5",
+            "amount_out": "1000",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    assert!(both.get("error").is_some(), "{both}");
+
+    // "I want exactly 1000 DAI, how much WETH do I need?"
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.dai,
+            "amount_out": "1000",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    assert_eq!(quote["result"]["mode"], "exact_output", "{quote}");
+    assert_eq!(quote["result"]["requires_approval"], false, "{quote}");
+    assert_eq!(quote["result"]["amount_out"], "1000", "{quote}");
+    let amount_in = Decimal::from_str(quote["result"]["amount_in"].as_str().unwrap()).unwrap();
+    let maximum =
+        Decimal::from_str(quote["result"]["amount_in_maximum"].as_str().unwrap()).unwrap();
+    // 1000 DAI at $3000 is a third of an ETH, plus the 0.3% fee and price impact
+    assert!(
+        amount_in > Decimal::from_str("0.3343").unwrap()
+            && amount_in < Decimal::from_str("0.3346").unwrap(),
+        "{quote}"
+    );
+    // amountInMaximum is the quoted input plus 1% slippage, rounded up to wei
+    let expected_maximum = (amount_in * Decimal::from_str("1.01").unwrap())
+        .round_dp_with_strategy(18, rust_decimal::RoundingStrategy::AwayFromZero);
+    assert_eq!(maximum, expected_maximum, "{quote}");
+    // Gas counts against the input, here at 1:1 since the input is WETH
+    for route_quote in quote["result"]["route_quotes"].as_array().unwrap() {
+        let field = |name: &str| Decimal::from_str(route_quote[name].as_str().unwrap()).unwrap();
+        assert_eq!(
+            field("net_amount_in"),
+            field("amount_in") + field("gas_cost_eth"),
+            "{quote}"
+        );
+    }
+
+    let execution = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": quote["result"]["quote_id"]}),
+    )
+    .await;
+    let swap_hash = execution["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{execution}"))
+        .to_string();
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": swap_hash, "detail": true}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Confirmed", "{status}");
+    assert!(
+        status["result"]["detail"]["method"]["signature"]
+            .as_str()
+            .is_some_and(|signature| signature.starts_with("exactOutputSingle(")),
+        "{status}"
+    );
+
+    assert_eq!(
+        node.token_balance(dai, wallet).unwrap(),
+        U256::from(1000u64) * one_ether
+    );
+    let spent_raw = (amount_in * Decimal::from(10u64.pow(18)))
+        .normalize()
+        .to_string();
+    assert_eq!(
+        node.token_balance(weth, wallet).unwrap(),
+        one_ether - U256::from_str(&spent_raw).unwrap()
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_price_impact_limits() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::contracts::fees;
+    use ethereum_mcp_server::devnet::DevNode;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let dai = Address::from_str(&contracts.dai).unwrap();
+    // A thin pool of 1000 DAI against 1000 THIN
+    let thin = Address::repeat_byte(0x43);
+    node.add_token(thin, "Thin", "THIN", 18);
+    let ether = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+    node.add_pool(dai, thin, fees::HIGH, ether(1_000), ether(1_000))
+        .unwrap();
+
+    let quote = |amount: &'static str| {
+        let url = url.clone();
+        let to_token = format!("{:#x}", thin);
+        let from_token = contracts.dai.clone();
+        async move {
+            call_tool(
+                &url,
+                "swap_tokens",
+                json!({
+                    "from_token": from_token,
+                    "to_token": to_token,
+                    "amount": amount,
+                    "slippage_tolerance": "1"
+                }),
+            )
+            .await
+        }
+    };
+    let price_impact = |quote: &serde_json::Value| {
+        Decimal::from_str(quote["result"]["price_impact"].as_str().unwrap()).unwrap()
+    };
+
+    // 10 DAI moves the price by about 1%
+    let small = quote("10").await;
+    assert!(
+        price_impact(&small) > Decimal::from_str("0.9").unwrap()
+            && price_impact(&small) < Decimal::ONE,
+        "{small}"
+    );
+    assert_eq!(small["result"]["high_price_impact"], false, "{small}");
+    // Without an allowance the router call can't be estimated, so gas comes from the
+    // gas QuoterV2 measured for the pool swap plus the transaction base cost and transfers
+    assert_eq!(small["result"]["requires_approval"], true, "{small}");
+    assert_eq!(small["result"]["gas_estimate_units"], "149203", "{small}");
+    let best = &small["result"]["route_quotes"][0];
+    assert_eq!(best["initialized_ticks_crossed"], 0, "{small}");
+    let sqrt_prices_after = best["sqrt_prices_x96_after"].as_array().unwrap();
+    assert_eq!(sqrt_prices_after.len(), 1, "{small}");
+    // The pool started at a 1:1 price, 2^96 as sqrtPriceX96
+    assert_ne!(
+        sqrt_prices_after[0],
+        (U256::from(1u64) << 96usize).to_string(),
+        "{small}"
+    );
+
+    // 100 DAI moves it by about 9%: quoted, but flagged
+    let large = quote("100").await;
+    assert!(
+        price_impact(&large) > Decimal::from(9) && price_impact(&large) < Decimal::from(10),
+        "{large}"
+    );
+    assert_eq!(large["result"]["high_price_impact"], true, "{large}");
+
+    // 300 DAI would move it by about 23%, past the 15% limit
+    let excessive = quote("300").await;
+    assert!(
+        excessive["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("Price impact")),
+        "{excessive}"
+    );
+    assert_eq!(
+        excessive["error"]["data"]["max_price_impact"], "15",
+        "{excessive}"
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_swap_simulation_with_state_overrides() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let one_ether = U256::from(10u64).pow(U256::from(18));
+    node.mint(weth, wallet, one_ether).unwrap();
+    let unfunded = format!("{:#x}", Address::repeat_byte(0x42));
+
+    let quote = |extra: serde_json::Value| {
+        let url = url.clone();
+        let mut args = json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.dai,
+            "amount": "0.5",
+            "slippage_tolerance": "1"
+        });
+        args.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        async move { call_tool(&url, "swap_tokens", args).await }
+    };
+
+    // The wallet holds the WETH but hasn't approved the router
+    let from_wallet = quote(json!({})).await;
+    let simulation = &from_wallet["result"]["simulation"];
+    assert_eq!(
+        simulation["sender"],
+        format!("{:#x}", wallet),
+        "{from_wallet}"
+    );
+    assert_eq!(simulation["balance"], "1", "{from_wallet}");
+    assert_eq!(simulation["allowance"], "0", "{from_wallet}");
+    assert_eq!(simulation["success"], false, "{from_wallet}");
+    assert_eq!(
+        simulation["revert_reason"]["message"], "STF",
+        "{from_wallet}"
+    );
+
+    // Standing in for the approval shows the swap itself would go through
+    let overridden = quote(json!({"override_token_state": true})).await;
+    let simulation = &overridden["result"]["simulation"];
+    assert_eq!(simulation["success"], true, "{overridden}");
+    assert_eq!(simulation["allowance_overridden"], true, "{overridden}");
+    assert_eq!(simulation["balance_overridden"], false, "{overridden}");
+    assert_eq!(
+        overridden["result"]["requires_approval"], true,
+        "{overridden}"
+    );
+
+    // Any account can be simulated, with neither funds nor approval
+    let other = quote(json!({"simulate_from": unfunded})).await;
+    let simulation = &other["result"]["simulation"];
+    assert_eq!(simulation["sender"], unfunded, "{other}");
+    assert_eq!(simulation["balance"], "0", "{other}");
+    assert_eq!(simulation["success"], false, "{other}");
+    let other = quote(json!({"simulate_from": unfunded, "override_token_state": true})).await;
+    let simulation = &other["result"]["simulation"];
+    assert_eq!(simulation["success"], true, "{other}");
+    assert_eq!(simulation["balance_overridden"], true, "{other}");
+    assert_eq!(simulation["allowance_overridden"], true, "{other}");
+
+    // ...but only the wallet's own quotes can be executed
+    let execution = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": other["result"]["quote_id"]}),
+    )
+    .await;
+    assert!(
+        execution["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("not the wallet")),
+        "{execution}"
+    );
+
+    let invalid = quote(json!({"simulate_from": "0x1234"})).await;
+    assert_eq!(invalid["error"]["code"], -32602, "{invalid}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_unapproved_swap_is_still_quoted() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    node.mint(weth, wallet, U256::from(10u64).pow(U256::from(18)))
+        .unwrap();
+
+    // The router can't pull the input yet, so the dry run from the wallet reverts. The
+    // quote must still come back, with the quoter's gas figure rather than a failed
+    // eth_estimateGas, so the caller knows to approve and can then execute it
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.usdc,
+            "amount": "0.5",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    let result = &quote["result"];
+    assert_eq!(result["requires_approval"], true, "{quote}");
+    assert!(result["quote_id"].is_string(), "{quote}");
+    assert_eq!(
+        result["simulation"]["sender"],
+        format!("{:#x}", wallet),
+        "{quote}"
+    );
+    assert_eq!(result["simulation"]["success"], false, "{quote}");
+    assert_eq!(
+        result["simulation"]["revert_reason"]["message"], "STF",
+        "{quote}"
+    );
+    assert_eq!(
+        result["gas_estimate_units"], result["route_quotes"][0]["gas_estimate_units"],
+        "{quote}"
+    );
+    assert_ne!(result["gas_estimate_units"], "200000", "{quote}");
+    assert!(result["gas_cost_eth"].is_string(), "{quote}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_v3_skips_fee_tiers_the_factory_cannot_answer() {
+    use alloy::primitives::Address;
+    use alloy::sol_types::SolCall;
+    use ethereum_mcp_server::contracts::IUniswapV3Factory;
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let usdc = Address::from_str(&contracts.usdc).unwrap();
+    let factory = Address::from_str(&contracts.uniswap_v3_factory).unwrap();
+    let get_pool = IUniswapV3Factory::getPoolCall {
+        tokenA: weth,
+        tokenB: usdc,
+        fee: 500,
+    };
+    node.fail_calls(factory, get_pool.abi_encode().into());
+
+    // The 0.05% pool can't be found, but the 0.3% one still is
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.usdc,
+            "amount": "0.5",
+            "slippage_tolerance": "1",
+            "protocol": "uniswap_v3"
+        }),
+    )
+    .await;
+    let route_quotes = quote["result"]["route_quotes"]
+        .as_array()
+        .unwrap_or_else(|| panic!("{quote}"));
+    let direct_tiers: Vec<_> = route_quotes
+        .iter()
+        .filter(|q| q["route"]["hops"].as_array().unwrap().len() == 1)
+        .map(|q| q["route"]["hops"][0]["fee_tier"].as_u64().unwrap())
+        .collect();
+    assert_eq!(direct_tiers, vec![3000], "{quote}");
+    assert_eq!(
+        quote["result"]["route"]["hops"][0]["fee_tier"], 3000,
+        "{quote}"
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_route_quotes_run_concurrently() {
+    use ethereum_mcp_server::devnet::DevNode;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    // Long enough for lookups issued together to overlap at the node
+    node.set_latency(Duration::from_millis(20));
+
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.usdc,
+            "amount": "0.5",
+            "slippage_tolerance": "1",
+            "protocol": "uniswap_v3"
+        }),
+    )
+    .await;
+    assert!(quote["result"]["quote_id"].is_string(), "{quote}");
+    // At least the four fee tiers of each pair are looked up at once
+    let peak = node.peak_concurrent_requests();
+    assert!(peak >= 4, "peak of {peak} concurrent requests");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_route_quotes_stay_within_the_request_limit() {
+    use ethereum_mcp_server::devnet::DevNode;
+
+    let node = DevNode::spawn().await.unwrap();
+    let provider = ethereum_mcp_server::providers::ProviderFactory::create_ethereum_provider(
+        node.url(),
+        ethereum_mcp_server::devnet::DEV_PRIVATE_KEY.to_string(),
+        2,
+        5,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let (url, server) = spawn_server(provider, node.contracts()).await;
+    let contracts = node.contracts();
+    node.set_latency(Duration::from_millis(20));
+
+    // Every protocol, base token and fee tier is quoted
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.weth,
+            "to_token": contracts.usdc,
+            "amount": "0.5",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    assert!(quote["result"]["quote_id"].is_string(), "{quote}");
+    let peak = node.peak_concurrent_requests();
+    assert!(peak <= 2, "peak of {peak} concurrent requests");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_v2_and_sushiswap_routes() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use ethereum_mcp_server::types::SwapProtocol;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    // A token only listed on V2-style DEXes, which Sushiswap prices 2% better
+    let token = Address::repeat_byte(0x43);
+    node.add_token(token, "Pair Token", "PAIR", 18);
+    let ether = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+    let v2_pair = node
+        .add_v2_pair(
+            SwapProtocol::UniswapV2,
+            weth,
+            token,
+            ether(100),
+            ether(10_000),
+        )
+        .unwrap();
+    let sushi_pair = node
+        .add_v2_pair(
+            SwapProtocol::Sushiswap,
+            weth,
+            token,
+            ether(100),
+            ether(10_200),
+        )
+        .unwrap();
+    node.mint(weth, wallet, ether(1)).unwrap();
+    let swap = |protocol: Option<&str>| {
+        let mut args = json!({
+            "from_token": contracts.weth,
+            "to_token": format!("{:#x}", token),
+            "amount": "1",
+            "slippage_tolerance": "1"
+        });
+        if let Some(protocol) = protocol {
+            args["protocol"] = json!(protocol);
+        }
+        call_tool(&url, "swap_tokens", args)
+    };
+
+    // With no V3 pool, comparing protocols picks the better-priced Sushiswap pair
+    let quote = swap(None).await;
+    let route = &quote["result"]["route"];
+    assert_eq!(route["protocol"], "sushiswap", "{quote}");
+    assert_eq!(
+        route["hops"][0]["pool"],
+        format!("{:#x}", sushi_pair),
+        "{quote}"
+    );
+    assert_eq!(route["hops"][0]["fee_tier"], 3000, "{quote}");
+    // Constant-product impact of 1 ETH against 100: 1 - 100 / 100.997
+    let impact = Decimal::from_str(quote["result"]["price_impact"].as_str().unwrap()).unwrap();
+    assert!(
+        impact > Decimal::from_str("0.98").unwrap() && impact < Decimal::from_str("0.99").unwrap(),
+        "{quote}"
+    );
+
+    let unknown = swap(Some("curve")).await;
+    assert!(unknown.get("error").is_some(), "{unknown}");
+
+    let quote = swap(Some("uniswap_v2")).await;
+    let route = &quote["result"]["route"];
+    assert_eq!(route["protocol"], "uniswap_v2", "{quote}");
+    assert_eq!(
+        route["hops"][0]["pool"],
+        format!("{:#x}", v2_pair),
+        "{quote}"
+    );
+    // The V3 router's allowance doesn't cover the V2 router
+    assert_eq!(quote["result"]["requires_approval"], true, "{quote}");
+
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({
+            "token_address": contracts.weth,
+            "amount": "1",
+            "spender": contracts.uniswap_v2_router
+        }),
+    )
+    .await;
+    let approval_hash = approval["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{approval}"))
+        .to_string();
+    call_tool(
+        &url,
+        "wait_for_transaction",
+        json!({"transaction_hash": approval_hash}),
+    )
+    .await;
+
+    // Only the V3 router redeems permits
+    let quote = swap(Some("uniswap_v2")).await;
+    assert_eq!(quote["result"]["requires_approval"], false, "{quote}");
+    let with_permit = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": quote["result"]["quote_id"], "use_permit": true}),
+    )
+    .await;
+    assert!(with_permit.get("error").is_some(), "{with_permit}");
+
+    let quote = swap(Some("uniswap_v2")).await;
+    let execution = call_tool(
+        &url,
+        "execute_swap",
+        json!({"quote_id": quote["result"]["quote_id"]}),
+    )
+    .await;
+    let swap_hash = execution["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{execution}"))
+        .to_string();
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": swap_hash, "detail": true}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Confirmed", "{status}");
+    assert!(
+        status["result"]["detail"]["method"]["signature"]
+            .as_str()
+            .is_some_and(|signature| signature.starts_with("swapExactTokensForTokens(")),
+        "{status}"
+    );
+
+    // getAmountsOut: amountIn * 997 * reserveOut / (reserveIn * 1000 + amountIn * 997)
+    let amount_in = ether(1) * U256::from(997u64);
+    let expected = amount_in * ether(10_000) / (ether(100) * U256::from(1000u64) + amount_in);
+    assert_eq!(node.token_balance(token, wallet).unwrap(), expected);
+    assert_eq!(node.token_balance(weth, wallet).unwrap(), U256::ZERO);
+    assert_eq!(node.token_balance(weth, v2_pair).unwrap(), ether(101));
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_compare_quotes() {
+    use ethereum_mcp_server::devnet::DevNode;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let compare = |from: &str, to: &str, amount: &str| {
+        call_tool(
+            &url,
+            "compare_quotes",
+            json!({"from_token": from, "to_token": to, "amount": amount}),
+        )
+    };
+    let venue = |comparison: &serde_json::Value, name: &str| {
+        comparison["result"]["quotes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|quote| quote["venue"] == name)
+            .cloned()
+    };
+    let unavailable = |comparison: &serde_json::Value| -> Vec<String> {
+        comparison["result"]["unavailable"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["venue"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Curve's 0.01% constant-sum 3pool beats the 0.05% Uniswap pool for stablecoins
+    let stables = compare(&contracts.usdc, &contracts.dai, "10000").await;
+    assert_eq!(stables["result"]["best_venue"], "curve", "{stables}");
+    let curve = venue(&stables, "curve").unwrap_or_else(|| panic!("{stables}"));
+    assert_eq!(curve["amount_out"], "9999", "{stables}");
+    assert_eq!(
+        curve["pools"][0],
+        contracts.curve_pools[0].to_lowercase(),
+        "{stables}"
+    );
+    assert!(venue(&stables, "uniswap_v3").is_some(), "{stables}");
+    let missing = unavailable(&stables);
+    for name in ["uniswap_v2", "sushiswap", "balancer"] {
+        assert!(missing.iter().any(|v| v == name), "{stables}");
+    }
+
+    // The shallower Balancer pool loses to Uniswap V3 for WETH
+    let weth = compare(&contracts.weth, &contracts.usdc, "1").await;
+    assert_eq!(weth["result"]["best_venue"], "uniswap_v3", "{weth}");
+    let balancer = venue(&weth, "balancer").unwrap_or_else(|| panic!("{weth}"));
+    assert_eq!(
+        balancer["pools"][0], "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8",
+        "{weth}"
+    );
+    // 1 ETH against 500 ETH / 1.5M USDC after 0.3%: 1500000 * 0.997 / 500.997
+    let amount_out = Decimal::from_str(balancer["amount_out"].as_str().unwrap()).unwrap();
+    assert!(
+        amount_out > Decimal::from(2985) && amount_out < Decimal::from(2986),
+        "{weth}"
+    );
+    assert!(balancer["gas_cost_eth"].is_string(), "{weth}");
+    assert!(unavailable(&weth).iter().any(|v| v == "curve"), "{weth}");
+
+    let same = compare(&contracts.weth, &contracts.weth, "1").await;
+    assert!(same.get("error").is_some(), "{same}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_native_eth_swaps_and_wrapping() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::DevNode;
+    use ethereum_mcp_server::types::SwapProtocol;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    let weth = Address::from_str(&contracts.weth).unwrap();
+    let usdc = Address::from_str(&contracts.usdc).unwrap();
+    let v3_router = Address::from_str(&contracts.uniswap_v3_router).unwrap();
+    let ether = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+    let confirmed = |result: serde_json::Value| {
+        let url = url.clone();
+        async move {
+            let hash = result["result"]["transaction_hash"]
+                .as_str()
+                .unwrap_or_else(|| panic!("{result}"))
+                .to_string();
+            let status = call_tool(
+                &url,
+                "get_transaction_status",
+                json!({"transaction_hash": hash}),
+            )
+            .await;
+            assert_eq!(status["result"]["status"], "Confirmed", "{status}");
+        }
+    };
+
+    let balance = call_tool(
+        &url,
+        "get_balance",
+        json!({"wallet_address": format!("{:#x}", wallet), "token_contract_address": "ETH"}),
+    )
+    .await;
+    assert_eq!(balance["result"]["symbol"], "ETH", "{balance}");
+    assert!(balance["result"]["token_address"].is_null(), "{balance}");
+
+    // Wrapping and unwrapping move ether in and out of WETH 1:1
+    confirmed(call_tool(&url, "wrap_eth", json!({"amount": "2"})).await).await;
+    assert_eq!(node.token_balance(weth, wallet).unwrap(), ether(2));
+    let unwrap = call_tool(&url, "unwrap_weth", json!({"amount": "0.5"})).await;
+    assert_eq!(unwrap["result"]["amount"], "0.5", "{unwrap}");
+    confirmed(unwrap).await;
+    let weth_left = ether(3) / U256::from(2u64);
+    assert_eq!(node.token_balance(weth, wallet).unwrap(), weth_left);
+    let too_much = call_tool(&url, "unwrap_weth", json!({"amount": "5"})).await;
+    assert!(
+        too_much["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("Insufficient WETH")),
+        "{too_much}"
+    );
+
+    let swap = |from: &str, to: &str, args: serde_json::Value| {
+        let mut arguments = json!({
+            "from_token": from,
+            "to_token": to,
+            "slippage_tolerance": "1",
+            "protocol": "uniswap_v3"
+        });
+        arguments
+            .as_object_mut()
+            .unwrap()
+            .extend(args.as_object().unwrap().clone());
+        call_tool(&url, "swap_tokens", arguments)
+    };
+    let execute = |quote: serde_json::Value| {
+        call_tool(
+            &url,
+            "execute_swap",
+            json!({"quote_id": quote["result"]["quote_id"]}),
+        )
+    };
+
+    // Paying in ether needs no approval; the router wraps the transaction's value
+    let quote = swap("ETH", &contracts.usdc, json!({"amount": "1"})).await;
+    assert_eq!(quote["result"]["requires_approval"], false, "{quote}");
+    assert_eq!(quote["result"]["simulation"]["success"], true, "{quote}");
+    assert_eq!(
+        quote["result"]["route"]["path"][0],
+        contracts.weth.to_lowercase(),
+        "{quote}"
+    );
+    let eth_before = node.balance(wallet);
+    confirmed(execute(quote).await).await;
+    let usdc_received = node.token_balance(usdc, wallet).unwrap();
+    // About 2,997 USDC from the 0.05% pool
+    assert!(
+        usdc_received > U256::from(2_990_000_000u64),
+        "{usdc_received}"
+    );
+    assert!(node.balance(wallet) < eth_before - ether(1) + ether(1) / U256::from(100u64));
+    assert_eq!(node.token_balance(weth, wallet).unwrap(), weth_left);
+
+    // Selling for ether unwraps the router's WETH to the wallet
+    confirmed(
+        call_tool(
+            &url,
+            "approve_token",
+            json!({"token_address": contracts.usdc, "amount": "1000"}),
+        )
+        .await,
+    )
+    .await;
+    let quote = swap(&contracts.usdc, "eth", json!({"amount_out": "0.3"})).await;
+    let usdc_in = Decimal::from_str(
+        quote["result"]["amount_in"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{quote}")),
+    )
+    .unwrap();
+    assert!(
+        usdc_in > Decimal::from(895) && usdc_in < Decimal::from(905),
+        "{quote}"
+    );
+    let eth_before = node.balance(wallet);
+    confirmed(execute(quote).await).await;
+    assert!(node.balance(wallet) > eth_before + ether(29) / U256::from(100u64));
+    assert_eq!(node.token_balance(weth, v3_router).unwrap(), U256::ZERO);
+    assert_eq!(node.balance(v3_router), U256::ZERO);
+
+    // V2 exact-output swaps refund the ether the input maximum left over
+    let token = Address::repeat_byte(0x44);
+    node.add_token(token, "Pair Token", "PAIR", 18);
+    node.add_v2_pair(
+        SwapProtocol::UniswapV2,
+        weth,
+        token,
+        ether(100),
+        ether(10_000),
+    )
+    .unwrap();
+    let quote = swap(
+        "ETH",
+        &format!("{:#x}", token),
+        json!({"amount_out": "50", "protocol": "uniswap_v2", "slippage_tolerance": "5"}),
+    )
+    .await;
+    assert_eq!(
+        quote["result"]["route"]["protocol"], "uniswap_v2",
+        "{quote}"
+    );
+    confirmed(execute(quote).await).await;
+    assert_eq!(node.token_balance(token, wallet).unwrap(), ether(50));
+    let v2_router = Address::from_str(&contracts.uniswap_v2_router).unwrap();
+    assert_eq!(node.balance(v2_router), U256::ZERO);
+
+    let wrap_swap = swap("ETH", &contracts.weth, json!({"amount": "1"})).await;
+    assert_eq!(wrap_swap["error"]["code"], -32602, "{wrap_swap}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_swap_amounts_use_token_decimals() {
+    use ethereum_mcp_server::devnet::DevNode;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let swap = |from: &str, to: &str, extra: serde_json::Value| {
+        let mut arguments = json!({
+            "from_token": from,
+            "to_token": to,
+            "slippage_tolerance": "1",
+        });
+        arguments
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        call_tool(&url, "swap_tokens", arguments)
+    };
+
+    // 100 USDC is 100 * 10^6 raw units, worth about a thirtieth of an ETH
+    let human = swap(&contracts.usdc, &contracts.weth, json!({"amount": "100"})).await;
+    assert_eq!(human["result"]["amount_in"], "100", "{human}");
+    let out = Decimal::from_str(human["result"]["amount_out"].as_str().unwrap()).unwrap();
+    assert!(
+        out > Decimal::from_str("0.033").unwrap() && out < Decimal::from_str("0.0334").unwrap(),
+        "{human}"
+    );
+    let raw = swap(
+        &contracts.usdc,
+        &contracts.weth,
+        json!({"amount": "100000000", "amount_units": "raw"}),
+    )
+    .await;
+    assert_eq!(raw["result"]["amount_in"], "100", "{raw}");
+    assert_eq!(raw["result"]["amount_out"], human["result"]["amount_out"]);
+
+    // USDC has no unit below 0.000001
+    let too_fine = swap(
+        &contracts.usdc,
+        &contracts.weth,
+        json!({"amount": "0.0000001"}),
+    )
+    .await;
+    assert_eq!(too_fine["error"]["code"], -32602, "{too_fine}");
+    let fractional_raw = swap(
+        &contracts.usdc,
+        &contracts.weth,
+        json!({"amount": "1.5", "amount_units": "raw"}),
+    )
+    .await;
+    assert_eq!(fractional_raw["error"]["code"], -32602, "{fractional_raw}");
+
+    // The server caps swaps at $1M: 400 WETH is only 400 tokens but $1.2M
+    let too_large = swap(&contracts.weth, &contracts.usdc, json!({"amount": "400"})).await;
+    assert_eq!(too_large["error"]["code"], -32602, "{too_large}");
+    assert_eq!(
+        too_large["error"]["data"]["max_swap_value_usd"], "1000000",
+        "{too_large}"
+    );
+    let value =
+        Decimal::from_str(too_large["error"]["data"]["value_usd"].as_str().unwrap()).unwrap();
+    assert_eq!(value, Decimal::from(1_200_000), "{too_large}");
+    // Bought amounts are valued too
+    let too_large = swap(
+        &contracts.weth,
+        &contracts.usdc,
+        json!({"amount_out": "2000000"}),
+    )
+    .await;
+    assert_eq!(too_large["error"]["code"], -32602, "{too_large}");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_broadcast_failures_and_nonces() {
+    use alloy::primitives::{Address, U256};
+    use ethereum_mcp_server::devnet::{BroadcastFault, DevNode};
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+    let wallet = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    node.mint(
+        Address::from_str(&contracts.weth).unwrap(),
+        wallet,
+        U256::from(10u64).pow(U256::from(18)),
+    )
+    .unwrap();
+    let approve = |amount: &str| {
+        call_tool(
+            &url,
+            "approve_token",
+            json!({"token_address": contracts.weth, "amount": amount}),
+        )
+    };
+    let nonce_of = |hash: serde_json::Value| {
+        let url = url.clone();
+        async move {
+            let status = call_tool(
+                &url,
+                "get_transaction_status",
+                json!({"transaction_hash": hash, "detail": true}),
+            )
+            .await;
+            status["result"]["detail"]["nonce"]
+                .as_u64()
+                .unwrap_or_else(|| panic!("{status}"))
+        }
+    };
+    node.set_automine(false);
+
+    // The node takes the transaction but the response is lost: its nonce stays
+    // taken, so the next transaction doesn't collide with it in the mempool
+    node.fail_next_broadcast(BroadcastFault::LoseResponse);
+    let lost = approve("0.1").await;
+    assert!(
+        lost["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("may have been broadcast")),
+        "{lost}"
+    );
+    assert_eq!(node.pending_transactions().len(), 1);
+    let next = approve("0.2").await;
+    assert_eq!(
+        nonce_of(next["result"]["transaction_hash"].clone()).await,
+        1
+    );
+
+    // A node that refuses the transaction never had it, so its nonce is reused
+    node.fail_next_broadcast(BroadcastFault::Reject("txpool is full".to_string()));
+    let refused = approve("0.3").await;
+    assert!(refused.get("error").is_some(), "{refused}");
+    assert!(
+        !refused["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("may have been broadcast"),
+        "{refused}"
+    );
+    assert_eq!(node.pending_transactions().len(), 2);
+    let retried = approve("0.3").await;
+    assert_eq!(
+        nonce_of(retried["result"]["transaction_hash"].clone()).await,
+        2
+    );
+    assert_eq!(node.pending_transactions().len(), 3);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_simulated_journal_keeps_unconfirmed_broadcasts_pending() {
+    use alloy::primitives::U256;
+    use ethereum_mcp_server::devnet::{BroadcastFault, DevNode, DEV_PRIVATE_KEY};
+    use ethereum_mcp_server::providers::{ProviderFactory, StoredTxStatus, TransactionStore};
+    use ethereum_mcp_server::types::{TokenAddress, UnconfirmedBroadcast};
+    use std::sync::Arc;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.jsonl");
+    let node = DevNode::spawn().await.unwrap();
+    let weth = TokenAddress::from_hex(&node.contracts().weth).unwrap();
+    let open = |store: Arc<TransactionStore>| {
+        ProviderFactory::create_ethereum_provider(
+            node.url(),
+            DEV_PRIVATE_KEY.to_string(),
+            10,
+            5,
+            Some(store),
+            None,
+        )
+    };
+    let store = Arc::new(TransactionStore::open(&path).await.unwrap());
+    let provider = open(store.clone()).await.unwrap();
+    let wallet = provider.wallet_address();
+    node.set_automine(false);
+
+    // Accepted by the node, but the response never arrives: still pending
+    node.fail_next_broadcast(BroadcastFault::LoseResponse);
+    let error = provider
+        .wrap_eth(&weth, U256::from(1u64))
+        .await
+        .unwrap_err();
+    let tx_hash = error
+        .downcast_ref::<UnconfirmedBroadcast>()
+        .unwrap_or_else(|| panic!("{error}"))
+        .tx_hash;
+    assert_eq!(
+        store.get(&tx_hash).await.map(|tx| tx.status),
+        Some(StoredTxStatus::Pending)
+    );
+
+    // Refused by the node: dropped from the journal's pending set
+    node.fail_next_broadcast(BroadcastFault::Reject("txpool is full".to_string()));
+    let error = provider
+        .wrap_eth(&weth, U256::from(2u64))
+        .await
+        .unwrap_err();
+    assert!(error.downcast_ref::<UnconfirmedBroadcast>().is_none());
+    assert_eq!(store.pending(&wallet).await.len(), 1);
+
+    // After a restart, reconciliation finds the lost-response transaction mined
+    node.mine();
+    drop(provider);
+    drop(store);
+    let store = Arc::new(TransactionStore::open(&path).await.unwrap());
+    assert_eq!(store.pending(&wallet).await.len(), 1);
+    let _provider = open(store.clone()).await.unwrap();
+    assert!(store.pending(&wallet).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_simulated_journal_marks_replaced_transactions() {
+    use alloy::primitives::U256;
+    use ethereum_mcp_server::devnet::{DevNode, DEV_PRIVATE_KEY};
+    use ethereum_mcp_server::providers::{ProviderFactory, TransactionStore};
+    use ethereum_mcp_server::types::{ReplacementKind, TokenAddress};
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transactions.jsonl");
+    let node = DevNode::spawn().await.unwrap();
+    let weth = TokenAddress::from_hex(&node.contracts().weth).unwrap();
+    let open = |store: Arc<TransactionStore>| {
+        ProviderFactory::create_ethereum_provider(
+            node.url(),
+            DEV_PRIVATE_KEY.to_string(),
+            10,
+            5,
+            Some(store),
+            None,
+        )
+    };
+    let store = Arc::new(TransactionStore::open(&path).await.unwrap());
+    let provider = open(store.clone()).await.unwrap();
+    let wallet = provider.wallet_address();
+    node.set_automine(false);
+
+    let original = provider.wrap_eth(&weth, U256::from(1u64)).await.unwrap();
+    let replacement = provider
+        .replace_transaction(&original, ReplacementKind::SpeedUp)
+        .await
+        .unwrap();
+    let replacement = alloy::primitives::B256::from_str(&replacement.replacement_hash).unwrap();
+    assert!(store.get(&original).await.is_none());
+
+    // After a restart only the replacement is pending; the superseded transaction
+    // isn't rebroadcast and recorded as dropped
+    drop(provider);
+    drop(store);
+    let store = Arc::new(TransactionStore::open(&path).await.unwrap());
+    let pending: Vec<_> = store
+        .pending(&wallet)
+        .await
+        .into_iter()
+        .map(|tx| tx.tx_hash)
+        .collect();
+    assert_eq!(pending, vec![replacement]);
+    let _provider = open(store.clone()).await.unwrap();
+    assert_eq!(node.pending_transactions(), vec![replacement]);
+    assert_eq!(store.pending(&wallet).await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_simulated_pending_dropped_and_reverted_transactions() {
+    use alloy::primitives::B256;
+    use ethereum_mcp_server::devnet::DevNode;
+    use std::str::FromStr;
+
+    let node = DevNode::spawn().await.unwrap();
+    let (url, server) = spawn_simulated_server(&node).await;
+    let contracts = node.contracts();
+
+    // Without automine the approval waits in the mempool until evicted
+    node.set_automine(false);
+    let approval = call_tool(
+        &url,
+        "approve_token",
+        json!({"token_address": contracts.usdc, "amount": "5"}),
+    )
+    .await;
+    let hash = approval["result"]["transaction_hash"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{approval}"))
+        .to_string();
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": hash}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Pending", "{status}");
+
+    assert!(node.drop_transaction(&B256::from_str(&hash).unwrap()));
+    let status = call_tool(
+        &url,
+        "get_transaction_status",
+        json!({"transaction_hash": hash}),
+    )
+    .await;
+    assert_eq!(status["result"]["status"], "Dropped", "{status}");
+    node.set_automine(true);
+
+    // Swapping more USDC than the wallet holds reverts in the quote's dry run
+    let quote = call_tool(
+        &url,
+        "swap_tokens",
+        json!({
+            "from_token": contracts.usdc,
+            "to_token": contracts.weth,
+            "amount": "100",
+            "slippage_tolerance": "1"
+        }),
+    )
+    .await;
+    assert_eq!(quote["result"]["requires_approval"], true, "{quote}");
+
+    server.abort();
+}
+
+/// Wallet queries made in the record/replay tests, with the fields compared across runs
+async fn query_wallet_and_prices(
+    url: &str,
+    contracts: &ethereum_mcp_server::ContractAddresses,
+) -> Vec<serde_json::Value> {
+    let wallet = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    vec![
+        call_tool(url, "get_balance", json!({"wallet_address": wallet})).await["result"].clone(),
+        call_tool(
+            url,
+            "get_balance",
+            json!({"wallet_address": wallet, "token_contract_address": contracts.usdc}),
+        )
+        .await["result"]
+            .clone(),
+        call_tool(
+            url,
+            "get_token_price",
+            json!({"token_address": contracts.weth}),
+        )
+        .await["result"]["price_usd"]
+            .clone(),
+        call_tool(url, "get_token_price", json!({"token_symbol": "USDC"})).await["result"]
+            ["price_usd"]
+            .clone(),
+    ]
+}
+
+#[tokio::test]
+async fn test_recorded_rpc_session_replays_offline() {
+    use ethereum_mcp_server::devnet::{DevNode, DEV_PRIVATE_KEY};
+    use ethereum_mcp_server::providers::{ProviderFactory, RpcExchange, RpcRecorder};
+    use std::sync::Arc;
+
+    let dir = tempfile::tempdir().unwrap();
+    let fixture = dir.path().join("session.jsonl");
+    let node = DevNode::spawn().await.unwrap();
+    let contracts = node.contracts();
+
+    let recorder = Arc::new(RpcRecorder::create(&fixture).await.unwrap());
+    let provider = ProviderFactory::create_ethereum_provider(
+        node.url(),
+        DEV_PRIVATE_KEY.to_string(),
+        10,
+        5,
+        None,
+        Some(recorder),
+    )
+    .await
+    .unwrap();
+    let (url, server) = spawn_server(provider, contracts.clone()).await;
+    let recorded = query_wallet_and_prices(&url, &contracts).await;
+    server.abort();
+    drop(node);
+
+    assert_eq!(
+        recorded[0]["amount"]["human_readable"], "10000",
+        "{recorded:?}"
+    );
+    assert_eq!(recorded[1]["symbol"], "USDC", "{recorded:?}");
+    let exchanges = RpcExchange::load(&fixture).unwrap();
+    assert!(exchanges.iter().any(|e| e.method == "eth_getBalance"));
+    assert!(exchanges.iter().any(|e| e.method == "eth_call"));
+
+    // The chain is gone; every answer now comes from the fixture
+    let provider =
+        ProviderFactory::create_replay_provider(&fixture, &[], DEV_PRIVATE_KEY.to_string())
+            .await
+            .unwrap();
+    let (url, server) = spawn_server(provider, contracts.clone()).await;
+    let replayed = query_wallet_and_prices(&url, &contracts).await;
+    server.abort();
+
+    assert_eq!(recorded, replayed);
+}
+
+#[tokio::test]
+async fn test_checked_in_rpc_fixture_replays() {
+    use ethereum_mcp_server::devnet::DEV_PRIVATE_KEY;
+    use ethereum_mcp_server::providers::ProviderFactory;
+    use ethereum_mcp_server::types::TokenAddress;
+
+    // Recorded from the simulated node; nothing listens for these calls
+    let provider = ProviderFactory::create_replay_provider(
+        "tests/fixtures/rpc/balances_and_prices.jsonl",
+        &[],
+        DEV_PRIVATE_KEY.to_string(),
+    )
+    .await
+    .unwrap();
+    let contracts = ethereum_mcp_server::Config::new(
+        "http://unused".to_string(),
+        "127.0.0.1".to_string(),
+        0,
+        "info".to_string(),
+        DEV_PRIVATE_KEY.to_string(),
+    )
+    .contracts;
+    let wallet = provider.wallet_address();
+
+    let eth = provider.get_eth_balance(&wallet).await.unwrap();
+    assert_eq!(
+        eth.amount.to_human_readable(),
+        rust_decimal::Decimal::from(10_000)
+    );
+
+    let usdc = TokenAddress::from_hex(&contracts.usdc).unwrap();
+    let balance = provider.get_erc20_balance(&wallet, &usdc).await.unwrap();
+    assert_eq!(balance.symbol, "USDC");
+    assert_eq!(balance.amount.decimals, 6);
+
+    let weth = TokenAddress::from_hex(&contracts.weth).unwrap();
+    let price = provider.get_token_price(&weth, &contracts).await.unwrap();
+    assert_eq!(price.price_usd, Some(rust_decimal::Decimal::from(3000)));
+
+    // A call the fixture never saw fails instead of reaching a node
+    let dai = TokenAddress::from_hex(&contracts.dai).unwrap();
+    let error = provider.get_erc20_balance(&wallet, &dai).await.unwrap_err();
+    assert!(
+        error.to_string().contains("No recorded response"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_failover_keeps_serving_through_a_flaky_primary() {
+    use ethereum_mcp_server::devnet::{DevNode, DEV_PRIVATE_KEY};
+    use ethereum_mcp_server::providers::{
+        ChaosProvider, EthereumProvider, FailoverProvider, FaultConfig, ProviderFactory,
+    };
+    use std::sync::Arc;
+
+    let node = DevNode::spawn().await.unwrap();
+    let contracts = node.contracts();
+    let connect = || {
+        ProviderFactory::create_ethereum_provider(
+            node.url(),
+            DEV_PRIVATE_KEY.to_string(),
+            10,
+            5,
+            None,
+            None,
+        )
+    };
+    // A slow primary failing most calls in every way it can, in front of a healthy backup
+    let primary = Arc::new(
+        ChaosProvider::new(connect().await.unwrap())
+            .with_seed(11)
+            .with_hang_duration(Duration::from_millis(50))
+            .with_faults(FaultConfig {
+                latency: Duration::from_millis(5),
+                error_rate: 0.3,
+                timeout_rate: 0.2,
+                malformed_rate: 0.2,
+                stale_blocks: 0,
+            }),
+    );
+    let provider: Arc<dyn EthereumProvider> =
+        Arc::new(FailoverProvider::new(vec![primary.clone(), connect().await.unwrap()]).unwrap());
+    let (url, server) = spawn_server(provider.clone(), contracts.clone()).await;
+
+    for _ in 0..10 {
+        let price = call_tool(&url, "get_token_price", json!({"token_symbol": "WETH"})).await;
+        assert_eq!(price["result"]["price_usd"], "3000", "{price}");
+        let quote = call_tool(
+            &url,
+            "swap_tokens",
+            json!({
+                "from_token": contracts.weth,
+                "to_token": contracts.usdc,
+                "amount": "0.1",
+                "slippage_tolerance": "1"
+            }),
+        )
+        .await;
+        assert!(quote["result"]["quote_id"].is_string(), "{quote}");
+    }
+    assert!(primary.injected(ethereum_mcp_server::providers::Fault::Error) > 0);
+    server.abort();
+
+    // Startup failover skips an endpoint that is down
+    let provider = ProviderFactory::create_ethereum_provider_with_failover(
+        vec!["http://127.0.0.1:1".to_string(), node.url()],
+        DEV_PRIVATE_KEY.to_string(),
+        10,
+        5,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        provider.get_block_number().await.unwrap(),
+        node.block_number()
+    );
+}